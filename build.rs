@@ -0,0 +1,41 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+/// Generates the gRPC service from Rust, so building doesn't need `protoc`.
+/// Keep it in step with `proto/text_game.proto` and the messages in
+/// `src/server/grpc.rs`.
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    const METHODS: &[(&str, &str, &str, &str, bool)] = &[
+        ("list_stories", "ListStories", "ListStoriesRequest", "ListStoriesReply", false),
+        ("start_session", "StartSession", "StartSessionRequest", "SessionReply", false),
+        ("get_scene", "GetScene", "SessionRequest", "Scene", false),
+        ("make_choice", "MakeChoice", "MakeChoiceRequest", "Scene", false),
+        ("watch_session", "WatchSession", "SessionRequest", "Scene", true),
+        ("save", "Save", "SaveRequest", "SaveReply", false),
+        ("load", "Load", "LoadRequest", "SessionReply", false),
+        ("end_session", "EndSession", "SessionRequest", "Empty", false),
+    ];
+
+    pub fn generate() {
+        let mut service = Service::builder().name("TextGame").package("text_game");
+        for &(name, route, input, output, streaming) in METHODS {
+            let mut method = Method::builder()
+                .name(name)
+                .route_name(route)
+                .input_type(format!("crate::server::grpc::{}", input))
+                .output_type(format!("crate::server::grpc::{}", output))
+                .codec_path("tonic_prost::ProstCodec");
+            if streaming {
+                method = method.server_streaming();
+            }
+            service = service.method(method.build());
+        }
+        Builder::new().build_client(false).compile(&[service.build()]);
+    }
+}
@@ -1,6 +1,29 @@
+use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use crate::utils::{GameError, GameResult};
+use crate::core::{FlagLimits, GameEngine, DEFAULT_EVENT_LOG_CAPACITY, DEFAULT_MAX_FLAGS, DEFAULT_MAX_FLAG_VALUE_BYTES, DEFAULT_VISIT_HISTORY};
+use crate::utils::{GameError, GameResult, SaveCipher};
+use tracing::{info, warn};
+
+pub mod watcher;
+
+pub use watcher::ConfigWatcher;
+
+// Relative directories used before platform directories were introduced
+const LEGACY_STORIES_DIR: &str = "./assets/stories";
+const LEGACY_SAVES_DIR: &str = "./assets/saves";
+const LEGACY_LOGS_DIR: &str = "./assets/logs";
+const LEGACY_CONFIG_DIR: &str = "./assets/config";
+const LEGACY_CACHE_DIR: &str = "./assets/cache";
+const LEGACY_PLUGINS_DIR: &str = "./assets/plugins";
+
+/// Narrowest `ui.text_width` the layout supports.
+pub const MIN_TEXT_WIDTH: usize = 40;
+
+/// Environment variable checked before `saves.encryption_passphrase`.
+pub const SAVE_PASSPHRASE_ENV: &str = "TEXT_GAME_SAVE_PASSPHRASE";
+const SAVE_KEY_FILE: &str = "save.key";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -9,6 +32,14 @@ pub struct Config {
     pub paths: PathConfig,
     pub logging: LoggingConfig,
     pub saves: SaveConfig,
+    #[serde(default)]
+    pub ai_narrator: AiNarratorConfig,
+    #[serde(default)]
+    pub twitch: TwitchConfig,
+    #[serde(default)]
+    pub bots: BotConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +49,59 @@ pub struct GameConfig {
     pub max_recent_saves: usize,
     pub confirm_dangerous_choices: bool,
     pub show_choice_effects: bool,
+    /// Tools for story authors, such as the debug console.
+    #[serde(default)]
+    pub dev_mode: bool,
+    /// Events kept in the session's event history.
+    #[serde(default = "default_event_log_capacity")]
+    pub event_log_capacity: usize,
+    /// Store the event history in saves and restore it on load.
+    #[serde(default)]
+    pub save_event_log: bool,
+    /// Most recent scene visits kept in order in the game state.
+    #[serde(default = "default_visit_history")]
+    pub visit_history: usize,
+    /// Most flags a game may set.
+    #[serde(default = "default_max_flags")]
+    pub max_flags: usize,
+    /// Largest flag value, in bytes of JSON.
+    #[serde(default = "default_max_flag_value_bytes")]
+    pub max_flag_value_bytes: usize,
+}
+
+impl GameConfig {
+    pub fn flag_limits(&self) -> FlagLimits {
+        FlagLimits {
+            max_flags: self.max_flags,
+            max_value_bytes: self.max_flag_value_bytes,
+        }
+    }
+
+    /// An engine with the configured event log, visit history and flag
+    /// limits, and the debug tools in dev mode.
+    pub fn engine(&self) -> GameEngine {
+        GameEngine::new()
+            .with_event_log_capacity(self.event_log_capacity)
+            .with_visit_history(self.visit_history)
+            .with_flag_limits(self.flag_limits())
+            .with_debug_tools(self.dev_mode)
+    }
+}
+
+fn default_event_log_capacity() -> usize {
+    DEFAULT_EVENT_LOG_CAPACITY
+}
+
+fn default_visit_history() -> usize {
+    DEFAULT_VISIT_HISTORY
+}
+
+fn default_max_flags() -> usize {
+    DEFAULT_MAX_FLAGS
+}
+
+fn default_max_flag_value_bytes() -> usize {
+    DEFAULT_MAX_FLAG_VALUE_BYTES
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,7 +111,54 @@ pub struct UiConfig {
     pub show_scene_numbers: bool,
     pub animation_speed: AnimationSpeed,
     pub text_width: usize,
+    /// Shrink text to fit narrower terminals, down to `MIN_TEXT_WIDTH`.
+    #[serde(default = "default_fit_to_terminal")]
+    pub fit_to_terminal: bool,
+    /// Blank lines between paragraphs of scene text.
+    #[serde(default = "default_paragraph_spacing")]
+    pub paragraph_spacing: usize,
+    /// Spaces before the first line of each paragraph.
+    #[serde(default)]
+    pub paragraph_indent: usize,
+    /// Double line spacing and no italics.
+    #[serde(default)]
+    pub dyslexia_friendly: bool,
+    /// No pauses, delays or quit confirmations; feedback is collected into
+    /// one line above the next scene.
+    #[serde(default)]
+    pub streamlined: bool,
+    /// Show choice hints under the highlighted choice; otherwise they're
+    /// shown on pressing `h`.
+    #[serde(default = "default_show_choice_hints")]
+    pub show_choice_hints: bool,
+    /// Mark choices taken in an earlier completed playthrough with a ✓.
+    #[serde(default = "default_mark_taken_choices")]
+    pub mark_taken_choices: bool,
+    /// Show the last few items gained, level ups and `recent_event_flags`
+    /// changes under the stats header.
+    #[serde(default)]
+    pub show_recent_events: bool,
+    #[serde(default = "default_recent_events_count")]
+    pub recent_events_count: usize,
+    /// Flags whose changes count as recent events.
+    #[serde(default)]
+    pub recent_event_flags: Vec<String>,
+    /// Announce items found, level-ups and achievements before the next scene.
+    #[serde(default = "default_show_notifications")]
+    pub show_notifications: bool,
     pub page_size: usize,
+    /// Show the story's currency next to the player stats.
+    #[serde(default = "default_show_wallet")]
+    pub show_wallet: bool,
+    /// Read scenes and choices aloud (needs the `tts` feature).
+    #[serde(default)]
+    pub read_aloud: bool,
+    /// Speech command and arguments; the text is written to its stdin.
+    #[serde(default = "default_tts_command")]
+    pub tts_command: Vec<String>,
+    /// Frontend the game is drawn with.
+    #[serde(default)]
+    pub renderer: RendererKind,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +167,58 @@ pub struct PathConfig {
     pub saves_dir: PathBuf,
     pub logs_dir: PathBuf,
     pub config_dir: PathBuf,
+    #[serde(default = "default_cache_dir")]
+    pub cache_dir: PathBuf,
+    #[serde(default = "default_plugins_dir")]
+    pub plugins_dir: PathBuf,
+}
+
+fn default_cache_dir() -> PathBuf {
+    PathConfig::default().cache_dir
+}
+
+fn default_plugins_dir() -> PathBuf {
+    PathConfig::default().plugins_dir
+}
+
+fn default_show_wallet() -> bool {
+    true
+}
+
+fn default_show_choice_hints() -> bool {
+    true
+}
+
+fn default_mark_taken_choices() -> bool {
+    true
+}
+
+fn default_show_notifications() -> bool {
+    true
+}
+
+fn default_recent_events_count() -> usize {
+    3
+}
+
+fn default_fit_to_terminal() -> bool {
+    true
+}
+
+fn default_paragraph_spacing() -> usize {
+    1
+}
+
+fn default_tts_command() -> Vec<String> {
+    if cfg!(target_os = "macos") {
+        vec!["say".to_string()]
+    } else {
+        vec!["espeak".to_string(), "--stdin".to_string()]
+    }
+}
+
+fn default_slot_count() -> usize {
+    10
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,9 +235,203 @@ pub struct SaveConfig {
     pub auto_cleanup_saves: bool,
     pub compress_saves: bool,
     pub backup_saves: bool,
+    /// Number of numbered slots shown when saving.
+    #[serde(default = "default_slot_count")]
+    pub slot_count: usize,
+    #[serde(default)]
+    pub encryption: SaveEncryption,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_passphrase: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Endpoint for `story::flavor`, used only by stories that opt in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiNarratorConfig {
+    /// OpenAI-compatible chat completions URL; the narrator is off without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_ai_narrator_model")]
+    pub model: String,
+    /// Environment variable holding the API key, so it stays out of the config file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    #[serde(default = "default_ai_narrator_timeout")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for AiNarratorConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            model: default_ai_narrator_model(),
+            api_key_env: None,
+            timeout_seconds: default_ai_narrator_timeout(),
+        }
+    }
+}
+
+fn default_ai_narrator_model() -> String {
+    "llama3.2".to_string()
+}
+
+fn default_ai_narrator_timeout() -> u64 {
+    10
+}
+
+/// Chat voting for streamers, see `ui::twitch`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TwitchConfig {
+    /// Channel whose chat votes on choices; voting is off without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Account that announces votes; only used with a token.
+    #[serde(default = "default_twitch_nick")]
+    pub nick: String,
+    /// Environment variable holding the account's OAuth token. Without one,
+    /// chat is read anonymously and votes aren't announced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    #[serde(default = "default_twitch_vote_seconds")]
+    pub vote_seconds: u64,
+    #[serde(default = "default_twitch_server")]
+    pub server: String,
+}
+
+impl Default for TwitchConfig {
+    fn default() -> Self {
+        Self {
+            channel: None,
+            nick: default_twitch_nick(),
+            token_env: None,
+            vote_seconds: default_twitch_vote_seconds(),
+            server: default_twitch_server(),
+        }
+    }
+}
+
+fn default_twitch_nick() -> String {
+    "text_game_bot".to_string()
+}
+
+fn default_twitch_vote_seconds() -> u64 {
+    30
+}
+
+fn default_twitch_server() -> String {
+    "irc.chat.twitch.tv:6667".to_string()
+}
+
+/// Limits that keep a public chat bot or API server stable, see
+/// `ui::chat_bot` and `server::service`. 0 turns a limit off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotConfig {
+    /// Commands one user may send a minute; the rest are ignored.
+    #[serde(default = "default_bot_commands_per_minute")]
+    pub commands_per_minute: u32,
+    /// Rooms that may have a game going at once.
+    #[serde(default = "default_bot_max_sessions")]
+    pub max_sessions: usize,
+    /// Minutes before a quiet room's game is saved and put away; it picks
+    /// up again with the room's next command.
+    #[serde(default = "default_bot_idle_minutes")]
+    pub idle_minutes: u64,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            commands_per_minute: default_bot_commands_per_minute(),
+            max_sessions: default_bot_max_sessions(),
+            idle_minutes: default_bot_idle_minutes(),
+        }
+    }
+}
+
+fn default_bot_commands_per_minute() -> u32 {
+    20
+}
+
+fn default_bot_max_sessions() -> usize {
+    50
+}
+
+fn default_bot_idle_minutes() -> u64 {
+    30
+}
+
+/// The API servers of `serve`; their limits are in `BotConfig`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Environment variable holding the API tokens, as `user:token` pairs
+    /// separated by commas. Without tokens the servers only listen on this
+    /// machine.
+    #[serde(default = "default_tokens_env")]
+    pub tokens_env: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self { tokens_env: default_tokens_env() }
+    }
+}
+
+fn default_tokens_env() -> String {
+    "TEXT_GAME_API_TOKENS".to_string()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SaveEncryption {
+    #[default]
+    None,
+    /// Key derived from `encryption_passphrase` or the passphrase environment variable.
+    Passphrase,
+    /// Random key generated once and kept in the config directory.
+    MachineKey,
+}
+
+impl PathConfig {
+    /// Relative paths next to the working directory, as used by older versions.
+    pub fn legacy() -> Self {
+        Self {
+            stories_dir: PathBuf::from(LEGACY_STORIES_DIR),
+            saves_dir: PathBuf::from(LEGACY_SAVES_DIR),
+            logs_dir: PathBuf::from(LEGACY_LOGS_DIR),
+            config_dir: PathBuf::from(LEGACY_CONFIG_DIR),
+            cache_dir: PathBuf::from(LEGACY_CACHE_DIR),
+            plugins_dir: PathBuf::from(LEGACY_PLUGINS_DIR),
+        }
+    }
+}
+
+impl Default for PathConfig {
+    /// Platform data/config directories (XDG on Linux, AppData on Windows,
+    /// Application Support on macOS), falling back to the legacy relative paths
+    /// when no home directory can be determined.
+    fn default() -> Self {
+        match ProjectDirs::from("com", "hamzafulldev", "text-game") {
+            Some(dirs) => Self {
+                stories_dir: dirs.data_dir().join("stories"),
+                saves_dir: dirs.data_dir().join("saves"),
+                logs_dir: dirs.data_local_dir().join("logs"),
+                config_dir: dirs.config_dir().to_path_buf(),
+                cache_dir: dirs.cache_dir().to_path_buf(),
+                plugins_dir: dirs.data_dir().join("plugins"),
+            },
+            None => Self::legacy(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum RendererKind {
+    /// Colors, emoji and arrow-key menus.
+    #[default]
+    Console,
+    /// No colors, emoji or cursor movement, for piping and logging.
+    Plain,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AnimationSpeed {
     None,
     Slow,
@@ -71,6 +448,12 @@ impl Default for Config {
                 max_recent_saves: 10,
                 confirm_dangerous_choices: true,
                 show_choice_effects: false,
+                dev_mode: false,
+                event_log_capacity: default_event_log_capacity(),
+                save_event_log: false,
+                visit_history: default_visit_history(),
+                max_flags: default_max_flags(),
+                max_flag_value_bytes: default_max_flag_value_bytes(),
             },
             ui: UiConfig {
                 theme: "default".to_string(),
@@ -78,14 +461,24 @@ impl Default for Config {
                 show_scene_numbers: false,
                 animation_speed: AnimationSpeed::Medium,
                 text_width: 80,
+                fit_to_terminal: default_fit_to_terminal(),
+                paragraph_spacing: default_paragraph_spacing(),
+                paragraph_indent: 0,
+                dyslexia_friendly: false,
+                streamlined: false,
+                show_choice_hints: default_show_choice_hints(),
+                mark_taken_choices: default_mark_taken_choices(),
+                show_recent_events: false,
+                recent_events_count: default_recent_events_count(),
+                recent_event_flags: Vec::new(),
+                show_notifications: default_show_notifications(),
                 page_size: 10,
+                show_wallet: default_show_wallet(),
+                read_aloud: false,
+                tts_command: default_tts_command(),
+                renderer: RendererKind::default(),
             },
-            paths: PathConfig {
-                stories_dir: PathBuf::from("./assets/stories"),
-                saves_dir: PathBuf::from("./assets/saves"),
-                logs_dir: PathBuf::from("./assets/logs"),
-                config_dir: PathBuf::from("./assets/config"),
-            },
+            paths: PathConfig::default(),
             logging: LoggingConfig {
                 level: "info".to_string(),
                 log_to_file: true,
@@ -97,7 +490,14 @@ impl Default for Config {
                 auto_cleanup_saves: true,
                 compress_saves: false,
                 backup_saves: false,
+                slot_count: default_slot_count(),
+                encryption: SaveEncryption::None,
+                encryption_passphrase: None,
             },
+            ai_narrator: AiNarratorConfig::default(),
+            twitch: TwitchConfig::default(),
+            bots: BotConfig::default(),
+            server: ServerConfig::default(),
         }
     }
 }
@@ -156,12 +556,55 @@ impl Config {
         &self.paths.config_dir
     }
 
+    pub fn get_cache_dir(&self) -> &Path {
+        &self.paths.cache_dir
+    }
+
+    pub fn get_plugins_dir(&self) -> &Path {
+        &self.paths.plugins_dir
+    }
+
+    /// Builds the cipher for the configured save encryption, if any.
+    pub fn save_cipher(&self) -> GameResult<Option<SaveCipher>> {
+        match self.saves.encryption {
+            SaveEncryption::None => Ok(None),
+            SaveEncryption::Passphrase => {
+                let passphrase = std::env::var(SAVE_PASSPHRASE_ENV)
+                    .ok()
+                    .or_else(|| self.saves.encryption_passphrase.clone())
+                    .filter(|p| !p.is_empty())
+                    .ok_or_else(|| GameError::configuration(format!(
+                        "Save encryption needs a passphrase in saves.encryption_passphrase or {}",
+                        SAVE_PASSPHRASE_ENV
+                    )))?;
+                Ok(Some(SaveCipher::from_passphrase(passphrase)))
+            }
+            SaveEncryption::MachineKey => {
+                SaveCipher::from_key_file(self.paths.config_dir.join(SAVE_KEY_FILE)).map(Some)
+            }
+        }
+    }
+
+    /// The users of the API servers by their tokens, from the environment
+    /// variable named by `server.tokens_env`; empty if it isn't set.
+    pub fn api_tokens(&self) -> GameResult<HashMap<String, String>> {
+        let Ok(pairs) = std::env::var(&self.server.tokens_env) else {
+            return Ok(HashMap::new());
+        };
+        parse_api_tokens(&pairs).map_err(|pair| GameError::configuration(format!(
+            "{} has '{}', but needs user:token pairs separated by commas",
+            self.server.tokens_env, pair
+        )))
+    }
+
     pub fn ensure_directories(&self) -> GameResult<()> {
         let dirs = [
             &self.paths.stories_dir,
             &self.paths.saves_dir,
             &self.paths.logs_dir,
             &self.paths.config_dir,
+            &self.paths.cache_dir,
+            &self.paths.plugins_dir,
         ];
 
         for dir in &dirs {
@@ -174,6 +617,31 @@ impl Config {
         Ok(())
     }
 
+    /// Copies content from the legacy relative directories into the configured
+    /// ones. Only missing or empty directories are populated and non-empty ones
+    /// are left alone, so this is a no-op after the first run. Returns the
+    /// number of files migrated.
+    pub fn migrate_legacy_directories(&self) -> GameResult<usize> {
+        let legacy = PathConfig::legacy();
+        let pairs = [
+            (&legacy.stories_dir, &self.paths.stories_dir),
+            (&legacy.saves_dir, &self.paths.saves_dir),
+            (&legacy.logs_dir, &self.paths.logs_dir),
+            (&legacy.config_dir, &self.paths.config_dir),
+        ];
+
+        let mut migrated = 0;
+        for (from, to) in pairs {
+            migrated += migrate_directory(from, to)?;
+        }
+
+        if migrated > 0 {
+            info!("Migrated {} files from legacy directories", migrated);
+        }
+
+        Ok(migrated)
+    }
+
     pub fn validate(&self) -> GameResult<()> {
         // Validate logging level
         match self.logging.level.as_str() {
@@ -196,8 +664,14 @@ impl Config {
         if self.game.max_recent_saves == 0 {
             return Err(GameError::configuration("Max recent saves must be greater than 0"));
         }
-        if self.ui.text_width < 40 {
-            return Err(GameError::configuration("Text width must be at least 40"));
+        if self.game.event_log_capacity == 0 {
+            return Err(GameError::configuration("Event log capacity must be greater than 0"));
+        }
+        if self.ui.text_width < MIN_TEXT_WIDTH {
+            return Err(GameError::configuration(format!("Text width must be at least {}", MIN_TEXT_WIDTH)));
+        }
+        if self.ui.paragraph_indent >= self.ui.text_width / 2 {
+            return Err(GameError::configuration("Paragraph indent must be less than half the text width"));
         }
         if self.ui.page_size == 0 {
             return Err(GameError::configuration("Page size must be greater than 0"));
@@ -225,9 +699,107 @@ impl Config {
         if let Some(theme) = cli_config.theme {
             self.ui.theme = theme;
         }
+        if cli_config.dev {
+            self.game.dev_mode = true;
+        }
+        if cli_config.plain {
+            self.ui.renderer = RendererKind::Plain;
+        }
+        if let Some(channel) = cli_config.twitch_channel {
+            self.twitch.channel = Some(channel);
+        }
+    }
+
+    /// Copies the settings that are safe to change mid-game (theme, animation
+    /// speed, text width, stats header, AI narrator) from `other`. Returns the
+    /// names of the settings that actually changed.
+    pub fn apply_reloadable(&mut self, other: &Config) -> Vec<String> {
+        let mut changed = Vec::new();
+
+        if self.ui.theme != other.ui.theme {
+            self.ui.theme = other.ui.theme.clone();
+            changed.push("ui.theme".to_string());
+        }
+        if self.ui.animation_speed != other.ui.animation_speed {
+            self.ui.animation_speed = other.ui.animation_speed.clone();
+            changed.push("ui.animation_speed".to_string());
+        }
+        if self.ui.text_width != other.ui.text_width {
+            self.ui.text_width = other.ui.text_width;
+            changed.push("ui.text_width".to_string());
+        }
+        if self.ui.fit_to_terminal != other.ui.fit_to_terminal {
+            self.ui.fit_to_terminal = other.ui.fit_to_terminal;
+            changed.push("ui.fit_to_terminal".to_string());
+        }
+        if self.ui.paragraph_spacing != other.ui.paragraph_spacing {
+            self.ui.paragraph_spacing = other.ui.paragraph_spacing;
+            changed.push("ui.paragraph_spacing".to_string());
+        }
+        if self.ui.paragraph_indent != other.ui.paragraph_indent {
+            self.ui.paragraph_indent = other.ui.paragraph_indent;
+            changed.push("ui.paragraph_indent".to_string());
+        }
+        if self.ui.dyslexia_friendly != other.ui.dyslexia_friendly {
+            self.ui.dyslexia_friendly = other.ui.dyslexia_friendly;
+            changed.push("ui.dyslexia_friendly".to_string());
+        }
+        if self.ui.streamlined != other.ui.streamlined {
+            self.ui.streamlined = other.ui.streamlined;
+            changed.push("ui.streamlined".to_string());
+        }
+        if self.ui.show_choice_hints != other.ui.show_choice_hints {
+            self.ui.show_choice_hints = other.ui.show_choice_hints;
+            changed.push("ui.show_choice_hints".to_string());
+        }
+        if self.ui.mark_taken_choices != other.ui.mark_taken_choices {
+            self.ui.mark_taken_choices = other.ui.mark_taken_choices;
+            changed.push("ui.mark_taken_choices".to_string());
+        }
+        if self.ui.show_recent_events != other.ui.show_recent_events {
+            self.ui.show_recent_events = other.ui.show_recent_events;
+            changed.push("ui.show_recent_events".to_string());
+        }
+        if self.ui.recent_events_count != other.ui.recent_events_count {
+            self.ui.recent_events_count = other.ui.recent_events_count;
+            changed.push("ui.recent_events_count".to_string());
+        }
+        if self.ui.recent_event_flags != other.ui.recent_event_flags {
+            self.ui.recent_event_flags = other.ui.recent_event_flags.clone();
+            changed.push("ui.recent_event_flags".to_string());
+        }
+        if self.ui.show_notifications != other.ui.show_notifications {
+            self.ui.show_notifications = other.ui.show_notifications;
+            changed.push("ui.show_notifications".to_string());
+        }
+        if self.ui.show_stats_in_header != other.ui.show_stats_in_header {
+            self.ui.show_stats_in_header = other.ui.show_stats_in_header;
+            changed.push("ui.show_stats_in_header".to_string());
+        }
+        if self.ui.show_wallet != other.ui.show_wallet {
+            self.ui.show_wallet = other.ui.show_wallet;
+            changed.push("ui.show_wallet".to_string());
+        }
+        if self.ui.read_aloud != other.ui.read_aloud {
+            self.ui.read_aloud = other.ui.read_aloud;
+            changed.push("ui.read_aloud".to_string());
+        }
+        if self.ui.tts_command != other.ui.tts_command {
+            self.ui.tts_command = other.ui.tts_command.clone();
+            changed.push("ui.tts_command".to_string());
+        }
+        if self.ai_narrator != other.ai_narrator {
+            self.ai_narrator = other.ai_narrator.clone();
+            changed.push("ai_narrator".to_string());
+        }
+
+        changed
     }
 
     pub fn get_animation_delay_ms(&self) -> u64 {
+        if self.ui.streamlined {
+            return 0;
+        }
         match self.ui.animation_speed {
             AnimationSpeed::None => 0,
             AnimationSpeed::Slow => 150,
@@ -237,14 +809,64 @@ impl Config {
     }
 }
 
+/// `user:token,user:token` as users by token, or the first pair that isn't
+/// one.
+fn parse_api_tokens(pairs: &str) -> Result<HashMap<String, String>, String> {
+    pairs.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once(':') {
+            Some((user, token)) if !user.trim().is_empty() && !token.trim().is_empty() => {
+                Ok((token.trim().to_string(), user.trim().to_string()))
+            }
+            // Don't echo what may be a token
+            _ => Err(pair.chars().take(3).chain("...".chars()).collect()),
+        })
+        .collect()
+}
+
+fn migrate_directory(from: &Path, to: &Path) -> GameResult<usize> {
+    // An empty target, e.g. created by an earlier run or the installer, is
+    // still migrated into; one with anything in it is left alone
+    let target_in_use = std::fs::read_dir(to).is_ok_and(|mut entries| entries.next().is_some());
+    if from == to || !from.is_dir() || target_in_use {
+        return Ok(0);
+    }
+
+    std::fs::create_dir_all(to)
+        .map_err(|e| GameError::configuration(format!("Failed to create directory {:?}: {}", to, e)))?;
+
+    let entries = std::fs::read_dir(from)
+        .map_err(|e| GameError::configuration(format!("Failed to read directory {:?}: {}", from, e)))?;
+
+    let mut copied = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let target = to.join(entry.file_name());
+        match std::fs::copy(&path, &target) {
+            Ok(_) => copied += 1,
+            Err(e) => warn!("Failed to migrate {:?} to {:?}: {}", path, target, e),
+        }
+    }
+
+    Ok(copied)
+}
+
 // Configuration that can be overridden by CLI arguments
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct CliConfig {
     pub stories_dir: Option<PathBuf>,
     pub saves_dir: Option<PathBuf>,
     pub log_level: Option<String>,
     pub debug: bool,
+    pub dev: bool,
     pub theme: Option<String>,
+    pub plain: bool,
+    pub twitch_channel: Option<String>,
 }
 
 #[cfg(test)]
@@ -252,6 +874,14 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_parse_api_tokens() {
+        let tokens = parse_api_tokens("ana:s3cret, ben:hunter2,").unwrap();
+        assert_eq!(tokens.get("s3cret").map(String::as_str), Some("ana"));
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(parse_api_tokens("ana:ok,justatoken").unwrap_err(), "jus...");
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -299,6 +929,7 @@ mod tests {
             log_level: Some("debug".to_string()),
             debug: false,
             theme: Some("dark".to_string()),
+            plain: true,
             ..Default::default()
         };
 
@@ -306,6 +937,42 @@ mod tests {
         
         assert_eq!(config.logging.level, "debug");
         assert_eq!(config.ui.theme, "dark");
+        assert_eq!(config.ui.renderer, RendererKind::Plain);
+    }
+
+    #[test]
+    fn test_migrate_directory() {
+        let temp_dir = tempdir().unwrap();
+        let legacy = temp_dir.path().join("legacy");
+        let target = temp_dir.path().join("target");
+
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("save1.json"), "{}").unwrap();
+        std::fs::write(legacy.join("save2.json"), "{}").unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+
+        assert_eq!(migrate_directory(&legacy, &target).unwrap(), 2);
+        assert!(target.join("save1.json").exists());
+        assert!(legacy.join("save1.json").exists());
+
+        // Target already in use, nothing more to migrate
+        assert_eq!(migrate_directory(&legacy, &target).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_apply_reloadable() {
+        let mut config = Config::default();
+        let mut updated = Config::default();
+        updated.ui.theme = "dark".to_string();
+        updated.ui.text_width = 100;
+        updated.paths.saves_dir = PathBuf::from("/elsewhere");
+
+        let changed = config.apply_reloadable(&updated);
+
+        assert_eq!(changed, vec!["ui.theme".to_string(), "ui.text_width".to_string()]);
+        assert_eq!(config.ui.theme, "dark");
+        assert_eq!(config.ui.text_width, 100);
+        assert_ne!(config.paths.saves_dir, PathBuf::from("/elsewhere"));
     }
 
     #[test]
@@ -323,5 +990,8 @@ mod tests {
         
         config.ui.animation_speed = AnimationSpeed::Fast;
         assert_eq!(config.get_animation_delay_ms(), 25);
+
+        config.ui.streamlined = true;
+        assert_eq!(config.get_animation_delay_ms(), 0);
     }
 }
\ No newline at end of file
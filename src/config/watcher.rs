@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+use crate::config::{CliConfig, Config};
+use crate::utils::{GameError, GameResult};
+
+/// Polls a config file for changes between game turns.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    last_content: Option<String>,
+    /// Command line options, which win over the file after a reload too.
+    overrides: CliConfig,
+}
+
+impl ConfigWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let last_content = std::fs::read_to_string(&path).ok();
+
+        Self {
+            path,
+            last_content,
+            overrides: CliConfig::default(),
+        }
+    }
+
+    pub fn with_overrides(mut self, overrides: CliConfig) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the freshly parsed config if the file changed since the last poll.
+    pub fn poll(&mut self) -> GameResult<Option<Config>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            // The file may be mid-write or temporarily removed by an editor
+            Err(_) => return Ok(None),
+        };
+
+        if self.last_content.as_deref() == Some(content.as_str()) {
+            return Ok(None);
+        }
+        self.last_content = Some(content.clone());
+
+        let mut config: Config = toml::from_str(&content)
+            .map_err(|e| GameError::configuration(format!("Failed to parse config file: {}", e)))?;
+        config.merge_with_cli(self.overrides.clone());
+        config.validate()?;
+
+        Ok(Some(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_watcher_detects_changes() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let mut config = Config::default();
+        config.save_to_file(&config_path).unwrap();
+
+        let mut watcher = ConfigWatcher::new(&config_path);
+        assert!(watcher.poll().unwrap().is_none());
+
+        config.ui.theme = "dark".to_string();
+        config.save_to_file(&config_path).unwrap();
+
+        let reloaded = watcher.poll().unwrap().unwrap();
+        assert_eq!(reloaded.ui.theme, "dark");
+        assert!(watcher.poll().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_config_watcher_keeps_overrides() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let mut config = Config::default();
+        config.save_to_file(&config_path).unwrap();
+        let overrides = CliConfig { theme: Some("dark".to_string()), ..Default::default() };
+        let mut watcher = ConfigWatcher::new(&config_path).with_overrides(overrides);
+
+        config.ui.theme = "light".to_string();
+        config.ui.text_width = 100;
+        config.save_to_file(&config_path).unwrap();
+
+        let reloaded = watcher.poll().unwrap().unwrap();
+        assert_eq!(reloaded.ui.theme, "dark");
+        assert_eq!(reloaded.ui.text_width, 100);
+    }
+
+    #[test]
+    fn test_config_watcher_rejects_invalid_config() {
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+
+        let mut config = Config::default();
+        config.save_to_file(&config_path).unwrap();
+        let mut watcher = ConfigWatcher::new(&config_path);
+
+        config.ui.text_width = 10;
+        config.save_to_file(&config_path).unwrap();
+
+        assert!(watcher.poll().is_err());
+    }
+}
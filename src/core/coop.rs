@@ -0,0 +1,218 @@
+//! Co-op sessions: several participants share one game, each from their
+//! own client. Sessions don't know about transports; a frontend relays
+//! `join`, `submit` and `leave` calls and forwards the `SceneUpdate`s each
+//! participant receives.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use crate::core::GameEngine;
+use crate::core::party::majority;
+use crate::story::SceneView;
+use crate::utils::{GameError, GameResult};
+
+/// Scene updates a slow participant can fall behind by before missing some.
+const UPDATE_CAPACITY: usize = 16;
+
+/// Who gets to pick the choice in a co-op session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChoicePolicy {
+    /// The first choice submitted is made.
+    #[default]
+    FirstCome,
+    /// Once everyone has voted, the most voted choice is made; the host's
+    /// vote settles ties.
+    Vote,
+    /// Only the host's choices count.
+    HostDecides,
+}
+
+/// What participants are sent whenever the scene changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneUpdate {
+    pub scene_id: String,
+    pub title: String,
+    pub description: String,
+    /// Available choices as (ID, text) pairs, in display order.
+    pub choices: Vec<(String, String)>,
+    pub ended: bool,
+    /// The choice that led here, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chosen: Option<String>,
+}
+
+impl SceneUpdate {
+    pub fn new(scene: &SceneView, chosen: Option<String>) -> Self {
+        Self {
+            scene_id: scene.id().to_string(),
+            title: scene.scene.title.clone(),
+            description: scene.scene.description.clone(),
+            choices: scene.available_choices().map(|choice| (choice.id.clone(), choice.text.clone())).collect(),
+            ended: scene.is_ending(),
+            chosen,
+        }
+    }
+}
+
+pub struct CoopSession {
+    engine: GameEngine,
+    policy: ChoicePolicy,
+    /// Names of the participants in the order they joined; the first is the
+    /// host.
+    participants: Vec<String>,
+    /// Votes cast in the current scene, by participant.
+    votes: HashMap<String, String>,
+    updates: broadcast::Sender<SceneUpdate>,
+}
+
+impl CoopSession {
+    /// A session around `engine`, which must have a game in progress.
+    pub fn new(engine: GameEngine, policy: ChoicePolicy) -> GameResult<Self> {
+        if !engine.is_game_active() {
+            return Err(GameError::story("No active game".to_string()));
+        }
+        let (updates, _) = broadcast::channel(UPDATE_CAPACITY);
+        Ok(Self { engine, policy, participants: Vec::new(), votes: HashMap::new(), updates })
+    }
+
+    /// Adds `name` to the session. Returns the current scene and the stream
+    /// of later ones.
+    pub async fn join(&mut self, name: &str) -> GameResult<(SceneUpdate, broadcast::Receiver<SceneUpdate>)> {
+        if name.trim().is_empty() {
+            return Err(GameError::player("Participant names can't be empty".to_string()));
+        }
+        if self.participants.iter().any(|participant| participant == name) {
+            return Err(GameError::player(format!("'{}' has already joined", name)));
+        }
+        let scene = self.engine.get_current_scene().await?;
+        self.participants.push(name.to_string());
+        Ok((SceneUpdate::new(&scene, None), self.updates.subscribe()))
+    }
+
+    /// Removes `name`, handing the host role on if they had it. A vote that
+    /// was only waiting for them is settled.
+    pub async fn leave(&mut self, name: &str) -> GameResult<Option<String>> {
+        self.participants.retain(|participant| participant != name);
+        self.votes.remove(name);
+        if self.policy == ChoicePolicy::Vote && !self.votes.is_empty() && self.votes.len() == self.participants.len() {
+            return self.settle_vote().await.map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Submits `name`'s choice. Returns the choice made, if this settled
+    /// one; under `Vote` the choice waits until everyone has voted.
+    pub async fn submit(&mut self, name: &str, choice_id: &str) -> GameResult<Option<String>> {
+        if !self.participants.iter().any(|participant| participant == name) {
+            return Err(GameError::player(format!("'{}' hasn't joined", name)));
+        }
+        let scene = self.engine.get_current_scene().await?;
+        let choice = scene.get_choice(choice_id).ok_or_else(|| scene.missing_choice(choice_id))?;
+        if let Some(reason) = choice.disabled_reason() {
+            return Err(GameError::story(format!("Choice is disabled: {}", reason)));
+        }
+
+        match self.policy {
+            ChoicePolicy::FirstCome => self.choose(choice_id.to_string()).await.map(Some),
+            ChoicePolicy::HostDecides if self.host() != Some(name) => {
+                Err(GameError::player(format!("Only the host, {}, decides", self.host().unwrap_or_default())))
+            }
+            ChoicePolicy::HostDecides => self.choose(choice_id.to_string()).await.map(Some),
+            ChoicePolicy::Vote => {
+                self.votes.insert(name.to_string(), choice_id.to_string());
+                if self.votes.len() < self.participants.len() {
+                    return Ok(None);
+                }
+                self.settle_vote().await.map(Some)
+            }
+        }
+    }
+
+    pub fn host(&self) -> Option<&str> {
+        self.participants.first().map(String::as_str)
+    }
+
+    pub fn participants(&self) -> &[String] {
+        &self.participants
+    }
+
+    pub fn policy(&self) -> ChoicePolicy {
+        self.policy
+    }
+
+    pub fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    async fn settle_vote(&mut self) -> GameResult<String> {
+        // In join order, so the host's vote comes first and settles ties
+        let votes: Vec<String> = self.participants.iter()
+            .filter_map(|participant| self.votes.get(participant).cloned())
+            .collect();
+        let chosen = majority(&votes, 0).cloned()
+            .ok_or_else(|| GameError::player("No votes cast".to_string()))?;
+        self.choose(chosen).await
+    }
+
+    async fn choose(&mut self, choice_id: String) -> GameResult<String> {
+        self.engine.make_choice(&choice_id).await?;
+        self.votes.clear();
+        let scene = self.engine.get_current_scene().await?;
+        // Nobody listening is fine; they get the scene when they join
+        let _ = self.updates.send(SceneUpdate::new(&scene, Some(choice_id.clone())));
+        Ok(choice_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Choice, Scene, Story};
+
+    async fn session(policy: ChoicePolicy) -> CoopSession {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "A fork in the road");
+        start.add_choice(Choice::new("left", "Go left", "left"));
+        start.add_choice(Choice::new("right", "Go right", "right"));
+        story.add_scene(start);
+        story.add_scene(Scene::new("left", "Left", "The left path"));
+        story.add_scene(Scene::new("right", "Right", "The right path"));
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Party".to_string()).await.unwrap();
+        CoopSession::new(engine, policy).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_coop_policies() {
+        let mut coop = session(ChoicePolicy::Vote).await;
+        let (scene, mut updates) = coop.join("ana").await.unwrap();
+        assert_eq!(scene.choices.len(), 2);
+        assert!(coop.join("ana").await.is_err());
+        coop.join("ben").await.unwrap();
+        coop.join("cy").await.unwrap();
+        assert!(coop.submit("dee", "left").await.is_err());
+
+        assert_eq!(coop.submit("ana", "left").await.unwrap(), None);
+        assert_eq!(coop.submit("ben", "right").await.unwrap(), None);
+        // The last vote left ties, which the host settles
+        assert_eq!(coop.leave("cy").await.unwrap().as_deref(), Some("left"));
+        let update = updates.recv().await.unwrap();
+        assert_eq!((update.scene_id.as_str(), update.chosen.as_deref()), ("left", Some("left")));
+
+        let mut coop = session(ChoicePolicy::HostDecides).await;
+        coop.join("ana").await.unwrap();
+        coop.join("ben").await.unwrap();
+        assert!(coop.submit("ben", "right").await.is_err());
+        coop.leave("ana").await.unwrap();
+        assert_eq!(coop.host(), Some("ben"));
+        assert_eq!(coop.submit("ben", "right").await.unwrap().as_deref(), Some("right"));
+
+        let mut coop = session(ChoicePolicy::FirstCome).await;
+        coop.join("ana").await.unwrap();
+        assert!(coop.submit("ana", "up").await.is_err());
+        assert_eq!(coop.submit("ana", "left").await.unwrap().as_deref(), Some("left"));
+    }
+}
@@ -0,0 +1,180 @@
+//! Tools for story authors chasing logic bugs in dev mode.
+
+use std::collections::HashMap;
+use crate::core::{GameEvent, GameEventHandler};
+use crate::core::events::GameEventType;
+
+/// Remembers the scene in which each flag and stat last changed, from the
+/// `FlagSet` and `StatModified` events the engine emits.
+#[derive(Debug, Clone, Default)]
+pub struct VariableTracker {
+    current_scene: Option<String>,
+    flags: HashMap<String, String>,
+    stats: HashMap<String, String>,
+}
+
+/// One row of the variables panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugVariable {
+    /// "stat", "derived" or "flag".
+    pub kind: &'static str,
+    pub name: String,
+    pub value: String,
+    /// Scene the value last changed in, if it changed since the game was
+    /// started or loaded.
+    pub changed_in: Option<String>,
+}
+
+/// Where the game should stop for the debug console.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Whenever the flag is set, even to the same value.
+    Flag(String),
+    /// Whenever the player enters the scene.
+    Scene(String),
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Breakpoint::Flag(flag) => write!(f, "flag '{}' changes", flag),
+            Breakpoint::Scene(scene) => write!(f, "entering scene '{}'", scene),
+        }
+    }
+}
+
+/// Breakpoints and the ones hit by events since the console last looked.
+#[derive(Debug, Clone, Default)]
+pub struct Breakpoints {
+    breakpoints: Vec<Breakpoint>,
+    hits: Vec<Breakpoint>,
+}
+
+impl Breakpoints {
+    /// Returns false if the breakpoint was already set.
+    pub fn add(&mut self, breakpoint: Breakpoint) -> bool {
+        if self.breakpoints.contains(&breakpoint) {
+            return false;
+        }
+        self.breakpoints.push(breakpoint);
+        true
+    }
+
+    pub fn remove(&mut self, breakpoint: &Breakpoint) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|b| b != breakpoint);
+        self.breakpoints.len() != count
+    }
+
+    pub fn list(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Breakpoints hit since the last call, in the order they were hit.
+    pub fn take_hits(&mut self) -> Vec<Breakpoint> {
+        std::mem::take(&mut self.hits)
+    }
+}
+
+impl GameEventHandler for Breakpoints {
+    fn handle_event(&mut self, event: &GameEvent) {
+        let field = |name: &str| event.data.get(name).and_then(|value| value.as_str()).map(str::to_string);
+        let hit = match &event.event_type {
+            GameEventType::FlagSet => field("flag_name").map(Breakpoint::Flag),
+            GameEventType::SceneEntered => field("scene_id").map(Breakpoint::Scene),
+            _ => None,
+        };
+
+        if let Some(hit) = hit.filter(|hit| self.breakpoints.contains(hit)) {
+            if !self.hits.contains(&hit) {
+                self.hits.push(hit);
+            }
+        }
+    }
+}
+
+impl VariableTracker {
+    /// Forgets everything; the game is now in `scene_id`.
+    pub fn reset(&mut self, scene_id: &str) {
+        self.current_scene = Some(scene_id.to_string());
+        self.flags.clear();
+        self.stats.clear();
+    }
+
+    pub fn flag_changed_in(&self, flag: &str) -> Option<&str> {
+        self.flags.get(flag).map(String::as_str)
+    }
+
+    pub fn stat_changed_in(&self, stat: &str) -> Option<&str> {
+        self.stats.get(stat).map(String::as_str)
+    }
+}
+
+impl GameEventHandler for VariableTracker {
+    fn handle_event(&mut self, event: &GameEvent) {
+        let field = |name: &str| event.data.get(name).and_then(|value| value.as_str()).map(str::to_string);
+
+        match &event.event_type {
+            // Effects of a choice are applied before the next scene is entered
+            GameEventType::ChoiceMade => self.current_scene = field("from_scene"),
+            GameEventType::SceneEntered => self.current_scene = field("scene_id"),
+            GameEventType::FlagSet => {
+                if let (Some(flag), Some(scene)) = (field("flag_name"), self.current_scene.clone()) {
+                    self.flags.insert(flag, scene);
+                }
+            }
+            GameEventType::StatModified => {
+                if let (Some(stat), Some(scene)) = (field("stat_name"), self.current_scene.clone()) {
+                    self.stats.insert(stat, scene);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::{Choice, Scene};
+
+    #[test]
+    fn test_variable_tracker() {
+        let mut tracker = VariableTracker::default();
+        tracker.reset("gate");
+        tracker.handle_event(&GameEvent::flag_set("gate_open", &serde_json::json!(true)));
+
+        let choice = Choice::new("enter", "Enter", "hall");
+        tracker.handle_event(&GameEvent::choice_made(&choice, "gate"));
+        tracker.handle_event(&GameEvent::stat_modified("strength", 10, 11));
+        tracker.handle_event(&GameEvent::scene_entered(&Scene::new("hall", "Hall", "A hall.")));
+        tracker.handle_event(&GameEvent::flag_set("hall_seen", &serde_json::json!(true)));
+
+        assert_eq!(tracker.flag_changed_in("gate_open"), Some("gate"));
+        assert_eq!(tracker.stat_changed_in("strength"), Some("gate"));
+        assert_eq!(tracker.flag_changed_in("hall_seen"), Some("hall"));
+        assert_eq!(tracker.flag_changed_in("unknown"), None);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut breakpoints = Breakpoints::default();
+        assert!(breakpoints.add(Breakpoint::Flag("gate_open".to_string())));
+        assert!(!breakpoints.add(Breakpoint::Flag("gate_open".to_string())));
+        breakpoints.add(Breakpoint::Scene("hall".to_string()));
+
+        breakpoints.handle_event(&GameEvent::flag_set("other", &serde_json::json!(1)));
+        assert!(breakpoints.take_hits().is_empty());
+
+        breakpoints.handle_event(&GameEvent::flag_set("gate_open", &serde_json::json!(true)));
+        breakpoints.handle_event(&GameEvent::scene_entered(&Scene::new("hall", "Hall", "A hall.")));
+        assert_eq!(breakpoints.take_hits(), vec![
+            Breakpoint::Flag("gate_open".to_string()),
+            Breakpoint::Scene("hall".to_string()),
+        ]);
+        assert!(breakpoints.take_hits().is_empty());
+
+        assert!(breakpoints.remove(&Breakpoint::Scene("hall".to_string())));
+        assert_eq!(breakpoints.list().len(), 1);
+    }
+}
@@ -1,14 +1,70 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use futures::Stream;
 use tokio::sync::Mutex;
-use crate::core::{GameState, Player, PlayerStats, GameEvent, GameEventHandler, EventLogger};
-use crate::story::{Story, Scene, Choice, Condition, Effect, ConditionType, ComparisonOperator, EffectType, EffectOperation};
+use uuid::Uuid;
+use crate::core::{GameState, Player, PlayerStats, SeededRng, GameEvent, GameEventHandler, EventLogger, EnginePlugin, EngineHook, HookRegistry, Breakpoint, Breakpoints, DebugVariable, VariableTracker, Watchers, HotseatMode, Party, FlagLimits, DEFAULT_VISIT_HISTORY};
+use crate::story::{Story, Scene, SceneMap, SceneView, Currency, Choice, Condition, Effect, ConditionType, ComparisonOperator, EffectType, EffectOperation};
 use crate::utils::{GameError, GameResult};
-use tracing::{info, warn, error, debug};
+use tracing::{info, warn, debug};
+
+/// In-memory copy of the engine's mutable state, see `GameEngine::snapshot`.
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    pub game_state: Option<GameState>,
+    pub last_event_id: Option<Uuid>,
+    pub taken_at: DateTime<Utc>,
+}
+
+/// Result of `GameEngine::preview_choice`: what would happen if the choice
+/// were made now.
+#[derive(Debug, Clone)]
+pub struct ChoiceOutcome {
+    pub choice_id: String,
+    pub target_scene_id: String,
+    pub available: bool,
+    pub disabled_reason: Option<String>,
+    pub state_after: GameState,
+    pub events: Vec<GameEvent>,
+}
+
+/// How a saved game fits the loaded story, see
+/// `GameEngine::check_save_compatibility`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveCompatibility {
+    /// Story version recorded in the save; `None` for older saves.
+    pub saved_story_version: Option<String>,
+    pub story_version: String,
+    /// Scenes the save refers to that the story no longer has.
+    pub missing_scenes: Vec<String>,
+}
+
+impl SaveCompatibility {
+    pub fn version_changed(&self) -> bool {
+        self.saved_story_version.as_deref().is_some_and(|version| version != self.story_version)
+    }
+
+    /// Whether the save can be loaded as is.
+    pub fn is_compatible(&self) -> bool {
+        self.missing_scenes.is_empty()
+    }
+}
 
 pub struct GameEngine {
     story: Option<Story>,
     game_state: Option<GameState>,
     event_handler: Arc<Mutex<EventLogger>>,
+    variable_tracker: Arc<Mutex<VariableTracker>>,
+    breakpoints: Arc<Mutex<Breakpoints>>,
+    watchers: Arc<Mutex<Watchers>>,
+    plugins: Vec<Arc<dyn EnginePlugin>>,
+    hooks: HookRegistry,
+    /// Scene visits kept in order in started and loaded games.
+    visit_history: usize,
+    flag_limits: FlagLimits,
+    /// Track variable origins and breakpoints for the debug console.
+    debug_tools: bool,
 }
 
 impl GameEngine {
@@ -17,16 +73,69 @@ impl GameEngine {
             story: None,
             game_state: None,
             event_handler: Arc::new(Mutex::new(EventLogger::default())),
+            variable_tracker: Arc::new(Mutex::new(VariableTracker::default())),
+            breakpoints: Arc::new(Mutex::new(Breakpoints::default())),
+            watchers: Arc::new(Mutex::new(Watchers::default())),
+            plugins: Vec::new(),
+            hooks: HookRegistry::default(),
+            visit_history: DEFAULT_VISIT_HISTORY,
+            flag_limits: FlagLimits::default(),
+            debug_tools: false,
         }
     }
 
-    pub async fn load_story(&mut self, story: Story) -> GameResult<()> {
+    /// Keep up to `capacity` events in the event history.
+    pub fn with_event_log_capacity(mut self, capacity: usize) -> Self {
+        self.event_handler = Arc::new(Mutex::new(EventLogger::new(capacity)));
+        self
+    }
+
+    /// Keep up to `limit` scene visits in order in the game state.
+    pub fn with_visit_history(mut self, limit: usize) -> Self {
+        self.visit_history = limit;
+        self
+    }
+
+    /// Caps the flags of started and loaded games.
+    pub fn with_flag_limits(mut self, limits: FlagLimits) -> Self {
+        self.flag_limits = limits;
+        self
+    }
+
+    /// Feed the debug console's variable tracker and breakpoints (dev mode).
+    pub fn with_debug_tools(mut self, enabled: bool) -> Self {
+        self.debug_tools = enabled;
+        self
+    }
+
+    pub fn add_hook(&mut self, hook: Arc<dyn EngineHook>) {
+        self.hooks.add(hook);
+    }
+
+    /// Registry for closure hooks (`on_scene_enter`, `before_choice`,
+    /// `after_effects`).
+    pub fn hooks_mut(&mut self) -> &mut HookRegistry {
+        &mut self.hooks
+    }
+
+    /// Registers a plugin for custom effects, conditions and events.
+    pub fn register_plugin(&mut self, plugin: Arc<dyn EnginePlugin>) {
+        info!("Registered plugin: {}", plugin.name());
+        self.plugins.push(plugin);
+    }
+
+    pub fn plugins(&self) -> &[Arc<dyn EnginePlugin>] {
+        &self.plugins
+    }
+
+    pub async fn load_story(&mut self, mut story: Story) -> GameResult<()> {
         info!("Loading story: {} ({})", story.title, story.id);
         
-        // Validate story
+        // Index and validate story; `validate` reports duplicate IDs with the rest
+        let _ = story.rebuild_scene_index();
+        story.parse_formulas();
         if let Err(errors) = story.validate() {
-            let error_msg = errors.join("; ");
-            return Err(GameError::story(format!("Story validation failed: {}", error_msg)));
+            return Err(GameError::story_errors("Story validation failed", &errors));
         }
 
         self.story = Some(story);
@@ -43,31 +152,62 @@ impl GameEngine {
 
         info!("Starting new game for player: {}", player_name);
         
-        let player = Player::new(player_name.clone(), Some(story.initial_player_stats.clone()));
+        let mut player = Player::new(player_name.clone(), Some(story.initial_player_stats.clone()));
+        player.level_curve = story.leveling.clone();
         let mut game_state = GameState::new(
             story.id.clone(),
             story.starting_scene_id.clone(),
             player,
         );
+        game_state.story_version = Some(story.version.clone());
+        game_state.visited_scenes.set_history_limit(self.visit_history);
+        game_state.flag_limits = self.flag_limits;
+        for (name, variable) in &story.variables {
+            game_state.set_flag(name, variable.initial_value())?;
+        }
 
         // Visit the starting scene
         game_state.visit_scene(&story.starting_scene_id);
         
         // Apply starting scene effects if any
-        if let Some(starting_scene) = story.get_scene(&story.starting_scene_id) {
+        let mut events = Vec::new();
+        if let Some(starting_scene) = story.get_shared_scene(&story.starting_scene_id) {
             if let Some(effects) = &starting_scene.effects {
-                self.apply_effects(&mut game_state, effects).await?;
+                self.apply_effects(&mut game_state, effects, &mut events).await?;
             }
+            self.hooks.run_scene_enter(&starting_scene, &mut game_state);
         }
 
-        self.game_state = Some(game_state);
-        
+        self.reset_variable_tracker(&story.starting_scene_id);
+        self.emit_events(events).await;
         self.emit_event(GameEvent::game_started(&story.id, &player_name)).await;
+        self.game_state = Some(game_state);
+        self.refresh_watchers().await;
         
         Ok(())
     }
 
-    pub async fn load_game(&mut self, game_state: GameState) -> GameResult<()> {
+    /// Starts a new game shared by `names`, see `Party`. The first name
+    /// plays first.
+    pub async fn start_hotseat_game(&mut self, mode: HotseatMode, names: &[String]) -> GameResult<()> {
+        let first = names.first()
+            .ok_or_else(|| GameError::player("Hotseat play needs at least two players".to_string()))?;
+        self.start_new_game(first.clone()).await?;
+        let game_state = self.game_state.as_mut().expect("game was just started");
+        match Party::new(mode, names, &game_state.player) {
+            Ok(party) => game_state.party = Some(party),
+            Err(e) => {
+                self.game_state = None;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads a saved game. If its scene was removed from the story, the
+    /// player resumes at the story's fallback scene instead and a
+    /// `scene_recovered` event is emitted.
+    pub async fn load_game(&mut self, mut game_state: GameState) -> GameResult<()> {
         let story = self.story.as_ref()
             .ok_or_else(|| GameError::story("No story loaded".to_string()))?;
 
@@ -76,73 +216,353 @@ impl GameEngine {
         }
 
         info!("Loading game state for player: {}", game_state.player.name);
+
+        let mut recovery = None;
+        if !story.has_scene(&game_state.current_scene_id) {
+            let resume_scene_id = story.resume_scene_id().to_string();
+            warn!(
+                "Saved scene '{}' no longer exists, resuming at '{}'",
+                game_state.current_scene_id, resume_scene_id
+            );
+            recovery = Some(GameEvent::custom("scene_recovered", serde_json::json!({
+                "missing_scene_id": game_state.current_scene_id,
+                "resumed_scene_id": resume_scene_id,
+            })));
+            game_state.current_scene_id = resume_scene_id;
+        }
+        game_state.visited_scenes.set_history_limit(self.visit_history);
+        game_state.flag_limits = self.flag_limits;
+        // Saves follow the story's current curve
+        game_state.player.level_curve = story.leveling.clone();
+        for character in game_state.characters.values_mut() {
+            character.level_curve = story.leveling.clone();
+        }
+        // Variables declared since the game was saved start at their defaults
+        for (name, variable) in &story.variables {
+            if game_state.get_flag(name).is_none() {
+                game_state.set_flag(name, variable.initial_value())?;
+            }
+        }
         
+        self.reset_variable_tracker(&game_state.current_scene_id);
         self.game_state = Some(game_state);
+        self.refresh_watchers().await;
         self.emit_event(GameEvent::game_loaded("loaded_game")).await;
+        if let Some(event) = recovery {
+            self.emit_event(event).await;
+        }
         
         Ok(())
     }
 
-    pub async fn get_current_scene(&self) -> GameResult<Scene> {
+    /// The player uses a consumable from their inventory, subject to its
+    /// `cooldown_scenes` and `uses_per_scene` properties.
+    pub async fn use_item(&mut self, item_id: &str) -> GameResult<()> {
+        let game_state = self.game_state.as_mut()
+            .ok_or_else(|| GameError::story("No active game".to_string()))?;
+
+        let item = game_state.player.get_item(item_id)
+            .ok_or_else(|| GameError::player(format!("Item not found: {}", item_id)))?;
+        if let Some(reason) = game_state.item_use_blocked_reason(item) {
+            return Err(GameError::player(reason));
+        }
+
+        let mut events = Vec::new();
+        consume_item(game_state, item_id, &mut events)?;
+        game_state.record_item_use(item_id);
+        game_state.mark_dirty();
+
+        self.emit_events(events).await;
+        Ok(())
+    }
+
+    /// Spends the player's pending level-up points and emits the level-up
+    /// with the chosen allocation.
+    pub async fn allocate_level_points(&mut self, allocation: HashMap<String, i32>) -> GameResult<()> {
+        let game_state = self.game_state.as_mut()
+            .ok_or_else(|| GameError::story("No active game".to_string()))?;
+
+        let pending = game_state.player.allocate_points(&allocation)?;
+        game_state.mark_dirty();
+        let stats = &game_state.player.stats;
+        let event = GameEvent::level_up_allocated(pending.from_level, stats.level, stats.experience, &allocation);
+
+        self.emit_event(event).await;
+        Ok(())
+    }
+
+    /// Compares a saved game with the loaded story before loading it.
+    pub fn check_save_compatibility(&self, game_state: &GameState) -> GameResult<SaveCompatibility> {
+        let story = self.story.as_ref()
+            .ok_or_else(|| GameError::story("No story loaded".to_string()))?;
+
+        let mut missing_scenes: Vec<String> = Vec::new();
+        let mut visited: Vec<&String> = game_state.visited_scenes.scene_ids().collect();
+        visited.sort();
+        for scene_id in std::iter::once(&game_state.current_scene_id).chain(visited) {
+            if !story.has_scene(scene_id) && !missing_scenes.contains(scene_id) {
+                missing_scenes.push(scene_id.clone());
+            }
+        }
+
+        Ok(SaveCompatibility {
+            saved_story_version: game_state.story_version.clone(),
+            story_version: story.version.clone(),
+            missing_scenes,
+        })
+    }
+
+    /// Loads a save made with another version of the story, dropping
+    /// references to scenes that no longer exist. If the current scene is
+    /// gone the player resumes as described in `load_game`.
+    pub async fn load_game_best_effort(&mut self, mut game_state: GameState) -> GameResult<SaveCompatibility> {
+        let compatibility = self.check_save_compatibility(&game_state)?;
+        let story = self.story.as_ref()
+            .ok_or_else(|| GameError::story("No story loaded".to_string()))?;
+
+        game_state.visited_scenes.retain(|scene_id| story.has_scene(scene_id));
+        game_state.story_version = Some(story.version.clone());
+
+        if !compatibility.missing_scenes.is_empty() {
+            warn!("Dropped {} missing scene(s) from save", compatibility.missing_scenes.len());
+        }
+
+        self.load_game(game_state).await?;
+        Ok(compatibility)
+    }
+
+    pub async fn get_current_scene(&self) -> GameResult<SceneView> {
         let story = self.story.as_ref()
             .ok_or_else(|| GameError::story("No story loaded".to_string()))?;
         
         let game_state = self.game_state.as_ref()
             .ok_or_else(|| GameError::story("No active game".to_string()))?;
 
-        let scene = story.get_scene(&game_state.current_scene_id)
-            .ok_or_else(|| GameError::scene_not_found(&game_state.current_scene_id))?
-            .clone();
+        let scene = story.get_shared_scene(&game_state.current_scene_id)
+            .ok_or_else(|| {
+                GameError::scene_not_found(&game_state.current_scene_id)
+                    .with_story(&story.id)
+                    .with_suggestion("scene", story.similar_scene_id(&game_state.current_scene_id))
+            })?;
 
-        // Process the scene (filter choices based on conditions, etc.)
-        Ok(self.process_scene(scene, game_state).await?)
+        // Process the scene (work out which choices are available)
+        self.process_scene(&scene, game_state).await
     }
 
     pub async fn make_choice(&mut self, choice_id: &str) -> GameResult<()> {
         let current_scene = self.get_current_scene().await?;
         
-        let choice = current_scene.get_choice(choice_id)
-            .ok_or_else(|| GameError::choice_not_found(choice_id))?;
+        let choice_view = current_scene.get_choice(choice_id)
+            .ok_or_else(|| current_scene.missing_choice(choice_id))?;
+
+        if let Some(reason) = choice_view.disabled_reason() {
+            return Err(GameError::story(format!("Choice is disabled: {}", reason)));
+        }
+
+        let choice = choice_view.choice;
 
-        if choice.disabled.unwrap_or(false) {
-            return Err(GameError::story(format!(
-                "Choice is disabled: {}", 
-                choice.disabled_reason.as_deref().unwrap_or("Unknown reason")
-            )));
+        if let Some(game_state) = &self.game_state {
+            if let Some(reason) = self.hooks.check_choice(choice, game_state) {
+                return Err(GameError::choice_vetoed(reason));
+            }
         }
 
         info!("Player chose: {} ({})", choice.text, choice_id);
 
-        let game_state = self.game_state.as_mut()
+        // Work on a copy that's only kept if every effect succeeds, so a
+        // failing effect can't leave the game half changed
+        let mut game_state = self.game_state.clone()
             .ok_or_else(|| GameError::story("No active game".to_string()))?;
+        let mut events = Vec::new();
+        self.apply_choice(&mut game_state, choice, current_scene.id(), &mut events).await?;
+        if let Some(party) = game_state.party.as_mut() {
+            party.pass_turn(&mut game_state.player);
+        }
+        self.game_state = Some(game_state);
+        self.emit_events(events).await;
+        Ok(())
+    }
+
+    /// Makes the choice most of the party voted for; `votes` has one choice
+    /// ID per seat, in seat order. Returns the choice made.
+    pub async fn vote_choice(&mut self, votes: &[String]) -> GameResult<String> {
+        let party = self.game_state.as_ref()
+            .and_then(|state| state.party.as_ref())
+            .ok_or_else(|| GameError::story("No hotseat game".to_string()))?;
+        if votes.len() != party.seats.len() {
+            return Err(GameError::player(format!("Expected {} votes, got {}", party.seats.len(), votes.len())));
+        }
+        let chosen = party.tally(votes).cloned()
+            .ok_or_else(|| GameError::player("No votes cast".to_string()))?;
+
+        self.make_choice(&chosen).await?;
+        if let Some(party) = self.game_state.as_mut().and_then(|state| state.party.as_mut()) {
+            party.record_votes(votes, &chosen);
+        }
+        Ok(chosen)
+    }
+
+    /// Evaluates a choice against a copy of the current state without
+    /// committing anything or logging events.
+    pub async fn preview_choice(&self, choice_id: &str) -> GameResult<ChoiceOutcome> {
+        let current_scene = self.get_current_scene().await?;
+
+        let choice_view = current_scene.get_choice(choice_id)
+            .ok_or_else(|| current_scene.missing_choice(choice_id))?;
 
+        let game_state = self.game_state.as_ref()
+            .ok_or_else(|| GameError::story("No active game".to_string()))?;
+
+        let mut outcome = ChoiceOutcome {
+            choice_id: choice_id.to_string(),
+            target_scene_id: choice_view.choice.target_scene_id.clone(),
+            available: true,
+            disabled_reason: None,
+            state_after: game_state.clone(),
+            events: Vec::new(),
+        };
+
+        let disabled_reason = choice_view.disabled_reason()
+            .map(str::to_string)
+            .or_else(|| self.hooks.check_choice(choice_view.choice, game_state));
+
+        if let Some(reason) = disabled_reason {
+            outcome.available = false;
+            outcome.disabled_reason = Some(reason);
+            return Ok(outcome);
+        }
+
+        self.apply_choice(&mut outcome.state_after, choice_view.choice, current_scene.id(), &mut outcome.events).await?;
+        Ok(outcome)
+    }
+
+    async fn apply_choice(&self, game_state: &mut GameState, choice: &Choice, from_scene_id: &str, events: &mut Vec<GameEvent>) -> GameResult<()> {
         // Emit choice made event
-        self.emit_event(GameEvent::choice_made(choice, &current_scene.id)).await;
+        events.push(GameEvent::choice_made(choice, from_scene_id));
+        game_state.record_choice(from_scene_id, &choice.id);
 
         // Apply choice effects
         if let Some(effects) = &choice.effects {
-            self.apply_effects(game_state, effects).await?;
+            self.apply_effects(game_state, effects, events).await?;
         }
 
-        // Move to target scene
+        self.enter_scene(game_state, &choice.target_scene_id, events).await
+    }
+
+    /// Moves to the current scene's auto-advance target, used when the player
+    /// lets the scene's timer run out.
+    pub async fn auto_advance(&mut self) -> GameResult<()> {
+        let current_scene = self.get_current_scene().await?;
+        let auto_advance = current_scene.scene.auto_advance.as_ref()
+            .ok_or_else(|| GameError::story(format!("Scene '{}' does not auto-advance", current_scene.id())))?;
+
+        info!("Auto-advancing from '{}' to '{}'", current_scene.id(), auto_advance.target_scene_id);
+
+        let mut game_state = self.game_state.clone()
+            .ok_or_else(|| GameError::story("No active game".to_string()))?;
+        let mut events = vec![GameEvent::custom("auto_advanced", serde_json::json!({
+            "from_scene_id": current_scene.id(),
+            "to_scene_id": auto_advance.target_scene_id,
+        }))];
+        self.enter_scene(&mut game_state, &auto_advance.target_scene_id, &mut events).await?;
+        self.game_state = Some(game_state);
+        self.emit_events(events).await;
+        Ok(())
+    }
+
+    async fn enter_scene(&self, game_state: &mut GameState, target_scene_id: &str, events: &mut Vec<GameEvent>) -> GameResult<()> {
         let old_scene_id = game_state.current_scene_id.clone();
-        game_state.visit_scene(&choice.target_scene_id);
+        game_state.visit_scene(target_scene_id);
+        game_state.mark_dirty();
 
         // Apply target scene effects
         if let Some(story) = &self.story {
-            if let Some(target_scene) = story.get_scene(&choice.target_scene_id) {
-                self.emit_event(GameEvent::scene_entered(target_scene)).await;
+            if let Some(target_scene) = story.get_shared_scene(target_scene_id) {
+                events.push(GameEvent::scene_entered(&target_scene));
                 
                 if let Some(effects) = &target_scene.effects {
-                    self.apply_effects(game_state, effects).await?;
+                    self.apply_effects(game_state, effects, events).await?;
+                }
+                self.hooks.run_scene_enter(&target_scene, game_state);
+                if let Some(ending_id) = target_scene.ending_id() {
+                    game_state.completed_ending = Some(ending_id.to_string());
+                    events.push(GameEvent::game_ended(&target_scene));
                 }
             }
         }
 
-        debug!("Moved from scene '{}' to '{}'", old_scene_id, choice.target_scene_id);
+        debug!("Moved from scene '{}' to '{}'", old_scene_id, target_scene_id);
         Ok(())
     }
 
+    /// Captures the current game state and event log position without touching
+    /// disk. Used for undo, checkpoints and exploring choices ahead of time.
+    pub async fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            game_state: self.game_state.clone(),
+            last_event_id: self.last_event_id().await,
+            taken_at: Utc::now(),
+        }
+    }
+
+    /// ID of the newest event in the history, for `events_since`.
+    pub async fn last_event_id(&self) -> Option<Uuid> {
+        self.event_handler.lock().await
+            .get_events()
+            .last()
+            .map(|event| event.id)
+    }
+
+    /// Puts the engine back to `snapshot`, discarding events logged since.
+    pub async fn restore(&mut self, snapshot: StateSnapshot) -> GameResult<()> {
+        if let (Some(story), Some(game_state)) = (&self.story, &snapshot.game_state) {
+            if game_state.story_id != story.id {
+                return Err(GameError::story("Snapshot story ID does not match loaded story".to_string()));
+            }
+        }
+
+        if !self.event_handler.lock().await.rewind_to(snapshot.last_event_id) {
+            warn!("Snapshot event position is no longer in the event log; keeping events");
+        }
+
+        self.game_state = snapshot.game_state;
+        self.refresh_watchers().await;
+        debug!("Restored snapshot taken at {}", snapshot.taken_at);
+        Ok(())
+    }
+
+    /// The story's derived stats for the current game, by name.
+    pub fn derived_stats(&self) -> GameResult<Vec<(String, i64)>> {
+        let (Some(story), Some(game_state)) = (self.story.as_ref(), self.game_state.as_ref()) else {
+            return Ok(Vec::new());
+        };
+
+        story.derived_stats.keys()
+            .map(|name| {
+                let value = story.derived_stat(name, game_state)?.unwrap_or(0);
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+
+    pub fn story(&self) -> Option<&Story> {
+        self.story.as_ref()
+    }
+
+    pub fn story_metadata(&self) -> Option<&HashMap<String, serde_json::Value>> {
+        self.story.as_ref()?.metadata.as_ref()
+    }
+
+    /// Visited scenes around the current one, up to `depth` steps away.
+    pub fn scene_map(&self, depth: usize) -> Option<SceneMap> {
+        SceneMap::build(self.story.as_ref()?, self.game_state.as_ref()?, depth)
+    }
+
+    /// The loaded story's currency, if it has one.
+    pub fn currency(&self) -> Option<&Currency> {
+        self.story.as_ref()?.currency.as_ref()
+    }
+
     pub fn get_game_state(&self) -> Option<&GameState> {
         self.game_state.as_ref()
     }
@@ -151,6 +571,10 @@ impl GameEngine {
         self.game_state.as_mut()
     }
 
+    pub fn has_unsaved_progress(&self) -> bool {
+        self.game_state.as_ref().is_some_and(|state| state.has_unsaved_changes())
+    }
+
     pub fn is_game_active(&self) -> bool {
         self.story.is_some() && self.game_state.is_some()
     }
@@ -168,44 +592,91 @@ impl GameEngine {
             .ok_or_else(|| GameError::save_load("No active game to save".to_string()))?;
 
         game_state.mark_saved();
+        let saved_state = game_state.clone();
         
         self.emit_event(GameEvent::game_saved(&save_name)).await;
         info!("Game saved: {}", save_name);
         
-        Ok(game_state.clone())
+        Ok(saved_state)
     }
 
-    async fn process_scene(&self, mut scene: Scene, game_state: &GameState) -> GameResult<Scene> {
-        // Process choices - filter and update based on conditions
-        let mut processed_choices = Vec::new();
+    async fn process_scene(&self, scene: &Arc<Scene>, game_state: &GameState) -> GameResult<SceneView> {
+        // Work out which choices are available based on their conditions
+        let mut available = Vec::with_capacity(scene.choices.len());
+        let mut reasons = Vec::with_capacity(scene.choices.len());
         
-        for choice in scene.choices {
-            let mut processed_choice = choice.clone();
+        for choice in &scene.choices {
+            let mut is_available = !choice.disabled.unwrap_or(false);
+            let mut reason = None;
             
-            // Check if choice should be disabled based on conditions
-            if let Some(conditions) = &choice.conditions {
-                if !self.check_conditions(conditions, game_state).await? {
-                    processed_choice.disabled = Some(true);
-                    if processed_choice.disabled_reason.is_none() {
-                        processed_choice.disabled_reason = Some("Requirements not met".to_string());
+            if is_available {
+                if let Some(conditions) = &choice.conditions {
+                    if let Some(failed) = self.first_failed_condition(conditions, game_state).await? {
+                        is_available = false;
+                        reason = self.describe_failed_condition(failed, game_state, false).await?;
                     }
                 }
             }
             
-            processed_choices.push(processed_choice);
+            available.push(is_available);
+            reasons.push(reason);
         }
         
-        scene.choices = processed_choices;
-        Ok(scene)
+        let mut view = SceneView::new(Arc::clone(scene), available).with_disabled_reasons(reasons);
+        if scene.shuffle_choices {
+            // Seeded per visit, so the order holds while the player stays in the scene
+            let key = format!("{}#{}", scene.id, game_state.current_turn());
+            view = view.with_shuffled_choices(&mut SeededRng::for_key(game_state.seed, &key));
+        }
+        Ok(view)
     }
 
-    async fn check_conditions(&self, conditions: &[Condition], game_state: &GameState) -> GameResult<bool> {
+    async fn first_failed_condition<'c>(&self, conditions: &'c [Condition], game_state: &GameState) -> GameResult<Option<&'c Condition>> {
         for condition in conditions {
             if !self.check_condition(condition, game_state).await? {
-                return Ok(false);
+                return Ok(Some(condition));
+            }
+        }
+        Ok(None)
+    }
+
+    /// What the player is missing for `condition`, which failed. A failed
+    /// `Any` group names each alternative ("Requires: Sword or Strength 99"),
+    /// leaving out the "Requires" of an `alternative` after the first, and
+    /// `Not` groups aren't described.
+    async fn describe_failed_condition(&self, condition: &Condition, game_state: &GameState, alternative: bool) -> GameResult<Option<String>> {
+        match condition.condition_type {
+            ConditionType::All => match self.first_failed_condition(&condition.conditions, game_state).await? {
+                Some(failed) => Box::pin(self.describe_failed_condition(failed, game_state, alternative)).await,
+                None => Ok(None),
+            },
+            ConditionType::Any => {
+                let mut alternatives = Vec::with_capacity(condition.conditions.len());
+                for (index, option) in condition.conditions.iter().enumerate() {
+                    match Box::pin(self.describe_failed_condition(option, game_state, alternative || index > 0)).await? {
+                        Some(description) => alternatives.push(description),
+                        None => return Ok(None),
+                    }
+                }
+                Ok((!alternatives.is_empty()).then(|| alternatives.join(" or ")))
+            }
+            ConditionType::Not => Ok(None),
+            _ => {
+                let item_name = self.item_name(&condition.key, game_state);
+                Ok(match alternative {
+                    true => condition.describe_alternative(item_name),
+                    false => condition.describe_requirement(item_name),
+                })
             }
         }
-        Ok(true)
+    }
+
+    /// Display name of an item, from the story's catalog or the inventory.
+    fn item_name<'s>(&'s self, item_id: &str, game_state: &'s GameState) -> Option<&'s str> {
+        self.story.as_ref()
+            .and_then(|story| story.items.get(item_id))
+            .map(|item| item.name.as_str())
+            .or_else(|| game_state.player.get_item(item_id).map(|item| item.name.as_str()))
     }
 
     async fn check_condition(&self, condition: &Condition, game_state: &GameState) -> GameResult<bool> {
@@ -215,15 +686,13 @@ impl GameEngine {
                     .unwrap_or(serde_json::Value::Null)
             }
             ConditionType::Stat => {
-                let stat_value = match condition.key.as_str() {
-                    "health" => game_state.player.stats.health,
-                    "max_health" => game_state.player.stats.max_health,
-                    "experience" => game_state.player.stats.experience,
-                    "level" => game_state.player.stats.level,
-                    "strength" => game_state.player.stats.strength,
-                    "intelligence" => game_state.player.stats.intelligence,
-                    "charisma" => game_state.player.stats.charisma,
-                    _ => return Err(GameError::story(format!("Unknown stat: {}", condition.key))),
+                let stat_value = match game_state.player.stats.get(&condition.key) {
+                    Some(value) => value as i64,
+                    None => self.story.as_ref()
+                        .map(|story| story.derived_stat(&condition.key, game_state))
+                        .transpose()?
+                        .flatten()
+                        .ok_or_else(|| GameError::story(format!("Unknown stat: {}", condition.key)))?,
                 };
                 serde_json::Value::Number(serde_json::Number::from(stat_value))
             }
@@ -239,10 +708,33 @@ impl GameEngine {
             ConditionType::Level => {
                 serde_json::Value::Number(serde_json::Number::from(game_state.player.stats.level))
             }
+            ConditionType::ActiveCharacter => {
+                serde_json::Value::Bool(game_state.active_character == condition.key)
+            }
             ConditionType::Custom => {
-                // For custom conditions, we'll just return the flag value or false
-                game_state.get_flag(&condition.key).cloned()
-                    .unwrap_or(serde_json::Value::Bool(false))
+                match self.plugins.iter().find(|p| p.handles_condition(&condition.key)) {
+                    Some(plugin) => plugin.condition_value(condition, game_state)?,
+                    // Without a plugin, custom conditions read the flag value or false
+                    None => game_state.get_flag(&condition.key).cloned()
+                        .unwrap_or(serde_json::Value::Bool(false)),
+                }
+            }
+            ConditionType::Macro => {
+                return Err(GameError::story(format!("Condition macro '{}' was not expanded", condition.key)));
+            }
+            ConditionType::All => {
+                return Ok(Box::pin(self.first_failed_condition(&condition.conditions, game_state)).await?.is_none());
+            }
+            ConditionType::Any => {
+                for alternative in &condition.conditions {
+                    if Box::pin(self.check_condition(alternative, game_state)).await? {
+                        return Ok(true);
+                    }
+                }
+                return Ok(false);
+            }
+            ConditionType::Not => {
+                return Ok(Box::pin(self.first_failed_condition(&condition.conditions, game_state)).await?.is_some());
             }
         };
 
@@ -299,20 +791,41 @@ impl GameEngine {
         }
     }
 
-    async fn apply_effects(&mut self, game_state: &mut GameState, effects: &[Effect]) -> GameResult<()> {
+    async fn apply_effects(&self, game_state: &mut GameState, effects: &[Effect], events: &mut Vec<GameEvent>) -> GameResult<()> {
         for effect in effects {
-            self.apply_effect(game_state, effect).await?;
+            let old_stats = game_state.player.stats.clone();
+            let old_character = game_state.active_character.clone();
+            self.apply_effect(game_state, effect, events).await?;
+            // Switching protagonist isn't a level-up, even if the new one is higher
+            if game_state.active_character == old_character {
+                push_level_up(&old_stats, &game_state.player.stats, events);
+            }
         }
+        self.hooks.run_after_effects(effects, game_state);
         Ok(())
     }
 
-    async fn apply_effect(&mut self, game_state: &mut GameState, effect: &Effect) -> GameResult<()> {
+    async fn apply_effect(&self, game_state: &mut GameState, effect: &Effect, events: &mut Vec<GameEvent>) -> GameResult<()> {
         match &effect.effect_type {
             EffectType::SetFlag => {
                 let old_value = game_state.get_flag(&effect.key).cloned();
-                game_state.set_flag(&effect.key, effect.value.clone());
-                self.emit_event(GameEvent::flag_set(&effect.key, &effect.value)).await;
-                debug!("Set flag '{}' to {:?} (was: {:?})", effect.key, effect.value, old_value);
+                // Numeric flags, such as money, can be changed by an amount,
+                // stopping at the i64 limits rather than overflowing
+                let new_value = match (&effect.operation, effect.value.as_i64()) {
+                    (Some(operation), Some(amount)) => {
+                        let current = game_state.get_flag_as_i64(&effect.key);
+                        serde_json::json!(match operation {
+                            EffectOperation::Set => amount,
+                            EffectOperation::Add => current.saturating_add(amount),
+                            EffectOperation::Subtract => current.saturating_sub(amount),
+                            EffectOperation::Multiply => current.saturating_mul(amount),
+                        })
+                    }
+                    _ => effect.value.clone(),
+                };
+                game_state.set_flag(&effect.key, new_value.clone())?;
+                events.push(GameEvent::flag_set(&effect.key, &new_value));
+                debug!("Set flag '{}' to {:?} (was: {:?})", effect.key, new_value, old_value);
             }
             EffectType::ModifyStat => {
                 if let Some(value) = effect.value.as_i64() {
@@ -334,7 +847,8 @@ impl GameEngine {
                         _ => 0,
                     };
 
-                    game_state.player.modify_stat(&effect.key, value as i32, operation)?;
+                    let value = value.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+                    game_state.player.modify_stat(&effect.key, value, operation)?;
 
                     let new_value = match effect.key.as_str() {
                         "health" => game_state.player.stats.health,
@@ -347,26 +861,25 @@ impl GameEngine {
                         _ => 0,
                     };
 
-                    self.emit_event(GameEvent::stat_modified(&effect.key, old_value, new_value)).await;
-
-                    // Check for level up
-                    if effect.key == "experience" && new_value != old_value {
-                        let current_level = game_state.player.stats.level;
-                        if current_level > old_value {
-                            self.emit_event(GameEvent::level_up(old_value, current_level, game_state.player.stats.experience)).await;
-                        }
-                    }
+                    events.push(GameEvent::stat_modified(&effect.key, old_value, new_value));
 
                     // Check for player death
                     if effect.key == "health" && new_value <= 0 {
-                        self.emit_event(GameEvent::player_died("Health reached zero")).await;
+                        events.push(GameEvent::player_died("Health reached zero"));
                     }
                 }
             }
             EffectType::AddItem => {
-                if let Ok(item) = serde_json::from_value::<crate::core::InventoryItem>(effect.value.clone()) {
+                // Either a full item, or a catalog ID with an optional quantity
+                let item = if effect.value.is_object() {
+                    serde_json::from_value::<crate::core::InventoryItem>(effect.value.clone()).ok()
+                } else {
+                    let quantity = effect.value.as_i64().unwrap_or(1) as i32;
+                    self.story.as_ref().and_then(|story| story.catalog_item(&effect.key, quantity))
+                };
+                if let Some(item) = item {
                     game_state.player.add_item(item.clone());
-                    self.emit_event(GameEvent::item_added(&item.id, &item.name, item.quantity)).await;
+                    events.push(GameEvent::item_added(&item.id, &item.name, item.quantity));
                     debug!("Added item '{}' ({})", item.name, item.quantity);
                 }
             }
@@ -381,7 +894,7 @@ impl GameEngine {
                             .unwrap_or_else(|| item_id.to_string());
 
                         if game_state.player.remove_item(item_id, quantity as i32).is_ok() {
-                            self.emit_event(GameEvent::item_removed(item_id, &item_name, quantity as i32)).await;
+                            events.push(GameEvent::item_removed(item_id, &item_name, quantity as i32));
                             debug!("Removed item '{}' ({})", item_name, quantity);
                         }
                     }
@@ -400,26 +913,176 @@ impl GameEngine {
                     game_state.player.modify_stat("health", value as i32, operation)?;
                     let new_health = game_state.player.stats.health;
 
-                    self.emit_event(GameEvent::stat_modified("health", old_health, new_health)).await;
+                    events.push(GameEvent::stat_modified("health", old_health, new_health));
 
                     if new_health <= 0 {
-                        self.emit_event(GameEvent::player_died("Health reached zero")).await;
+                        events.push(GameEvent::player_died("Health reached zero"));
+                    }
+                }
+            }
+            EffectType::UseItem => {
+                // Forced by the story, so cooldowns don't apply
+                if game_state.player.has_item(&effect.key, 1) {
+                    if let Err(e) = consume_item(game_state, &effect.key, events) {
+                        warn!("Could not use item '{}': {}", effect.key, e);
+                    }
+                }
+            }
+            EffectType::SwitchCharacter => {
+                let story = self.story.as_ref()
+                    .ok_or_else(|| GameError::story("No story loaded".to_string()))?;
+                let previous = game_state.active_character.clone();
+
+                if game_state.switch_character(&effect.key, || story.new_character(&effect.key))? {
+                    events.push(GameEvent::custom("character_switched", serde_json::json!({
+                        "from": previous,
+                        "to": effect.key,
+                        "name": game_state.player.name,
+                    })));
+                    debug!("Switched protagonist from '{}' to '{}'", previous, effect.key);
+                }
+            }
+            EffectType::ModifyDurability => {
+                if let (Some(value), Some(item)) = (effect.value.as_i64(), game_state.player.get_item(&effect.key)) {
+                    let item_name = item.name.clone();
+                    let operation = match effect.operation.as_ref().unwrap_or(&EffectOperation::Subtract) {
+                        EffectOperation::Set => crate::core::player::StatOperation::Set,
+                        EffectOperation::Add => crate::core::player::StatOperation::Add,
+                        EffectOperation::Subtract => crate::core::player::StatOperation::Subtract,
+                        EffectOperation::Multiply => crate::core::player::StatOperation::Multiply,
+                    };
+
+                    match game_state.player.modify_durability(
+                        &effect.key,
+                        value.clamp(i32::MIN.into(), i32::MAX.into()) as i32,
+                        operation,
+                    ) {
+                        Ok(change) if change.broken => {
+                            events.push(GameEvent::item_broken(&effect.key, &item_name));
+                            debug!("Item '{}' broke", item_name);
+                        }
+                        Ok(change) => debug!(
+                            "Durability of '{}' changed from {} to {}",
+                            item_name, change.old_durability, change.new_durability
+                        ),
+                        Err(e) => warn!("Could not change durability of '{}': {}", effect.key, e),
                     }
                 }
             }
             EffectType::Custom => {
-                // Custom effects can be handled by the game or ignored
+                if let Some(plugin) = self.plugins.iter().find(|p| p.handles_effect(&effect.key)) {
+                    plugin.apply_effect(effect, game_state)?;
+                }
                 debug!("Applied custom effect: {} -> {:?}", effect.key, effect.value);
-                self.emit_event(GameEvent::custom(&format!("custom_effect_{}", effect.key), effect.value.clone())).await;
+                events.push(GameEvent::custom(format!("custom_effect_{}", effect.key), effect.value.clone()));
+            }
+            EffectType::Macro => {
+                return Err(GameError::story(format!("Effect macro '{}' was not expanded", effect.key)));
             }
         }
 
         Ok(())
     }
 
-    async fn emit_event(&self, event: GameEvent) {
-        if let Ok(mut handler) = self.event_handler.try_lock() {
-            handler.handle_event(&event);
+    pub async fn emit_event(&self, event: GameEvent) {
+        for plugin in &self.plugins {
+            plugin.on_event(&event);
+        }
+        if self.debug_tools {
+            self.variable_tracker.lock().await.handle_event(&event);
+            self.breakpoints.lock().await.handle_event(&event);
+        }
+        self.watchers.lock().await.handle_event(&event);
+        self.event_handler.lock().await.handle_event(&event);
+    }
+
+    /// Stops the game for the debug console when `breakpoint` is hit.
+    /// Returns false if it was already set.
+    pub async fn add_breakpoint(&self, breakpoint: Breakpoint) -> bool {
+        self.breakpoints.lock().await.add(breakpoint)
+    }
+
+    pub async fn remove_breakpoint(&self, breakpoint: &Breakpoint) -> bool {
+        self.breakpoints.lock().await.remove(breakpoint)
+    }
+
+    pub async fn breakpoints(&self) -> Vec<Breakpoint> {
+        self.breakpoints.lock().await.list().to_vec()
+    }
+
+    /// Breakpoints hit since the last call.
+    pub async fn take_breakpoint_hits(&self) -> Vec<Breakpoint> {
+        self.breakpoints.lock().await.take_hits()
+    }
+
+    /// Stream of the values of flag `key` as they change, starting with its
+    /// current value, for binding UI widgets.
+    pub async fn watch_flag(&self, key: &str) -> impl Stream<Item = serde_json::Value> {
+        self.watchers.lock().await.watch_flag(key, self.game_state.as_ref())
+    }
+
+    /// Stream of the values of stat `name` as they change, starting with its
+    /// current value.
+    pub async fn watch_stat(&self, name: &str) -> impl Stream<Item = i32> {
+        self.watchers.lock().await.watch_stat(name, self.game_state.as_ref())
+    }
+
+    /// Tells watchers about a game state that was replaced wholesale.
+    async fn refresh_watchers(&self) {
+        if let Some(game_state) = &self.game_state {
+            self.watchers.lock().await.refresh(game_state);
+        }
+    }
+
+    fn reset_variable_tracker(&self, scene_id: &str) {
+        if let Ok(mut tracker) = self.variable_tracker.try_lock() {
+            tracker.reset(scene_id);
+        }
+    }
+
+    /// Every stat, derived stat and flag with its value and the scene it
+    /// last changed in, for the debug console.
+    pub fn debug_variables(&self) -> GameResult<Vec<DebugVariable>> {
+        let Some(game_state) = self.game_state.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let tracker = self.variable_tracker.try_lock().ok();
+        let changed_in = |origin: Option<&str>| origin.map(str::to_string);
+
+        let stats = &game_state.player.stats;
+        let mut variables: Vec<DebugVariable> = PlayerStats::NAMES.iter()
+            .chain(std::iter::once(&"level"))
+            .filter_map(|&name| {
+                Some(DebugVariable {
+                    kind: "stat",
+                    name: name.to_string(),
+                    value: stats.get(name)?.to_string(),
+                    changed_in: changed_in(tracker.as_ref().and_then(|t| t.stat_changed_in(name))),
+                })
+            })
+            .collect();
+
+        for (name, value) in self.derived_stats()? {
+            variables.push(DebugVariable { kind: "derived", name, value: value.to_string(), changed_in: None });
+        }
+
+        let mut flags: Vec<_> = game_state.flags.iter().collect();
+        flags.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in flags {
+            variables.push(DebugVariable {
+                kind: "flag",
+                name: name.clone(),
+                value: value.to_string(),
+                changed_in: changed_in(tracker.as_ref().and_then(|t| t.flag_changed_in(name))),
+            });
+        }
+
+        Ok(variables)
+    }
+
+    async fn emit_events(&self, events: Vec<GameEvent>) {
+        for event in events {
+            self.emit_event(event).await;
         }
     }
 
@@ -431,6 +1094,34 @@ impl GameEngine {
         }
     }
 
+    /// Events logged after the event with `event_id`, e.g. the last one
+    /// before a choice; all events if it's `None` or no longer held.
+    pub async fn events_since(&self, event_id: Option<Uuid>) -> Vec<GameEvent> {
+        let handler = self.event_handler.lock().await;
+        let events = handler.get_events();
+        let start = event_id
+            .and_then(|id| events.iter().position(|event| event.id == id))
+            .map_or(0, |position| position + 1);
+        events[start..].to_vec()
+    }
+
+    /// Replaces the event history, e.g. with the one saved alongside a game
+    /// that is about to be loaded.
+    pub async fn replace_event_history(&self, events: Vec<GameEvent>) {
+        self.event_handler.lock().await.replace_events(events);
+    }
+
+    /// The event history as pretty JSON, or as readable text with
+    /// `readable`, for attaching to bug reports.
+    pub async fn export_event_history(&self, readable: bool) -> GameResult<String> {
+        let handler = self.event_handler.lock().await;
+        if readable {
+            return Ok(handler.export_text());
+        }
+        handler.export_events()
+            .map_err(|e| GameError::save_load(format!("Failed to serialize event history: {}", e)))
+    }
+
     pub async fn get_recent_events(&self, count: usize) -> Vec<GameEvent> {
         if let Ok(handler) = self.event_handler.try_lock() {
             handler.get_recent_events(count).into_iter().cloned().collect()
@@ -440,6 +1131,47 @@ impl GameEngine {
     }
 }
 
+/// Uses up one consumable and records the events.
+fn consume_item(game_state: &mut GameState, item_id: &str, events: &mut Vec<GameEvent>) -> GameResult<()> {
+    let item_name = game_state.player.get_item(item_id)
+        .map(|item| item.name.clone())
+        .ok_or_else(|| GameError::player(format!("Item not found: {}", item_id)))?;
+    let old_stats = game_state.player.stats.clone();
+
+    game_state.player.use_consumable(item_id)?;
+
+    events.push(GameEvent::item_used(item_id, &item_name));
+    push_stat_changes(&old_stats, &game_state.player.stats, events);
+    debug!("Used item '{}'", item_name);
+    Ok(())
+}
+
+/// Pushes a `level_up` event if the player gained a level, whatever caused
+/// it. Level-ups whose points are still to be allocated are announced by
+/// `allocate_level_points` instead.
+fn push_level_up(old: &PlayerStats, new: &PlayerStats, events: &mut Vec<GameEvent>) {
+    if new.level > old.level && new.pending_level_up.is_none() {
+        events.push(GameEvent::level_up(old.level, new.level, new.experience));
+    }
+}
+
+/// Pushes a `stat_modified` event for every stat that changed.
+fn push_stat_changes(old: &PlayerStats, new: &PlayerStats, events: &mut Vec<GameEvent>) {
+    let stats = [
+        ("health", old.health, new.health),
+        ("max_health", old.max_health, new.max_health),
+        ("strength", old.strength, new.strength),
+        ("intelligence", old.intelligence, new.intelligence),
+        ("charisma", old.charisma, new.charisma),
+    ];
+
+    for (stat_name, old_value, new_value) in stats {
+        if old_value != new_value {
+            events.push(GameEvent::stat_modified(stat_name, old_value, new_value));
+        }
+    }
+}
+
 impl Default for GameEngine {
     fn default() -> Self {
         Self::new()
@@ -449,7 +1181,9 @@ impl Default for GameEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::story::{Scene, Choice};
+    use crate::core::{HookDecision, InventoryItem, ItemType};
+    use crate::core::events::GameEventType;
+    use crate::story::{Ending, Variable, VariableType};
 
     #[tokio::test]
     async fn test_game_engine_creation() {
@@ -509,6 +1243,7 @@ mod tests {
         
         engine.load_story(story).await.unwrap();
         engine.start_new_game("Test Player".to_string()).await.unwrap();
+        assert!(!engine.has_unsaved_progress());
         
         // Make choice
         assert!(engine.make_choice("go_forward").await.is_ok());
@@ -517,5 +1252,671 @@ mod tests {
         assert_eq!(game_state.current_scene_id, "next");
         assert!(game_state.has_visited_scene("start"));
         assert!(game_state.has_visited_scene("next"));
+        assert!(engine.has_unsaved_progress());
+
+        engine.save_game("test".to_string()).await.unwrap();
+        assert!(!engine.has_unsaved_progress());
+    }
+
+    struct DoubleGold;
+
+    impl EnginePlugin for DoubleGold {
+        fn name(&self) -> &str {
+            "double_gold"
+        }
+
+        fn handles_effect(&self, key: &str) -> bool {
+            key == "double_gold"
+        }
+
+        fn apply_effect(&self, _effect: &Effect, game_state: &mut GameState) -> GameResult<()> {
+            let gold = game_state.get_flag_as_i64("gold");
+            game_state.set_flag("gold", serde_json::json!(gold * 2))?;
+            Ok(())
+        }
+
+        fn handles_condition(&self, key: &str) -> bool {
+            key == "rich"
+        }
+
+        fn condition_value(&self, _condition: &Condition, game_state: &GameState) -> GameResult<serde_json::Value> {
+            Ok(serde_json::json!(game_state.get_flag_as_i64("gold") >= 10))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_choice_changes_nothing() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "A chest");
+        start.add_choice(Choice::new("loot", "Loot the chest", "next").with_effects(vec![
+            Effect::modify_stat("strength", 2, EffectOperation::Add),
+            Effect::set_flag("looted", true),
+        ]));
+        story.add_scene(start);
+        story.add_scene(Scene::new("next", "Next", "Onwards"));
+
+        let mut engine = GameEngine::new().with_flag_limits(FlagLimits { max_flags: 0, ..FlagLimits::default() });
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        let events = engine.get_event_history().await.len();
+
+        assert!(engine.make_choice("loot").await.is_err());
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.current_scene_id, "start");
+        assert_eq!(game_state.player.stats.strength, PlayerStats::default().strength);
+        assert!(game_state.taken_choices.is_empty());
+        assert_eq!(engine.get_event_history().await.len(), events);
+    }
+
+    #[tokio::test]
+    async fn test_plugin_effects_and_conditions() {
+        let mut engine = GameEngine::new();
+        engine.register_plugin(Arc::new(DoubleGold));
+
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start_scene = Scene::new("start", "Start", "Starting scene");
+        start_scene.add_choice(Choice::new("gamble", "Gamble", "start").with_effects(vec![
+            Effect::custom("double_gold", serde_json::Value::Null, None),
+        ]));
+        start_scene.add_choice(Choice::new("buy", "Buy a castle", "start").with_conditions(vec![
+            Condition::custom("rich", ComparisonOperator::Equals, serde_json::json!(true)),
+        ]));
+        story.add_scene(start_scene);
+
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.game_state.as_mut().unwrap().set_flag("gold", serde_json::json!(5)).unwrap();
+
+        assert_eq!(engine.get_current_scene().await.unwrap().available_choices().count(), 1);
+
+        engine.make_choice("gamble").await.unwrap();
+        assert_eq!(engine.get_game_state().unwrap().get_flag_as_i64("gold"), 10);
+        assert_eq!(engine.get_current_scene().await.unwrap().available_choices().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_auto_advance() {
+        let mut engine = GameEngine::new();
+
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start_scene = Scene::new("start", "Start", "The bridge is collapsing!")
+            .with_auto_advance(10, "fall");
+        start_scene.add_choice(Choice::new("jump", "Jump", "safe"));
+        story.add_scene(start_scene);
+        story.add_scene(Scene::new("safe", "Safe", "You made it"));
+        let mut fall_scene = Scene::new("fall", "Fall", "Too slow");
+        fall_scene.effects = Some(vec![Effect::subtract_health(20)]);
+        story.add_scene(fall_scene);
+
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+
+        engine.auto_advance().await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.current_scene_id, "fall");
+        assert_eq!(game_state.player.stats.health, 80);
+
+        // The target scene has no timer of its own
+        assert!(engine.auto_advance().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hooks() {
+        let mut engine = GameEngine::new();
+        engine.hooks_mut().on_scene_enter(|scene, game_state| {
+            game_state.increment_flag(&format!("entered_{}", scene.id), 1).unwrap();
+        });
+        engine.hooks_mut().before_choice(|choice, _| match choice.id.as_str() {
+            "forbidden" => HookDecision::Veto("Not in this demo".to_string()),
+            _ => HookDecision::Allow,
+        });
+        engine.hooks_mut().after_effects(|effects, game_state| {
+            game_state.increment_flag("effect_batches", effects.len() as i64).unwrap();
+        });
+
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start_scene = Scene::new("start", "Start", "Starting scene");
+        start_scene.add_choice(
+            Choice::new("next", "Go", "next").with_effects(vec![Effect::add_experience(5)])
+        );
+        start_scene.add_choice(Choice::new("forbidden", "Go anyway", "next"));
+        story.add_scene(start_scene);
+        story.add_scene(Scene::new("next", "Next Scene", "You moved forward"));
+
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        assert_eq!(engine.get_game_state().unwrap().get_flag_as_i64("entered_start"), 1);
+
+        let vetoed = engine.preview_choice("forbidden").await.unwrap();
+        assert!(!vetoed.available);
+        assert_eq!(vetoed.disabled_reason.as_deref(), Some("Not in this demo"));
+        let error = engine.make_choice("forbidden").await.unwrap_err();
+        assert!(matches!(error.inner(), GameError::ChoiceVetoed { .. }));
+        assert_eq!(error.code(), "E103");
+
+        engine.make_choice("next").await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.current_scene_id, "next");
+        assert_eq!(game_state.get_flag_as_i64("entered_next"), 1);
+        assert_eq!(game_state.get_flag_as_i64("effect_batches"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_preview_choice() {
+        let mut engine = GameEngine::new();
+
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start_scene = Scene::new("start", "Start", "Starting scene");
+        start_scene.add_choice(
+            Choice::new("fall", "Jump down", "next").with_effects(vec![Effect::subtract_health(30)])
+        );
+        start_scene.add_choice(
+            Choice::new("locked", "Open the vault", "next").with_conditions(vec![Condition::has_item("key", 1)])
+        );
+        story.add_scene(start_scene);
+        story.add_scene(Scene::new("next", "Next Scene", "You moved forward"));
+
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        let event_count = engine.get_event_history().await.len();
+
+        let outcome = engine.preview_choice("fall").await.unwrap();
+        assert!(outcome.available);
+        assert_eq!(outcome.state_after.current_scene_id, "next");
+        assert_eq!(outcome.state_after.player.stats.health, 70);
+        assert!(!outcome.events.is_empty());
+
+        let locked = engine.preview_choice("locked").await.unwrap();
+        assert!(!locked.available);
+        assert_eq!(locked.disabled_reason.as_deref(), Some("Requires: Key"));
+
+        // Nothing was committed
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.current_scene_id, "start");
+        assert_eq!(game_state.player.stats.health, 100);
+        assert_eq!(engine.get_event_history().await.len(), event_count);
+    }
+
+    #[tokio::test]
+    async fn test_condition_groups() {
+        let mut engine = GameEngine::new();
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start_scene = Scene::new("start", "Start", "A locked gate");
+        let sword_or_strength = || Condition::any(vec![Condition::has_item("sword", 1), Condition::stat_greater_equal("strength", 99)]);
+        start_scene.add_choice(Choice::new("force", "Force the gate", "next").with_conditions(vec![sword_or_strength()]));
+        start_scene.add_choice(Choice::new("sneak", "Sneak past", "next").with_conditions(vec![
+            Condition::all(vec![Condition::negate(sword_or_strength()), Condition::level_at_least(1)])
+        ]));
+        story.add_scene(start_scene);
+        story.add_scene(Scene::new("next", "Next Scene", "You're through"));
+
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+
+        let force = engine.preview_choice("force").await.unwrap();
+        assert!(!force.available);
+        assert_eq!(force.disabled_reason.as_deref(), Some("Requires: Sword or Strength 99"));
+        assert!(engine.preview_choice("sneak").await.unwrap().available);
+    }
+
+    #[tokio::test]
+    async fn test_load_save_from_older_story() {
+        let story = |version: &str, with_lake: bool| {
+            let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+            story.version = version.to_string();
+            let mut start = Scene::new("start", "Start", "Start");
+            start.add_choice(Choice::new("cave", "Cave", "cave"));
+            story.add_scene(start);
+            let mut cave = Scene::new("cave", "Cave", "Cave");
+            if with_lake {
+                cave.add_choice(Choice::new("lake", "Lake", "lake"));
+                story.add_scene(Scene::new("lake", "Lake", "Lake"));
+            }
+            story.add_scene(cave);
+            story
+        };
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story("1.0.0", true)).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.make_choice("cave").await.unwrap();
+        engine.make_choice("lake").await.unwrap();
+        let saved = engine.save_game("save".to_string()).await.unwrap();
+        assert_eq!(saved.story_version.as_deref(), Some("1.0.0"));
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story("2.0.0", false)).await.unwrap();
+        let compatibility = engine.check_save_compatibility(&saved).unwrap();
+        assert!(compatibility.version_changed());
+        assert!(!compatibility.is_compatible());
+        assert_eq!(compatibility.missing_scenes, vec!["lake"]);
+
+        engine.load_game_best_effort(saved).await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.current_scene_id, "start");
+        assert!(!game_state.has_visited_scene("lake"));
+        assert_eq!(game_state.story_version.as_deref(), Some("2.0.0"));
+        assert!(engine.get_current_scene().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resume_at_fallback_scene() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.resume_fallback_scene = Some("camp".to_string());
+        story.add_scene(Scene::new("start", "Start", "Start"));
+        story.add_scene(Scene::new("camp", "Camp", "You rest at the camp"));
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+
+        let mut saved = GameState::new("test".to_string(), "removed".to_string(), Player::new("Test Player", None));
+        saved.visit_scene("removed");
+        engine.load_game(saved).await.unwrap();
+
+        assert_eq!(engine.get_current_scene().await.unwrap().scene.id, "camp");
+        let recovered = engine.get_event_history().await.into_iter()
+            .find(|event| matches!(&event.event_type, GameEventType::Custom(name) if name == "scene_recovered"))
+            .unwrap();
+        assert_eq!(recovered.data["missing_scene_id"], "removed");
+    }
+
+    #[tokio::test]
+    async fn test_declared_variables_start_at_defaults() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.variables.insert("gold".to_string(), Variable::new(VariableType::Number).with_default(serde_json::json!(10)));
+        story.add_scene(Scene::new("start", "Start", "Start"));
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story.clone()).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        assert_eq!(engine.get_game_state().unwrap().get_flag("gold"), Some(&serde_json::json!(10)));
+
+        // A save from before the variable was declared gets the default too
+        story.variables.insert("has_map".to_string(), Variable::new(VariableType::Bool));
+        engine.load_story(story).await.unwrap();
+        let mut saved = GameState::new("test".to_string(), "start".to_string(), Player::new("Test Player", None));
+        saved.set_flag("gold", serde_json::json!(3)).unwrap();
+        engine.load_game(saved).await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.get_flag("gold"), Some(&serde_json::json!(3)));
+        assert_eq!(game_state.get_flag("has_map"), Some(&serde_json::json!(false)));
+    }
+
+    #[tokio::test]
+    async fn test_watch_flag_and_stat() {
+        use futures::StreamExt;
+
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "A chest");
+        start.add_choice(Choice::new("loot", "Loot the chest", "start").with_effects(vec![
+            Effect::add_to_flag("gold", 5),
+            Effect::modify_stat("strength", 2, EffectOperation::Add),
+        ]));
+        story.add_scene(start);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        let mut gold = Box::pin(engine.watch_flag("gold").await);
+        let mut strength = Box::pin(engine.watch_stat("strength").await);
+        assert_eq!(strength.next().await, Some(10));
+
+        let before = engine.snapshot().await;
+        engine.make_choice("loot").await.unwrap();
+        assert_eq!(gold.next().await, Some(serde_json::json!(5)));
+        assert_eq!(strength.next().await, Some(12));
+
+        // Restoring replaces the state without events
+        engine.restore(before).await.unwrap();
+        assert_eq!(strength.next().await, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_breakpoints_need_debug_tools() {
+        for debug_tools in [false, true] {
+            let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+            let mut start = Scene::new("start", "Start", "A gate");
+            start.add_choice(Choice::new("open", "Open the gate", "start").with_effects(vec![Effect::set_flag("gate_open", true)]));
+            story.add_scene(start);
+
+            let mut engine = GameEngine::new().with_debug_tools(debug_tools);
+            engine.load_story(story).await.unwrap();
+            engine.start_new_game("Test Player".to_string()).await.unwrap();
+            engine.add_breakpoint(Breakpoint::Flag("gate_open".to_string())).await;
+            engine.make_choice("open").await.unwrap();
+
+            assert_eq!(engine.take_breakpoint_hits().await.len(), usize::from(debug_tools));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_use_item_effect() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "A potion sits on the shelf");
+        start.add_choice(Choice::new("shelf", "Knock the shelf", "shelf"));
+        story.add_scene(start);
+        let mut shelf = Scene::new("shelf", "Shelf", "The potion shatters");
+        shelf.effects = Some(vec![Effect::use_item("potion")]);
+        story.add_scene(shelf);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+
+        let player = &mut engine.get_game_state_mut().unwrap().player;
+        player.stats.health = 50;
+        player.add_item(InventoryItem {
+            id: "potion".to_string(),
+            name: "Health Potion".to_string(),
+            description: "Restores health".to_string(),
+            item_type: ItemType::Consumable,
+            quantity: 1,
+            properties: [("health_restore".to_string(), serde_json::json!(30))].into(),
+        });
+
+        engine.make_choice("shelf").await.unwrap();
+        let player = &engine.get_game_state().unwrap().player;
+        assert_eq!(player.stats.health, 80);
+        assert!(!player.has_item("potion", 1));
+
+        let events = engine.get_event_history().await;
+        assert!(events.iter().any(|event| matches!(event.event_type, GameEventType::ItemUsed)));
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_durability_effect() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "A troll blocks the bridge");
+        start.add_choice(Choice::new("block", "Raise your shield", "bridge"));
+        story.add_scene(start);
+        let mut bridge = Scene::new("bridge", "Bridge", "The club comes down");
+        // Wraps to 0 if truncated to i32
+        bridge.effects = Some(vec![Effect::new(
+            EffectType::ModifyDurability,
+            "shield".to_string(),
+            serde_json::json!(4_294_967_296_i64),
+            Some(EffectOperation::Subtract),
+        )]);
+        story.add_scene(bridge);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.get_game_state_mut().unwrap().player.add_item(InventoryItem {
+            id: "shield".to_string(),
+            name: "Wooden Shield".to_string(),
+            description: "Splinters easily".to_string(),
+            item_type: ItemType::Armor,
+            quantity: 1,
+            properties: [("durability".to_string(), serde_json::json!(10))].into(),
+        });
+
+        engine.make_choice("block").await.unwrap();
+        assert!(!engine.get_game_state().unwrap().player.has_item("shield", 1));
+    }
+
+    #[tokio::test]
+    async fn test_game_ended_event() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "Starting scene");
+        start.add_choice(Choice::new("hide", "Hide", "vault"));
+        let mut vault = Scene::new("vault", "The Vault", "Gold everywhere.");
+        vault.ending = Some(Ending { id: None, title: Some("Rich Beyond Dreams".to_string()), category: Some("secret".to_string()) });
+        story.add_scene(start);
+        story.add_scene(vault);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.make_choice("hide").await.unwrap();
+        assert!(engine.is_game_ended().await);
+
+        let events = engine.get_event_history().await;
+        let ended = events.iter().find(|event| matches!(event.event_type, GameEventType::GameEnded)).unwrap();
+        assert_eq!(ended.data["ending_id"], "vault");
+        assert_eq!(ended.data["ending_title"], "Rich Beyond Dreams");
+        assert_eq!(ended.data["category"], "secret");
+        assert_eq!(engine.get_game_state().unwrap().completed_ending.as_deref(), Some("vault"));
+    }
+
+    #[tokio::test]
+    async fn test_level_up_event() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "Starting scene");
+        let mut train = Choice::new("train", "Train", "yard");
+        train.effects = Some(vec![Effect {
+            effect_type: EffectType::ModifyStat,
+            key: "experience".to_string(),
+            value: serde_json::json!(150),
+            operation: Some(EffectOperation::Add),
+        }]);
+        start.add_choice(train);
+        story.add_scene(start);
+        story.add_scene(Scene::new("yard", "Yard", "You feel stronger."));
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        let before = engine.last_event_id().await;
+        engine.make_choice("train").await.unwrap();
+
+        let events = engine.events_since(before).await;
+        assert!(matches!(events[0].event_type, GameEventType::ChoiceMade));
+        let level_ups: Vec<_> = events.iter().filter(|event| matches!(event.event_type, GameEventType::LevelUp)).collect();
+        assert_eq!(level_ups.len(), 1);
+        assert_eq!(level_ups[0].data["old_level"], 1);
+        assert_eq!(level_ups[0].data["new_level"], engine.get_game_state().unwrap().player.stats.level);
+    }
+
+    #[tokio::test]
+    async fn test_derived_stats() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.derived_stats.insert("carry_capacity".to_string(), "strength * 5".to_string());
+        story.derived_stats.insert("defense".to_string(), "armor_bonus + level".to_string());
+        let mut start = Scene::new("start", "Start", "A heavy chest");
+        start.add_choice(
+            Choice::new("lift", "Lift the chest", "start")
+                .with_conditions(vec![Condition::stat_greater_equal("carry_capacity", 60)])
+        );
+        story.add_scene(start);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.get_game_state_mut().unwrap().set_flag("armor_bonus", serde_json::json!(4)).unwrap();
+
+        assert_eq!(
+            engine.derived_stats().unwrap(),
+            vec![("carry_capacity".to_string(), 50), ("defense".to_string(), 5)]
+        );
+        assert_eq!(engine.get_current_scene().await.unwrap().available_choices().count(), 0);
+
+        engine.get_game_state_mut().unwrap().player.stats.strength = 12;
+        assert_eq!(engine.get_current_scene().await.unwrap().available_choices().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_switch_character() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.characters.insert("ada".to_string(), crate::story::Character {
+            name: "Ada".to_string(),
+            stats: Some(PlayerStats { strength: 4, ..Default::default() }),
+        });
+        let mut start = Scene::new("start", "Start", "Chapter one");
+        start.add_choice(Choice::new("next", "Next chapter", "ada"));
+        story.add_scene(start);
+        let mut ada = Scene::new("ada", "Ada", "Chapter two");
+        ada.effects = Some(vec![Effect::switch_character("ada")]);
+        ada.add_choice(
+            Choice::new("back", "Back to you", "back")
+                .with_conditions(vec![Condition::active_character("ada")])
+        );
+        story.add_scene(ada);
+        let mut back = Scene::new("back", "Back", "Chapter three");
+        back.effects = Some(vec![Effect::switch_character(crate::core::MAIN_CHARACTER)]);
+        story.add_scene(back);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.get_game_state_mut().unwrap().player.stats.strength = 20;
+
+        engine.make_choice("next").await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.active_character, "ada");
+        assert_eq!((game_state.player.name.as_str(), game_state.player.stats.strength), ("Ada", 4));
+        assert_eq!(engine.get_current_scene().await.unwrap().available_choices().count(), 1);
+
+        engine.make_choice("back").await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.player.stats.strength, 20);
+        assert!(game_state.characters.contains_key("ada"));
+    }
+
+    #[tokio::test]
+    async fn test_hotseat_game() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "A fork in the road");
+        start.add_choice(Choice::new("left", "Go left", "start"));
+        start.add_choice(Choice::new("right", "Go right", "start"));
+        story.add_scene(start);
+        let names = ["Ana", "Ben", "Cy"].map(String::from);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        assert!(engine.start_hotseat_game(HotseatMode::Vote, &names[..1]).await.is_err());
+        assert!(!engine.is_game_active());
+
+        engine.start_hotseat_game(HotseatMode::Alternate, &names).await.unwrap();
+        engine.make_choice("left").await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.player.name, "Ben");
+        assert_eq!(game_state.party.as_ref().unwrap().seats[0].choices_made, 1);
+
+        engine.start_hotseat_game(HotseatMode::Vote, &names).await.unwrap();
+        assert!(engine.vote_choice(&names[..2]).await.is_err());
+        let votes = ["right", "left", "right"].map(String::from);
+        assert_eq!(engine.vote_choice(&votes).await.unwrap(), "right");
+        let party = engine.get_game_state().unwrap().party.as_ref().unwrap();
+        assert_eq!((party.turn, party.seats[2].votes_won), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_item_catalog() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.items.insert("rusty_key".to_string(), crate::story::CatalogItem {
+            name: "Rusty Key".to_string(),
+            description: "Opens something, once".to_string(),
+            item_type: ItemType::KeyItem,
+            properties: HashMap::new(),
+        });
+        let mut start = Scene::new("start", "Start", "A key glints in the mud");
+        start.add_choice(
+            Choice::new("take", "Take it", "start").with_effects(vec![Effect::add_catalog_item("rusty_key", 2)])
+        );
+        story.add_scene(start);
+
+        let mut typo = story.clone();
+        let mut typo_scene = Scene::new("typo", "Typo", "Oops");
+        typo_scene.effects = Some(vec![Effect::add_catalog_item("rusty_kye", 1)]);
+        typo.add_scene(typo_scene);
+        assert!(GameEngine::new().load_story(typo).await.is_err());
+
+        for quantity in [serde_json::json!(0), serde_json::json!(5_000_000_000_i64), serde_json::json!("2")] {
+            let mut huge = story.clone();
+            let mut huge_scene = Scene::new("huge", "Huge", "Too many");
+            huge_scene.effects = Some(vec![Effect::add_catalog_item("rusty_key", 1)]);
+            huge_scene.effects.as_mut().unwrap()[0].value = quantity;
+            huge.add_scene(huge_scene);
+            assert!(GameEngine::new().load_story(huge).await.is_err());
+        }
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.make_choice("take").await.unwrap();
+
+        let key = engine.get_game_state().unwrap().player.get_item("rusty_key").unwrap();
+        assert_eq!((key.name.as_str(), key.quantity), ("Rusty Key", 2));
+    }
+
+    #[tokio::test]
+    async fn test_shuffle_choices() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "Pick a door");
+        start.shuffle_choices = true;
+        for door in 1..=8 {
+            start.add_choice(Choice::new(format!("door{}", door), format!("Door {}", door), "start".to_string()));
+        }
+        story.add_scene(start);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.get_game_state_mut().unwrap().seed = 1;
+
+        let order = |view: &SceneView| view.choices().map(|c| c.choice.id.clone()).collect::<Vec<_>>();
+        let first = order(&engine.get_current_scene().await.unwrap());
+        assert_eq!(first, order(&engine.get_current_scene().await.unwrap()));
+        assert_ne!(first, (1..=8).map(|door| format!("door{}", door)).collect::<Vec<_>>());
+
+        // The ids stay valid whatever the order
+        engine.make_choice(&first[0]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_item_cooldown() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "Start");
+        start.add_choice(Choice::new("wait", "Wait", "start"));
+        story.add_scene(start);
+
+        let mut engine = GameEngine::new();
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+        engine.get_game_state_mut().unwrap().player.add_item(InventoryItem {
+            id: "elixir".to_string(),
+            name: "Elixir".to_string(),
+            description: "Very strong".to_string(),
+            item_type: ItemType::Consumable,
+            quantity: 5,
+            properties: [("cooldown_scenes".to_string(), serde_json::json!(2))].into(),
+        });
+
+        engine.use_item("elixir").await.unwrap();
+        assert!(engine.use_item("elixir").await.is_err());
+
+        engine.make_choice("wait").await.unwrap();
+        let game_state = engine.get_game_state().unwrap();
+        assert_eq!(game_state.item_cooldown_remaining(game_state.player.get_item("elixir").unwrap()), 1);
+        assert!(engine.use_item("elixir").await.is_err());
+
+        engine.make_choice("wait").await.unwrap();
+        engine.use_item("elixir").await.unwrap();
+        assert_eq!(engine.get_game_state().unwrap().player.get_item("elixir").unwrap().quantity, 3);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore() {
+        let mut engine = GameEngine::new();
+
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start_scene = Scene::new("start", "Start", "Starting scene");
+        start_scene.add_choice(Choice::new("go_forward", "Go forward", "next"));
+        story.add_scene(start_scene);
+        story.add_scene(Scene::new("next", "Next Scene", "You moved forward"));
+
+        engine.load_story(story).await.unwrap();
+        engine.start_new_game("Test Player".to_string()).await.unwrap();
+
+        let snapshot = engine.snapshot().await;
+        let event_count = engine.get_event_history().await.len();
+
+        engine.make_choice("go_forward").await.unwrap();
+        assert_eq!(engine.get_game_state().unwrap().current_scene_id, "next");
+
+        engine.restore(snapshot).await.unwrap();
+        assert_eq!(engine.get_game_state().unwrap().current_scene_id, "start");
+        assert_eq!(engine.get_event_history().await.len(), event_count);
     }
 }
\ No newline at end of file
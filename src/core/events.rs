@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use crate::core::GameState;
 use crate::story::{Scene, Choice};
+use crate::utils::title_case;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameEvent {
@@ -25,9 +25,11 @@ pub enum GameEventType {
     ItemAdded,
     ItemRemoved,
     ItemUsed,
+    ItemBroken,
     LevelUp,
     FlagSet,
     PlayerDied,
+    ConfigReloaded,
     Custom(String),
 }
 
@@ -64,9 +66,12 @@ impl GameEvent {
         Self::new(GameEventType::GameSaved, data)
     }
 
-    pub fn game_ended(ending_scene_id: &str) -> Self {
+    pub fn game_ended(ending_scene: &Scene) -> Self {
         let data = serde_json::json!({
-            "ending_scene_id": ending_scene_id
+            "ending_scene_id": ending_scene.id,
+            "ending_id": ending_scene.ending_id(),
+            "ending_title": ending_scene.ending_title(),
+            "category": ending_scene.ending_category()
         });
         Self::new(GameEventType::GameEnded, data)
     }
@@ -125,6 +130,14 @@ impl GameEvent {
         Self::new(GameEventType::ItemUsed, data)
     }
 
+    pub fn item_broken(item_id: &str, item_name: &str) -> Self {
+        let data = serde_json::json!({
+            "item_id": item_id,
+            "item_name": item_name
+        });
+        Self::new(GameEventType::ItemBroken, data)
+    }
+
     pub fn level_up(old_level: i32, new_level: i32, experience: i32) -> Self {
         let data = serde_json::json!({
             "old_level": old_level,
@@ -134,6 +147,17 @@ impl GameEvent {
         Self::new(GameEventType::LevelUp, data)
     }
 
+    /// Level-up whose points the player allocated, see `LevelUpMode::Allocate`.
+    pub fn level_up_allocated(old_level: i32, new_level: i32, experience: i32, allocation: &std::collections::HashMap<String, i32>) -> Self {
+        let data = serde_json::json!({
+            "old_level": old_level,
+            "new_level": new_level,
+            "experience": experience,
+            "allocation": allocation
+        });
+        Self::new(GameEventType::LevelUp, data)
+    }
+
     pub fn flag_set(flag_name: &str, value: &serde_json::Value) -> Self {
         let data = serde_json::json!({
             "flag_name": flag_name,
@@ -149,15 +173,152 @@ impl GameEvent {
         Self::new(GameEventType::PlayerDied, data)
     }
 
+    pub fn config_reloaded(changed_settings: &[String]) -> Self {
+        let data = serde_json::json!({
+            "changed_settings": changed_settings
+        });
+        Self::new(GameEventType::ConfigReloaded, data)
+    }
+
     pub fn custom<S: Into<String>>(event_name: S, data: serde_json::Value) -> Self {
         Self::new(GameEventType::Custom(event_name.into()), data)
     }
+
+    /// For plugins and embedders to emit; the UI announces it.
+    pub fn achievement_unlocked(name: &str) -> Self {
+        Self::custom(ACHIEVEMENT_UNLOCKED, serde_json::json!({
+            "name": name
+        }))
+    }
+
+    /// Message and style to announce the event with before the next scene:
+    /// items found or broken, level-ups and achievements.
+    pub fn notification(&self) -> Option<(String, &'static str)> {
+        let text = |key: &str| self.data.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        match &self.event_type {
+            GameEventType::ItemAdded => {
+                let quantity = self.data.get("quantity").and_then(|v| v.as_i64()).unwrap_or(1);
+                let count = if quantity > 1 { format!("{} × ", quantity) } else { String::new() };
+                Some((format!("🎒 Found {}{}", count, text("item_name")), "success"))
+            }
+            GameEventType::ItemBroken => Some((format!("💔 {} broke", text("item_name")), "warning")),
+            // Allocated level-ups were already celebrated on the allocation screen
+            GameEventType::LevelUp if self.data.get("allocation").is_none() => {
+                Some((format!("🎉 Level up! You are now level {}", self.data["new_level"]), "success"))
+            }
+            GameEventType::Custom(name) if name == ACHIEVEMENT_UNLOCKED => {
+                Some((format!("🏆 Achievement unlocked: {}", text("name")), "success"))
+            }
+            _ => None,
+        }
+    }
+
+    /// Short text for the recent happenings strip: items gained, lost or
+    /// broken, level ups and changes to `flags`. `None` for other events.
+    pub fn happening(&self, flags: &[String]) -> Option<String> {
+        let text = |key: &str| self.data.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let number = |key: &str| self.data.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+        let quantity = match number("quantity") {
+            1 => String::new(),
+            quantity => format!("{} ", quantity),
+        };
+
+        match &self.event_type {
+            GameEventType::ItemAdded => Some(format!("+{}{}", quantity, text("item_name"))),
+            GameEventType::ItemRemoved => Some(format!("-{}{}", quantity, text("item_name"))),
+            GameEventType::ItemBroken => Some(format!("{} broke", text("item_name"))),
+            GameEventType::LevelUp => Some(format!("Level {}!", number("new_level"))),
+            GameEventType::FlagSet if flags.contains(&text("flag_name")) => {
+                let value = match &self.data["value"] {
+                    serde_json::Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                Some(format!("{}: {}", text("flag_name"), value))
+            }
+            _ => None,
+        }
+    }
+
+    /// Line and style for the event log screen, or `None` for events the
+    /// player wouldn't recognise, like flags and config reloads.
+    pub fn describe(&self) -> Option<(String, &'static str)> {
+        let text = |key: &str| self.data.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let number = |key: &str| self.data.get(key).and_then(|v| v.as_i64()).unwrap_or(0);
+        let quantity = match number("quantity") {
+            0 | 1 => String::new(),
+            quantity => format!("{} × ", quantity),
+        };
+
+        match &self.event_type {
+            GameEventType::GameStarted => Some((format!("Started \"{}\" as {}", text("story_id"), text("player_name")), "info")),
+            GameEventType::GameLoaded => Some((format!("Loaded {}", text("save_name")), "info")),
+            GameEventType::GameSaved => Some((format!("Saved {}", text("save_name")), "info")),
+            GameEventType::SceneEntered => Some((format!("→ {}", text("scene_title")), "scene_title")),
+            GameEventType::ChoiceMade => Some((format!("You chose \"{}\"", text("choice_text")), "choice")),
+            GameEventType::StatModified => {
+                let (stat, change, new_value) = (text("stat_name"), number("change"), number("new_value"));
+                match stat.as_str() {
+                    "health" if change < 0 => Some((format!("Took {} damage (health {})", -change, new_value), "warning")),
+                    "health" if change > 0 => Some((format!("Healed {} (health {})", change, new_value), "success")),
+                    _ if change == 0 => None,
+                    _ => Some((format!("{} {:+} ({})", title_case(&stat), change, new_value), "stats")),
+                }
+            }
+            GameEventType::ItemAdded => Some((format!("Found {}{}", quantity, text("item_name")), "success")),
+            GameEventType::ItemRemoved => Some((format!("Lost {}{}", quantity, text("item_name")), "warning")),
+            GameEventType::ItemUsed => Some((format!("Used {}", text("item_name")), "info")),
+            GameEventType::ItemBroken => Some((format!("{} broke", text("item_name")), "warning")),
+            GameEventType::LevelUp => Some((format!("Reached level {}", number("new_level")), "success")),
+            GameEventType::PlayerDied => Some((format!("Died: {}", text("cause")), "error")),
+            GameEventType::GameEnded => {
+                let ending = self.data["ending_title"].as_str().unwrap_or("an ending");
+                Some((format!("Reached {}", ending), "success"))
+            }
+            GameEventType::Custom(name) if name == ACHIEVEMENT_UNLOCKED => {
+                Some((format!("Achievement unlocked: {}", text("name")), "success"))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Health at the end of each scene in `events`, oldest first. Health before
+/// the first logged change is taken from that change, or is
+/// `current_health` if it never changed.
+pub fn health_by_scene(events: &[GameEvent], current_health: i32) -> Vec<i32> {
+    let is_health = |event: &GameEvent| matches!(event.event_type, GameEventType::StatModified)
+        && event.data["stat_name"] == "health";
+    let mut health = events.iter()
+        .find(|event| is_health(event))
+        .and_then(|event| event.data["old_value"].as_i64())
+        .map_or(current_health, |old_value| old_value as i32);
+
+    let mut history = Vec::new();
+    for event in events {
+        match event.event_type {
+            GameEventType::SceneEntered => history.push(health),
+            GameEventType::StatModified if is_health(event) => {
+                health = event.data["new_value"].as_i64().map_or(health, |new_value| new_value as i32);
+                if let Some(last) = history.last_mut() {
+                    *last = health;
+                }
+            }
+            _ => {}
+        }
+    }
+    history
 }
 
 pub trait GameEventHandler {
     fn handle_event(&mut self, event: &GameEvent);
 }
 
+/// Name of the custom event announcing an achievement.
+pub const ACHIEVEMENT_UNLOCKED: &str = "achievement_unlocked";
+
+/// Events an `EventLogger` keeps unless configured otherwise.
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 1000;
+
 pub struct EventLogger {
     events: Vec<GameEvent>,
     max_events: usize,
@@ -175,6 +336,18 @@ impl EventLogger {
         &self.events
     }
 
+    pub fn capacity(&self) -> usize {
+        self.max_events
+    }
+
+    /// Replaces the log, e.g. with one restored from a save, keeping only
+    /// the newest events if there are more than the capacity.
+    pub fn replace_events(&mut self, mut events: Vec<GameEvent>) {
+        let excess = events.len().saturating_sub(self.max_events);
+        events.drain(..excess);
+        self.events = events;
+    }
+
     pub fn get_events_by_type(&self, event_type: &GameEventType) -> Vec<&GameEvent> {
         self.events
             .iter()
@@ -194,10 +367,42 @@ impl EventLogger {
         self.events.clear();
     }
 
+    /// Drops every event logged after the event with `event_id`, or all events
+    /// when `event_id` is `None`. Returns false if the event is no longer held.
+    pub fn rewind_to(&mut self, event_id: Option<Uuid>) -> bool {
+        match event_id {
+            None => {
+                self.events.clear();
+                true
+            }
+            Some(id) => match self.events.iter().position(|event| event.id == id) {
+                Some(pos) => {
+                    self.events.truncate(pos + 1);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
     pub fn export_events(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string_pretty(&self.events)
     }
 
+    /// One line per event, in the words of the log screen. Events it leaves
+    /// out, like flags, are written as their type and data.
+    pub fn export_text(&self) -> String {
+        self.events.iter()
+            .map(|event| {
+                let line = match event.describe() {
+                    Some((line, _)) => line,
+                    None => format!("{:?} {}", event.event_type, event.data),
+                };
+                format!("{}  {}\n", event.timestamp.format("%Y-%m-%d %H:%M:%S UTC"), line)
+            })
+            .collect()
+    }
+
     pub fn get_event_count(&self) -> usize {
         self.events.len()
     }
@@ -212,7 +417,7 @@ impl EventLogger {
 
 impl Default for EventLogger {
     fn default() -> Self {
-        Self::new(1000) // Default max 1000 events
+        Self::new(DEFAULT_EVENT_LOG_CAPACITY)
     }
 }
 
@@ -293,6 +498,54 @@ mod tests {
         assert_eq!(event.data["target_scene"], "target_scene");
     }
 
+    #[test]
+    fn test_happening() {
+        let flags = vec!["quest".to_string()];
+        assert_eq!(GameEvent::item_added("torch", "Torch", 1).happening(&flags).as_deref(), Some("+Torch"));
+        assert_eq!(GameEvent::item_removed("coin", "Coin", 3).happening(&flags).as_deref(), Some("-3 Coin"));
+        assert_eq!(GameEvent::level_up(1, 2, 100).happening(&flags).as_deref(), Some("Level 2!"));
+        assert_eq!(GameEvent::flag_set("quest", &serde_json::json!("started")).happening(&flags).as_deref(), Some("quest: started"));
+        assert_eq!(GameEvent::flag_set("visited_inn", &serde_json::json!(true)).happening(&flags), None);
+        assert_eq!(GameEvent::game_saved("Slot 1").happening(&flags), None);
+    }
+
+    #[test]
+    fn test_notification() {
+        let message = |event: GameEvent| event.notification().map(|(message, _)| message);
+        assert_eq!(message(GameEvent::item_added("coin", "Coin", 3)).as_deref(), Some("🎒 Found 3 × Coin"));
+        assert_eq!(message(GameEvent::level_up(1, 2, 100)).as_deref(), Some("🎉 Level up! You are now level 2"));
+        assert_eq!(message(GameEvent::level_up_allocated(1, 2, 100, &Default::default())), None);
+        assert_eq!(message(GameEvent::achievement_unlocked("Dragon Slayer")).as_deref(), Some("🏆 Achievement unlocked: Dragon Slayer"));
+        assert_eq!(message(GameEvent::stat_modified("health", 100, 90)), None);
+    }
+
+    #[test]
+    fn test_describe() {
+        let line = |event: GameEvent| event.describe().map(|(line, _)| line);
+        let choice = Choice::new("left", "Go left", "cave");
+        assert_eq!(line(GameEvent::choice_made(&choice, "start")).as_deref(), Some("You chose \"Go left\""));
+        assert_eq!(line(GameEvent::stat_modified("health", 100, 85)).as_deref(), Some("Took 15 damage (health 85)"));
+        assert_eq!(line(GameEvent::stat_modified("magic_power", 5, 7)).as_deref(), Some("Magic Power +2 (7)"));
+        assert_eq!(line(GameEvent::item_added("coin", "Coin", 3)).as_deref(), Some("Found 3 × Coin"));
+        assert_eq!(line(GameEvent::flag_set("visited_inn", &serde_json::json!(true))), None);
+    }
+
+    #[test]
+    fn test_health_by_scene() {
+        let cave = Scene::new("cave", "Cave", "");
+        let events = vec![
+            GameEvent::scene_entered(&Scene::new("start", "Start", "")),
+            GameEvent::stat_modified("strength", 10, 12),
+            GameEvent::scene_entered(&cave),
+            GameEvent::stat_modified("health", 100, 70),
+            GameEvent::stat_modified("health", 70, 60),
+            GameEvent::scene_entered(&cave),
+            GameEvent::stat_modified("health", 60, 90),
+        ];
+        assert_eq!(health_by_scene(&events, 90), vec![100, 60, 90]);
+        assert_eq!(health_by_scene(&events[..3], 100), vec![100, 100]);
+    }
+
     #[test]
     fn test_event_logger() {
         let mut logger = EventLogger::new(3);
@@ -309,6 +562,17 @@ mod tests {
         
         assert_eq!(logger.get_event_count(), 3);
         assert_eq!(logger.get_events()[0].data["story_id"], "story2"); // First event should be story2 now
+
+        let saved: Vec<GameEvent> = (0..5).map(|i| GameEvent::game_saved(&format!("save{}", i))).collect();
+        logger.replace_events(saved);
+        assert_eq!(logger.get_event_count(), 3);
+        assert_eq!(logger.get_events()[0].data["save_name"], "save2");
+
+        logger.handle_event(&GameEvent::flag_set("door_open", &serde_json::json!(true)));
+        let text = logger.export_text();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.lines().next().unwrap().ends_with("UTC  Saved save3"));
+        assert!(text.contains("FlagSet {\"flag_name\":\"door_open\",\"value\":true}"));
     }
 
     #[test]
@@ -326,6 +590,23 @@ mod tests {
         assert_eq!(start_events.len(), 1);
     }
 
+    #[test]
+    fn test_event_logger_rewind() {
+        let mut logger = EventLogger::default();
+        let first = GameEvent::game_started("story", "player");
+        let first_id = first.id;
+
+        logger.handle_event(&first);
+        logger.handle_event(&GameEvent::game_saved("save1"));
+
+        assert!(logger.rewind_to(Some(first_id)));
+        assert_eq!(logger.get_event_count(), 1);
+
+        assert!(!logger.rewind_to(Some(Uuid::new_v4())));
+        assert!(logger.rewind_to(None));
+        assert_eq!(logger.get_event_count(), 0);
+    }
+
     #[test]
     fn test_composite_event_handler() {
         let mut composite = CompositeEventHandler::new();
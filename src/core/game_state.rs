@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use crate::core::{Player, InventoryItem};
+use crate::core::{InventoryItem, Party, Player, StateDiff, VisitedScenes};
+use crate::utils::{GameError, GameResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
@@ -10,11 +11,75 @@ pub struct GameState {
     pub player: Player,
     pub current_scene_id: String,
     pub story_id: String,
-    pub visited_scenes: Vec<String>,
+    /// Version of the story when the game was started or last migrated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub story_version: Option<String>,
+    pub visited_scenes: VisitedScenes,
     pub flags: HashMap<String, serde_json::Value>,
     pub game_start_time: DateTime<Utc>,
     pub last_save_time: Option<DateTime<Utc>>,
     pub playtime_seconds: i64,
+    /// When each item was last used, for cooldowns and per-scene limits.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub item_usage: HashMap<String, ItemUsage>,
+    /// ID of the protagonist currently in `player`.
+    #[serde(default = "default_active_character")]
+    pub active_character: String,
+    /// The protagonists not being played right now, by ID.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub characters: HashMap<String, Player>,
+    /// Seed for everything random in this playthrough, see `SeededRng`.
+    #[serde(default = "new_seed")]
+    pub seed: u64,
+    /// Choice IDs taken in this playthrough, by scene ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub taken_choices: BTreeMap<String, BTreeSet<String>>,
+    /// ID of the ending reached, once the game is over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_ending: Option<String>,
+    /// The people taking turns at the keyboard, in hotseat games.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub party: Option<Party>,
+    /// Whether anything changed since the last save. Not persisted, so a
+    /// loaded game starts out clean.
+    #[serde(skip)]
+    pub dirty: bool,
+    /// Caps on the flags, so a runaway story script can't blow up saves.
+    /// Not persisted; the engine applies the configured ones.
+    #[serde(skip)]
+    pub flag_limits: FlagLimits,
+}
+
+/// Flags a game may have by default.
+pub const DEFAULT_MAX_FLAGS: usize = 10_000;
+/// Largest flag value by default, in bytes of JSON.
+pub const DEFAULT_MAX_FLAG_VALUE_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagLimits {
+    pub max_flags: usize,
+    /// Size of a value as JSON.
+    pub max_value_bytes: usize,
+}
+
+impl Default for FlagLimits {
+    fn default() -> Self {
+        Self {
+            max_flags: DEFAULT_MAX_FLAGS,
+            max_value_bytes: DEFAULT_MAX_FLAG_VALUE_BYTES,
+        }
+    }
+}
+
+/// ID of the protagonist a game starts with.
+pub const MAIN_CHARACTER: &str = "player";
+
+fn new_seed() -> u64 {
+    Uuid::new_v4().as_u64_pair().0
+}
+
+fn default_active_character() -> String {
+    MAIN_CHARACTER.to_string()
 }
 
 impl GameState {
@@ -24,28 +89,90 @@ impl GameState {
             player,
             current_scene_id,
             story_id,
-            visited_scenes: Vec::new(),
+            story_version: None,
+            visited_scenes: VisitedScenes::default(),
             flags: HashMap::new(),
             game_start_time: Utc::now(),
             last_save_time: None,
             playtime_seconds: 0,
+            item_usage: HashMap::new(),
+            active_character: default_active_character(),
+            characters: HashMap::new(),
+            seed: new_seed(),
+            taken_choices: BTreeMap::new(),
+            completed_ending: None,
+            party: None,
+            dirty: false,
+            flag_limits: FlagLimits::default(),
         }
     }
 
+    /// Makes `character_id` the active protagonist, keeping the current one
+    /// for later. A character played for the first time is created with
+    /// `create`. Returns whether the protagonist changed.
+    pub fn switch_character<F>(&mut self, character_id: &str, create: F) -> GameResult<bool>
+    where
+        F: FnOnce() -> GameResult<Player>,
+    {
+        if self.active_character == character_id {
+            return Ok(false);
+        }
+
+        let next = match self.characters.remove(character_id) {
+            Some(player) => player,
+            None => create()?,
+        };
+        let previous = std::mem::replace(&mut self.player, next);
+        let previous_id = std::mem::replace(&mut self.active_character, character_id.to_string());
+        self.characters.insert(previous_id, previous);
+        Ok(true)
+    }
+
+    /// What changed from this state to `other`.
+    pub fn diff(&self, other: &GameState) -> StateDiff {
+        StateDiff::between(self, other)
+    }
+
     pub fn visit_scene(&mut self, scene_id: &str) {
         self.current_scene_id = scene_id.to_string();
-        
-        if !self.visited_scenes.contains(&scene_id.to_string()) {
-            self.visited_scenes.push(scene_id.to_string());
-        }
+        self.visited_scenes.visit(scene_id);
+    }
+
+    pub fn record_choice(&mut self, scene_id: &str, choice_id: &str) {
+        self.taken_choices.entry(scene_id.to_string()).or_default().insert(choice_id.to_string());
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed_ending.is_some()
     }
 
     pub fn has_visited_scene(&self, scene_id: &str) -> bool {
-        self.visited_scenes.contains(&scene_id.to_string())
+        self.visited_scenes.contains(scene_id)
     }
 
-    pub fn set_flag<S: Into<String>>(&mut self, key: S, value: serde_json::Value) {
-        self.flags.insert(key.into(), value);
+    /// Fails, leaving the flags as they were, if the value is larger than
+    /// `flag_limits` allow or the flag is new and there are already as many
+    /// flags as allowed.
+    pub fn set_flag<S: Into<String>>(&mut self, key: S, value: serde_json::Value) -> GameResult<()> {
+        let key = key.into();
+        let limits = self.flag_limits;
+
+        let size = value.to_string().len();
+        if size > limits.max_value_bytes {
+            return Err(GameError::story(format!(
+                "Flag '{}' not set: its value is {} bytes, over the limit of {} (game.max_flag_value_bytes)",
+                key, size, limits.max_value_bytes
+            )));
+        }
+        if !self.flags.contains_key(&key) && self.flags.len() >= limits.max_flags {
+            return Err(GameError::story(format!(
+                "Flag '{}' not set: the game already has {} flags, the most allowed (game.max_flags)",
+                key, limits.max_flags
+            )));
+        }
+
+        self.flags.insert(key, value);
+        Ok(())
     }
 
     pub fn get_flag(&self, key: &str) -> Option<&serde_json::Value> {
@@ -91,6 +218,15 @@ impl GameState {
     pub fn mark_saved(&mut self) {
         self.update_playtime();
         self.last_save_time = Some(Utc::now());
+        self.dirty = false;
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.dirty
     }
 
     pub fn get_playtime_formatted(&self) -> String {
@@ -108,39 +244,87 @@ impl GameState {
     }
 
     pub fn get_scene_visit_count(&self, scene_id: &str) -> usize {
-        self.visited_scenes
-            .iter()
-            .filter(|&id| id == scene_id)
-            .count()
+        self.visited_scenes.count(scene_id)
     }
 
     pub fn get_total_scenes_visited(&self) -> usize {
-        self.visited_scenes.len()
+        self.visited_scenes.total()
     }
 
     pub fn get_unique_scenes_visited(&self) -> usize {
-        let mut unique_scenes: std::collections::HashSet<&String> = std::collections::HashSet::new();
-        for scene_id in &self.visited_scenes {
-            unique_scenes.insert(scene_id);
-        }
-        unique_scenes.len()
+        self.visited_scenes.unique()
     }
 
     // Helper methods for common flag operations
-    pub fn increment_flag(&mut self, key: &str, amount: i64) {
+    pub fn increment_flag(&mut self, key: &str, amount: i64) -> GameResult<()> {
         let current = self.get_flag_as_i64(key);
-        self.set_flag(key, serde_json::Value::Number(serde_json::Number::from(current + amount)));
+        self.set_flag(key, serde_json::Value::Number(serde_json::Number::from(current.saturating_add(amount))))
     }
 
-    pub fn decrement_flag(&mut self, key: &str, amount: i64) {
+    pub fn decrement_flag(&mut self, key: &str, amount: i64) -> GameResult<()> {
         let current = self.get_flag_as_i64(key);
-        let new_value = (current - amount).max(0);
-        self.set_flag(key, serde_json::Value::Number(serde_json::Number::from(new_value)));
+        let new_value = current.saturating_sub(amount).max(0);
+        self.set_flag(key, serde_json::Value::Number(serde_json::Number::from(new_value)))
     }
 
-    pub fn toggle_flag(&mut self, key: &str) {
+    pub fn toggle_flag(&mut self, key: &str) -> GameResult<()> {
         let current = self.get_flag_as_bool(key);
-        self.set_flag(key, serde_json::Value::Bool(!current));
+        self.set_flag(key, serde_json::Value::Bool(!current))
+    }
+
+    /// Counts scene visits; cooldowns are measured in turns.
+    pub fn current_turn(&self) -> usize {
+        self.visited_scenes.total()
+    }
+
+    /// Scenes to go before an item with a `cooldown_scenes` property can be
+    /// used again.
+    pub fn item_cooldown_remaining(&self, item: &InventoryItem) -> usize {
+        let cooldown = item.properties.get("cooldown_scenes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+
+        match self.item_usage.get(&item.id) {
+            Some(usage) => cooldown.saturating_sub(self.current_turn() - usage.last_used_turn),
+            None => 0,
+        }
+    }
+
+    /// Uses left in the current scene for an item with a `uses_per_scene`
+    /// property, or `None` if it has no limit.
+    pub fn item_uses_left_in_scene(&self, item: &InventoryItem) -> Option<u32> {
+        let limit = item.properties.get("uses_per_scene")
+            .and_then(|v| v.as_u64())? as u32;
+
+        let used = self.item_usage.get(&item.id)
+            .filter(|usage| usage.last_used_turn == self.current_turn())
+            .map_or(0, |usage| usage.uses_this_turn);
+        Some(limit.saturating_sub(used))
+    }
+
+    /// Why the player can't use the item right now, if they can't.
+    pub fn item_use_blocked_reason(&self, item: &InventoryItem) -> Option<String> {
+        match self.item_cooldown_remaining(item) {
+            0 => {}
+            1 => return Some(format!("{} can be used again after the next scene", item.name)),
+            remaining => return Some(format!("{} can be used again in {} scenes", item.name, remaining)),
+        }
+
+        if self.item_uses_left_in_scene(item) == Some(0) {
+            return Some(format!("{} can't be used again in this scene", item.name));
+        }
+
+        None
+    }
+
+    pub fn record_item_use(&mut self, item_id: &str) {
+        let turn = self.current_turn();
+        let usage = self.item_usage.entry(item_id.to_string()).or_default();
+        if usage.last_used_turn == turn && usage.uses_this_turn > 0 {
+            usage.uses_this_turn += 1;
+        } else {
+            *usage = ItemUsage { last_used_turn: turn, uses_this_turn: 1 };
+        }
     }
 
     // Statistics methods
@@ -160,6 +344,12 @@ impl GameState {
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ItemUsage {
+    pub last_used_turn: usize,
+    pub uses_this_turn: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameStatistics {
     pub playtime_seconds: i64,
@@ -231,6 +421,23 @@ mod tests {
         assert_eq!(game_state.get_unique_scenes_visited(), 1);
     }
 
+    #[test]
+    fn test_flag_limits() {
+        let player = Player::new("Test Player", Some(PlayerStats::default()));
+        let mut game_state = GameState::new("test_story".to_string(), "start".to_string(), player);
+        game_state.flag_limits = FlagLimits { max_flags: 2, max_value_bytes: 8 };
+
+        game_state.set_flag("gold", serde_json::json!(5)).unwrap();
+        game_state.set_flag("door_open", serde_json::json!(true)).unwrap();
+        let error = game_state.set_flag("torch", serde_json::json!(true)).unwrap_err();
+        assert!(error.to_string().contains("already has 2 flags"));
+
+        // Existing flags can still change, within the value size
+        game_state.increment_flag("gold", 1).unwrap();
+        assert!(game_state.set_flag("gold", serde_json::json!("a long string")).is_err());
+        assert_eq!(game_state.get_flag_as_i64("gold"), 6);
+    }
+
     #[test]
     fn test_flag_operations() {
         let player = Player::new("Test Player", Some(PlayerStats::default()));
@@ -241,33 +448,65 @@ mod tests {
         );
 
         // Test setting and getting flags
-        game_state.set_flag("test_bool", serde_json::Value::Bool(true));
+        game_state.set_flag("test_bool", serde_json::Value::Bool(true)).unwrap();
         assert!(game_state.get_flag_as_bool("test_bool"));
 
-        game_state.set_flag("test_number", serde_json::Value::Number(serde_json::Number::from(42)));
+        game_state.set_flag("test_number", serde_json::Value::Number(serde_json::Number::from(42))).unwrap();
         assert_eq!(game_state.get_flag_as_i64("test_number"), 42);
 
-        game_state.set_flag("test_string", serde_json::Value::String("hello".to_string()));
+        game_state.set_flag("test_string", serde_json::Value::String("hello".to_string())).unwrap();
         assert_eq!(game_state.get_flag_as_string("test_string"), "hello");
 
         // Test increment/decrement
-        game_state.increment_flag("counter", 5);
+        game_state.increment_flag("counter", 5).unwrap();
         assert_eq!(game_state.get_flag_as_i64("counter"), 5);
 
-        game_state.increment_flag("counter", 3);
+        game_state.increment_flag("counter", 3).unwrap();
         assert_eq!(game_state.get_flag_as_i64("counter"), 8);
 
-        game_state.decrement_flag("counter", 2);
+        game_state.decrement_flag("counter", 2).unwrap();
         assert_eq!(game_state.get_flag_as_i64("counter"), 6);
 
+        game_state.increment_flag("counter", i64::MAX).unwrap();
+        assert_eq!(game_state.get_flag_as_i64("counter"), i64::MAX);
+        game_state.decrement_flag("counter", i64::MIN).unwrap();
+        assert_eq!(game_state.get_flag_as_i64("counter"), i64::MAX);
+
         // Test toggle
-        game_state.toggle_flag("toggle_test");
+        game_state.toggle_flag("toggle_test").unwrap();
         assert!(game_state.get_flag_as_bool("toggle_test"));
 
-        game_state.toggle_flag("toggle_test");
+        game_state.toggle_flag("toggle_test").unwrap();
         assert!(!game_state.get_flag_as_bool("toggle_test"));
     }
 
+    #[test]
+    fn test_item_uses_per_scene() {
+        let mut game_state = GameState::new(
+            "test_story".to_string(),
+            "start".to_string(),
+            Player::new("Test Player", None),
+        );
+        let item = InventoryItem {
+            id: "bandage".to_string(),
+            name: "Bandage".to_string(),
+            description: String::new(),
+            item_type: crate::core::ItemType::Consumable,
+            quantity: 10,
+            properties: [("uses_per_scene".to_string(), serde_json::json!(2))].into(),
+        };
+
+        assert_eq!(game_state.item_uses_left_in_scene(&item), Some(2));
+        game_state.record_item_use("bandage");
+        game_state.record_item_use("bandage");
+        assert_eq!(game_state.item_uses_left_in_scene(&item), Some(0));
+        assert!(game_state.item_use_blocked_reason(&item).is_some());
+
+        game_state.visit_scene("next");
+        assert_eq!(game_state.item_uses_left_in_scene(&item), Some(2));
+        assert!(game_state.item_use_blocked_reason(&item).is_none());
+    }
+
     #[test]
     fn test_statistics() {
         let player = Player::new("Test Player", Some(PlayerStats::default()));
@@ -280,8 +519,8 @@ mod tests {
         game_state.visit_scene("scene1");
         game_state.visit_scene("scene2");
         game_state.visit_scene("scene1"); // Revisit
-        game_state.set_flag("test1", serde_json::Value::Bool(true));
-        game_state.set_flag("test2", serde_json::Value::Number(serde_json::Number::from(10)));
+        game_state.set_flag("test1", serde_json::Value::Bool(true)).unwrap();
+        game_state.set_flag("test2", serde_json::Value::Number(serde_json::Number::from(10))).unwrap();
 
         let stats = game_state.get_statistics();
         assert_eq!(stats.total_scenes_visited, 3);
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use crate::core::GameState;
+use crate::story::{Choice, Effect, Scene};
+
+/// Answer of a `before_choice` hook.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookDecision {
+    Allow,
+    /// Block the choice; the reason is shown to the player.
+    Veto(String),
+}
+
+/// Hooks for applications embedding the engine, see `GameEngine::add_hook`.
+///
+/// Hooks also run when a choice is previewed, against the preview's copy of
+/// the state, so they should not have side effects outside the game state.
+pub trait EngineHook: Send + Sync {
+    /// Called after the player enters a scene and its effects were applied.
+    fn on_scene_enter(&self, _scene: &Scene, _game_state: &mut GameState) {}
+
+    /// Called before a choice is applied; any veto cancels the choice.
+    fn before_choice(&self, _choice: &Choice, _game_state: &GameState) -> HookDecision {
+        HookDecision::Allow
+    }
+
+    /// Called after a list of effects (from a choice or a scene) was applied.
+    fn after_effects(&self, _effects: &[Effect], _game_state: &mut GameState) {}
+}
+
+type SceneEnterFn = dyn Fn(&Scene, &mut GameState) + Send + Sync;
+type BeforeChoiceFn = dyn Fn(&Choice, &GameState) -> HookDecision + Send + Sync;
+type AfterEffectsFn = dyn Fn(&[Effect], &mut GameState) + Send + Sync;
+
+struct SceneEnterHook(Box<SceneEnterFn>);
+struct BeforeChoiceHook(Box<BeforeChoiceFn>);
+struct AfterEffectsHook(Box<AfterEffectsFn>);
+
+impl EngineHook for SceneEnterHook {
+    fn on_scene_enter(&self, scene: &Scene, game_state: &mut GameState) {
+        (self.0)(scene, game_state)
+    }
+}
+
+impl EngineHook for BeforeChoiceHook {
+    fn before_choice(&self, choice: &Choice, game_state: &GameState) -> HookDecision {
+        (self.0)(choice, game_state)
+    }
+}
+
+impl EngineHook for AfterEffectsHook {
+    fn after_effects(&self, effects: &[Effect], game_state: &mut GameState) {
+        (self.0)(effects, game_state)
+    }
+}
+
+/// Registered hooks, run in registration order.
+#[derive(Default, Clone)]
+pub struct HookRegistry {
+    hooks: Vec<Arc<dyn EngineHook>>,
+}
+
+impl HookRegistry {
+    pub fn add(&mut self, hook: Arc<dyn EngineHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn on_scene_enter<F>(&mut self, hook: F)
+    where
+        F: Fn(&Scene, &mut GameState) + Send + Sync + 'static,
+    {
+        self.add(Arc::new(SceneEnterHook(Box::new(hook))));
+    }
+
+    pub fn before_choice<F>(&mut self, hook: F)
+    where
+        F: Fn(&Choice, &GameState) -> HookDecision + Send + Sync + 'static,
+    {
+        self.add(Arc::new(BeforeChoiceHook(Box::new(hook))));
+    }
+
+    pub fn after_effects<F>(&mut self, hook: F)
+    where
+        F: Fn(&[Effect], &mut GameState) + Send + Sync + 'static,
+    {
+        self.add(Arc::new(AfterEffectsHook(Box::new(hook))));
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub(crate) fn run_scene_enter(&self, scene: &Scene, game_state: &mut GameState) {
+        for hook in &self.hooks {
+            hook.on_scene_enter(scene, game_state);
+        }
+    }
+
+    /// Returns the reason of the first veto, if any.
+    pub(crate) fn check_choice(&self, choice: &Choice, game_state: &GameState) -> Option<String> {
+        self.hooks.iter().find_map(|hook| match hook.before_choice(choice, game_state) {
+            HookDecision::Allow => None,
+            HookDecision::Veto(reason) => Some(reason),
+        })
+    }
+
+    pub(crate) fn run_after_effects(&self, effects: &[Effect], game_state: &mut GameState) {
+        for hook in &self.hooks {
+            hook.after_effects(effects, game_state);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+
+/// The highest level a player can reach, whatever their experience.
+pub const MAX_LEVEL: i32 = 1000;
+
+/// Exponents outside this range make the curve too flat or too steep to
+/// level through.
+const EXPONENT_RANGE: std::ops::RangeInclusive<f32> = 0.5..=4.0;
+
+/// How experience turns into levels, and what a level grants. Stories may
+/// declare their own under `leveling`; the default is
+/// `experience = 100 * (level - 1)²`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelCurve {
+    /// Total experience needed for level 2, 3, ...; levels past the end of
+    /// the table use the formula.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thresholds: Vec<i32>,
+    /// Formula: `base * (level - 1) ^ exponent`.
+    #[serde(default = "default_base")]
+    pub base: i32,
+    #[serde(default = "default_exponent")]
+    pub exponent: f32,
+    #[serde(default)]
+    pub mode: LevelUpMode,
+    #[serde(default)]
+    pub rewards: LevelRewards,
+}
+
+/// How stat increases are handed out on level up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum LevelUpMode {
+    /// Every stat grows by its reward.
+    #[default]
+    Automatic,
+    /// The player spends `rewards.points` per level on the stats they like.
+    Allocate,
+}
+
+/// Stat increases granted for each level gained.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelRewards {
+    #[serde(default = "default_max_health_reward")]
+    pub max_health: i32,
+    #[serde(default = "default_stat_reward")]
+    pub strength: i32,
+    #[serde(default = "default_stat_reward")]
+    pub intelligence: i32,
+    #[serde(default = "default_stat_reward")]
+    pub charisma: i32,
+    /// Restore health to the maximum on level up.
+    #[serde(default = "default_full_heal")]
+    pub full_heal: bool,
+    /// Points to spend per level in `Allocate` mode, instead of the stat
+    /// rewards above.
+    #[serde(default = "default_points")]
+    pub points: i32,
+}
+
+fn default_base() -> i32 {
+    100
+}
+
+fn default_exponent() -> f32 {
+    2.0
+}
+
+fn default_max_health_reward() -> i32 {
+    10
+}
+
+fn default_stat_reward() -> i32 {
+    1
+}
+
+fn default_full_heal() -> bool {
+    true
+}
+
+fn default_points() -> i32 {
+    3
+}
+
+impl Default for LevelCurve {
+    fn default() -> Self {
+        Self {
+            thresholds: Vec::new(),
+            base: default_base(),
+            exponent: default_exponent(),
+            mode: LevelUpMode::default(),
+            rewards: LevelRewards::default(),
+        }
+    }
+}
+
+impl Default for LevelRewards {
+    fn default() -> Self {
+        Self {
+            max_health: default_max_health_reward(),
+            strength: default_stat_reward(),
+            intelligence: default_stat_reward(),
+            charisma: default_stat_reward(),
+            full_heal: default_full_heal(),
+            points: default_points(),
+        }
+    }
+}
+
+impl LevelCurve {
+    /// Total experience needed to reach `level`.
+    pub fn experience_for_level(&self, level: i32) -> i32 {
+        if level <= 1 {
+            return 0;
+        }
+
+        match self.thresholds.get((level - 2) as usize) {
+            Some(&threshold) => threshold,
+            None => {
+                // The float-to-int cast saturates, so huge levels cap out
+                let formula = (self.base as f64 * ((level - 1) as f64).powf(self.exponent as f64)) as i64;
+                // Stay above the last table entry so levels keep increasing
+                let floor = self.thresholds.last().map_or(0, |&last| i64::from(last) + 1);
+                i32::try_from(formula.max(floor)).unwrap_or(i32::MAX)
+            }
+        }
+    }
+
+    pub fn level_for_experience(&self, experience: i32) -> i32 {
+        let mut level = 1;
+        while level < MAX_LEVEL && self.experience_for_level(level + 1) <= experience {
+            level += 1;
+        }
+        level
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.base <= 0 {
+            return Err("leveling.base must be positive".to_string());
+        }
+        if !EXPONENT_RANGE.contains(&self.exponent) {
+            return Err(format!(
+                "leveling.exponent must be between {} and {}",
+                EXPONENT_RANGE.start(),
+                EXPONENT_RANGE.end()
+            ));
+        }
+        if self.mode == LevelUpMode::Allocate && self.rewards.points <= 0 {
+            return Err("leveling.rewards.points must be positive in Allocate mode".to_string());
+        }
+        if self.thresholds.first().is_some_and(|&first| first <= 0)
+            || self.thresholds.windows(2).any(|pair| pair[1] <= pair[0])
+        {
+            return Err("leveling.thresholds must be positive and increasing".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_curve() {
+        let curve = LevelCurve::default();
+        assert_eq!(curve.experience_for_level(2), 100);
+        assert_eq!(curve.experience_for_level(3), 400);
+        assert_eq!(curve.level_for_experience(0), 1);
+        assert_eq!(curve.level_for_experience(399), 2);
+        assert_eq!(curve.level_for_experience(400), 3);
+    }
+
+    #[test]
+    fn test_experience_overflow() {
+        let curve = LevelCurve::default();
+        assert_eq!(curve.level_for_experience(i32::MAX), MAX_LEVEL);
+        assert_eq!(curve.experience_for_level(i32::MAX), i32::MAX);
+
+        let table = LevelCurve { thresholds: vec![10, i32::MAX], ..Default::default() };
+        assert_eq!(table.experience_for_level(4), i32::MAX);
+        assert_eq!(table.level_for_experience(i32::MAX), MAX_LEVEL);
+
+        let flat = LevelCurve { exponent: 0.0001, ..Default::default() };
+        assert!(flat.validate().is_err());
+    }
+
+    #[test]
+    fn test_threshold_table() {
+        let curve = LevelCurve {
+            thresholds: vec![20, 40, 60],
+            base: 20,
+            exponent: 1.0,
+            ..Default::default()
+        };
+        assert!(curve.validate().is_ok());
+        assert_eq!(curve.level_for_experience(45), 3);
+        assert_eq!(curve.level_for_experience(60), 4);
+        // Past the table the formula takes over
+        assert_eq!(curve.experience_for_level(5), 80);
+
+        let unordered = LevelCurve { thresholds: vec![50, 20], ..Default::default() };
+        assert!(unordered.validate().is_err());
+    }
+}
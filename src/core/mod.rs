@@ -1,9 +1,29 @@
 pub mod engine;
 pub mod game_state;
+pub mod visits;
 pub mod player;
+pub mod leveling;
+pub mod rng;
 pub mod events;
+pub mod plugin;
+pub mod hooks;
+pub mod debug;
+pub mod state_diff;
+pub mod watchers;
+pub mod party;
+pub mod coop;
 
-pub use engine::GameEngine;
-pub use game_state::GameState;
-pub use player::{Player, PlayerStats};
-pub use events::{GameEvent, GameEventHandler};
\ No newline at end of file
+pub use engine::{GameEngine, StateSnapshot, ChoiceOutcome, SaveCompatibility};
+pub use game_state::{GameState, ItemUsage, FlagLimits, MAIN_CHARACTER, DEFAULT_MAX_FLAGS, DEFAULT_MAX_FLAG_VALUE_BYTES};
+pub use visits::{VisitedScenes, DEFAULT_VISIT_HISTORY};
+pub use player::{Player, PlayerStats, StatBounds, PendingLevelUp, InventoryItem, ItemType, DurabilityChange};
+pub use rng::SeededRng;
+pub use leveling::{LevelCurve, LevelRewards, LevelUpMode};
+pub use events::{GameEvent, GameEventType, GameEventHandler, EventLogger, health_by_scene, DEFAULT_EVENT_LOG_CAPACITY, ACHIEVEMENT_UNLOCKED};
+pub use plugin::EnginePlugin;
+pub use hooks::{EngineHook, HookDecision, HookRegistry};
+pub use state_diff::{StateDiff, StatChange, FlagChange, ItemChange};
+pub use debug::{Breakpoint, Breakpoints, DebugVariable, VariableTracker};
+pub use watchers::Watchers;
+pub use party::{HotseatMode, Party, Seat};
+pub use coop::{ChoicePolicy, CoopSession, SceneUpdate};
\ No newline at end of file
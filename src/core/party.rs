@@ -0,0 +1,149 @@
+//! Hotseat play: two or more people share one game at one keyboard. They
+//! either take turns, each playing their own protagonist for a scene, or
+//! vote on every choice of a shared one.
+
+use serde::{Deserialize, Serialize};
+use crate::core::Player;
+use crate::utils::{GameError, GameResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HotseatMode {
+    /// Players take turns, one scene each, with their own stats.
+    #[default]
+    Alternate,
+    /// Everyone votes on each choice of a shared protagonist.
+    Vote,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Seat {
+    pub name: String,
+    /// This seat's protagonist while another seat plays. Always `None` in
+    /// vote mode and for the seat whose turn it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player: Option<Player>,
+    /// Choices made on this seat's turns.
+    #[serde(default)]
+    pub choices_made: u32,
+    /// Votes this seat cast for the choice that was taken.
+    #[serde(default)]
+    pub votes_won: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Party {
+    pub mode: HotseatMode,
+    pub seats: Vec<Seat>,
+    /// Index of the seat whose turn it is. In vote mode it settles ties.
+    #[serde(default)]
+    pub turn: usize,
+}
+
+impl Party {
+    /// A party of `names`, first seat first. In alternate mode every seat
+    /// after the first starts with a copy of `player`, renamed.
+    pub fn new(mode: HotseatMode, names: &[String], player: &Player) -> GameResult<Self> {
+        if names.len() < 2 {
+            return Err(GameError::player("Hotseat play needs at least two players".to_string()));
+        }
+        for (index, name) in names.iter().enumerate() {
+            if name.trim().is_empty() {
+                return Err(GameError::player("Player names can't be empty".to_string()));
+            }
+            if names[..index].contains(name) {
+                return Err(GameError::player(format!("Player '{}' is seated twice", name)));
+            }
+        }
+
+        let seats = names.iter().enumerate()
+            .map(|(index, name)| Seat {
+                name: name.clone(),
+                player: (mode == HotseatMode::Alternate && index > 0).then(|| Player { name: name.clone(), ..player.clone() }),
+                choices_made: 0,
+                votes_won: 0,
+            })
+            .collect();
+        Ok(Self { mode, seats, turn: 0 })
+    }
+
+    pub fn current(&self) -> &Seat {
+        &self.seats[self.turn]
+    }
+
+    /// The choice with the most of `votes`, one per seat in seat order. Ties
+    /// go to the current seat's vote if it's among them, else to the tied
+    /// choice voted for first.
+    pub fn tally<'a>(&self, votes: &'a [String]) -> Option<&'a String> {
+        majority(votes, self.turn)
+    }
+
+    /// Credits the seats whose vote was `chosen`.
+    pub fn record_votes(&mut self, votes: &[String], chosen: &str) {
+        for (seat, vote) in self.seats.iter_mut().zip(votes) {
+            if vote == chosen {
+                seat.votes_won += 1;
+            }
+        }
+    }
+
+    /// Hands the turn to the next seat. In alternate mode `player` is the
+    /// current seat's protagonist, and is swapped for the next seat's.
+    pub fn pass_turn(&mut self, player: &mut Player) {
+        let next = (self.turn + 1) % self.seats.len();
+        if self.mode == HotseatMode::Alternate {
+            self.seats[self.turn].choices_made += 1;
+            if let Some(next_player) = self.seats[next].player.take() {
+                self.seats[self.turn].player = Some(std::mem::replace(player, next_player));
+            }
+        }
+        self.turn = next;
+    }
+}
+
+/// The choice with the most of `votes`. Ties go to `votes[preferred]` if
+/// it's among them, else to the tied choice voted for first.
+pub fn majority(votes: &[String], preferred: usize) -> Option<&String> {
+    let count = |choice: &String| votes.iter().filter(|vote| *vote == choice).count();
+    let most = votes.iter().map(count).max()?;
+    votes.get(preferred)
+        .filter(|vote| count(vote) == most)
+        .or_else(|| votes.iter().find(|vote| count(vote) == most))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_party_turns_and_votes() {
+        let mut player = Player::new("Ana", None);
+        assert!(Party::new(HotseatMode::Alternate, &names(&["Ana"]), &player).is_err());
+        assert!(Party::new(HotseatMode::Alternate, &names(&["Ana", "Ana"]), &player).is_err());
+
+        let mut party = Party::new(HotseatMode::Alternate, &names(&["Ana", "Ben"]), &player).unwrap();
+        player.stats.health = 50;
+        party.pass_turn(&mut player);
+        assert_eq!((party.turn, player.name.as_str(), player.stats.health), (1, "Ben", 100));
+        party.pass_turn(&mut player);
+        assert_eq!((party.turn, player.name.as_str(), player.stats.health), (0, "Ana", 50));
+        assert_eq!(party.seats[1].choices_made, 1);
+
+        let mut party = Party::new(HotseatMode::Vote, &names(&["Ana", "Ben", "Cy"]), &player).unwrap();
+        assert!(party.seats.iter().all(|seat| seat.player.is_none()));
+        let votes = names(&["left", "right", "right"]);
+        assert_eq!(party.tally(&votes).map(String::as_str), Some("right"));
+        party.record_votes(&votes, "right");
+        assert_eq!(party.seats.iter().map(|seat| seat.votes_won).collect::<Vec<_>>(), [0, 1, 1]);
+
+        // Ties go to the seat whose turn it is
+        let votes = names(&["left", "right"]);
+        assert_eq!(party.tally(&votes).map(String::as_str), Some("left"));
+        party.pass_turn(&mut player);
+        assert_eq!(party.tally(&votes).map(String::as_str), Some("right"));
+        assert_eq!(player.name, "Ana");
+    }
+}
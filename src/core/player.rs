@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::collections::HashMap;
+use crate::core::{LevelCurve, LevelUpMode};
 use crate::utils::{GameError, GameResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +13,63 @@ pub struct PlayerStats {
     pub strength: i32,
     pub intelligence: i32,
     pub charisma: i32,
+    /// Limits declared by the story, keyed by stat name. Stats without
+    /// bounds keep the built-in minimums.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub bounds: HashMap<String, StatBounds>,
+    /// Level-up points the player has yet to spend, in `Allocate` mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_level_up: Option<PendingLevelUp>,
+}
+
+/// Levels gained whose stat points have not been allocated yet.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PendingLevelUp {
+    /// Level before the first unallocated level-up.
+    pub from_level: i32,
+    pub points: i32,
+}
+
+/// Minimum and maximum of one stat, e.g. `{"min": 0, "max": 10}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatBounds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
+}
+
+impl PlayerStats {
+    /// Names accepted by `Player::modify_stat`.
+    pub const NAMES: [&'static str; 6] = ["health", "max_health", "experience", "strength", "intelligence", "charisma"];
+
+    /// Stats level-up points can be spent on.
+    pub const ALLOCATABLE: [&'static str; 3] = ["strength", "intelligence", "charisma"];
+
+    /// Value of a stat by name.
+    pub fn get(&self, stat: &str) -> Option<i32> {
+        match stat {
+            "health" => Some(self.health),
+            "max_health" => Some(self.max_health),
+            "experience" => Some(self.experience),
+            "level" => Some(self.level),
+            "strength" => Some(self.strength),
+            "intelligence" => Some(self.intelligence),
+            "charisma" => Some(self.charisma),
+            _ => None,
+        }
+    }
+
+    /// Clamps `value` to the story's bounds for `stat`, falling back to
+    /// `default_min` when the story declares no minimum.
+    pub fn clamp_stat(&self, stat: &str, value: i32, default_min: i32) -> i32 {
+        let bounds = self.bounds.get(stat).copied().unwrap_or_default();
+        let value = value.max(bounds.min.unwrap_or(default_min));
+        match bounds.max {
+            Some(max) => value.min(max),
+            None => value,
+        }
+    }
 }
 
 impl Default for PlayerStats {
@@ -24,6 +82,8 @@ impl Default for PlayerStats {
             strength: 10,
             intelligence: 10,
             charisma: 10,
+            bounds: HashMap::new(),
+            pending_level_up: None,
         }
     }
 }
@@ -38,6 +98,33 @@ pub struct InventoryItem {
     pub properties: HashMap<String, serde_json::Value>,
 }
 
+impl InventoryItem {
+    /// Current durability, for items with a `durability` property.
+    pub fn durability(&self) -> Option<i32> {
+        self.properties.get("durability")
+            .and_then(|v| v.as_i64())
+            .map(|v| v as i32)
+    }
+
+    /// The `max_durability` property, or the durability the item started with.
+    pub fn max_durability(&self) -> Option<i32> {
+        self.properties.get("max_durability")
+            .and_then(|v| v.as_i64())
+            .map(|v| v as i32)
+            .or_else(|| self.durability())
+    }
+}
+
+/// Result of `Player::modify_durability`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DurabilityChange {
+    pub old_durability: i32,
+    pub new_durability: i32,
+    pub max_durability: i32,
+    /// The item reached zero durability and one of it was removed.
+    pub broken: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ItemType {
     Weapon,
@@ -53,6 +140,9 @@ pub struct Player {
     pub name: String,
     pub stats: PlayerStats,
     pub inventory: Vec<InventoryItem>,
+    /// The story's leveling curve, set by the engine; `None` uses the default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level_curve: Option<LevelCurve>,
 }
 
 impl Player {
@@ -62,6 +152,7 @@ impl Player {
             name: name.into(),
             stats: initial_stats.unwrap_or_default(),
             inventory: Vec::new(),
+            level_curve: None,
         }
     }
 
@@ -69,11 +160,11 @@ impl Player {
         match stat_name {
             "health" => {
                 let new_value = self.apply_operation(self.stats.health, value, operation);
-                self.stats.health = new_value.max(0).min(self.stats.max_health);
+                self.stats.health = self.stats.clamp_stat("health", new_value, 0).min(self.stats.max_health);
             }
             "max_health" => {
                 let new_value = self.apply_operation(self.stats.max_health, value, operation);
-                self.stats.max_health = new_value.max(1);
+                self.stats.max_health = self.stats.clamp_stat("max_health", new_value, 1);
                 if self.stats.health > self.stats.max_health {
                     self.stats.health = self.stats.max_health;
                 }
@@ -81,24 +172,24 @@ impl Player {
             "experience" => {
                 let old_level = self.stats.level;
                 let new_value = self.apply_operation(self.stats.experience, value, operation);
-                self.stats.experience = new_value.max(0);
+                self.stats.experience = self.stats.clamp_stat("experience", new_value, 0);
                 self.update_level();
                 
                 if self.stats.level > old_level {
-                    self.level_up_benefits(self.stats.level - old_level);
+                    self.level_up_benefits(old_level, self.stats.level - old_level);
                 }
             }
             "strength" => {
                 let new_value = self.apply_operation(self.stats.strength, value, operation);
-                self.stats.strength = new_value.max(1);
+                self.stats.strength = self.stats.clamp_stat("strength", new_value, 1);
             }
             "intelligence" => {
                 let new_value = self.apply_operation(self.stats.intelligence, value, operation);
-                self.stats.intelligence = new_value.max(1);
+                self.stats.intelligence = self.stats.clamp_stat("intelligence", new_value, 1);
             }
             "charisma" => {
                 let new_value = self.apply_operation(self.stats.charisma, value, operation);
-                self.stats.charisma = new_value.max(1);
+                self.stats.charisma = self.stats.clamp_stat("charisma", new_value, 1);
             }
             _ => return Err(GameError::player(format!("Unknown stat: {}", stat_name))),
         }
@@ -135,13 +226,69 @@ impl Player {
         self.inventory
             .iter()
             .find(|i| i.id == item_id)
-            .map_or(false, |item| item.quantity >= quantity)
+            .is_some_and(|item| item.quantity >= quantity)
     }
 
     pub fn get_item(&self, item_id: &str) -> Option<&InventoryItem> {
         self.inventory.iter().find(|i| i.id == item_id)
     }
 
+    /// Wears down or repairs an item, keeping durability between zero and
+    /// its maximum. An item worn down to zero breaks; if the player has
+    /// more than one, the next one is intact.
+    pub fn modify_durability(&mut self, item_id: &str, value: i32, operation: StatOperation) -> GameResult<DurabilityChange> {
+        let item = self.get_item(item_id)
+            .ok_or_else(|| GameError::player(format!("Item not found: {}", item_id)))?;
+        let old_durability = item.durability()
+            .ok_or_else(|| GameError::player(format!("{} has no durability", item.name)))?;
+        let max_durability = item.max_durability().unwrap_or(old_durability);
+        let new_durability = self.apply_operation(old_durability, value, operation).clamp(0, max_durability);
+        let broken = new_durability == 0;
+
+        let item = self.inventory.iter_mut()
+            .find(|i| i.id == item_id)
+            .expect("item was found above");
+        // Remember the maximum so repairs can't go past it
+        item.properties.insert("max_durability".to_string(), serde_json::json!(max_durability));
+        let stored = if broken { max_durability } else { new_durability };
+        item.properties.insert("durability".to_string(), serde_json::json!(stored));
+
+        if broken {
+            self.remove_item(item_id, 1)?;
+        }
+
+        Ok(DurabilityChange {
+            old_durability,
+            new_durability,
+            max_durability,
+            broken,
+        })
+    }
+
+    /// Spends all pending level-up points, e.g. `{"strength": 2, "charisma": 1}`,
+    /// and returns the level-up they came from.
+    pub fn allocate_points(&mut self, allocation: &HashMap<String, i32>) -> GameResult<PendingLevelUp> {
+        let pending = self.stats.pending_level_up
+            .ok_or_else(|| GameError::player("No level-up points to allocate"))?;
+
+        if let Some(stat) = allocation.keys().find(|stat| !PlayerStats::ALLOCATABLE.contains(&stat.as_str())) {
+            return Err(GameError::player(format!("Points can't be spent on {}", stat)));
+        }
+        if allocation.values().any(|&points| points < 0) {
+            return Err(GameError::player("Points can't be negative"));
+        }
+        let total: i32 = allocation.values().sum();
+        if total != pending.points {
+            return Err(GameError::player(format!("Allocate exactly {} points (got {})", pending.points, total)));
+        }
+
+        for (stat, &points) in allocation {
+            self.modify_stat(stat, points, StatOperation::Add)?;
+        }
+        self.stats.pending_level_up = None;
+        Ok(pending)
+    }
+
     pub fn use_consumable(&mut self, item_id: &str) -> GameResult<()> {
         let item = self.get_item(item_id)
             .ok_or_else(|| GameError::player(format!("Item not found: {}", item_id)))?;
@@ -233,34 +380,44 @@ impl Player {
     fn apply_operation(&self, current: i32, value: i32, operation: StatOperation) -> i32 {
         match operation {
             StatOperation::Set => value,
-            StatOperation::Add => current + value,
-            StatOperation::Subtract => current - value,
-            StatOperation::Multiply => current * value,
+            StatOperation::Add => current.saturating_add(value),
+            StatOperation::Subtract => current.saturating_sub(value),
+            StatOperation::Multiply => current.saturating_mul(value),
         }
     }
 
-    fn update_level(&mut self) {
-        let new_level = self.calculate_level_from_experience(self.stats.experience);
-        self.stats.level = new_level;
+    fn level_curve(&self) -> LevelCurve {
+        self.level_curve.clone().unwrap_or_default()
     }
 
-    fn calculate_level_from_experience(&self, experience: i32) -> i32 {
-        // Level = floor(sqrt(experience / 100)) + 1
-        ((experience as f32 / 100.0).sqrt().floor() as i32) + 1
+    fn update_level(&mut self) {
+        self.stats.level = self.level_curve().level_for_experience(self.stats.experience);
     }
 
     fn experience_required_for_level(&self, level: i32) -> i32 {
-        // Inverse of level calculation: exp = (level - 1)² * 100
-        (level - 1).pow(2) * 100
+        self.level_curve().experience_for_level(level)
     }
 
-    fn level_up_benefits(&mut self, levels_gained: i32) {
-        // Grant stat increases on level up
-        self.stats.max_health += levels_gained * 10;
-        self.stats.health = self.stats.max_health; // Full heal on level up
-        self.stats.strength += levels_gained;
-        self.stats.intelligence += levels_gained;
-        self.stats.charisma += levels_gained;
+    fn level_up_benefits(&mut self, old_level: i32, levels_gained: i32) {
+        // Grant the curve's rewards, within the story's bounds
+        let curve = self.level_curve();
+        let rewards = curve.rewards;
+        let stats = &mut self.stats;
+        stats.max_health = stats.clamp_stat("max_health", stats.max_health + levels_gained * rewards.max_health, 1);
+        if rewards.full_heal {
+            stats.health = stats.clamp_stat("health", stats.max_health, 0).min(stats.max_health);
+        }
+
+        if curve.mode == LevelUpMode::Allocate {
+            // The player spends these points later; see `allocate_points`
+            let pending = stats.pending_level_up.get_or_insert(PendingLevelUp { from_level: old_level, points: 0 });
+            pending.points += levels_gained * rewards.points;
+            return;
+        }
+
+        stats.strength = stats.clamp_stat("strength", stats.strength + levels_gained * rewards.strength, 1);
+        stats.intelligence = stats.clamp_stat("intelligence", stats.intelligence + levels_gained * rewards.intelligence, 1);
+        stats.charisma = stats.clamp_stat("charisma", stats.charisma + levels_gained * rewards.charisma, 1);
     }
 }
 
@@ -294,6 +451,63 @@ mod tests {
         assert_eq!(player.stats.strength, 15);
     }
 
+    #[test]
+    fn test_stat_bounds() {
+        let mut stats = PlayerStats { intelligence: 5, ..Default::default() };
+        stats.bounds.insert("intelligence".to_string(), StatBounds { min: Some(0), max: Some(10) });
+        let mut player = Player::new("Test", Some(stats));
+
+        // The story's minimum replaces the built-in minimum of 1
+        player.modify_stat("intelligence", 8, StatOperation::Subtract).unwrap();
+        assert_eq!(player.stats.intelligence, 0);
+
+        player.modify_stat("intelligence", 25, StatOperation::Add).unwrap();
+        assert_eq!(player.stats.intelligence, 10);
+
+        // Level-up bonuses respect the cap too
+        player.modify_stat("experience", 100, StatOperation::Add).unwrap();
+        assert_eq!(player.stats.level, 2);
+        assert_eq!(player.stats.intelligence, 10);
+        assert_eq!(player.stats.strength, 11);
+    }
+
+    #[test]
+    fn test_custom_level_curve() {
+        let mut player = Player::new("Test", None);
+        player.level_curve = Some(LevelCurve {
+            thresholds: vec![20, 40],
+            rewards: crate::core::LevelRewards { strength: 2, full_heal: false, ..Default::default() },
+            ..Default::default()
+        });
+        player.modify_stat("health", 50, StatOperation::Subtract).unwrap();
+
+        player.modify_stat("experience", 45, StatOperation::Add).unwrap();
+        assert_eq!(player.stats.level, 3);
+        assert_eq!(player.stats.strength, 14);
+        assert_eq!(player.stats.health, 50);
+        assert_eq!(player.experience_to_next_level(), 855);
+    }
+
+    #[test]
+    fn test_allocate_level_points() {
+        let mut player = Player::new("Test", None);
+        player.level_curve = Some(LevelCurve { mode: LevelUpMode::Allocate, ..Default::default() });
+
+        player.modify_stat("experience", 400, StatOperation::Add).unwrap();
+        assert_eq!(player.stats.strength, 10);
+        assert_eq!(player.stats.pending_level_up, Some(PendingLevelUp { from_level: 1, points: 6 }));
+
+        let too_few: HashMap<String, i32> = [("strength".to_string(), 2)].into();
+        assert!(player.allocate_points(&too_few).is_err());
+        let wrong_stat: HashMap<String, i32> = [("health".to_string(), 6)].into();
+        assert!(player.allocate_points(&wrong_stat).is_err());
+
+        let allocation: HashMap<String, i32> = [("strength".to_string(), 4), ("charisma".to_string(), 2)].into();
+        player.allocate_points(&allocation).unwrap();
+        assert_eq!((player.stats.strength, player.stats.charisma), (14, 12));
+        assert!(player.stats.pending_level_up.is_none());
+    }
+
     #[test]
     fn test_inventory_management() {
         let mut player = Player::new("Test", None);
@@ -316,6 +530,35 @@ mod tests {
         assert_eq!(player.inventory.len(), 0);
     }
 
+    #[test]
+    fn test_durability() {
+        let mut player = Player::new("Test", None);
+        player.add_item(InventoryItem {
+            id: "shield".to_string(),
+            name: "Wooden Shield".to_string(),
+            description: "Splinters easily".to_string(),
+            item_type: ItemType::Armor,
+            quantity: 2,
+            properties: [("durability".to_string(), serde_json::json!(10))].into(),
+        });
+
+        let change = player.modify_durability("shield", 4, StatOperation::Subtract).unwrap();
+        assert_eq!((change.old_durability, change.new_durability, change.broken), (10, 6, false));
+
+        // Repairs stop at the maximum
+        player.modify_durability("shield", 50, StatOperation::Add).unwrap();
+        assert_eq!(player.get_item("shield").unwrap().durability(), Some(10));
+
+        let change = player.modify_durability("shield", 12, StatOperation::Subtract).unwrap();
+        assert!(change.broken);
+        let shield = player.get_item("shield").unwrap();
+        assert_eq!(shield.quantity, 1);
+        assert_eq!(shield.durability(), Some(10));
+
+        player.modify_durability("shield", 10, StatOperation::Subtract).unwrap();
+        assert!(!player.has_item("shield", 1));
+    }
+
     #[test]
     fn test_experience_and_leveling() {
         let mut player = Player::new("Test", None);
@@ -0,0 +1,36 @@
+use crate::core::{GameEvent, GameState};
+use crate::story::{Condition, Effect};
+use crate::utils::{GameError, GameResult};
+
+/// Extension registered with `GameEngine::register_plugin`.
+///
+/// Plugins claim `Custom` effects and conditions by key. The first registered
+/// plugin that handles a key wins; unclaimed keys keep the built-in behavior.
+pub trait EnginePlugin: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn handles_effect(&self, _key: &str) -> bool {
+        false
+    }
+
+    /// Applies a `Custom` effect whose key this plugin handles.
+    fn apply_effect(&self, effect: &Effect, _game_state: &mut GameState) -> GameResult<()> {
+        Err(GameError::plugin(format!(
+            "Plugin '{}' cannot apply effect '{}'", self.name(), effect.key
+        )))
+    }
+
+    fn handles_condition(&self, _key: &str) -> bool {
+        false
+    }
+
+    /// Returns the value a `Custom` condition compares against with its operator.
+    fn condition_value(&self, condition: &Condition, _game_state: &GameState) -> GameResult<serde_json::Value> {
+        Err(GameError::plugin(format!(
+            "Plugin '{}' cannot evaluate condition '{}'", self.name(), condition.key
+        )))
+    }
+
+    /// Called for every event the engine emits.
+    fn on_event(&self, _event: &GameEvent) {}
+}
@@ -0,0 +1,66 @@
+/// Small deterministic random number generator (SplitMix64). Games keep a
+/// seed in their state so anything random about a playthrough can be
+/// reproduced from a save.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A generator for one purpose, e.g. a scene, derived from the game's
+    /// seed and a key so different purposes don't share a sequence.
+    pub fn for_key(seed: u64, key: &str) -> Self {
+        // FNV-1a, which unlike std's hasher is stable across releases
+        let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self::new(seed ^ hash)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`; `bound` must not be zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_shuffle() {
+        let mut first: Vec<u32> = (0..10).collect();
+        let mut second = first.clone();
+        SeededRng::for_key(42, "tavern").shuffle(&mut first);
+        SeededRng::for_key(42, "tavern").shuffle(&mut second);
+        assert_eq!(first, second);
+
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+
+        let mut other: Vec<u32> = (0..10).collect();
+        SeededRng::for_key(43, "tavern").shuffle(&mut other);
+        assert_ne!(first, other);
+    }
+}
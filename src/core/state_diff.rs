@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use serde_json::Value;
+use crate::core::{GameState, PlayerStats};
+use crate::utils::title_case;
+
+/// What changed between two game states, see `GameState::diff`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateDiff {
+    /// Old and new scene ID, if the scene changed.
+    pub scene: Option<(String, String)>,
+    /// Old and new protagonist ID, if a `SwitchCharacter` effect ran.
+    pub character: Option<(String, String)>,
+    pub stats: Vec<StatChange>,
+    pub flags: Vec<FlagChange>,
+    pub items: Vec<ItemChange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatChange {
+    pub stat: String,
+    pub old_value: i32,
+    pub new_value: i32,
+}
+
+/// `None` means the flag wasn't set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlagChange {
+    pub flag: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+/// A quantity of 0 means the item wasn't in the inventory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemChange {
+    pub item_id: String,
+    pub name: String,
+    pub old_quantity: i32,
+    pub new_quantity: i32,
+}
+
+impl StateDiff {
+    pub fn between(old: &GameState, new: &GameState) -> Self {
+        let changed = |old: &String, new: &String| (old != new).then(|| (old.clone(), new.clone()));
+
+        let stats = PlayerStats::NAMES.iter()
+            .chain(std::iter::once(&"level"))
+            .filter_map(|&stat| {
+                let old_value = old.player.stats.get(stat)?;
+                let new_value = new.player.stats.get(stat)?;
+                (old_value != new_value).then(|| StatChange { stat: stat.to_string(), old_value, new_value })
+            })
+            .collect();
+
+        let flag_names: BTreeSet<&String> = old.flags.keys().chain(new.flags.keys()).collect();
+        let flags = flag_names.into_iter()
+            .filter_map(|flag| {
+                let old_value = old.flags.get(flag).cloned();
+                let new_value = new.flags.get(flag).cloned();
+                (old_value != new_value).then(|| FlagChange { flag: flag.clone(), old_value, new_value })
+            })
+            .collect();
+
+        let item_ids: BTreeSet<&String> = old.player.inventory.iter()
+            .chain(&new.player.inventory)
+            .map(|item| &item.id)
+            .collect();
+        let items = item_ids.into_iter()
+            .filter_map(|item_id| {
+                let old_item = old.player.get_item(item_id);
+                let new_item = new.player.get_item(item_id);
+                let old_quantity = old_item.map_or(0, |item| item.quantity);
+                let new_quantity = new_item.map_or(0, |item| item.quantity);
+                let name = new_item.or(old_item)?.name.clone();
+                (old_quantity != new_quantity).then(|| ItemChange { item_id: item_id.clone(), name, old_quantity, new_quantity })
+            })
+            .collect();
+
+        Self {
+            scene: changed(&old.current_scene_id, &new.current_scene_id),
+            character: changed(&old.active_character, &new.active_character),
+            stats,
+            flags,
+            items,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scene.is_none()
+            && self.character.is_none()
+            && self.stats.is_empty()
+            && self.flags.is_empty()
+            && self.items.is_empty()
+    }
+
+    pub fn stat(&self, stat: &str) -> Option<&StatChange> {
+        self.stats.iter().find(|change| change.stat == stat)
+    }
+
+    pub fn flag(&self, flag: &str) -> Option<&FlagChange> {
+        self.flags.iter().find(|change| change.flag == flag)
+    }
+
+    pub fn item(&self, item_id: &str) -> Option<&ItemChange> {
+        self.items.iter().find(|change| change.item_id == item_id)
+    }
+
+    /// Player-facing summary of stat and item changes, e.g.
+    /// `["Strength +2", "+1 Rusty Key"]`. Flags and scenes are left out so
+    /// they don't spoil the story. After a character switch the stats and
+    /// items are another character's, so there's nothing to compare.
+    pub fn summary(&self) -> Vec<String> {
+        if self.character.is_some() {
+            return Vec::new();
+        }
+
+        let stats = self.stats.iter().map(|change| {
+            format!("{} {:+}", title_case(&change.stat), change.new_value - change.old_value)
+        });
+        let items = self.items.iter().map(|change| {
+            format!("{:+} {}", change.new_quantity - change.old_quantity, change.name)
+        });
+        stats.chain(items).collect()
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &Option<Value>| value.as_ref().map_or("unset".to_string(), Value::to_string);
+
+        if let Some((old, new)) = &self.scene {
+            writeln!(f, "scene: {} -> {}", old, new)?;
+        }
+        if let Some((old, new)) = &self.character {
+            writeln!(f, "character: {} -> {}", old, new)?;
+        }
+        for change in &self.stats {
+            writeln!(f, "stat {}: {} -> {}", change.stat, change.old_value, change.new_value)?;
+        }
+        for change in &self.flags {
+            writeln!(f, "flag {}: {} -> {}", change.flag, value(&change.old_value), value(&change.new_value))?;
+        }
+        for change in &self.items {
+            writeln!(f, "item {}: {} -> {}", change.item_id, change.old_quantity, change.new_quantity)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{InventoryItem, ItemType, Player};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_state_diff() {
+        let mut old = GameState::new("story".to_string(), "gate".to_string(), Player::new("Tester", None));
+        old.set_flag("gold", serde_json::json!(5)).unwrap();
+        old.set_flag("door_open", serde_json::json!(false)).unwrap();
+
+        let mut new = old.clone();
+        new.current_scene_id = "hall".to_string();
+        new.player.stats.strength += 2;
+        new.set_flag("gold", serde_json::json!(10)).unwrap();
+        new.flags.remove("door_open");
+        new.player.add_item(InventoryItem {
+            id: "key".to_string(),
+            name: "Rusty Key".to_string(),
+            description: String::new(),
+            item_type: ItemType::KeyItem,
+            quantity: 1,
+            properties: HashMap::new(),
+        });
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.scene, Some(("gate".to_string(), "hall".to_string())));
+        assert_eq!(diff.stat("strength").map(|change| change.new_value - change.old_value), Some(2));
+        assert_eq!(diff.flag("gold").and_then(|change| change.new_value.clone()), Some(serde_json::json!(10)));
+        assert_eq!(diff.flag("door_open").map(|change| change.new_value.is_none()), Some(true));
+        assert_eq!(diff.item("key").map(|change| change.new_quantity), Some(1));
+        assert_eq!(diff.summary(), vec!["Strength +2".to_string(), "+1 Rusty Key".to_string()]);
+
+        assert!(new.diff(&new).is_empty());
+
+        let mut switched = new.clone();
+        switched.active_character = "ranger".to_string();
+        switched.player.stats.strength += 5;
+        assert!(new.diff(&switched).summary().is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Scenes kept in the visit history by default.
+pub const DEFAULT_VISIT_HISTORY: usize = 1000;
+
+/// Which scenes were visited and how often, plus the most recent visits in
+/// order. Only the history is bounded, so long runs don't grow saves while
+/// `SceneVisited` conditions and visit counts stay exact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StoredVisits")]
+pub struct VisitedScenes {
+    /// Visits per scene; a scene is visited once it's in here.
+    counts: HashMap<String, usize>,
+    /// Every visit, including those no longer in the history.
+    total: usize,
+    /// The latest visits, oldest first.
+    history: VecDeque<String>,
+    /// Visits kept in `history`; 0 keeps none.
+    history_limit: usize,
+}
+
+/// Saves from before visits were counted hold the full list of visits.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredVisits {
+    List(Vec<String>),
+    Counted {
+        counts: HashMap<String, usize>,
+        total: usize,
+        #[serde(default)]
+        history: VecDeque<String>,
+        #[serde(default = "default_history_limit")]
+        history_limit: usize,
+    },
+}
+
+fn default_history_limit() -> usize {
+    DEFAULT_VISIT_HISTORY
+}
+
+impl From<StoredVisits> for VisitedScenes {
+    fn from(stored: StoredVisits) -> Self {
+        match stored {
+            StoredVisits::List(scene_ids) => {
+                let mut visits = Self::default();
+                for scene_id in &scene_ids {
+                    visits.visit(scene_id);
+                }
+                visits
+            }
+            StoredVisits::Counted { counts, total, history, history_limit } => {
+                let mut visits = Self { counts, total, history, history_limit };
+                visits.trim_history();
+                visits
+            }
+        }
+    }
+}
+
+impl Default for VisitedScenes {
+    fn default() -> Self {
+        Self::with_history_limit(DEFAULT_VISIT_HISTORY)
+    }
+}
+
+impl VisitedScenes {
+    pub fn with_history_limit(history_limit: usize) -> Self {
+        Self {
+            counts: HashMap::new(),
+            total: 0,
+            history: VecDeque::new(),
+            history_limit,
+        }
+    }
+
+    /// Keeps at most `history_limit` visits in the history from now on,
+    /// dropping the oldest ones if there are more.
+    pub fn set_history_limit(&mut self, history_limit: usize) {
+        self.history_limit = history_limit;
+        self.trim_history();
+    }
+
+    pub fn visit(&mut self, scene_id: &str) {
+        *self.counts.entry(scene_id.to_string()).or_default() += 1;
+        self.total += 1;
+        self.history.push_back(scene_id.to_string());
+        self.trim_history();
+    }
+
+    pub fn contains(&self, scene_id: &str) -> bool {
+        self.counts.contains_key(scene_id)
+    }
+
+    pub fn count(&self, scene_id: &str) -> usize {
+        self.counts.get(scene_id).copied().unwrap_or(0)
+    }
+
+    /// Every visit, counting revisits.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn unique(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// The scenes visited at least once, in no particular order.
+    pub fn scene_ids(&self) -> impl Iterator<Item = &String> {
+        self.counts.keys()
+    }
+
+    /// The latest visits, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &String> {
+        self.history.iter()
+    }
+
+    /// Forgets the scenes `keep` returns false for. The total is left
+    /// alone, since item cooldowns count turns with it.
+    pub fn retain<F: FnMut(&str) -> bool>(&mut self, mut keep: F) {
+        self.counts.retain(|scene_id, _| keep(scene_id));
+        let counts = &self.counts;
+        self.history.retain(|scene_id| counts.contains_key(scene_id));
+    }
+
+    fn trim_history(&mut self) {
+        while self.history.len() > self.history_limit {
+            self.history.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_history_keeps_counts() {
+        let mut visits = VisitedScenes::with_history_limit(2);
+        for scene_id in ["start", "hall", "start", "cellar"] {
+            visits.visit(scene_id);
+        }
+
+        assert_eq!(visits.history().collect::<Vec<_>>(), ["start", "cellar"]);
+        assert!(visits.contains("hall"));
+        assert_eq!(visits.count("start"), 2);
+        assert_eq!((visits.total(), visits.unique()), (4, 3));
+
+        let json = serde_json::to_value(&visits).unwrap();
+        assert_eq!(serde_json::from_value::<VisitedScenes>(json).unwrap(), visits);
+    }
+
+    #[test]
+    fn test_loads_visit_lists_from_old_saves() {
+        let visits: VisitedScenes = serde_json::from_str(r#"["start", "hall", "start"]"#).unwrap();
+        assert_eq!(visits.count("start"), 2);
+        assert_eq!(visits.total(), 3);
+        assert_eq!(visits.history().collect::<Vec<_>>(), ["start", "hall", "start"]);
+    }
+}
@@ -0,0 +1,126 @@
+//! Subscriptions to single flags and stats, so frontends can bind a widget
+//! to a value instead of polling the whole `GameState` every frame.
+
+use futures::Stream;
+use serde_json::Value;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use crate::core::{GameEvent, GameEventHandler, GameState};
+use crate::core::events::GameEventType;
+
+struct Watch<T> {
+    key: String,
+    last: Option<T>,
+    sender: UnboundedSender<T>,
+}
+
+impl<T: Clone + PartialEq> Watch<T> {
+    /// Sends `value` unless it's the last one sent. Returns false once the
+    /// stream has been dropped.
+    fn send(&mut self, value: T) -> bool {
+        if self.last.as_ref() == Some(&value) {
+            return !self.sender.is_closed();
+        }
+        self.last = Some(value.clone());
+        self.sender.send(value).is_ok()
+    }
+}
+
+/// Open `watch_flag` and `watch_stat` streams, fed from the `FlagSet` and
+/// `StatModified` events the engine emits.
+#[derive(Default)]
+pub struct Watchers {
+    flags: Vec<Watch<Value>>,
+    stats: Vec<Watch<i32>>,
+}
+
+impl Watchers {
+    /// Stream of the values of flag `key`, starting with its value in
+    /// `game_state`, if set.
+    pub fn watch_flag(&mut self, key: &str, game_state: Option<&GameState>) -> impl Stream<Item = Value> {
+        let current = game_state.and_then(|state| state.get_flag(key)).cloned();
+        watch(&mut self.flags, key, current)
+    }
+
+    /// Stream of the values of stat `name`, starting with its value in
+    /// `game_state`.
+    pub fn watch_stat(&mut self, name: &str, game_state: Option<&GameState>) -> impl Stream<Item = i32> {
+        let current = game_state.and_then(|state| state.player.stats.get(name));
+        watch(&mut self.stats, name, current)
+    }
+
+    /// Sends every watched value in `game_state` that changed, after the
+    /// state was replaced without events, e.g. by loading a save.
+    pub fn refresh(&mut self, game_state: &GameState) {
+        self.flags.retain_mut(|watch| match game_state.get_flag(&watch.key) {
+            Some(value) => watch.send(value.clone()),
+            None => !watch.sender.is_closed(),
+        });
+        self.stats.retain_mut(|watch| match game_state.player.stats.get(&watch.key) {
+            Some(value) => watch.send(value),
+            None => !watch.sender.is_closed(),
+        });
+    }
+}
+
+impl GameEventHandler for Watchers {
+    fn handle_event(&mut self, event: &GameEvent) {
+        let data = &event.data;
+        match event.event_type {
+            GameEventType::FlagSet => {
+                let (Some(key), Some(value)) = (data.get("flag_name").and_then(Value::as_str), data.get("value")) else {
+                    return;
+                };
+                self.flags.retain_mut(|watch| watch.key != key || watch.send(value.clone()));
+            }
+            GameEventType::StatModified => {
+                let (Some(name), Some(value)) = (
+                    data.get("stat_name").and_then(Value::as_str),
+                    data.get("new_value").and_then(Value::as_i64),
+                ) else {
+                    return;
+                };
+                self.stats.retain_mut(|watch| watch.key != name || watch.send(value as i32));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn watch<T: Clone + PartialEq>(watches: &mut Vec<Watch<T>>, key: &str, current: Option<T>) -> impl Stream<Item = T> {
+    let (sender, receiver) = unbounded_channel();
+    let mut watch = Watch { key: key.to_string(), last: None, sender };
+    if let Some(value) = current {
+        watch.send(value);
+    }
+    watches.push(watch);
+    futures::stream::unfold(receiver, |mut receiver: UnboundedReceiver<T>| async move {
+        receiver.recv().await.map(|value| (value, receiver))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_watchers_skip_unchanged_values() {
+        let mut watchers = Watchers::default();
+        let mut gold = Box::pin(watchers.watch_flag("gold", None));
+        let mut health = Box::pin(watchers.watch_stat("health", None));
+
+        watchers.handle_event(&GameEvent::flag_set("gold", &Value::from(5)));
+        watchers.handle_event(&GameEvent::flag_set("gold", &Value::from(5)));
+        watchers.handle_event(&GameEvent::flag_set("silver", &Value::from(1)));
+        watchers.handle_event(&GameEvent::stat_modified("health", 100, 90));
+        watchers.handle_event(&GameEvent::flag_set("gold", &Value::from(6)));
+        assert_eq!(gold.next().await, Some(Value::from(5)));
+        assert_eq!(gold.next().await, Some(Value::from(6)));
+        assert_eq!(health.next().await, Some(90));
+
+        drop(gold);
+        watchers.handle_event(&GameEvent::flag_set("gold", &Value::from(7)));
+        assert!(watchers.flags.is_empty());
+        assert_eq!(watchers.stats.len(), 1);
+    }
+}
@@ -3,6 +3,8 @@ pub mod story;
 pub mod ui;
 pub mod config;
 pub mod utils;
+pub mod plugins;
+pub mod server;
 
 pub use core::{engine::GameEngine, player::Player, game_state::GameState};
 pub use story::{Story, Scene, Choice};
@@ -1,6 +1,19 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use dialoguer::{Confirm, Input};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use text_adventure_game::{GameInterface, Config, VERSION};
+use text_adventure_game::ui::{run_irc_bot, run_matrix_bot, ChatBot, IrcBotSettings, KioskSettings, MatrixBotSettings};
+use text_adventure_game::config::{CliConfig, RendererKind};
+use text_adventure_game::core::{HotseatMode, PlayerStats};
+use text_adventure_game::story::{ChoiceScriptImport, Dictionary, ExportFormat, Layout, Linter, PathAnalysis, StoryDiff, StoryExport, StoryGenerator, StoryLoader, StoryTemplate};
+use text_adventure_game::story::loader::is_valid_story_id;
+use text_adventure_game::story::testing::{Explorer, Invariant, Strategy};
+use text_adventure_game::server::{serve_grpc, serve_http, GameService};
+use text_adventure_game::utils::{serve_metrics, Metrics};
 use tracing::{info, error};
 
 #[derive(Parser)]
@@ -19,44 +32,445 @@ struct Cli {
     /// Story to load directly
     #[arg(short, long)]
     story: Option<String>,
+
+    /// Directory containing story files
+    #[arg(long)]
+    stories_dir: Option<PathBuf>,
+
+    /// Directory for save games
+    #[arg(long)]
+    saves_dir: Option<PathBuf>,
+
+    /// Theme to use for the game interface
+    #[arg(long)]
+    theme: Option<String>,
+
+    /// Logging level: error, warn, info, debug, trace
+    #[arg(long)]
+    log_level: Option<String>,
+
+    /// Enable tools for story authors, such as the debug console
+    #[arg(long)]
+    dev: bool,
+
+    /// Plain text output without colors, emoji or cursor movement (the
+    /// default when output isn't a terminal)
+    #[arg(long)]
+    plain: bool,
+
+    /// Let this Twitch channel's chat vote on choices (needs the twitch feature)
+    #[arg(long, value_name = "CHANNEL")]
+    twitch: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show added, removed and changed scenes and choices between two story versions
+    Diff {
+        /// Old story file (or chunked story directory)
+        old: PathBuf,
+        /// New story file (or chunked story directory)
+        new: PathBuf,
+    },
+    /// Show a story's shortest and longest paths to an ending and its estimated playtime
+    Stats {
+        /// Story file (or chunked story directory)
+        story: PathBuf,
+    },
+    /// Check a story's spelling and style (long sentences, duplicate titles, empty text) and every chunk of a chunked story
+    Lint {
+        /// Story file (or chunked story directory)
+        story: PathBuf,
+        /// Word list to spell check against, one word per line (default: the system's)
+        #[arg(long)]
+        dictionary: Option<PathBuf>,
+    },
+    /// Play every path through a story and check the invariants it declares
+    Explore {
+        /// Story file (or chunked story directory)
+        story: PathBuf,
+        /// Most choices to make in one playthrough
+        #[arg(long, default_value_t = 50)]
+        depth: usize,
+        /// Take this many random playthroughs instead of exploring every path
+        #[arg(long)]
+        random: Option<usize>,
+        /// Seed for the random playthroughs, so a failing run can be repeated
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Create a story from a template, asking for its title, author and what to scaffold
+    New {
+        /// Story ID, also its file name (asked for if left out)
+        id: Option<String>,
+    },
+    /// Write a generated grid, maze or dungeon story, for stress tests and experiments
+    Generate {
+        layout: GeneratedLayout,
+        /// Columns of a grid or maze
+        #[arg(long, default_value_t = 10)]
+        width: usize,
+        /// Rows of a grid or maze
+        #[arg(long, default_value_t = 10)]
+        height: usize,
+        /// Rooms in a dungeon
+        #[arg(long, default_value_t = 30)]
+        rooms: usize,
+        /// Seed for mazes and dungeons; the same seed gives the same story
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+        /// Story file to write (default: standard output)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert a basic ChoiceScript file (*choice, *goto, *set, *if) into a story
+    Import {
+        /// ChoiceScript source file
+        source: PathBuf,
+        /// Story ID (default: the file name)
+        #[arg(long)]
+        id: Option<String>,
+        /// Story file to write (default: standard output)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Convert a story to Twee (SugarCube) or Ink, warning about what doesn't carry over
+    Export {
+        format: ExportedFormat,
+        /// Story file (or chunked story directory)
+        story: PathBuf,
+        /// File to write (default: standard output)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Attract mode for exhibitions: demo random playthroughs until someone presses a key
+    Kiosk {
+        /// Seconds the title screen waits for a key before a demo starts
+        #[arg(long, default_value_t = 30)]
+        idle: u64,
+        /// Seconds each demo scene is shown
+        #[arg(long, default_value_t = 3)]
+        delay: u64,
+        /// Stop after this many demos (default: never)
+        #[arg(long)]
+        demos: Option<usize>,
+    },
+    /// Run an IRC bot that plays a game in each of its channels (needs the irc feature)
+    IrcBot {
+        /// Server as host:port
+        #[arg(long)]
+        server: String,
+        #[arg(long, default_value = "text-game")]
+        nick: String,
+        /// Channel to join (repeatable)
+        #[arg(long = "channel", required = true)]
+        channels: Vec<String>,
+        /// Environment variable holding the server password, if it needs one
+        #[arg(long)]
+        password_env: Option<String>,
+        /// Serve Prometheus metrics at http://ADDR/metrics (e.g. 127.0.0.1:9090)
+        #[arg(long)]
+        metrics: Option<String>,
+    },
+    /// Run a Matrix bot that plays a game in each room it's invited to (needs the matrix feature)
+    MatrixBot {
+        /// Homeserver URL, e.g. https://matrix.example.org
+        #[arg(long)]
+        homeserver: String,
+        /// Environment variable holding the bot account's access token
+        #[arg(long, default_value = "MATRIX_ACCESS_TOKEN")]
+        token_env: String,
+        /// User allowed to !save and !reset, e.g. @ana:example.org (repeatable; default: everyone)
+        #[arg(long = "admin")]
+        admins: Vec<String>,
+        /// Serve Prometheus metrics at http://ADDR/metrics (e.g. 127.0.0.1:9090)
+        #[arg(long)]
+        metrics: Option<String>,
+    },
+    /// Serve the engine as an API for other frontends
+    #[command(group(ArgGroup::new("api").required(true).multiple(true)))]
+    Serve {
+        /// Serve gRPC (see proto/text_game.proto) at this host:port (needs the grpc feature)
+        #[arg(long, group = "api")]
+        grpc: Option<String>,
+        /// Serve the HTTP API at this host:port: GraphQL at /graphql (graphql feature), REST at /api
+        /// with docs at /docs (rest feature)
+        #[arg(long, group = "api")]
+        http: Option<String>,
+        /// Serve Prometheus metrics at http://ADDR/metrics (e.g. 127.0.0.1:9090)
+        #[arg(long)]
+        metrics: Option<String>,
+    },
+    /// Local multiplayer: 2+ players share the game given with --story
+    Hotseat {
+        /// Player names, in turn order (e.g. Ana,Ben)
+        #[arg(required = true, num_args = 1.., value_delimiter = ',')]
+        players: Vec<String>,
+        /// Vote on every choice instead of taking turns with separate stats
+        #[arg(long)]
+        vote: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportedFormat {
+    Twee,
+    Ink,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GeneratedLayout {
+    Grid,
+    Maze,
+    Dungeon,
+}
+
+impl Cli {
+    fn overrides(&self) -> CliConfig {
+        CliConfig {
+            stories_dir: self.stories_dir.clone(),
+            saves_dir: self.saves_dir.clone(),
+            log_level: self.log_level.clone(),
+            debug: self.debug,
+            dev: self.dev,
+            theme: self.theme.clone(),
+            plain: self.plain || !std::io::stdout().is_terminal(),
+            twitch_channel: self.twitch.clone(),
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+
+    match &cli.command {
+        Some(Command::Diff { old, new }) => {
+            let old_story = StoryLoader::load_story_from_path(old).await?;
+            let new_story = StoryLoader::load_story_from_path(new).await?;
+            print!("{}", StoryDiff::between(&old_story, &new_story));
+            return Ok(());
+        }
+        Some(Command::Stats { story }) => {
+            let story = StoryLoader::load_story_from_path(story).await?;
+            println!("{} {}", story.title, story.byline());
+            print!("{}", PathAnalysis::of(&story));
+            for scene in story.get_endings() {
+                let category = scene.ending_category().map(|category| format!(" [{}]", category)).unwrap_or_default();
+                println!("Ending '{}': {}{}", scene.ending_id().unwrap_or(&scene.id), scene.ending_title().unwrap_or(&scene.title), category);
+            }
+            return Ok(());
+        }
+        Some(Command::Lint { story, dictionary }) => {
+            let story = StoryLoader::load_story_from_path(story).await?;
+            let dictionary = match dictionary {
+                Some(path) => Some(Dictionary::from_file(path)?),
+                None => Dictionary::system(),
+            };
+            let linter = match dictionary {
+                Some(dictionary) => Linter::new().with_dictionary(dictionary),
+                None => {
+                    println!("No dictionary found; only checking for likely typos. Pass --dictionary to spell check.");
+                    Linter::new()
+                }
+            };
+            let mut warnings = story.validate_chunks().err().unwrap_or_default();
+            warnings.extend(linter.lint(&story).iter().map(ToString::to_string));
+            for warning in &warnings {
+                println!("{}", warning);
+            }
+            println!("{} warning{}", warnings.len(), if warnings.len() == 1 { "" } else { "s" });
+            if !warnings.is_empty() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Explore { story, depth, random, seed }) => {
+            let story = StoryLoader::load_story_from_path(story).await?;
+            let mut explorer = Explorer::new(Invariant::from_story(&story)?).with_max_depth(*depth);
+            if let Some(walks) = random {
+                explorer = explorer.with_strategy(Strategy::RandomWalks { walks: *walks, seed: *seed });
+            }
+            let report = explorer.explore(story).await?;
+            print!("{}", report);
+            if !report.passed() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::New { id }) => {
+            let config = load_config(&cli)?;
+            new_story_wizard(&StoryLoader::new(config.get_stories_dir()), id.clone()).await?;
+            return Ok(());
+        }
+        Some(Command::Generate { layout, width, height, rooms, seed, output }) => {
+            let layout = match layout {
+                GeneratedLayout::Grid => Layout::Grid { width: *width, height: *height },
+                GeneratedLayout::Maze => Layout::Maze { width: *width, height: *height },
+                GeneratedLayout::Dungeon => Layout::Dungeon { rooms: *rooms },
+            };
+            let story = StoryGenerator::new(layout).with_seed(*seed).generate()?;
+            let json = serde_json::to_string_pretty(&story)?;
+            match output {
+                Some(path) => {
+                    tokio::fs::write(path, json).await?;
+                    println!("Wrote {} scenes to {}", story.scenes.len(), path.display());
+                }
+                None => println!("{}", json),
+            }
+            return Ok(());
+        }
+        Some(Command::Import { source, id, output }) => {
+            let id = id.clone()
+                .or_else(|| source.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+                .unwrap_or_else(|| "imported".to_string());
+            let text = tokio::fs::read_to_string(source).await?;
+            let import = ChoiceScriptImport::parse(&id, &text)?;
+            for warning in &import.warnings {
+                eprintln!("warning: {}", warning);
+            }
+            if let Err(errors) = import.story.validate() {
+                for error in errors {
+                    eprintln!("warning: {}", error);
+                }
+            }
+            let json = serde_json::to_string_pretty(&import.story)?;
+            match output {
+                Some(path) => {
+                    tokio::fs::write(path, json).await?;
+                    println!("Wrote {} scenes to {}", import.story.scenes.len(), path.display());
+                }
+                None => println!("{}", json),
+            }
+            return Ok(());
+        }
+        Some(Command::Export { format, story, output }) => {
+            let story = StoryLoader::load_story_from_path(story).await?;
+            let format = match format {
+                ExportedFormat::Twee => ExportFormat::Twee,
+                ExportedFormat::Ink => ExportFormat::Ink,
+            };
+            let export = StoryExport::new(&story, format);
+            for warning in &export.warnings {
+                eprintln!("warning: {}", warning);
+            }
+            match output {
+                Some(path) => {
+                    tokio::fs::write(path, &export.text).await?;
+                    println!("Wrote {} scenes to {}", story.scenes.len(), path.display());
+                }
+                None => print!("{}", export.text),
+            }
+            return Ok(());
+        }
+        Some(Command::Hotseat { .. }) if cli.story.is_none() => {
+            anyhow::bail!("Hotseat play needs a story, e.g. --story forest");
+        }
+        Some(Command::Kiosk { .. } | Command::Hotseat { .. } | Command::IrcBot { .. } | Command::MatrixBot { .. } | Command::Serve { .. }) | None => {}
+    }
+    
+    let config = load_config(&cli)?;
     
     // Initialize logging
-    let log_level = if cli.debug { "debug" } else { "info" };
     tracing_subscriber::fmt()
-        .with_env_filter(format!("text_adventure_game={},warn", log_level))
+        .with_env_filter(format!("text_adventure_game={},warn", config.logging.level))
+        .with_ansi(config.ui.renderer == RendererKind::Console)
         .init();
     
     info!("Starting Text Adventure Game v{}", VERSION);
+
+    // The bots play in chat rooms, without the terminal interface
+    if let Some(Command::IrcBot { server, nick, channels, password_env, metrics }) = &cli.command {
+        let settings = IrcBotSettings {
+            server: server.clone(),
+            nick: nick.clone(),
+            password: password_env.as_ref().and_then(|name| std::env::var(name).ok()),
+            channels: channels.clone(),
+        };
+        let mut bot = ChatBot::new(&config, "irc")?.with_default_story(cli.story.clone());
+        spawn_metrics(bot.metrics(), metrics.as_deref());
+        run_irc_bot(&mut bot, &settings).await?;
+        return Ok(());
+    }
+    if let Some(Command::MatrixBot { homeserver, token_env, admins, metrics }) = &cli.command {
+        let Ok(access_token) = std::env::var(token_env) else {
+            anyhow::bail!("Set {} to the bot account's access token", token_env);
+        };
+        let settings = MatrixBotSettings { homeserver: homeserver.clone(), access_token };
+        let mut bot = ChatBot::new(&config, "matrix")?
+            .with_default_story(cli.story.clone())
+            .with_admins(admins.clone());
+        spawn_metrics(bot.metrics(), metrics.as_deref());
+        run_matrix_bot(&mut bot, &settings).await?;
+        return Ok(());
+    }
     
-    // Load configuration
-    let config = match cli.config {
-        Some(config_path) => Config::from_file(&config_path)?,
-        None => Config::default(),
-    };
+    if let Some(Command::Serve { grpc, http, metrics }) = &cli.command {
+        let service = Arc::new(GameService::new(&config)?);
+        spawn_metrics(service.metrics(), metrics.as_deref());
+        let grpc = async {
+            match grpc {
+                Some(addr) => serve_grpc(service.clone(), addr).await,
+                None => Ok(()),
+            }
+        };
+        let http = async {
+            match http {
+                Some(addr) => serve_http(service.clone(), addr).await,
+                None => Ok(()),
+            }
+        };
+        tokio::try_join!(grpc, http)?;
+        return Ok(());
+    }
     
     // Create and start the game interface
     let mut game_interface = GameInterface::new(config).await?;
+    game_interface.install_crash_handler();
+    if let Some(config_path) = &cli.config {
+        game_interface.watch_config(config_path, cli.overrides());
+    }
     
-    match cli.story {
+    if let Some(Command::Kiosk { idle, delay, demos }) = cli.command {
+        let kiosk = KioskSettings {
+            story_id: cli.story,
+            idle: Duration::from_secs(idle),
+            delay: Duration::from_secs(delay),
+            max_demos: demos,
+        };
+        game_interface.run_kiosk(kiosk).await?;
+        info!("Kiosk mode ended");
+        return Ok(());
+    }
+
+    // With --story, play it straight away and only show the main menu
+    // afterwards if the player quit back to it
+    let show_menu = match cli.story {
         Some(story_id) => {
             info!("Loading story: {}", story_id);
             game_interface.load_story(&story_id).await?;
-            game_interface.start_new_game().await?;
+            match cli.command {
+                Some(Command::Hotseat { players, vote }) => {
+                    let mode = if vote { HotseatMode::Vote } else { HotseatMode::Alternate };
+                    game_interface.start_hotseat_game(mode, &players).await?
+                }
+                _ => game_interface.start_new_game().await?,
+            }
         }
-        None => {
-            game_interface.show_main_menu().await?;
-        }
-    }
+        None => true,
+    };
     
     // Start the game loop
+    if !show_menu {
+        info!("Game session ended");
+        return Ok(());
+    }
     if let Err(e) = game_interface.run().await {
         error!("Game error: {}", e);
-        eprintln!("An error occurred: {}", e);
+        eprintln!("An error occurred: {}", e.report());
         std::process::exit(1);
     }
     
@@ -64,13 +478,245 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Serves `metrics` in the background if `--metrics` was given.
+fn spawn_metrics(metrics: Arc<Metrics>, addr: Option<&str>) {
+    let Some(addr) = addr.map(str::to_string) else {
+        return;
+    };
+    tokio::spawn(async move {
+        if let Err(e) = serve_metrics(metrics, &addr).await {
+            error!("Metrics endpoint on {} failed: {}", addr, e);
+        }
+    });
+}
+
+/// The config file, if given, with the command line's overrides applied.
+fn load_config(cli: &Cli) -> Result<Config> {
+    let mut config = match &cli.config {
+        Some(config_path) => Config::from_file(config_path)?,
+        None => Config::default(),
+    };
+    config.merge_with_cli(cli.overrides());
+    config.validate()?;
+    Ok(config)
+}
+
+/// Asks for a new story's details and writes it to the stories directory.
+async fn new_story_wizard(loader: &StoryLoader, id: Option<String>) -> Result<()> {
+    let id = match id {
+        Some(id) => id,
+        None => Input::<String>::new().with_prompt("Story ID (letters, digits, - and _)").interact_text()?,
+    };
+    if !is_valid_story_id(&id) {
+        anyhow::bail!("Story IDs may only use letters, digits, - and _");
+    }
+    let title: String = Input::new().with_prompt("Title").interact_text()?;
+    let author: String = Input::new().with_prompt("Author").allow_empty(true).interact_text()?;
+    let mut template = StoryTemplate::new(id, title, author);
+
+    if Confirm::new().with_prompt("Change the starting stats?").default(false).interact()? {
+        let mut stats = PlayerStats::default();
+        for (name, stat) in [
+            ("Health", &mut stats.health),
+            ("Strength", &mut stats.strength),
+            ("Intelligence", &mut stats.intelligence),
+            ("Charisma", &mut stats.charisma),
+        ] {
+            *stat = Input::new().with_prompt(name).default(*stat).interact_text()?;
+        }
+        stats.max_health = stats.health;
+        template = template.with_initial_stats(stats);
+    }
+
+    let chapters: usize = Input::new().with_prompt("Chapters to scaffold").default(0).interact_text()?;
+    let titles = (1..=chapters)
+        .map(|number| Input::new()
+            .with_prompt(format!("Title of chapter {}", number))
+            .default(format!("Chapter {}", number))
+            .interact_text())
+        .collect::<Result<Vec<String>, _>>()?;
+    template = template.with_chapters(titles);
+    if Confirm::new().with_prompt("Add an example item?").default(true).interact()? {
+        template = template.with_item();
+    }
+    if Confirm::new().with_prompt("Add an ending?").default(true).interact()? {
+        template = template.with_ending();
+    }
+
+    let story = loader.create_story_from_template(&template).await?;
+    println!("Wrote \"{}\" ({} scenes) to {}", story.title, story.scenes.len(), loader.story_path(&story.id)?.display());
+    println!("The \"author.notes\" entries explain each part. Play it with: text-game --story {}", story.id);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_cli_parsing() {
-        let cli = Cli::try_parse_from(&["text-game", "--debug"]).unwrap();
+        let cli = Cli::try_parse_from(["text-game", "--debug"]).unwrap();
         assert!(cli.debug);
     }
+
+    #[test]
+    fn test_diff_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "diff", "old.json", "new.json"]).unwrap();
+        match cli.command {
+            Some(Command::Diff { old, new }) => {
+                assert_eq!(old, PathBuf::from("old.json"));
+                assert_eq!(new, PathBuf::from("new.json"));
+            }
+            _ => panic!("expected diff subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_explore_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "explore", "story.json", "--random", "100"]).unwrap();
+        match cli.command {
+            Some(Command::Explore { story, depth, random, seed }) => {
+                assert_eq!(story, PathBuf::from("story.json"));
+                assert_eq!((depth, random, seed), (50, Some(100), 1));
+            }
+            _ => panic!("expected explore subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_new_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "new", "my-tale"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::New { id: Some(ref id) }) if id == "my-tale"));
+    }
+
+    #[test]
+    fn test_generate_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "generate", "maze", "--width", "30", "-o", "maze.json"]).unwrap();
+        match cli.command {
+            Some(Command::Generate { layout: GeneratedLayout::Maze, width, height, output, .. }) => {
+                assert_eq!((width, height), (30, 10));
+                assert_eq!(output, Some(PathBuf::from("maze.json")));
+            }
+            _ => panic!("expected generate maze subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_import_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "import", "startup.txt", "--id", "dragon"]).unwrap();
+        match cli.command {
+            Some(Command::Import { source, id, output }) => {
+                assert_eq!(source, PathBuf::from("startup.txt"));
+                assert_eq!(id.as_deref(), Some("dragon"));
+                assert_eq!(output, None);
+            }
+            _ => panic!("expected import subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_export_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "export", "ink", "tale.json", "-o", "tale.ink"]).unwrap();
+        match cli.command {
+            Some(Command::Export { format: ExportedFormat::Ink, story, output }) => {
+                assert_eq!(story, PathBuf::from("tale.json"));
+                assert_eq!(output, Some(PathBuf::from("tale.ink")));
+            }
+            _ => panic!("expected export ink subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_kiosk_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "--story", "forest", "kiosk", "--delay", "5"]).unwrap();
+        assert_eq!(cli.story.as_deref(), Some("forest"));
+        match cli.command {
+            Some(Command::Kiosk { idle, delay, demos }) => {
+                assert_eq!((idle, delay, demos), (30, 5, None));
+            }
+            _ => panic!("expected kiosk subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_hotseat_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "--story", "forest", "hotseat", "Ana,Ben", "Cy", "--vote"]).unwrap();
+        match cli.command {
+            Some(Command::Hotseat { players, vote }) => {
+                assert_eq!(players, ["Ana", "Ben", "Cy"]);
+                assert!(vote);
+            }
+            _ => panic!("expected hotseat subcommand"),
+        }
+        assert!(Cli::try_parse_from(["text-game", "hotseat"]).is_err());
+    }
+
+    #[test]
+    fn test_irc_bot_subcommand() {
+        let cli = Cli::try_parse_from([
+            "text-game", "irc-bot", "--server", "irc.libera.chat:6667", "--channel", "#games", "--channel", "#fiction",
+        ]).unwrap();
+        match cli.command {
+            Some(Command::IrcBot { server, nick, channels, password_env, metrics }) => {
+                assert_eq!((server.as_str(), nick.as_str()), ("irc.libera.chat:6667", "text-game"));
+                assert_eq!(channels, ["#games", "#fiction"]);
+                assert_eq!((password_env, metrics), (None, None));
+            }
+            _ => panic!("expected irc-bot subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_matrix_bot_subcommand() {
+        let cli = Cli::try_parse_from([
+            "text-game", "matrix-bot", "--homeserver", "https://matrix.example.org", "--admin", "@ana:example.org",
+            "--metrics", "127.0.0.1:9090",
+        ]).unwrap();
+        match cli.command {
+            Some(Command::MatrixBot { homeserver, token_env, admins, metrics }) => {
+                assert_eq!(metrics.as_deref(), Some("127.0.0.1:9090"));
+                assert_eq!(homeserver, "https://matrix.example.org");
+                assert_eq!(token_env, "MATRIX_ACCESS_TOKEN");
+                assert_eq!(admins, ["@ana:example.org"]);
+            }
+            _ => panic!("expected matrix-bot subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_serve_subcommand() {
+        let cli = Cli::try_parse_from(["text-game", "serve", "--grpc", "127.0.0.1:50051", "--http", "127.0.0.1:8080"]).unwrap();
+        match cli.command {
+            Some(Command::Serve { grpc, http, .. }) => {
+                assert_eq!(grpc.as_deref(), Some("127.0.0.1:50051"));
+                assert_eq!(http.as_deref(), Some("127.0.0.1:8080"));
+            }
+            _ => panic!("expected serve subcommand"),
+        }
+        assert!(Cli::try_parse_from(["text-game", "serve"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_overrides() {
+        let cli = Cli::try_parse_from([
+            "text-game",
+            "--stories-dir", "/tmp/stories",
+            "--saves-dir", "/tmp/saves",
+            "--theme", "dark",
+            "--log-level", "warn",
+            "--plain",
+            "--twitch", "somestreamer",
+        ]).unwrap();
+
+        let mut config = Config::default();
+        config.merge_with_cli(cli.overrides());
+
+        assert_eq!(config.paths.stories_dir, PathBuf::from("/tmp/stories"));
+        assert_eq!(config.paths.saves_dir, PathBuf::from("/tmp/saves"));
+        assert_eq!(config.ui.theme, "dark");
+        assert_eq!(config.logging.level, "warn");
+        assert_eq!(config.ui.renderer, RendererKind::Plain);
+        assert_eq!(config.twitch.channel.as_deref(), Some("somestreamer"));
+        assert!(config.validate().is_ok());
+    }
 }
\ No newline at end of file
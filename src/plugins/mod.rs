@@ -0,0 +1,58 @@
+//! Loading of engine plugins from the plugins directory.
+//!
+//! WebAssembly plugins (`*.wasm`) are supported when the crate is built with
+//! the `wasm-plugins` feature; see [`wasm`] for the ABI they must implement.
+
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+
+#[cfg(feature = "wasm-plugins")]
+pub use wasm::{WasmPlugin, PluginManifest};
+
+use std::path::Path;
+use std::sync::Arc;
+use crate::core::EnginePlugin;
+use crate::utils::GameResult;
+
+/// Loads every plugin in `plugins_dir`, in file name order. A plugin that
+/// fails to load is skipped with a warning so one broken mod can't stop the game.
+pub fn load_plugins<P: AsRef<Path>>(plugins_dir: P) -> GameResult<Vec<Arc<dyn EnginePlugin>>> {
+    let plugins_dir = plugins_dir.as_ref();
+    if !plugins_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<_> = std::fs::read_dir(plugins_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("wasm"))
+        .collect();
+    paths.sort();
+
+    load_wasm_plugins(&paths)
+}
+
+#[cfg(feature = "wasm-plugins")]
+fn load_wasm_plugins(paths: &[std::path::PathBuf]) -> GameResult<Vec<Arc<dyn EnginePlugin>>> {
+    let engine = wasm::new_engine();
+    let mut plugins: Vec<Arc<dyn EnginePlugin>> = Vec::new();
+
+    for path in paths {
+        match WasmPlugin::load(&engine, path) {
+            Ok(plugin) => plugins.push(Arc::new(plugin)),
+            Err(e) => tracing::warn!("Skipping plugin {:?}: {}", path, e),
+        }
+    }
+
+    Ok(plugins)
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+fn load_wasm_plugins(paths: &[std::path::PathBuf]) -> GameResult<Vec<Arc<dyn EnginePlugin>>> {
+    if !paths.is_empty() {
+        tracing::warn!(
+            "Ignoring {} WebAssembly plugin(s): built without the wasm-plugins feature",
+            paths.len()
+        );
+    }
+    Ok(Vec::new())
+}
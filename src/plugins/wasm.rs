@@ -0,0 +1,297 @@
+//! WebAssembly plugin host.
+//!
+//! A plugin is a core WebAssembly module without imports. Data crosses the
+//! boundary as UTF-8 JSON in the plugin's linear memory; functions returning
+//! data pack it into an `i64` as `(ptr << 32) | len`.
+//!
+//! Required exports:
+//! - `memory`
+//! - `tg_alloc(len: i32) -> i32`: returns a buffer the host writes input into
+//! - `tg_manifest() -> i64`: a [`PluginManifest`]
+//!
+//! Optional exports, needed for what the manifest declares:
+//! - `tg_apply_effect(ptr: i32, len: i32) -> i64`: input
+//!   `{"effect": Effect, "game_state": GameState}`, output
+//!   `{"game_state": GameState}` or `{"error": "..."}`
+//! - `tg_condition_value(ptr: i32, len: i32) -> i64`: input
+//!   `{"condition": Condition, "game_state": GameState}`, output
+//!   `{"value": any}` or `{"error": "..."}`
+//! - `tg_on_event(ptr: i32, len: i32)`: input is a `GameEvent`
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, TypedFunc};
+use crate::core::{EnginePlugin, GameEvent, GameState};
+use crate::story::{Condition, Effect};
+use crate::utils::{GameError, GameResult};
+
+/// Instructions a single plugin call may execute before it is aborted.
+const CALL_FUEL: u64 = 50_000_000;
+
+/// What a plugin provides, returned by its `tg_manifest` export.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    /// Keys of `Custom` effects the plugin applies.
+    #[serde(default)]
+    pub effects: Vec<String>,
+    /// Keys of `Custom` conditions the plugin evaluates.
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    /// Whether the plugin wants every game event.
+    #[serde(default)]
+    pub events: bool,
+}
+
+pub struct WasmPlugin {
+    manifest: PluginManifest,
+    instance: Mutex<WasmInstance>,
+}
+
+/// `(ptr, len) -> packed reply` plugin export.
+type ReplyFunc = TypedFunc<(i32, i32), i64>;
+
+struct WasmInstance {
+    store: Store<()>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    apply_effect: Option<ReplyFunc>,
+    condition_value: Option<ReplyFunc>,
+    on_event: Option<TypedFunc<(i32, i32), ()>>,
+}
+
+#[derive(Serialize)]
+struct EffectCall<'a> {
+    effect: &'a Effect,
+    game_state: &'a GameState,
+}
+
+#[derive(Serialize)]
+struct ConditionCall<'a> {
+    condition: &'a Condition,
+    game_state: &'a GameState,
+}
+
+#[derive(Deserialize)]
+struct PluginReply {
+    error: Option<String>,
+    game_state: Option<GameState>,
+    value: Option<serde_json::Value>,
+}
+
+/// Engine shared by all plugins, with fuel metering so a runaway plugin
+/// can't hang the game.
+pub fn new_engine() -> Engine {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("default wasmtime configuration is valid")
+}
+
+fn wasm_error(context: &str, e: wasmtime::Error) -> GameError {
+    GameError::plugin(format!("{}: {:#}", context, e))
+}
+
+impl WasmPlugin {
+    pub fn load<P: AsRef<Path>>(engine: &Engine, path: P) -> GameResult<Self> {
+        let module = Module::from_file(engine, path.as_ref())
+            .map_err(|e| wasm_error("Failed to compile plugin", e))?;
+        Self::from_module(engine, &module)
+    }
+
+    pub fn from_module(engine: &Engine, module: &Module) -> GameResult<Self> {
+        let mut store = Store::new(engine, ());
+        store.set_fuel(CALL_FUEL).map_err(|e| wasm_error("Failed to set fuel", e))?;
+
+        let instance = Instance::new(&mut store, module, &[])
+            .map_err(|e| wasm_error("Failed to instantiate plugin", e))?;
+
+        let memory = instance.get_memory(&mut store, "memory")
+            .ok_or_else(|| GameError::plugin("Plugin does not export memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "tg_alloc")
+            .map_err(|e| wasm_error("Plugin does not export tg_alloc", e))?;
+        let manifest_fn = instance.get_typed_func::<(), i64>(&mut store, "tg_manifest")
+            .map_err(|e| wasm_error("Plugin does not export tg_manifest", e))?;
+
+        let mut wasm = WasmInstance {
+            apply_effect: instance.get_typed_func(&mut store, "tg_apply_effect").ok(),
+            condition_value: instance.get_typed_func(&mut store, "tg_condition_value").ok(),
+            on_event: instance.get_typed_func(&mut store, "tg_on_event").ok(),
+            store,
+            memory,
+            alloc,
+        };
+
+        let packed = manifest_fn.call(&mut wasm.store, ())
+            .map_err(|e| wasm_error("tg_manifest failed", e))?;
+        let manifest: PluginManifest = serde_json::from_slice(&wasm.read_packed(packed)?)
+            .map_err(|e| GameError::plugin(format!("Invalid plugin manifest: {}", e)))?;
+
+        if !manifest.effects.is_empty() && wasm.apply_effect.is_none() {
+            return Err(GameError::plugin(format!("Plugin '{}' declares effects but does not export tg_apply_effect", manifest.name)));
+        }
+        if !manifest.conditions.is_empty() && wasm.condition_value.is_none() {
+            return Err(GameError::plugin(format!("Plugin '{}' declares conditions but does not export tg_condition_value", manifest.name)));
+        }
+        if manifest.events && wasm.on_event.is_none() {
+            return Err(GameError::plugin(format!("Plugin '{}' subscribes to events but does not export tg_on_event", manifest.name)));
+        }
+
+        Ok(Self {
+            manifest,
+            instance: Mutex::new(wasm),
+        })
+    }
+
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    /// The plugin's instance. A panic mid-call can't leave it worse off than
+    /// a trapped call does, and fuel is reset before every call, so a
+    /// poisoned lock is recovered rather than taking the game down with it.
+    fn instance(&self) -> MutexGuard<'_, WasmInstance> {
+        self.instance.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn call_reply<T: Serialize>(
+        &self,
+        export: &str,
+        select: fn(&WasmInstance) -> Option<ReplyFunc>,
+        input: &T,
+    ) -> GameResult<PluginReply> {
+        let mut wasm = self.instance();
+        let func = select(&wasm).ok_or_else(|| GameError::plugin(format!("Plugin '{}' does not export {}", self.manifest.name, export)))?;
+
+        let (ptr, len) = wasm.write_json(input)?;
+        let packed = func.call(&mut wasm.store, (ptr, len))
+            .map_err(|e| wasm_error(&format!("{} failed in plugin '{}'", export, self.manifest.name), e))?;
+
+        let reply: PluginReply = serde_json::from_slice(&wasm.read_packed(packed)?)
+            .map_err(|e| GameError::plugin(format!("Invalid reply from plugin '{}': {}", self.manifest.name, e)))?;
+
+        match reply.error {
+            Some(error) => Err(GameError::plugin(format!("Plugin '{}': {}", self.manifest.name, error))),
+            None => Ok(reply),
+        }
+    }
+}
+
+impl WasmInstance {
+    fn write_json<T: Serialize>(&mut self, input: &T) -> GameResult<(i32, i32)> {
+        self.store.set_fuel(CALL_FUEL).map_err(|e| wasm_error("Failed to set fuel", e))?;
+
+        let bytes = serde_json::to_vec(input)?;
+        let len = i32::try_from(bytes.len())
+            .map_err(|_| GameError::plugin("Plugin input too large"))?;
+        let ptr = self.alloc.call(&mut self.store, len)
+            .map_err(|e| wasm_error("tg_alloc failed", e))?;
+
+        self.memory.write(&mut self.store, ptr as u32 as usize, &bytes)
+            .map_err(|e| GameError::plugin(format!("Plugin returned an invalid buffer: {}", e)))?;
+        Ok((ptr, len))
+    }
+
+    fn read_packed(&self, packed: i64) -> GameResult<Vec<u8>> {
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xffff_ffff) as usize;
+
+        let mut buffer = vec![0u8; len];
+        self.memory.read(&self.store, ptr, &mut buffer)
+            .map_err(|e| GameError::plugin(format!("Plugin returned an invalid buffer: {}", e)))?;
+        Ok(buffer)
+    }
+}
+
+impl EnginePlugin for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn handles_effect(&self, key: &str) -> bool {
+        self.manifest.effects.iter().any(|k| k == key)
+    }
+
+    fn apply_effect(&self, effect: &Effect, game_state: &mut GameState) -> GameResult<()> {
+        let reply = self.call_reply("tg_apply_effect", |w| w.apply_effect.clone(), &EffectCall { effect, game_state })?;
+        if let Some(new_state) = reply.game_state {
+            *game_state = new_state;
+        }
+        Ok(())
+    }
+
+    fn handles_condition(&self, key: &str) -> bool {
+        self.manifest.conditions.iter().any(|k| k == key)
+    }
+
+    fn condition_value(&self, condition: &Condition, game_state: &GameState) -> GameResult<serde_json::Value> {
+        let reply = self.call_reply("tg_condition_value", |w| w.condition_value.clone(), &ConditionCall { condition, game_state })?;
+        Ok(reply.value.unwrap_or(serde_json::Value::Null))
+    }
+
+    fn on_event(&self, event: &GameEvent) {
+        if !self.manifest.events {
+            return;
+        }
+
+        let mut wasm = self.instance();
+        let Some(on_event) = wasm.on_event.clone() else {
+            return;
+        };
+
+        let result = wasm.write_json(event).and_then(|(ptr, len)| {
+            on_event.call(&mut wasm.store, (ptr, len))
+                .map_err(|e| wasm_error("tg_on_event failed", e))
+        });
+        if let Err(e) = result {
+            tracing::warn!("Plugin '{}' failed to handle event: {}", self.manifest.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bump allocator with a fixed manifest and a condition that always
+    // reports the value `7`.
+    const TEST_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (data (i32.const 0) "{\"name\":\"lucky\",\"conditions\":[\"lucky_number\"]}")
+          (data (i32.const 64) "{\"value\":7}")
+          (func (export "tg_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            global.get $next
+            local.set $ptr
+            global.get $next
+            local.get $len
+            i32.add
+            global.set $next
+            local.get $ptr)
+          (func (export "tg_manifest") (result i64)
+            i64.const 46)
+          (func (export "tg_condition_value") (param i32 i32) (result i64)
+            i64.const 274877906955))
+    "#;
+
+    #[test]
+    fn test_wasm_condition_plugin() {
+        let engine = new_engine();
+        let module = Module::new(&engine, TEST_PLUGIN).unwrap();
+        let plugin = WasmPlugin::from_module(&engine, &module).unwrap();
+
+        assert_eq!(plugin.name(), "lucky");
+        assert!(plugin.handles_condition("lucky_number"));
+        assert!(!plugin.handles_effect("lucky_number"));
+
+        let game_state = GameState::new(
+            "story".to_string(),
+            "start".to_string(),
+            crate::core::Player::new("Tester", None),
+        );
+        let condition = Condition::custom("lucky_number", crate::story::ComparisonOperator::Equals, serde_json::json!(7));
+        assert_eq!(plugin.condition_value(&condition, &game_state).unwrap(), serde_json::json!(7));
+    }
+}
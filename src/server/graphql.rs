@@ -0,0 +1,302 @@
+//! Read-only GraphQL over the stories, for story browsers and editors.
+//! Conditions, effects and metadata are passed through as JSON, in the
+//! story file format.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Json, Object, Schema, SimpleObject};
+use axum::extract::State;
+use axum::handler::Handler;
+use axum::middleware;
+use axum::response::Html;
+use axum::routing::get;
+use axum::{Extension, Router};
+use crate::server::http::require_token;
+use crate::server::{ApiUser, GameService};
+use crate::story::{Choice, Condition, Effect, Scene, Story, StoryMetadata};
+
+pub type StorySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn story_schema(service: Arc<GameService>) -> StorySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription).data(service).finish()
+}
+
+/// `POST /graphql` runs queries; `GET /graphql` opens GraphiQL to try
+/// them out.
+pub fn routes(service: Arc<GameService>) -> Router {
+    let authenticated = middleware::from_fn_with_state(service.clone(), require_token);
+    Router::new()
+        .route("/graphql", get(graphiql).post(execute.layer(authenticated)))
+        .with_state(story_schema(service))
+}
+
+async fn execute(
+    State(schema): State<StorySchema>,
+    Extension(user): Extension<ApiUser>,
+    axum::Json(request): axum::Json<async_graphql::Request>,
+) -> axum::Json<async_graphql::Response> {
+    axum::Json(schema.execute(request.data(user)).await)
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+type Metadata = Option<Json<HashMap<String, serde_json::Value>>>;
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The stories in the stories directory.
+    async fn stories(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<StorySummary>> {
+        let stories = ctx.data::<Arc<GameService>>()?.list_stories(ctx.data::<ApiUser>()?).await?;
+        Ok(stories.into_iter().map(StorySummary::from).collect())
+    }
+
+    async fn story(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<StoryNode> {
+        let story = ctx.data::<Arc<GameService>>()?.load_story(ctx.data::<ApiUser>()?, &id).await?;
+        Ok(StoryNode(Arc::new(story)))
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct StorySummary {
+    id: String,
+    title: String,
+    description: String,
+    author: String,
+    version: String,
+    scene_count: usize,
+    estimated_minutes: Option<u32>,
+}
+
+impl From<StoryMetadata> for StorySummary {
+    fn from(metadata: StoryMetadata) -> Self {
+        Self {
+            id: metadata.id,
+            title: metadata.title,
+            description: metadata.description,
+            author: metadata.author,
+            version: metadata.version,
+            scene_count: metadata.scene_count,
+            estimated_minutes: metadata.estimated_minutes,
+        }
+    }
+}
+
+pub struct StoryNode(Arc<Story>);
+
+#[Object(name = "Story")]
+impl StoryNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    async fn author(&self) -> &str {
+        &self.0.author
+    }
+
+    async fn version(&self) -> &str {
+        &self.0.version
+    }
+
+    async fn license(&self) -> Option<&str> {
+        self.0.license.as_deref()
+    }
+
+    async fn intro(&self) -> Option<&str> {
+        self.0.intro.as_deref()
+    }
+
+    async fn credits(&self) -> Option<&str> {
+        self.0.credits.as_deref()
+    }
+
+    async fn starting_scene_id(&self) -> &str {
+        &self.0.starting_scene_id
+    }
+
+    async fn metadata(&self) -> Metadata {
+        self.0.metadata.clone().map(Json)
+    }
+
+    async fn scene_count(&self) -> usize {
+        self.0.get_scene_count()
+    }
+
+    async fn scenes(&self) -> Vec<SceneNode> {
+        self.0.all_scenes().into_iter().map(SceneNode).collect()
+    }
+
+    async fn scene(&self, id: String) -> Option<SceneNode> {
+        self.0.get_shared_scene(&id).map(SceneNode)
+    }
+
+    /// Scenes that end the story.
+    async fn endings(&self) -> Vec<SceneNode> {
+        self.0.all_scenes().into_iter().filter(|scene| scene.is_ending()).map(SceneNode).collect()
+    }
+}
+
+pub struct SceneNode(Arc<Scene>);
+
+#[Object(name = "Scene")]
+impl SceneNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    async fn is_ending(&self) -> bool {
+        self.0.is_ending()
+    }
+
+    /// The ending's ID for the ending gallery; null unless this is an ending.
+    async fn ending_id(&self) -> Option<&str> {
+        self.0.ending_id()
+    }
+
+    async fn ending_title(&self) -> Option<&str> {
+        self.0.ending_title()
+    }
+
+    async fn ending_category(&self) -> Option<&str> {
+        self.0.ending_category()
+    }
+
+    async fn image(&self) -> Option<&str> {
+        self.0.image.as_deref()
+    }
+
+    async fn background_music(&self) -> Option<&str> {
+        self.0.background_music.as_deref()
+    }
+
+    async fn conditions(&self) -> Option<Json<Vec<Condition>>> {
+        self.0.conditions.clone().map(Json)
+    }
+
+    async fn effects(&self) -> Option<Json<Vec<Effect>>> {
+        self.0.effects.clone().map(Json)
+    }
+
+    async fn metadata(&self) -> Metadata {
+        self.0.metadata.clone().map(Json)
+    }
+
+    async fn choices(&self) -> Vec<ChoiceNode> {
+        self.0.choices.iter().cloned().map(ChoiceNode).collect()
+    }
+}
+
+pub struct ChoiceNode(Choice);
+
+#[Object(name = "Choice")]
+impl ChoiceNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn text(&self) -> &str {
+        &self.0.text
+    }
+
+    async fn target_scene_id(&self) -> &str {
+        &self.0.target_scene_id
+    }
+
+    async fn group(&self) -> Option<&str> {
+        self.0.group.as_deref()
+    }
+
+    async fn hint(&self) -> Option<&str> {
+        self.0.hint.as_deref()
+    }
+
+    async fn disabled(&self) -> bool {
+        self.0.disabled.unwrap_or(false)
+    }
+
+    async fn disabled_reason(&self) -> Option<&str> {
+        self.0.disabled_reason.as_deref()
+    }
+
+    async fn conditions(&self) -> Option<Json<Vec<Condition>>> {
+        self.0.conditions.clone().map(Json)
+    }
+
+    async fn effects(&self) -> Option<Json<Vec<Effect>>> {
+        self.0.effects.clone().map(Json)
+    }
+
+    async fn metadata(&self) -> Metadata {
+        self.0.metadata.clone().map(Json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::core::PlayerStats;
+    use crate::server::service::LOCAL_USER;
+    use crate::story::{ComparisonOperator, ConditionType};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_story_graphql() {
+        let temp_dir = tempdir().unwrap();
+        let mut story = Story::new("cave", "The Cave", "mouth", PlayerStats::default());
+        let mut mouth = Scene::new("mouth", "Cave Mouth", "Darkness ahead.");
+        let torch = Condition::new(ConditionType::Inventory, "torch".to_string(), ComparisonOperator::Equals, serde_json::json!(true));
+        mouth.add_choice(Choice::new("enter", "Go in", "hall").with_conditions(vec![torch]));
+        story.add_scene(mouth);
+        let mut hall = Scene::new("hall", "Great Hall", "Echoes everywhere.");
+        hall.is_ending = Some(true);
+        story.add_scene(hall);
+        let stories_dir = temp_dir.path().join("stories");
+        std::fs::create_dir_all(&stories_dir).unwrap();
+        std::fs::write(stories_dir.join("cave.json"), serde_json::to_string(&story).unwrap()).unwrap();
+        let mut config = Config::default();
+        config.paths.stories_dir = stories_dir;
+
+        let schema = story_schema(Arc::new(GameService::new(&config).unwrap()));
+        let query = |query: &str| async_graphql::Request::new(query).data(ApiUser(LOCAL_USER.to_string()));
+        let response = schema.execute(query(r#"{
+            stories { id sceneCount }
+            story(id: "cave") {
+                title
+                endings { id }
+                scene(id: "mouth") { choices { targetSceneId conditions } }
+            }
+        }"#)).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        assert_eq!(data["stories"][0], serde_json::json!({"id": "cave", "sceneCount": 2}));
+        assert_eq!(data["story"]["endings"], serde_json::json!([{"id": "hall"}]));
+        let choice = &data["story"]["scene"]["choices"][0];
+        assert_eq!(choice["targetSceneId"], "hall");
+        assert_eq!(choice["conditions"][0]["key"], "torch");
+
+        assert!(!schema.execute(query(r#"{ story(id: "missing") { id } }"#)).await.errors.is_empty());
+        // Transports add the user; a query without one is refused
+        assert!(!schema.execute(r#"{ stories { id } }"#).await.errors.is_empty());
+    }
+}
@@ -0,0 +1,290 @@
+//! gRPC transport for `GameService`, following `proto/text_game.proto`.
+//! The messages are written out here and the service is generated by
+//! `build.rs`, so building doesn't need `protoc`.
+
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use futures::Stream;
+use tokio::sync::broadcast::error::RecvError;
+use tonic::{Code, Request, Response, Status};
+use uuid::Uuid;
+use crate::core::SceneUpdate;
+use crate::server::{ApiUser, GameService};
+use crate::utils::{GameError, GameResult};
+
+include!(concat!(env!("OUT_DIR"), "/text_game.TextGame.rs"));
+pub use text_game_server::{TextGame, TextGameServer};
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Empty {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListStoriesRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StoryInfo {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub title: String,
+    #[prost(string, tag = "3")]
+    pub description: String,
+    #[prost(string, tag = "4")]
+    pub author: String,
+    #[prost(uint32, tag = "5")]
+    pub scene_count: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListStoriesReply {
+    #[prost(message, repeated, tag = "1")]
+    pub stories: Vec<StoryInfo>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StartSessionRequest {
+    #[prost(string, tag = "1")]
+    pub story_id: String,
+    #[prost(string, tag = "2")]
+    pub player_name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SessionRequest {
+    #[prost(string, tag = "1")]
+    pub session_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SessionReply {
+    #[prost(string, tag = "1")]
+    pub session_id: String,
+    #[prost(message, optional, tag = "2")]
+    pub scene: Option<Scene>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Choice {
+    #[prost(string, tag = "1")]
+    pub id: String,
+    #[prost(string, tag = "2")]
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Scene {
+    #[prost(string, tag = "1")]
+    pub scene_id: String,
+    #[prost(string, tag = "2")]
+    pub title: String,
+    #[prost(string, tag = "3")]
+    pub description: String,
+    #[prost(message, repeated, tag = "4")]
+    pub choices: Vec<Choice>,
+    #[prost(bool, tag = "5")]
+    pub ended: bool,
+    #[prost(string, tag = "6")]
+    pub chosen: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MakeChoiceRequest {
+    #[prost(string, tag = "1")]
+    pub session_id: String,
+    #[prost(string, tag = "2")]
+    pub choice_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SaveRequest {
+    #[prost(string, tag = "1")]
+    pub session_id: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SaveReply {
+    #[prost(string, tag = "1")]
+    pub save_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LoadRequest {
+    #[prost(string, tag = "1")]
+    pub save_id: String,
+}
+
+impl From<SceneUpdate> for Scene {
+    fn from(update: SceneUpdate) -> Self {
+        Self {
+            scene_id: update.scene_id,
+            title: update.title,
+            description: update.description,
+            choices: update.choices.into_iter().map(|(id, text)| Choice { id, text }).collect(),
+            ended: update.ended,
+            chosen: update.chosen.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<GameError> for Status {
+    fn from(error: GameError) -> Self {
+        let code = match error.inner() {
+            GameError::SceneNotFound { .. } | GameError::ChoiceNotFound { .. } => Code::NotFound,
+            GameError::Player { .. } => Code::InvalidArgument,
+            GameError::Story { .. } => Code::FailedPrecondition,
+            _ => Code::Internal,
+        };
+        Status::new(code, error.report())
+    }
+}
+
+fn parse_id(id: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(id).map_err(|_| Status::invalid_argument(format!("'{}' isn't a valid ID", id)))
+}
+
+struct GrpcService {
+    service: Arc<GameService>,
+}
+
+/// The user the interceptor authenticated the request as.
+fn user<T>(request: &Request<T>) -> Result<ApiUser, Status> {
+    request.extensions().get::<ApiUser>().cloned()
+        .ok_or_else(|| Status::unauthenticated("Missing or unknown API token"))
+}
+
+/// Lets through requests with a known `authorization: Bearer` token, with
+/// the `ApiUser` it belongs to added.
+fn require_token(service: &GameService, mut request: Request<()>) -> Result<Request<()>, Status> {
+    let token = request.metadata().get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let user = service.authenticate(token)
+        .ok_or_else(|| Status::unauthenticated("Missing or unknown API token"))?;
+    request.extensions_mut().insert(user);
+    Ok(request)
+}
+
+#[tonic::async_trait]
+impl TextGame for GrpcService {
+    async fn list_stories(&self, request: Request<ListStoriesRequest>) -> Result<Response<ListStoriesReply>, Status> {
+        let stories = self.service.list_stories(&user(&request)?).await?.into_iter()
+            .map(|story| StoryInfo {
+                id: story.id,
+                title: story.title,
+                description: story.description,
+                author: story.author,
+                scene_count: story.scene_count as u32,
+            })
+            .collect();
+        Ok(Response::new(ListStoriesReply { stories }))
+    }
+
+    async fn start_session(&self, request: Request<StartSessionRequest>) -> Result<Response<SessionReply>, Status> {
+        let user = user(&request)?;
+        let request = request.into_inner();
+        let (session_id, scene) = self.service.start_session(&user, &request.story_id, &request.player_name).await?;
+        Ok(Response::new(SessionReply { session_id: session_id.to_string(), scene: Some(scene.into()) }))
+    }
+
+    async fn get_scene(&self, request: Request<SessionRequest>) -> Result<Response<Scene>, Status> {
+        let user = user(&request)?;
+        let session_id = parse_id(&request.into_inner().session_id)?;
+        Ok(Response::new(self.service.scene(&user, session_id).await?.into()))
+    }
+
+    async fn make_choice(&self, request: Request<MakeChoiceRequest>) -> Result<Response<Scene>, Status> {
+        let user = user(&request)?;
+        let request = request.into_inner();
+        let session_id = parse_id(&request.session_id)?;
+        Ok(Response::new(self.service.make_choice(&user, session_id, &request.choice_id).await?.into()))
+    }
+
+    type WatchSessionStream = Pin<Box<dyn Stream<Item = Result<Scene, Status>> + Send>>;
+
+    async fn watch_session(&self, request: Request<SessionRequest>) -> Result<Response<Self::WatchSessionStream>, Status> {
+        let user = user(&request)?;
+        let session_id = parse_id(&request.into_inner().session_id)?;
+        let updates = self.service.watch(&user, session_id).await?;
+        // Ends with the session; a watcher that fell behind skips ahead
+        let scenes = futures::stream::unfold(updates, |mut updates| async move {
+            loop {
+                match updates.recv().await {
+                    Ok(update) => return Some((Ok(update.into()), updates)),
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(scenes)))
+    }
+
+    async fn save(&self, request: Request<SaveRequest>) -> Result<Response<SaveReply>, Status> {
+        let user = user(&request)?;
+        let request = request.into_inner();
+        let save_id = self.service.save(&user, parse_id(&request.session_id)?, &request.name).await?;
+        Ok(Response::new(SaveReply { save_id: save_id.to_string() }))
+    }
+
+    async fn load(&self, request: Request<LoadRequest>) -> Result<Response<SessionReply>, Status> {
+        let user = user(&request)?;
+        let save_id = parse_id(&request.into_inner().save_id)?;
+        let (session_id, scene) = self.service.load(&user, save_id).await?;
+        Ok(Response::new(SessionReply { session_id: session_id.to_string(), scene: Some(scene.into()) }))
+    }
+
+    async fn end_session(&self, request: Request<SessionRequest>) -> Result<Response<Empty>, Status> {
+        let user = user(&request)?;
+        self.service.end_session(&user, parse_id(&request.into_inner().session_id)?).await?;
+        Ok(Response::new(Empty {}))
+    }
+}
+
+/// Serves `service` over gRPC at `addr` until the server fails.
+pub async fn serve_grpc(service: Arc<GameService>, addr: &str) -> GameResult<()> {
+    let addr: SocketAddr = addr.parse()
+        .map_err(|_| GameError::configuration(format!("'{}' isn't a host:port address", addr)))?;
+    service.check_address(addr)?;
+    tracing::info!("Serving gRPC on {}", addr);
+    let authenticator = service.clone();
+    let server = TextGameServer::with_interceptor(GrpcService { service }, move |request| require_token(&authenticator, request));
+    tonic::transport::Server::builder()
+        .add_service(server)
+        .serve(addr)
+        .await
+        .map_err(|e| GameError::configuration(format!("gRPC server failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grpc_conversions() {
+        let update = SceneUpdate {
+            scene_id: "hall".to_string(),
+            title: "Great Hall".to_string(),
+            description: "Echoes.".to_string(),
+            choices: vec![("back".to_string(), "Back out".to_string())],
+            ended: false,
+            chosen: None,
+        };
+        let scene = Scene::from(update);
+        assert_eq!(scene.choices, [Choice { id: "back".to_string(), text: "Back out".to_string() }]);
+        assert_eq!(scene.chosen, "");
+
+        assert_eq!(Status::from(GameError::choice_not_found("leave").with_scene("mouth")).code(), Code::NotFound);
+        assert_eq!(Status::from(GameError::player("No session")).code(), Code::InvalidArgument);
+        assert_eq!(parse_id("nope").unwrap_err().code(), Code::InvalidArgument);
+
+        let tokens = std::collections::HashMap::from([("a-token".to_string(), "ana".to_string())]);
+        let service = GameService::new(&crate::config::Config::default()).unwrap().with_tokens(tokens);
+        assert_eq!(require_token(&service, Request::new(())).unwrap_err().code(), Code::Unauthenticated);
+        let mut request = Request::new(());
+        request.metadata_mut().insert("authorization", "Bearer a-token".parse().unwrap());
+        let request = require_token(&service, request).unwrap();
+        assert_eq!(user(&request).unwrap(), ApiUser("ana".to_string()));
+    }
+}
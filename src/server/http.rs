@@ -0,0 +1,42 @@
+//! The HTTP API: GraphQL over the stories at `/graphql` (graphql feature)
+//! and the REST API at `/api`, documented at `/docs` (rest feature).
+
+use std::sync::Arc;
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use tokio::net::TcpListener;
+use crate::server::GameService;
+use crate::utils::{GameError, GameResult};
+
+/// Serves the HTTP API at `addr` until the server fails.
+pub async fn serve_http(service: Arc<GameService>, addr: &str) -> GameResult<()> {
+    let router = Router::new();
+    #[cfg(feature = "graphql")]
+    let router = router.merge(crate::server::graphql::routes(service.clone()));
+    #[cfg(feature = "rest")]
+    let router = router.merge(crate::server::rest::routes(service.clone()));
+    let listener = TcpListener::bind(addr).await
+        .map_err(|e| GameError::configuration(format!("Can't serve HTTP on {}: {}", addr, e)))?;
+    service.check_address(listener.local_addr()?)?;
+    tracing::info!("Serving HTTP on http://{}", listener.local_addr()?);
+    axum::serve(listener, router).await
+        .map_err(|e| GameError::configuration(format!("HTTP server failed: {}", e)))
+}
+
+/// Middleware letting through requests with a known `Authorization: Bearer`
+/// token, with the `ApiUser` it belongs to added.
+pub async fn require_token(State(service): State<Arc<GameService>>, mut request: Request, next: Next) -> Response {
+    let token = request.headers().get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match service.authenticate(token) {
+        Some(user) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        None => (StatusCode::UNAUTHORIZED, "Missing or unknown API token").into_response(),
+    }
+}
@@ -0,0 +1,35 @@
+//! APIs for running the engine as a service. `GameService` holds the games;
+//! each transport only translates its calls.
+
+pub mod service;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "rest")]
+pub mod rest;
+#[cfg(any(feature = "graphql", feature = "rest"))]
+pub mod http;
+
+pub use service::{ApiUser, GameService};
+
+#[cfg(feature = "grpc")]
+pub use grpc::serve_grpc;
+#[cfg(any(feature = "graphql", feature = "rest"))]
+pub use http::serve_http;
+
+#[cfg(not(feature = "grpc"))]
+pub async fn serve_grpc(_service: std::sync::Arc<GameService>, addr: &str) -> crate::utils::GameResult<()> {
+    Err(crate::utils::GameError::configuration(format!(
+        "Can't serve gRPC on {}: the game was built without the grpc feature",
+        addr
+    )))
+}
+
+#[cfg(not(any(feature = "graphql", feature = "rest")))]
+pub async fn serve_http(_service: std::sync::Arc<GameService>, addr: &str) -> crate::utils::GameResult<()> {
+    Err(crate::utils::GameError::configuration(format!(
+        "Can't serve HTTP on {}: the game was built without the graphql and rest features",
+        addr
+    )))
+}
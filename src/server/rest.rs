@@ -0,0 +1,236 @@
+//! REST transport for `GameService` under `/api`, described by an OpenAPI
+//! document browsable at `/docs` (raw at `/docs/openapi.json`).
+
+use std::sync::Arc;
+use axum::extract::{Path, State};
+use axum::middleware;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use utoipa_scalar::{Scalar, Servable};
+use uuid::Uuid;
+use crate::core::SceneUpdate;
+use crate::server::http::require_token;
+use crate::server::{ApiUser, GameService};
+use crate::story::StoryMetadata;
+use crate::utils::GameError;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "text-game", description = "Play the engine's stories over HTTP. Send an API token as `Authorization: Bearer <token>`. Sessions live in the server's memory; save to keep them."),
+    paths(list_stories, start_session, get_scene, make_choice, end_session, save, load),
+    tags(
+        (name = "stories", description = "The stories the server can play"),
+        (name = "sessions", description = "Games in progress"),
+    ),
+)]
+pub struct ApiDoc;
+
+pub fn routes(service: Arc<GameService>) -> Router {
+    Router::new()
+        .route("/api/stories", get(list_stories))
+        .route("/api/sessions", post(start_session))
+        .route("/api/sessions/{id}", get(get_scene).delete(end_session))
+        .route("/api/sessions/{id}/choices", post(make_choice))
+        .route("/api/sessions/{id}/saves", post(save))
+        .route("/api/saves/{id}/sessions", post(load))
+        .route_layer(middleware::from_fn_with_state(service.clone(), require_token))
+        .with_state(service)
+        .route("/docs/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
+        .merge(Scalar::with_url("/docs", ApiDoc::openapi()))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StoryBody {
+    id: String,
+    title: String,
+    description: String,
+    author: String,
+    version: String,
+    scene_count: usize,
+    estimated_minutes: Option<u32>,
+}
+
+impl From<StoryMetadata> for StoryBody {
+    fn from(metadata: StoryMetadata) -> Self {
+        Self {
+            id: metadata.id,
+            title: metadata.title,
+            description: metadata.description,
+            author: metadata.author,
+            version: metadata.version,
+            scene_count: metadata.scene_count,
+            estimated_minutes: metadata.estimated_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChoiceBody {
+    id: String,
+    text: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SceneBody {
+    scene_id: String,
+    title: String,
+    description: String,
+    /// Available choices, in display order; empty for endings.
+    choices: Vec<ChoiceBody>,
+    ended: bool,
+    /// The choice that led here, if any.
+    chosen: Option<String>,
+}
+
+impl From<SceneUpdate> for SceneBody {
+    fn from(update: SceneUpdate) -> Self {
+        Self {
+            scene_id: update.scene_id,
+            title: update.title,
+            description: update.description,
+            choices: update.choices.into_iter().map(|(id, text)| ChoiceBody { id, text }).collect(),
+            ended: update.ended,
+            chosen: update.chosen,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionBody {
+    session_id: Uuid,
+    scene: SceneBody,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StartSessionBody {
+    story_id: String,
+    player_name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChoiceRequestBody {
+    choice_id: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveRequestBody {
+    name: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SaveBody {
+    save_id: Uuid,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Stable error code, e.g. `E101` for a missing scene.
+    code: String,
+    error: String,
+    /// What might fix it, when known.
+    hint: Option<String>,
+}
+
+/// A `GameError` as an HTTP response.
+pub struct ApiError(GameError);
+
+impl From<GameError> for ApiError {
+    fn from(error: GameError) -> Self {
+        Self(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0.inner() {
+            GameError::SceneNotFound { .. } | GameError::ChoiceNotFound { .. } => StatusCode::NOT_FOUND,
+            GameError::Player { .. } => StatusCode::BAD_REQUEST,
+            GameError::Story { .. } => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = ErrorBody {
+            code: self.0.code().to_string(),
+            error: self.0.inner().to_string(),
+            hint: self.0.context().and_then(|context| context.hint.clone()),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[utoipa::path(get, path = "/api/stories", tag = "stories",
+    responses((status = 200, body = [StoryBody])))]
+async fn list_stories(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>) -> ApiResult<Vec<StoryBody>> {
+    let stories = service.list_stories(&user).await?;
+    Ok(Json(stories.into_iter().map(StoryBody::from).collect()))
+}
+
+/// Starts a story in a new session.
+#[utoipa::path(post, path = "/api/sessions", tag = "sessions", request_body = StartSessionBody,
+    responses((status = 200, body = SessionBody), (status = 409, description = "The story can't be loaded", body = ErrorBody)))]
+async fn start_session(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>, Json(body): Json<StartSessionBody>) -> ApiResult<SessionBody> {
+    let (session_id, scene) = service.start_session(&user, &body.story_id, &body.player_name).await?;
+    Ok(Json(SessionBody { session_id, scene: scene.into() }))
+}
+
+#[utoipa::path(get, path = "/api/sessions/{id}", tag = "sessions",
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses((status = 200, body = SceneBody), (status = 400, description = "No such session", body = ErrorBody)))]
+async fn get_scene(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>, Path(id): Path<Uuid>) -> ApiResult<SceneBody> {
+    Ok(Json(service.scene(&user, id).await?.into()))
+}
+
+/// Makes a choice and returns the scene it leads to.
+#[utoipa::path(post, path = "/api/sessions/{id}/choices", tag = "sessions", request_body = ChoiceRequestBody,
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses((status = 200, body = SceneBody), (status = 404, description = "No such choice", body = ErrorBody)))]
+async fn make_choice(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>, Path(id): Path<Uuid>, Json(body): Json<ChoiceRequestBody>) -> ApiResult<SceneBody> {
+    Ok(Json(service.make_choice(&user, id, &body.choice_id).await?.into()))
+}
+
+#[utoipa::path(delete, path = "/api/sessions/{id}", tag = "sessions",
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses((status = 204), (status = 400, description = "No such session", body = ErrorBody)))]
+async fn end_session(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>, Path(id): Path<Uuid>) -> Result<StatusCode, ApiError> {
+    service.end_session(&user, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Saves the session's game.
+#[utoipa::path(post, path = "/api/sessions/{id}/saves", tag = "sessions", request_body = SaveRequestBody,
+    params(("id" = Uuid, Path, description = "Session ID")),
+    responses((status = 200, body = SaveBody), (status = 400, description = "No such session", body = ErrorBody)))]
+async fn save(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>, Path(id): Path<Uuid>, Json(body): Json<SaveRequestBody>) -> ApiResult<SaveBody> {
+    Ok(Json(SaveBody { save_id: service.save(&user, id, &body.name).await? }))
+}
+
+/// Resumes a save in a new session.
+#[utoipa::path(post, path = "/api/saves/{id}/sessions", tag = "sessions",
+    params(("id" = Uuid, Path, description = "Save ID")),
+    responses((status = 200, body = SessionBody), (status = 500, description = "The save can't be read", body = ErrorBody)))]
+async fn load(State(service): State<Arc<GameService>>, Extension(user): Extension<ApiUser>, Path(id): Path<Uuid>) -> ApiResult<SessionBody> {
+    let (session_id, scene) = service.load(&user, id).await?;
+    Ok(Json(SessionBody { session_id, scene: scene.into() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_document() {
+        let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = document["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/api/sessions/{id}/choices"));
+        assert!(paths["/api/sessions/{id}"]["delete"].is_object());
+        assert!(document["components"]["schemas"]["SceneBody"].is_object());
+
+        let response = ApiError(GameError::choice_not_found("leave").with_scene("mouth")).into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
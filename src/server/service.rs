@@ -0,0 +1,349 @@
+//! Games played through an API. `GameService` holds them; the transports
+//! (gRPC, HTTP) only translate calls, after checking the caller's API token.
+//!
+//! Servers reachable by others get the protections of the chat bots: each
+//! API user makes `config.bots.commands_per_minute` calls a minute, plays
+//! only their own sessions and has their own saves, `max_sessions` caps the
+//! games in memory and quiet sessions are ended after `idle_minutes`.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex};
+use uuid::Uuid;
+use crate::config::{BotConfig, Config, GameConfig};
+use crate::core::{GameEngine, SceneUpdate};
+use crate::story::{Story, StoryLoader, StoryMetadata};
+use crate::utils::{GameError, GameResult, Metrics, RateLimiter, SaveManager};
+use tracing::warn;
+
+/// Scene updates a slow watcher can fall behind by before missing some.
+const UPDATE_CAPACITY: usize = 16;
+
+/// Who calls a server that has no API tokens, which only listens locally.
+pub const LOCAL_USER: &str = "local";
+
+/// The user an API call was authenticated as; transports add it to the
+/// request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiUser(pub String);
+
+struct Session {
+    /// The user who started it; nobody else sees it.
+    owner: String,
+    engine: GameEngine,
+    updates: broadcast::Sender<SceneUpdate>,
+    last_active: Instant,
+}
+
+/// Games played through an API, one per session ID. Each method is one
+/// call of the API by `user`.
+pub struct GameService {
+    story_loader: StoryLoader,
+    save_manager: SaveManager,
+    /// Settings of the engines sessions are played with.
+    game: GameConfig,
+    /// Users by API token; empty for a local-only server.
+    tokens: HashMap<String, String>,
+    limits: BotConfig,
+    rate_limiter: std::sync::Mutex<RateLimiter>,
+    metrics: Arc<Metrics>,
+    sessions: Mutex<HashMap<Uuid, Session>>,
+}
+
+impl GameService {
+    pub fn new(config: &Config) -> GameResult<Self> {
+        let mut save_manager = SaveManager::new(config.get_saves_dir());
+        if let Some(cipher) = config.save_cipher()? {
+            save_manager = save_manager.with_cipher(cipher);
+        }
+        Ok(Self {
+            story_loader: StoryLoader::new(config.get_stories_dir()),
+            save_manager,
+            game: config.game.clone(),
+            tokens: config.api_tokens()?,
+            limits: config.bots.clone(),
+            rate_limiter: std::sync::Mutex::new(RateLimiter::new(config.bots.commands_per_minute)),
+            metrics: Arc::new(Metrics::default()),
+            sessions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Accepts `tokens` (users by token) instead of those in the config.
+    pub fn with_tokens(mut self, tokens: HashMap<String, String>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// What the API has been doing, for `serve_metrics`.
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// The user `token` belongs to. Without API tokens every caller is
+    /// `LOCAL_USER`.
+    pub fn authenticate(&self, token: Option<&str>) -> Option<ApiUser> {
+        if self.tokens.is_empty() {
+            return Some(ApiUser(LOCAL_USER.to_string()));
+        }
+        token.and_then(|token| self.tokens.get(token)).map(|user| ApiUser(user.clone()))
+    }
+
+    /// Refuses to serve on `addr` if others could reach it and there are
+    /// no API tokens to tell them apart.
+    pub fn check_address(&self, addr: SocketAddr) -> GameResult<()> {
+        if self.tokens.is_empty() && !addr.ip().is_loopback() {
+            return Err(GameError::configuration(format!(
+                "Serving on {} would let anyone in; set API tokens (see server.tokens_env) or serve on 127.0.0.1",
+                addr
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn list_stories(&self, user: &ApiUser) -> GameResult<Vec<StoryMetadata>> {
+        self.check_rate(user)?;
+        self.story_loader.list_available_stories().await
+    }
+
+    pub async fn load_story(&self, user: &ApiUser, story_id: &str) -> GameResult<Story> {
+        self.check_rate(user)?;
+        self.read_story(story_id).await
+    }
+
+    /// Starts `story_id` for `player_name`. Returns the new session and its
+    /// first scene.
+    pub async fn start_session(&self, user: &ApiUser, story_id: &str, player_name: &str) -> GameResult<(Uuid, SceneUpdate)> {
+        self.check_rate(user)?;
+        self.evict_idle(Instant::now()).await;
+        self.check_quota().await?;
+        let story = self.read_story(story_id).await?;
+        let mut engine = self.game.engine();
+        engine.load_story(story).await?;
+        engine.start_new_game(player_name.to_string()).await?;
+        self.add_session(user, engine).await
+    }
+
+    pub async fn scene(&self, user: &ApiUser, session_id: Uuid) -> GameResult<SceneUpdate> {
+        self.check_rate(user)?;
+        let mut sessions = self.sessions.lock().await;
+        let session = Self::session(&mut sessions, user, session_id)?;
+        Ok(SceneUpdate::new(&session.engine.get_current_scene().await?, None))
+    }
+
+    /// Makes `choice_id` in the session and returns the scene it leads to,
+    /// which watchers are sent too.
+    pub async fn make_choice(&self, user: &ApiUser, session_id: Uuid, choice_id: &str) -> GameResult<SceneUpdate> {
+        self.check_rate(user)?;
+        let started = Instant::now();
+        let mut sessions = self.sessions.lock().await;
+        let session = Self::session(&mut sessions, user, session_id)?;
+        session.engine.make_choice(choice_id).await?;
+        let update = SceneUpdate::new(&session.engine.get_current_scene().await?, Some(choice_id.to_string()));
+        // Nobody watching isn't an error
+        let _ = session.updates.send(update.clone());
+        self.metrics.record_choice();
+        self.metrics.record_scene_latency(started.elapsed());
+        Ok(update)
+    }
+
+    /// The session's scenes from now on, as choices are made.
+    pub async fn watch(&self, user: &ApiUser, session_id: Uuid) -> GameResult<broadcast::Receiver<SceneUpdate>> {
+        self.check_rate(user)?;
+        let mut sessions = self.sessions.lock().await;
+        Ok(Self::session(&mut sessions, user, session_id)?.updates.subscribe())
+    }
+
+    /// Saves the session's game as `name` among the user's saves. Returns
+    /// the save's ID.
+    pub async fn save(&self, user: &ApiUser, session_id: Uuid, name: &str) -> GameResult<Uuid> {
+        self.check_rate(user)?;
+        let mut sessions = self.sessions.lock().await;
+        let session = Self::session(&mut sessions, user, session_id)?;
+        let game_state = session.engine.save_game(name.to_string()).await?;
+        let save = self.user_saves(user)?.save_game(name.to_string(), game_state, None).await
+            .inspect_err(|_| self.metrics.record_save_error())?;
+        Ok(save.id)
+    }
+
+    /// Resumes one of the user's saves in a new session.
+    pub async fn load(&self, user: &ApiUser, save_id: Uuid) -> GameResult<(Uuid, SceneUpdate)> {
+        self.check_rate(user)?;
+        self.evict_idle(Instant::now()).await;
+        self.check_quota().await?;
+        let save = self.user_saves(user)?.load_game(save_id).await?;
+        let story = self.read_story(&save.game_state.story_id).await?;
+        let mut engine = self.game.engine();
+        engine.load_story(story).await?;
+        engine.load_game(save.game_state).await?;
+        self.add_session(user, engine).await
+    }
+
+    pub async fn end_session(&self, user: &ApiUser, session_id: Uuid) -> GameResult<()> {
+        self.check_rate(user)?;
+        let mut sessions = self.sessions.lock().await;
+        Self::session(&mut sessions, user, session_id)?;
+        sessions.remove(&session_id);
+        self.metrics.set_sessions(sessions.len());
+        Ok(())
+    }
+
+    /// Ends the sessions nobody used for `idle_minutes` before `now`.
+    /// Returns how many were ended.
+    pub async fn evict_idle(&self, now: Instant) -> usize {
+        if self.limits.idle_minutes == 0 {
+            return 0;
+        }
+        let idle = Duration::from_secs(self.limits.idle_minutes * 60);
+        let mut sessions = self.sessions.lock().await;
+        let before = sessions.len();
+        sessions.retain(|_, session| now.saturating_duration_since(session.last_active) < idle);
+        self.metrics.set_sessions(sessions.len());
+        before - sessions.len()
+    }
+
+    async fn add_session(&self, user: &ApiUser, engine: GameEngine) -> GameResult<(Uuid, SceneUpdate)> {
+        let scene = SceneUpdate::new(&engine.get_current_scene().await?, None);
+        let session_id = Uuid::new_v4();
+        let (updates, _) = broadcast::channel(UPDATE_CAPACITY);
+        let mut sessions = self.sessions.lock().await;
+        sessions.insert(session_id, Session { owner: user.0.clone(), engine, updates, last_active: Instant::now() });
+        self.metrics.set_sessions(sessions.len());
+        Ok((session_id, scene))
+    }
+
+    /// The story, with errors that would quote the story file replaced, so
+    /// callers are only told the story can't be loaded; the details go to
+    /// the log.
+    async fn read_story(&self, story_id: &str) -> GameResult<Story> {
+        self.story_loader.load_story(story_id).await.map_err(|error| match error.inner() {
+            GameError::Story { .. } => {
+                warn!("Story '{}' requested over the API can't be loaded: {}", story_id, error.report());
+                GameError::story(format!("Story '{}' can't be loaded", story_id)).with_story(story_id)
+            }
+            _ => error,
+        })
+    }
+
+    fn user_saves(&self, user: &ApiUser) -> GameResult<SaveManager> {
+        self.save_manager.for_user(&format!("api:{}", user.0))
+    }
+
+    fn check_rate(&self, user: &ApiUser) -> GameResult<()> {
+        let mut rate_limiter = self.rate_limiter.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match rate_limiter.check(&user.0, Instant::now()) {
+            true => Ok(()),
+            false => Err(GameError::player("Too many requests; slow down".to_string())),
+        }
+    }
+
+    /// Refuses a new session if `max_sessions` are already going.
+    async fn check_quota(&self) -> GameResult<()> {
+        let max = self.limits.max_sessions;
+        if max > 0 && self.sessions.lock().await.len() >= max {
+            return Err(GameError::player(format!("{} games are already going; try again later", max)));
+        }
+        Ok(())
+    }
+
+    /// The user's session, marked as used.
+    fn session<'s>(sessions: &'s mut HashMap<Uuid, Session>, user: &ApiUser, session_id: Uuid) -> GameResult<&'s mut Session> {
+        // Someone else's session looks the same as a missing one
+        let session = sessions.get_mut(&session_id)
+            .filter(|session| session.owner == user.0)
+            .ok_or_else(|| GameError::player(format!("No session {}", session_id)))?;
+        session.last_active = Instant::now();
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Choice, Scene};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_game_service_sessions() {
+        let temp_dir = tempdir().unwrap();
+        let mut story = Story::new("cave", "The Cave", "mouth", PlayerStats::default());
+        let mut mouth = Scene::new("mouth", "Cave Mouth", "Darkness ahead.");
+        mouth.add_choice(Choice::new("enter", "Go in", "hall"));
+        story.add_scene(mouth);
+        story.add_scene(Scene::new("hall", "Great Hall", "Echoes everywhere."));
+        let stories_dir = temp_dir.path().join("stories");
+        std::fs::create_dir_all(&stories_dir).unwrap();
+        std::fs::write(stories_dir.join("cave.json"), serde_json::to_string(&story).unwrap()).unwrap();
+        let mut config = Config::default();
+        config.paths.stories_dir = stories_dir;
+        config.paths.saves_dir = temp_dir.path().join("saves");
+
+        let service = GameService::new(&config).unwrap();
+        let ana = service.authenticate(None).unwrap();
+        assert_eq!(service.list_stories(&ana).await.unwrap()[0].id, "cave");
+        let (session, scene) = service.start_session(&ana, "cave", "Ana").await.unwrap();
+        assert_eq!(scene.choices, [("enter".to_string(), "Go in".to_string())]);
+
+        let save_id = service.save(&ana, session, "Before").await.unwrap();
+        let mut updates = service.watch(&ana, session).await.unwrap();
+        assert!(service.make_choice(&ana, session, "leave").await.is_err());
+        assert_eq!(service.make_choice(&ana, session, "enter").await.unwrap().scene_id, "hall");
+        assert_eq!(updates.recv().await.unwrap().chosen.as_deref(), Some("enter"));
+        assert!(service.metrics().render().contains("\ntext_game_choices_total 1\n"));
+
+        let (loaded, scene) = service.load(&ana, save_id).await.unwrap();
+        assert_ne!(loaded, session);
+        assert_eq!(scene.scene_id, "mouth");
+        service.end_session(&ana, session).await.unwrap();
+        assert!(service.scene(&ana, session).await.is_err());
+        assert_eq!(service.scene(&ana, loaded).await.unwrap().title, "Cave Mouth");
+
+        std::fs::write(temp_dir.path().join("stories").join("broken.json"), "{\"password\": ").unwrap();
+        let error = service.start_session(&ana, "broken", "Ana").await.unwrap_err().report();
+        assert!(error.contains("can't be loaded") && !error.contains("password"), "{}", error);
+        assert!(service.start_session(&ana, "../stories/cave", "Ana").await.is_err());
+
+        // Quiet sessions are ended
+        assert_eq!(service.evict_idle(Instant::now() + Duration::from_secs(31 * 60)).await, 1);
+        assert!(service.scene(&ana, loaded).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_game_service_users() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path().join("stories"));
+        loader.create_story_template("tale", "A Tale", "Ada").await.unwrap();
+        let mut config = Config::default();
+        config.paths.stories_dir = temp_dir.path().join("stories");
+        config.paths.saves_dir = temp_dir.path().join("saves");
+        config.bots = BotConfig { commands_per_minute: 5, max_sessions: 2, idle_minutes: 30 };
+        let tokens = HashMap::from([("a-token".to_string(), "ana".to_string()), ("b-token".to_string(), "ben".to_string())]);
+        let service = GameService::new(&config).unwrap().with_tokens(tokens);
+
+        assert!(service.authenticate(None).is_none());
+        assert!(service.authenticate(Some("guess")).is_none());
+        assert!(service.check_address("0.0.0.0:8080".parse().unwrap()).is_ok());
+        let ana = service.authenticate(Some("a-token")).unwrap();
+        let ben = service.authenticate(Some("b-token")).unwrap();
+
+        // Sessions and saves are the user's own
+        let (session, _) = service.start_session(&ana, "tale", "Ana").await.unwrap();
+        let save_id = service.save(&ana, session, "Mine").await.unwrap();
+        assert!(service.scene(&ben, session).await.is_err());
+        assert!(service.load(&ben, save_id).await.is_err());
+
+        // Two sessions at most, and five calls a minute per user
+        service.start_session(&ben, "tale", "Ben").await.unwrap();
+        assert!(service.start_session(&ben, "tale", "Ben").await.is_err());
+        for _ in 0..3 {
+            assert!(service.scene(&ana, session).await.is_ok());
+        }
+        assert!(service.scene(&ana, session).await.is_err());
+
+        let local = GameService::new(&config).unwrap();
+        assert!(local.check_address("0.0.0.0:8080".parse().unwrap()).is_err());
+        assert!(local.check_address("127.0.0.1:8080".parse().unwrap()).is_ok());
+    }
+}
@@ -0,0 +1,220 @@
+//! Path lengths and reading time through a story's scene graph, for the
+//! `stats` command and story catalogs. Conditions aren't evaluated, so every
+//! choice counts as open.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use crate::story::Story;
+
+/// Reading speed used for playtime estimates.
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Scenes from the start to an ending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndingPath {
+    pub scenes: Vec<String>,
+    /// Words in the scene descriptions along the path.
+    pub words: usize,
+}
+
+impl EndingPath {
+    pub fn minutes(&self) -> u32 {
+        reading_minutes(self.words)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathAnalysis {
+    /// Fewest scenes to an ending.
+    pub shortest: Option<EndingPath>,
+    /// Most words to an ending without visiting a scene twice. Found greedily,
+    /// so in stories with loops it can fall short of the true longest path.
+    pub longest: Option<EndingPath>,
+    pub reachable_scenes: usize,
+    pub reachable_endings: usize,
+    /// Words in every reachable scene.
+    pub total_words: usize,
+}
+
+fn reading_minutes(words: usize) -> u32 {
+    words.div_ceil(WORDS_PER_MINUTE).max(1) as u32
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Choice and auto-advance targets of a scene, without duplicates.
+fn exits(story: &Story, scene_id: &str) -> Vec<String> {
+    let Some(scene) = story.get_shared_scene(scene_id) else {
+        return Vec::new();
+    };
+    let mut targets: Vec<String> = Vec::new();
+    let auto_target = scene.auto_advance.iter().map(|auto| &auto.target_scene_id);
+    for target in scene.choices.iter().map(|choice| &choice.target_scene_id).chain(auto_target) {
+        if !targets.contains(target) && story.has_scene(target) {
+            targets.push(target.clone());
+        }
+    }
+    targets
+}
+
+impl PathAnalysis {
+    pub fn of(story: &Story) -> Self {
+        let start = story.starting_scene_id.clone();
+        let words = |scene_id: &str| story.get_shared_scene(scene_id)
+            .map_or(0, |scene| word_count(&scene.description));
+        let is_ending = |scene_id: &str| story.get_shared_scene(scene_id)
+            .is_some_and(|scene| scene.is_ending());
+
+        if !story.has_scene(&start) {
+            return Self::default();
+        }
+
+        // Breadth-first from the start gives the shortest path to each scene
+        let mut came_from: HashMap<String, Option<String>> = HashMap::from([(start.clone(), None)]);
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([start.clone()]);
+        while let Some(scene_id) = queue.pop_front() {
+            for target in exits(story, &scene_id) {
+                if !came_from.contains_key(&target) {
+                    came_from.insert(target.clone(), Some(scene_id.clone()));
+                    queue.push_back(target);
+                }
+            }
+            order.push(scene_id);
+        }
+
+        let shortest = order.iter().find(|scene_id| is_ending(scene_id)).map(|ending| {
+            let mut scenes = vec![ending.clone()];
+            while let Some(Some(previous)) = came_from.get(scenes.last().unwrap()) {
+                scenes.push(previous.clone());
+            }
+            scenes.reverse();
+            let words = scenes.iter().map(|scene_id| words(scene_id)).sum();
+            EndingPath { scenes, words }
+        });
+
+        let mut longest_from = HashMap::new();
+        let longest = longest_path(story, &start, &mut HashSet::new(), &mut longest_from, &words, &is_ending);
+
+        Self {
+            shortest,
+            longest,
+            reachable_scenes: order.len(),
+            reachable_endings: order.iter().filter(|scene_id| is_ending(scene_id)).count(),
+            total_words: order.iter().map(|scene_id| words(scene_id)).sum(),
+        }
+    }
+
+    /// Halfway between the shortest and longest playthrough, in minutes.
+    pub fn estimated_minutes(&self) -> Option<u32> {
+        match (&self.shortest, &self.longest) {
+            (Some(shortest), Some(longest)) => Some((shortest.minutes() + longest.minutes()).div_ceil(2)),
+            _ => None,
+        }
+    }
+}
+
+/// The wordiest path from `scene_id` to an ending that avoids scenes on
+/// `path`. Results are memoized per scene, which ignores that a different
+/// `path` might have allowed a longer route.
+fn longest_path(
+    story: &Story,
+    scene_id: &str,
+    path: &mut HashSet<String>,
+    memo: &mut HashMap<String, Option<EndingPath>>,
+    words: &dyn Fn(&str) -> usize,
+    is_ending: &dyn Fn(&str) -> bool,
+) -> Option<EndingPath> {
+    if let Some(known) = memo.get(scene_id) {
+        return known.clone();
+    }
+    if is_ending(scene_id) {
+        return Some(EndingPath { scenes: vec![scene_id.to_string()], words: words(scene_id) });
+    }
+
+    path.insert(scene_id.to_string());
+    let mut best: Option<EndingPath> = None;
+    for target in exits(story, scene_id) {
+        if path.contains(&target) {
+            continue;
+        }
+        if let Some(rest) = longest_path(story, &target, path, memo, words, is_ending) {
+            if best.as_ref().is_none_or(|best| rest.words > best.words) {
+                best = Some(rest);
+            }
+        }
+    }
+    path.remove(scene_id);
+
+    let result = best.map(|mut rest| {
+        rest.scenes.insert(0, scene_id.to_string());
+        rest.words += words(scene_id);
+        rest
+    });
+    memo.insert(scene_id.to_string(), result.clone());
+    result
+}
+
+impl fmt::Display for PathAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Reachable: {} scenes, {} endings, {} words (~{} min to read everything)",
+            self.reachable_scenes, self.reachable_endings, self.total_words, reading_minutes(self.total_words)
+        )?;
+        for (label, path) in [("Shortest", &self.shortest), ("Longest", &self.longest)] {
+            match path {
+                Some(path) => writeln!(
+                    f,
+                    "{} ending path: {} scenes, {} words (~{} min): {}",
+                    label, path.scenes.len(), path.words, path.minutes(), path.scenes.join(" → ")
+                )?,
+                None => writeln!(f, "{} ending path: no ending is reachable", label)?,
+            }
+        }
+        if let Some(minutes) = self.estimated_minutes() {
+            writeln!(f, "Estimated playtime: ~{} minutes", minutes)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Choice, Scene};
+
+    #[test]
+    fn test_path_analysis() {
+        let mut story = Story::new("paths", "Paths", "road", PlayerStats::default());
+        let mut road = Scene::new("road", "Road", "A fork in the road.");
+        road.add_choice(Choice::new("left", "Go left", "cottage"));
+        road.add_choice(Choice::new("right", "Go right", "forest"));
+        let mut forest = Scene::new("forest", "Forest", &"Trees. ".repeat(400));
+        forest.add_choice(Choice::new("back", "Turn back", "road"));
+        forest.add_choice(Choice::new("on", "Press on", "cottage"));
+        let mut cottage = Scene::new("cottage", "Cottage", "Home.");
+        cottage.is_ending = Some(true);
+        story.add_scene(road);
+        story.add_scene(forest);
+        story.add_scene(cottage);
+        story.add_scene(Scene::new("unused", "Unused", "Never reached."));
+
+        let analysis = PathAnalysis::of(&story);
+        assert_eq!(analysis.reachable_scenes, 3);
+        assert_eq!(analysis.reachable_endings, 1);
+
+        let shortest = analysis.shortest.as_ref().unwrap();
+        assert_eq!(shortest.scenes, vec!["road".to_string(), "cottage".to_string()]);
+        assert_eq!(shortest.minutes(), 1);
+
+        let longest = analysis.longest.as_ref().unwrap();
+        assert_eq!(longest.scenes, vec!["road".to_string(), "forest".to_string(), "cottage".to_string()]);
+        assert_eq!(longest.words, 406);
+        assert_eq!(longest.minutes(), 3);
+        assert_eq!(analysis.estimated_minutes(), Some(2));
+    }
+}
@@ -0,0 +1,195 @@
+//! Images and music referenced by scenes (`image`, `background_music`).
+//! A frontend that shows them can prefetch the assets of the scenes a
+//! scene's choices lead to while the player reads it, so it doesn't wait on
+//! the disk when the player moves on. The console UI shows neither, so it
+//! doesn't.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use crate::story::{Scene, SceneView, Story};
+use tracing::{debug, warn};
+
+/// Bytes of asset contents kept in memory by default.
+const DEFAULT_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+type Asset = Arc<Vec<u8>>;
+
+/// Asset contents keyed by the path the story gives. Only the most recently
+/// used assets are kept in memory, up to a total size.
+#[derive(Debug, Clone)]
+pub struct AssetCache {
+    /// Relative asset paths are resolved against this directory.
+    base_directory: PathBuf,
+    cache: Arc<Mutex<AssetLru>>,
+}
+
+#[derive(Debug)]
+struct AssetLru {
+    max_bytes: usize,
+    bytes: usize,
+    assets: HashMap<String, Asset>,
+    recent: VecDeque<String>,
+}
+
+impl AssetCache {
+    pub fn new<P: AsRef<Path>>(base_directory: P) -> Self {
+        Self {
+            base_directory: base_directory.as_ref().to_path_buf(),
+            cache: Arc::new(Mutex::new(AssetLru::new(DEFAULT_CACHE_BYTES))),
+        }
+    }
+
+    /// Keeps at most `max_bytes` of asset contents in memory; larger assets
+    /// are read every time.
+    pub fn with_max_bytes(self, max_bytes: usize) -> Self {
+        self.cache.lock().unwrap().max_bytes = max_bytes;
+        self
+    }
+
+    pub fn is_cached(&self, path: &str) -> bool {
+        self.cache.lock().unwrap().assets.contains_key(path)
+    }
+
+    pub fn cached_asset_count(&self) -> usize {
+        self.cache.lock().unwrap().assets.len()
+    }
+
+    pub fn cached_bytes(&self) -> usize {
+        self.cache.lock().unwrap().bytes
+    }
+
+    /// The asset's contents, read from disk unless it was prefetched.
+    pub fn get(&self, path: &str) -> std::io::Result<Asset> {
+        if let Some(asset) = self.cache.lock().unwrap().get(path) {
+            return Ok(asset);
+        }
+
+        // Read outside the lock; a concurrent miss just reads the file twice
+        let asset = Arc::new(std::fs::read(self.resolve(path))?);
+        self.cache.lock().unwrap().insert(path.to_string(), Arc::clone(&asset));
+        Ok(asset)
+    }
+
+    /// Reads the assets that aren't cached yet in the background. Missing
+    /// files are only logged; `get` reports them when they're needed.
+    pub fn prefetch(&self, paths: Vec<String>) -> JoinHandle<()> {
+        let uncached: Vec<String> = paths.into_iter()
+            .filter(|path| !self.is_cached(path))
+            .collect();
+        let cache = self.clone();
+
+        tokio::spawn(async move {
+            for path in uncached {
+                match tokio::fs::read(cache.resolve(&path)).await {
+                    Ok(contents) => {
+                        debug!("Prefetched asset {}", path);
+                        cache.cache.lock().unwrap().insert(path, Arc::new(contents));
+                    }
+                    Err(e) => warn!("Failed to prefetch asset {}: {}", path, e),
+                }
+            }
+        })
+    }
+
+    fn resolve(&self, path: &str) -> PathBuf {
+        self.base_directory.join(path)
+    }
+}
+
+impl AssetLru {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            bytes: 0,
+            assets: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, path: &str) -> Option<Asset> {
+        let asset = self.assets.get(path).cloned()?;
+        self.touch(path);
+        Some(asset)
+    }
+
+    fn insert(&mut self, path: String, asset: Asset) {
+        if asset.len() > self.max_bytes {
+            return;
+        }
+        self.bytes += asset.len();
+        if let Some(replaced) = self.assets.insert(path.clone(), asset) {
+            self.bytes -= replaced.len();
+        }
+        self.touch(&path);
+
+        while self.bytes > self.max_bytes {
+            let Some(evicted) = self.recent.pop_back() else {
+                break;
+            };
+            if let Some(asset) = self.assets.remove(&evicted) {
+                self.bytes -= asset.len();
+            }
+        }
+    }
+
+    fn touch(&mut self, path: &str) {
+        if let Some(pos) = self.recent.iter().position(|p| p == path) {
+            self.recent.remove(pos);
+        }
+        self.recent.push_front(path.to_string());
+    }
+}
+
+/// The assets a scene shows or plays.
+pub fn scene_assets(scene: &Scene) -> impl Iterator<Item = &str> {
+    scene.image.as_deref().into_iter().chain(scene.background_music.as_deref())
+}
+
+/// The assets of the scenes the player can go to next from `scene`: the
+/// targets of its available choices and of its auto-advance.
+pub fn upcoming_assets(story: &Story, scene: &SceneView) -> Vec<String> {
+    let auto_advance = scene.scene.auto_advance.as_ref().map(|auto_advance| &auto_advance.target_scene_id);
+    let targets: HashSet<&String> = scene.available_choices()
+        .map(|choice| &choice.target_scene_id)
+        .chain(auto_advance)
+        .collect();
+
+    let mut assets: Vec<String> = targets.into_iter()
+        .filter_map(|scene_id| story.get_shared_scene(scene_id))
+        .flat_map(|target| scene_assets(&target).map(str::to_string).collect::<Vec<_>>())
+        .collect();
+    assets.sort();
+    assets.dedup();
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_prefetch_with_lru_eviction() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("cave.png"), b"cave").unwrap();
+        std::fs::write(temp_dir.path().join("drip.ogg"), b"drip").unwrap();
+        std::fs::write(temp_dir.path().join("theme.ogg"), b"a long theme").unwrap();
+
+        let assets = AssetCache::new(temp_dir.path()).with_max_bytes(6);
+        assets.prefetch(vec!["cave.png".to_string(), "missing.png".to_string()]).await.unwrap();
+        assert!(assets.is_cached("cave.png"));
+        assert_eq!(assets.cached_asset_count(), 1);
+
+        assert_eq!(assets.get("drip.ogg").unwrap().as_slice(), b"drip");
+        assert!(!assets.is_cached("cave.png"));
+        assert_eq!(assets.cached_bytes(), 4);
+        assert!(assets.get("missing.png").is_err());
+
+        // Too big to keep, but still read
+        assert_eq!(assets.get("theme.ogg").unwrap().len(), 12);
+        assert!(!assets.is_cached("theme.ogg"));
+        assert!(assets.is_cached("drip.ogg"));
+    }
+}
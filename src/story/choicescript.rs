@@ -0,0 +1,707 @@
+//! Importer for basic ChoiceScript. `*label`s become scenes and `*goto`s
+//! "Continue" choices into them, `*choice` options become choices, `*create`,
+//! `*temp` and `*set` become effects and `*if` becomes conditions. Variables
+//! named after a player stat change that stat; the rest are flags. Since
+//! choices can't be hidden, a scene-level `*if` becomes a "Continue" choice
+//! per branch, with the branches that don't apply greyed out. Commands
+//! beyond these are skipped with a warning.
+
+use std::collections::{HashMap, HashSet};
+use serde_json::Value;
+use crate::core::PlayerStats;
+use crate::story::{Choice, ComparisonOperator, Condition, ConditionType, Effect, EffectOperation, EffectType, Scene, Story};
+use crate::utils::{GameError, GameResult};
+
+/// ID of the scene a file starts in, named as in ChoiceScript.
+pub const STARTUP_SCENE: &str = "startup";
+/// ID of the shared ending scene that `*finish` leads to when there's no
+/// text before it to end on.
+pub const FINISH_SCENE: &str = "the_end";
+
+const CONTINUE: &str = "Continue";
+const REUSE_MODIFIERS: [&str; 3] = ["*hide_reuse", "*disable_reuse", "*allow_reuse"];
+const OPERATORS: [(&str, ComparisonOperator); 6] = [
+    ("!=", ComparisonOperator::NotEquals),
+    ("<=", ComparisonOperator::LessEqual),
+    (">=", ComparisonOperator::GreaterEqual),
+    ("=", ComparisonOperator::Equals),
+    ("<", ComparisonOperator::LessThan),
+    (">", ComparisonOperator::GreaterThan),
+];
+
+/// A story imported from ChoiceScript source.
+#[derive(Debug, Clone)]
+pub struct ChoiceScriptImport {
+    pub story: Story,
+    /// Commands that were skipped or only approximated, with their line numbers.
+    pub warnings: Vec<String>,
+}
+
+impl ChoiceScriptImport {
+    pub fn parse(id: &str, source: &str) -> GameResult<Self> {
+        let lines = nest(source);
+        let mut importer = Importer::default();
+        let mut story = Story::new(id, id, STARTUP_SCENE, PlayerStats::default());
+        let mut effects = Vec::new();
+
+        // Split the file into the startup section and one section per label
+        let mut sections: Vec<(String, Vec<Line>)> = vec![(STARTUP_SCENE.to_string(), Vec::new())];
+        for line in &lines {
+            let (name, args) = command(line.text).unwrap_or_default();
+            match name {
+                "title" => story.title = args.to_string(),
+                "author" => story.author = args.to_string(),
+                "create" => effects.extend(importer.set_effect(line.number, args, true)?),
+                "scene_list" | "comment" => {}
+                "label" => {
+                    let label = args.to_lowercase();
+                    if label.is_empty() || label == STARTUP_SCENE || label == FINISH_SCENE {
+                        return Err(GameError::story(format!("line {}: invalid label '{}'", line.number, args)));
+                    }
+                    if !importer.labels.insert(label.clone()) {
+                        return Err(GameError::story(format!("line {}: duplicate label '{}'", line.number, label)));
+                    }
+                    sections.push((label, Vec::new()));
+                }
+                _ => sections.last_mut().expect("startup section").1.push(line.clone()),
+            }
+        }
+        importer.story_title = story.title.clone();
+
+        // A file that opens straight onto a label starts there
+        if sections[0].1.is_empty() && effects.is_empty() && sections.len() > 1 {
+            sections.remove(0);
+            story.starting_scene_id = sections[0].0.clone();
+        }
+        let ids: Vec<String> = sections.iter().map(|(id, _)| id.clone()).collect();
+        for (index, (id, section)) in sections.into_iter().enumerate() {
+            let mut draft = Draft::new(Some(id.clone()), id);
+            if index == 0 {
+                draft.effects = std::mem::take(&mut effects);
+            }
+            let fallthrough = ids.get(index + 1).map(|next| Exit::Goto(next.clone())).unwrap_or(Exit::Finish);
+            importer.compile(&section, draft, Some(&fallthrough), 0)?;
+        }
+
+        if let Some((number, label)) = importer.gotos.iter().find(|(_, label)| !importer.labels.contains(label)) {
+            return Err(GameError::story(format!("line {}: *goto to unknown label '{}'", number, label)));
+        }
+        if importer.finish_used {
+            let mut end = Scene::new(FINISH_SCENE, "The End", "");
+            end.is_ending = Some(true);
+            importer.scenes.push(end);
+        }
+        for scene in importer.scenes {
+            story.add_scene(scene);
+        }
+        Ok(Self { story, warnings: importer.warnings })
+    }
+}
+
+/// A non-blank source line and the lines indented under it.
+#[derive(Debug, Clone)]
+struct Line<'a> {
+    number: usize,
+    text: &'a str,
+    /// Whether a blank line follows, ending the paragraph.
+    paragraph_end: bool,
+    children: Vec<Line<'a>>,
+}
+
+fn nest(source: &str) -> Vec<Line<'_>> {
+    let mut flat: Vec<(usize, Line)> = Vec::new();
+    for (index, raw) in source.lines().enumerate() {
+        let text = raw.trim();
+        if text.is_empty() {
+            if let Some((_, last)) = flat.last_mut() {
+                last.paragraph_end = true;
+            }
+            continue;
+        }
+        let indent = raw.len() - raw.trim_start().len();
+        flat.push((indent, Line { number: index + 1, text, paragraph_end: false, children: Vec::new() }));
+    }
+    block(&mut flat.into_iter().peekable(), 0)
+}
+
+fn block<'a, I: Iterator<Item = (usize, Line<'a>)>>(lines: &mut std::iter::Peekable<I>, indent: usize) -> Vec<Line<'a>> {
+    let mut block: Vec<Line> = Vec::new();
+    while let Some(&(next_indent, _)) = lines.peek() {
+        if next_indent < indent {
+            break;
+        }
+        match block.last_mut() {
+            Some(parent) if next_indent > indent => parent.children.extend(self::block(lines, next_indent)),
+            _ => block.push(lines.next().expect("peeked line").1),
+        }
+    }
+    block
+}
+
+/// Name and arguments of a `*command` line.
+fn command(text: &str) -> Option<(&str, &str)> {
+    let command = text.strip_prefix('*')?;
+    let (name, args) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    Some((name, args.trim()))
+}
+
+/// "dark_cave" as "Dark Cave".
+fn humanize(label: &str) -> String {
+    label.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone)]
+enum Exit {
+    Goto(String),
+    Finish,
+}
+
+/// Where a block of lines leads: a scene, and the effects to apply on the
+/// way when the block had no text of its own to show.
+struct Link {
+    target: String,
+    effects: Vec<Effect>,
+}
+
+/// The scene being written while compiling a block.
+struct Draft {
+    /// Fixed for labels; other scenes only get an ID if they turn out to
+    /// need one.
+    id: Option<String>,
+    /// Label the scene belongs to, for its title and generated ID.
+    base: String,
+    text: String,
+    paragraph_end: bool,
+    effects: Vec<Effect>,
+}
+
+impl Draft {
+    fn new(id: Option<String>, base: String) -> Self {
+        Self { id, base, text: String::new(), paragraph_end: false, effects: Vec::new() }
+    }
+
+    fn push_text(&mut self, line: &Line) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push_str(if self.paragraph_end { "\n\n" } else { " " });
+        }
+        self.text.push_str(line.text);
+        self.paragraph_end = line.paragraph_end;
+    }
+
+    /// Whether the draft can be skipped, passing its effects on.
+    fn is_empty(&self) -> bool {
+        self.id.is_none() && self.text.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct Importer {
+    story_title: String,
+    scenes: Vec<Scene>,
+    labels: HashSet<String>,
+    generated: HashSet<String>,
+    counters: HashMap<String, usize>,
+    gotos: Vec<(usize, String)>,
+    warnings: Vec<String>,
+    finish_used: bool,
+}
+
+impl Importer {
+    /// Compiles `lines` into scenes starting with `draft`, continuing to
+    /// `fallthrough` if they run out without a `*goto` or `*finish`. `owner`
+    /// is the line the block is indented under, for errors.
+    fn compile(&mut self, lines: &[Line], mut draft: Draft, fallthrough: Option<&Exit>, owner: usize) -> GameResult<Link> {
+        let mut entry: Option<Link> = None;
+        let mut index = 0;
+        while let Some(line) = lines.get(index) {
+            index += 1;
+            let Some((name, args)) = command(line.text) else {
+                draft.push_text(line);
+                continue;
+            };
+            match name {
+                "comment" => {}
+                "line_break" => draft.text.push('\n'),
+                "set" | "temp" => draft.effects.extend(self.set_effect(line.number, args, name == "temp")?),
+                "goto" => {
+                    let label = args.to_lowercase();
+                    self.gotos.push((line.number, label.clone()));
+                    let link = self.exit(draft, Exit::Goto(label));
+                    return Ok(entry.unwrap_or(link));
+                }
+                "finish" | "ending" => {
+                    let link = self.exit(draft, Exit::Finish);
+                    return Ok(entry.unwrap_or(link));
+                }
+                "page_break" => {
+                    let next = self.fresh_id(&draft.base);
+                    let text = if args.is_empty() { "Next" } else { args };
+                    let base = draft.base.clone();
+                    let link = self.scene(draft, vec![Choice::new("next".to_string(), text.to_string(), next.clone())]);
+                    entry.get_or_insert(link);
+                    draft = Draft::new(Some(next), base);
+                }
+                "choice" | "fake_choice" => {
+                    let rest = &lines[index..];
+                    let after = match name {
+                        "fake_choice" if rest.is_empty() => fallthrough.cloned(),
+                        "fake_choice" => Some(Exit::Goto(self.fresh_id(&draft.base))),
+                        _ => {
+                            if let Some(unreachable) = rest.first() {
+                                self.warnings.push(format!("line {}: text after a *choice is never reached", unreachable.number));
+                            }
+                            None
+                        }
+                    };
+                    let mut choices = Vec::new();
+                    self.options(&line.children, &draft.base, after.as_ref(), &[], &mut choices)?;
+                    if choices.is_empty() {
+                        return Err(GameError::story(format!("line {}: *{} has no #options", line.number, name)));
+                    }
+                    let base = draft.base.clone();
+                    let link = self.scene(draft, choices);
+                    if let (false, Some(Exit::Goto(next))) = (rest.is_empty(), &after) {
+                        self.compile(rest, Draft::new(Some(next.clone()), base), fallthrough, line.number)?;
+                    }
+                    return Ok(entry.unwrap_or(link));
+                }
+                "if" => {
+                    let mut branches = vec![(Some(args), line)];
+                    while let Some(next) = lines.get(index) {
+                        match command(next.text) {
+                            Some(("elseif" | "elsif", args)) => branches.push((Some(args), next)),
+                            Some(("else", _)) => branches.push((None, next)),
+                            _ => break,
+                        }
+                        index += 1;
+                        if branches.last().is_some_and(|(condition, _)| condition.is_none()) {
+                            break;
+                        }
+                    }
+                    let rest = &lines[index..];
+                    let after = match rest.is_empty() {
+                        true => fallthrough.cloned(),
+                        false => Some(Exit::Goto(self.fresh_id(&draft.base))),
+                    };
+                    let choices = self.branches(&branches, &draft.base, after.as_ref())?;
+                    let base = draft.base.clone();
+                    let link = self.scene(draft, choices);
+                    if let (false, Some(Exit::Goto(next))) = (rest.is_empty(), &after) {
+                        self.compile(rest, Draft::new(Some(next.clone()), base), fallthrough, line.number)?;
+                    }
+                    return Ok(entry.unwrap_or(link));
+                }
+                "elseif" | "elsif" | "else" => {
+                    return Err(GameError::story(format!("line {}: *{} without *if", line.number, name)));
+                }
+                _ => self.warnings.push(format!("line {}: *{} isn't supported and was skipped", line.number, name)),
+            }
+        }
+
+        match fallthrough {
+            Some(exit) => {
+                let link = self.exit(draft, exit.clone());
+                Ok(entry.unwrap_or(link))
+            }
+            None => Err(GameError::story(format!("line {}: option ends without a *goto or *finish", owner))),
+        }
+    }
+
+    /// Choices for the `#options` among `lines`, each requiring `conditions`
+    /// on top of its own.
+    fn options(&mut self, lines: &[Line], base: &str, after: Option<&Exit>, conditions: &[Condition], choices: &mut Vec<Choice>) -> GameResult<()> {
+        for line in lines {
+            let mut text = line.text;
+            while let Some(rest) = REUSE_MODIFIERS.iter().find_map(|modifier| text.strip_prefix(modifier)) {
+                text = rest.trim_start();
+            }
+            let mut conditions = conditions.to_vec();
+            if let Some(rest) = text.strip_prefix("*selectable_if").or_else(|| text.strip_prefix("*if")) {
+                let (expression, rest) = split_condition(rest);
+                conditions.extend(parse_condition(line.number, expression)?);
+                text = rest.trim_start();
+                if text.is_empty() {
+                    self.options(&line.children, base, after, &conditions, choices)?;
+                    continue;
+                }
+            }
+
+            if let Some(option) = text.strip_prefix('#') {
+                let link = self.compile(&line.children, Draft::new(None, base.to_string()), after, line.number)?;
+                let mut choice = Choice::new(format!("option_{}", choices.len() + 1), option.trim().to_string(), link.target);
+                if !conditions.is_empty() {
+                    choice.conditions = Some(conditions);
+                }
+                if !link.effects.is_empty() {
+                    choice.effects = Some(link.effects);
+                }
+                choices.push(choice);
+            } else if !text.starts_with("*comment") {
+                return Err(GameError::story(format!("line {}: expected a #option", line.number)));
+            }
+        }
+        Ok(())
+    }
+
+    /// A "Continue" choice per branch of an `*if` chain, each requiring that
+    /// no earlier branch applies.
+    fn branches(&mut self, branches: &[(Option<&str>, &Line)], base: &str, after: Option<&Exit>) -> GameResult<Vec<Choice>> {
+        let mut earlier_false: Vec<Condition> = Vec::new();
+        let mut compound: Option<usize> = None;
+        let mut choices = Vec::new();
+
+        for (expression, line) in branches {
+            let mut conditions = if choices.is_empty() { Vec::new() } else { otherwise(compound, &earlier_false)? };
+            if let Some(expression) = expression {
+                let parsed = parse_condition(line.number, expression)?;
+                match parsed.as_slice() {
+                    [single] => earlier_false.push(negate(single)),
+                    _ => compound = Some(line.number),
+                }
+                conditions.extend(parsed);
+            }
+            let link = self.compile(&line.children, Draft::new(None, base.to_string()), after, line.number)?;
+            let choice = Choice::new(format!("branch_{}", choices.len() + 1), CONTINUE.to_string(), link.target);
+            choices.push(with_effects(choice.with_conditions(conditions), link.effects));
+        }
+
+        // Without an *else, carry on past the chain when nothing applied
+        if branches.last().is_some_and(|(expression, _)| expression.is_some()) {
+            let Some(exit) = after else {
+                let number = branches[0].1.number;
+                return Err(GameError::story(format!("line {}: *if needs an *else when nothing follows it", number)));
+            };
+            let conditions = otherwise(compound, &earlier_false)?;
+            let target = self.exit_target(exit);
+            choices.push(Choice::new("otherwise".to_string(), CONTINUE.to_string(), target).with_conditions(conditions));
+        }
+        Ok(choices)
+    }
+
+    /// Ends `draft` at `exit`, skipping the draft if it has nothing to show.
+    fn exit(&mut self, draft: Draft, exit: Exit) -> Link {
+        match exit {
+            _ if draft.is_empty() => Link { target: self.exit_target(&exit), effects: draft.effects },
+            Exit::Goto(target) => self.scene(draft, vec![Choice::new("continue".to_string(), CONTINUE.to_string(), target)]),
+            Exit::Finish => {
+                let link = self.scene(draft, Vec::new());
+                self.scenes.last_mut().expect("scene just added").is_ending = Some(true);
+                link
+            }
+        }
+    }
+
+    fn exit_target(&mut self, exit: &Exit) -> String {
+        match exit {
+            Exit::Goto(target) => target.clone(),
+            Exit::Finish => {
+                self.finish_used = true;
+                FINISH_SCENE.to_string()
+            }
+        }
+    }
+
+    fn scene(&mut self, draft: Draft, choices: Vec<Choice>) -> Link {
+        let id = draft.id.unwrap_or_else(|| self.fresh_id(&draft.base));
+        let title = match draft.base.as_str() {
+            STARTUP_SCENE => self.story_title.clone(),
+            base => humanize(base),
+        };
+        let mut scene = Scene::new(id.clone(), title, draft.text);
+        if !draft.effects.is_empty() {
+            scene.effects = Some(draft.effects);
+        }
+        for choice in choices {
+            scene.add_choice(choice);
+        }
+        self.scenes.push(scene);
+        Link { target: id, effects: Vec::new() }
+    }
+
+    /// An unused scene ID after the label `base`, like `cave_2`.
+    fn fresh_id(&mut self, base: &str) -> String {
+        loop {
+            let counter = self.counters.entry(base.to_string()).or_insert(0);
+            *counter += 1;
+            let id = format!("{}_{}", base, counter);
+            if !self.labels.contains(&id) && self.generated.insert(id.clone()) {
+                return id;
+            }
+        }
+    }
+
+    /// The effect of `*set`, or of `*create`/`*temp` when `declare` is set;
+    /// those only take a value.
+    fn set_effect(&mut self, number: usize, args: &str, declare: bool) -> GameResult<Option<Effect>> {
+        let Some((variable, value)) = args.split_once(char::is_whitespace) else {
+            return Err(GameError::story(format!("line {}: expected a variable and a value", number)));
+        };
+        let variable = variable.to_lowercase();
+        let mut value = value.trim();
+        if let Some(fair) = value.strip_prefix('%').filter(|_| !declare) {
+            self.warnings.push(format!("line {}: fairmath is imported as plain arithmetic", number));
+            value = fair.trim_start();
+        }
+
+        let arithmetic = [('+', EffectOperation::Add), ('-', EffectOperation::Subtract), ('*', EffectOperation::Multiply)];
+        let (operation, value) = match arithmetic.into_iter().find(|(symbol, _)| !declare && value.starts_with(*symbol)) {
+            Some((_, operation)) => (Some(operation), value[1..].trim_start()),
+            None => (None, value),
+        };
+        let Some(value) = parse_value(value) else {
+            self.warnings.push(format!("line {}: only literal values can be set; skipped", number));
+            return Ok(None);
+        };
+        if operation.is_some() && !value.is_i64() {
+            return Err(GameError::story(format!("line {}: arithmetic needs a whole number", number)));
+        }
+
+        if PlayerStats::NAMES.contains(&variable.as_str()) {
+            let Some(amount) = value.as_i64() else {
+                return Err(GameError::story(format!("line {}: stat '{}' needs a whole number", number, variable)));
+            };
+            return Ok(Some(Effect::modify_stat(variable, amount as i32, operation.unwrap_or(EffectOperation::Set))));
+        }
+        Ok(Some(Effect::new(EffectType::SetFlag, variable, value, operation)))
+    }
+}
+
+/// Conditions for a branch after `earlier_false`, which can't be built when
+/// an earlier branch's condition used `and`.
+fn otherwise(compound: Option<usize>, earlier_false: &[Condition]) -> GameResult<Vec<Condition>> {
+    match compound {
+        Some(number) => Err(GameError::story(format!(
+            "line {}: an *elseif or *else after a condition with `and` isn't supported", number
+        ))),
+        None => Ok(earlier_false.to_vec()),
+    }
+}
+
+fn with_effects(choice: Choice, effects: Vec<Effect>) -> Choice {
+    if effects.is_empty() { choice } else { choice.with_effects(effects) }
+}
+
+/// The condition of an `*if` and the text after it: a bracketed expression,
+/// or everything up to a `#option`.
+fn split_condition(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    if text.starts_with('(') {
+        let mut depth = 0;
+        for (index, c) in text.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return (&text[..=index], &text[index + 1..]);
+            }
+        }
+    }
+    text.split_at(text.find('#').unwrap_or(text.len()))
+}
+
+/// Conditions that must all hold; `or` can't be expressed.
+fn parse_condition(number: usize, expression: &str) -> GameResult<Vec<Condition>> {
+    let expression = strip_brackets(expression.trim());
+    let invalid = || GameError::story(format!("line {}: can't import the condition '{}'", number, expression));
+
+    if split_top_level(expression, " or ").is_some() {
+        return Err(GameError::story(format!("line {}: conditions with `or` aren't supported", number)));
+    }
+    if let Some(parts) = split_top_level(expression, " and ") {
+        let mut conditions = Vec::new();
+        for part in parts {
+            conditions.extend(parse_condition(number, part)?);
+        }
+        return Ok(conditions);
+    }
+    if let Some(inner) = expression.strip_prefix("not").map(str::trim_start).filter(|inner| inner.starts_with('(')) {
+        return match parse_condition(number, inner)?.as_slice() {
+            [single] => Ok(vec![negate(single)]),
+            _ => Err(invalid()),
+        };
+    }
+
+    let (variable, operator, value) = match OPERATORS.iter().find_map(|(symbol, operator)| {
+        expression.split_once(symbol).map(|(variable, value)| (variable, operator.clone(), value))
+    }) {
+        Some((variable, operator, value)) => (variable.trim(), operator, parse_value(value.trim()).ok_or_else(invalid)?),
+        None => (expression, ComparisonOperator::Equals, Value::Bool(true)),
+    };
+    if variable.is_empty() || !variable.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+    let variable = variable.to_lowercase();
+    let condition_type = match PlayerStats::NAMES.contains(&variable.as_str()) {
+        true => ConditionType::Stat,
+        false => ConditionType::Flag,
+    };
+    Ok(vec![Condition::new(condition_type, variable, operator, value)])
+}
+
+/// `expression` split at each `separator` outside brackets, if there is one.
+fn split_top_level<'a>(expression: &'a str, separator: &str) -> Option<Vec<&'a str>> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (index, c) in expression.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ if depth == 0 && expression[index..].starts_with(separator) && index >= start => {
+                parts.push(&expression[start..index]);
+                start = index + separator.len();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&expression[start..]);
+    (parts.len() > 1).then_some(parts)
+}
+
+/// `expression` without brackets around the whole of it.
+fn strip_brackets(mut expression: &str) -> &str {
+    while let Some(inner) = expression.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        // "(a) and (b)" starts and ends with brackets that don't pair up
+        if split_condition(expression).0.len() != expression.len() {
+            break;
+        }
+        expression = inner.trim();
+    }
+    expression
+}
+
+fn parse_value(text: &str) -> Option<Value> {
+    match text {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') => Some(Value::String(text[1..text.len() - 1].to_string())),
+        _ => text.parse::<i64>().ok().map(Value::from),
+    }
+}
+
+fn negate(condition: &Condition) -> Condition {
+    let operator = match condition.operator {
+        ComparisonOperator::Equals => ComparisonOperator::NotEquals,
+        ComparisonOperator::NotEquals => ComparisonOperator::Equals,
+        ComparisonOperator::GreaterThan => ComparisonOperator::LessEqual,
+        ComparisonOperator::LessEqual => ComparisonOperator::GreaterThan,
+        ComparisonOperator::LessThan => ComparisonOperator::GreaterEqual,
+        ComparisonOperator::GreaterEqual => ComparisonOperator::LessThan,
+        ComparisonOperator::Has => ComparisonOperator::NotHas,
+        ComparisonOperator::NotHas => ComparisonOperator::Has,
+        ComparisonOperator::Contains => ComparisonOperator::NotContains,
+        ComparisonOperator::NotContains => ComparisonOperator::Contains,
+    };
+    Condition { operator, ..condition.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::PathAnalysis;
+
+    const SOURCE: &str = r#"*title The Dragon's Lair
+*author Ada
+*create gold 0
+*create has_sword false
+*create strength 10
+
+You stand at the mouth of a cave.
+Smoke curls out of it.
+
+*choice
+  #Take the sword from the rack.
+    *set has_sword true
+    *goto cave
+  #Count your coins.
+    You have very few.
+    *set gold +5
+    *goto cave
+  *if (strength > 50) #Lift the boulder.
+    *finish
+
+*label cave
+The dragon wakes.
+*if has_sword
+  You draw your sword.
+  *goto victory
+*elseif (gold >= 5)
+  *set gold -5
+  You toss it your coins.
+*comment the dragon takes the bribe
+*page_break Run
+*fake_choice
+  #Run left
+  #Run right
+    *set strength %+10
+You escape into the daylight.
+*finish
+
+*label victory
+The dragon flees.
+*ending
+"#;
+
+    #[test]
+    fn test_import_choicescript() {
+        let import = ChoiceScriptImport::parse("dragon", SOURCE).unwrap();
+        let story = &import.story;
+        assert_eq!(story.validate(), Ok(()));
+        assert_eq!((story.title.as_str(), story.author.as_str()), ("The Dragon's Lair", "Ada"));
+        assert_eq!(PathAnalysis::of(story).reachable_scenes, story.scenes.len());
+        assert_eq!(import.warnings, ["line 34: fairmath is imported as plain arithmetic"]);
+
+        let startup = story.get_scene(STARTUP_SCENE).unwrap();
+        assert_eq!(startup.description, "You stand at the mouth of a cave. Smoke curls out of it.");
+        assert_eq!(startup.effects.as_ref().unwrap().len(), 3);
+        // Options without text go straight to their target, setting on the way
+        let take = &startup.choices[0];
+        assert_eq!(take.target_scene_id, "cave");
+        assert!(matches!(take.effects.as_deref(), Some([Effect { effect_type: EffectType::SetFlag, .. }])));
+        let count = story.get_scene(&startup.choices[1].target_scene_id).unwrap();
+        assert_eq!(count.description, "You have very few.");
+        assert_eq!(count.choices[0].target_scene_id, "cave");
+        let lift = &startup.choices[2];
+        assert_eq!(lift.target_scene_id, FINISH_SCENE);
+        assert!(matches!(lift.conditions.as_deref(), Some([Condition { condition_type: ConditionType::Stat, operator: ComparisonOperator::GreaterThan, .. }])));
+
+        // The *if chain, and carrying on when neither branch applies
+        let cave = story.get_scene("cave").unwrap();
+        let branches: Vec<_> = cave.choices.iter().map(|choice| choice.conditions.clone().unwrap_or_default()).collect();
+        assert_eq!(branches.len(), 3);
+        assert_eq!(branches[0].len(), 1);
+        assert!(matches!(branches[1].as_slice(), [
+            Condition { operator: ComparisonOperator::NotEquals, .. },
+            Condition { operator: ComparisonOperator::GreaterEqual, .. },
+        ]));
+        assert_eq!(branches[2].len(), 2);
+        let bribe = story.get_scene(&cave.choices[1].target_scene_id).unwrap();
+        assert_eq!(bribe.description, "You toss it your coins.");
+        assert_eq!(bribe.choices[0].target_scene_id, cave.choices[2].target_scene_id);
+
+        let victory = story.get_scene("victory").unwrap();
+        assert!(victory.is_ending());
+        assert_eq!(PathAnalysis::of(story).reachable_endings, 3);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let falls_through = "*choice\n  #Wait\n    You wait.\n";
+        assert!(ChoiceScriptImport::parse("x", falls_through).unwrap_err().to_string().contains("line 2"));
+        assert!(ChoiceScriptImport::parse("x", "*goto nowhere\n").is_err());
+        assert!(ChoiceScriptImport::parse("x", "*if (a = 1) or (b = 2)\n  *finish\n*else\n  *finish\n").is_err());
+
+        let import = ChoiceScriptImport::parse("x", "*label only\n*image dragon.png\nHi.\n").unwrap();
+        assert_eq!(import.story.starting_scene_id, "only");
+        assert!(import.story.get_scene("only").unwrap().is_ending());
+        assert_eq!(import.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::story::{Macro, Scene};
+use crate::story::macros::expand_scene;
+use crate::story::parse::parse_json;
+use crate::story::snippets::expand_snippets;
+use crate::utils::{GameError, GameResult};
+
+pub const CHUNK_MANIFEST_FILE: &str = "chunks.json";
+pub const CHUNKS_DIR: &str = "chunks";
+const DEFAULT_CACHED_CHUNKS: usize = 16;
+
+/// Maps chunk file names to the scene IDs they contain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    pub chunks: BTreeMap<String, Vec<String>>,
+}
+
+impl ChunkManifest {
+    pub fn scene_count(&self) -> usize {
+        self.chunks.values().map(|ids| ids.len()).sum()
+    }
+}
+
+type Chunk = Arc<HashMap<String, Arc<Scene>>>;
+
+/// Scenes stored in separate chunk files and read on first access. Only the
+/// most recently used chunks are kept in memory.
+#[derive(Debug)]
+pub struct ChunkedScenes {
+    chunks_directory: PathBuf,
+    chunk_of: HashMap<String, String>,
+    /// The story's snippets and macros, to expand in chunk scenes.
+    snippets: BTreeMap<String, String>,
+    macros: BTreeMap<String, Macro>,
+    cache: Mutex<ChunkCache>,
+}
+
+#[derive(Debug)]
+struct ChunkCache {
+    capacity: usize,
+    chunks: HashMap<String, Chunk>,
+    recent: VecDeque<String>,
+}
+
+impl ChunkedScenes {
+    pub fn new<P: AsRef<Path>>(story_directory: P, manifest: ChunkManifest) -> Self {
+        let chunk_of = manifest.chunks
+            .into_iter()
+            .flat_map(|(chunk, ids)| ids.into_iter().map(move |id| (id, chunk.clone())))
+            .collect();
+
+        Self {
+            chunks_directory: story_directory.as_ref().join(CHUNKS_DIR),
+            chunk_of,
+            snippets: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            cache: Mutex::new(ChunkCache::new(DEFAULT_CACHED_CHUNKS)),
+        }
+    }
+
+    pub fn with_cache_capacity(self, capacity: usize) -> Self {
+        self.cache.lock().unwrap().capacity = capacity.max(1);
+        self
+    }
+
+    pub fn with_snippets(mut self, snippets: BTreeMap<String, String>) -> Self {
+        self.snippets = snippets;
+        self
+    }
+
+    pub fn with_macros(mut self, macros: BTreeMap<String, Macro>) -> Self {
+        self.macros = macros;
+        self
+    }
+
+    pub fn contains(&self, scene_id: &str) -> bool {
+        self.chunk_of.contains_key(scene_id)
+    }
+
+    pub fn scene_count(&self) -> usize {
+        self.chunk_of.len()
+    }
+
+    pub fn scene_ids(&self) -> impl Iterator<Item = &str> {
+        self.chunk_of.keys().map(String::as_str)
+    }
+
+    pub fn cached_chunk_count(&self) -> usize {
+        self.cache.lock().unwrap().chunks.len()
+    }
+
+    pub fn get(&self, scene_id: &str) -> GameResult<Option<Arc<Scene>>> {
+        let Some(chunk_name) = self.chunk_of.get(scene_id) else {
+            return Ok(None);
+        };
+
+        let cached = self.cache.lock().unwrap().get(chunk_name);
+        let chunk = match cached {
+            Some(chunk) => chunk,
+            None => {
+                // Read outside the lock; a concurrent miss just loads the chunk twice
+                let chunk = self.load_chunk(chunk_name)?;
+                self.cache.lock().unwrap().insert(chunk_name.clone(), Arc::clone(&chunk));
+                chunk
+            }
+        };
+
+        Ok(chunk.get(scene_id).cloned())
+    }
+
+    /// Reads every chunk file, bypassing the cache, for a full validation
+    /// pass. Returns the scenes read along with chunks that can't be read and
+    /// scenes missing from, or not listed for, the chunk they're in.
+    pub fn load_all(&self) -> (Vec<Scene>, Vec<String>) {
+        let mut listed: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (scene_id, chunk_name) in &self.chunk_of {
+            listed.entry(chunk_name.as_str()).or_default().push(scene_id.as_str());
+        }
+
+        let mut all_scenes = Vec::new();
+        let mut errors = Vec::new();
+        for (chunk_name, scene_ids) in listed {
+            let scenes = match self.read_chunk(chunk_name) {
+                Ok(scenes) => scenes,
+                Err(e) => {
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+
+            let mut seen = HashSet::new();
+            for scene in &scenes {
+                if !seen.insert(scene.id.as_str()) {
+                    errors.push(format!("Chunk '{}': Duplicate scene ID: '{}'", chunk_name, scene.id));
+                } else if self.chunk_of.get(&scene.id).is_none_or(|listed_in| listed_in != chunk_name) {
+                    errors.push(format!("Chunk '{}': Scene '{}' is not listed for this chunk in {}", chunk_name, scene.id, CHUNK_MANIFEST_FILE));
+                }
+            }
+            for scene_id in scene_ids.into_iter().filter(|scene_id| !seen.contains(scene_id)) {
+                errors.push(format!("Chunk '{}': Scene '{}' is listed in {} but missing from the chunk", chunk_name, scene_id, CHUNK_MANIFEST_FILE));
+            }
+            all_scenes.extend(scenes);
+        }
+
+        (all_scenes, errors)
+    }
+
+    fn load_chunk(&self, chunk_name: &str) -> GameResult<Chunk> {
+        Ok(Arc::new(
+            self.read_chunk(chunk_name)?
+                .into_iter()
+                .map(|scene| (scene.id.clone(), Arc::new(scene)))
+                .collect(),
+        ))
+    }
+
+    fn read_chunk(&self, chunk_name: &str) -> GameResult<Vec<Scene>> {
+        let path = self.chunks_directory.join(chunk_name);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| GameError::story(format!("Failed to read story chunk {:?}: {}", path, e)))?;
+
+        let mut scenes: Vec<Scene> = parse_json(&content)
+            .map_err(|e| GameError::story(format!("Failed to parse story chunk {:?}: {}", path, e)))?;
+        for scene in &mut scenes {
+            scene.description = expand_snippets(&scene.description, &self.snippets);
+            expand_scene(scene, &self.macros);
+        }
+
+        Ok(scenes)
+    }
+}
+
+impl ChunkCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            chunks: HashMap::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, name: &str) -> Option<Chunk> {
+        let chunk = self.chunks.get(name).cloned()?;
+        self.touch(name);
+        Some(chunk)
+    }
+
+    fn insert(&mut self, name: String, chunk: Chunk) {
+        self.chunks.insert(name.clone(), chunk);
+        self.touch(&name);
+
+        while self.recent.len() > self.capacity {
+            if let Some(evicted) = self.recent.pop_back() {
+                self.chunks.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, name: &str) {
+        if let Some(pos) = self.recent.iter().position(|n| n == name) {
+            self.recent.remove(pos);
+        }
+        self.recent.push_front(name.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_chunk(dir: &Path, name: &str, ids: &[&str]) {
+        let scenes: Vec<Scene> = ids.iter().map(|id| Scene::new(*id, *id, "Scene")).collect();
+        std::fs::write(dir.join(CHUNKS_DIR).join(name), serde_json::to_string(&scenes).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_chunks_load_on_demand_with_lru_eviction() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(CHUNKS_DIR)).unwrap();
+        write_chunk(temp_dir.path(), "a.json", &["a1", "a2"]);
+        write_chunk(temp_dir.path(), "b.json", &["b1"]);
+
+        let mut manifest = ChunkManifest::default();
+        manifest.chunks.insert("a.json".to_string(), vec!["a1".to_string(), "a2".to_string()]);
+        manifest.chunks.insert("b.json".to_string(), vec!["b1".to_string()]);
+
+        let scenes = ChunkedScenes::new(temp_dir.path(), manifest).with_cache_capacity(1);
+        assert_eq!(scenes.scene_count(), 3);
+        assert_eq!(scenes.cached_chunk_count(), 0);
+
+        assert_eq!(scenes.get("a2").unwrap().unwrap().id, "a2");
+        assert_eq!(scenes.cached_chunk_count(), 1);
+
+        assert_eq!(scenes.get("b1").unwrap().unwrap().id, "b1");
+        assert_eq!(scenes.cached_chunk_count(), 1);
+
+        assert!(scenes.get("missing").unwrap().is_none());
+    }
+}
@@ -1,11 +1,20 @@
 use serde::{Deserialize, Serialize};
+use crate::utils::title_case;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Condition {
     pub condition_type: ConditionType,
+    /// Unused by `All`, `Any` and `Not`.
+    #[serde(default)]
     pub key: String,
+    /// Optional for `Macro` conditions, which have no comparison of their own.
+    #[serde(default)]
     pub operator: ComparisonOperator,
+    #[serde(default)]
     pub value: serde_json::Value,
+    /// The conditions combined by `All`, `Any` and `Not`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,11 +24,23 @@ pub enum ConditionType {
     Inventory,
     SceneVisited,
     Level,
+    /// Whether character `key` is the active protagonist.
+    ActiveCharacter,
     Custom,
+    /// Stands for the conditions of the story's macro `key`; loaders expand
+    /// it.
+    Macro,
+    /// Holds if all of `conditions` hold.
+    All,
+    /// Holds if at least one of `conditions` holds.
+    Any,
+    /// Holds unless all of `conditions` hold; with one condition, its opposite.
+    Not,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum ComparisonOperator {
+    #[default]
     Equals,
     NotEquals,
     GreaterThan,
@@ -44,9 +65,58 @@ impl Condition {
             key,
             operator,
             value,
+            conditions: Vec::new(),
+        }
+    }
+
+    fn group(condition_type: ConditionType, conditions: Vec<Condition>) -> Self {
+        Self {
+            conditions,
+            ..Self::new(condition_type, String::new(), ComparisonOperator::Equals, serde_json::Value::Null)
         }
     }
 
+    pub fn all(conditions: Vec<Condition>) -> Self {
+        Self::group(ConditionType::All, conditions)
+    }
+
+    /// E.g. "has sword OR strength >= 15":
+    /// `Condition::any(vec![Condition::has_item("sword", 1), Condition::stat_greater_equal("strength", 15)])`.
+    pub fn any(conditions: Vec<Condition>) -> Self {
+        Self::group(ConditionType::Any, conditions)
+    }
+
+    pub fn negate(condition: Condition) -> Self {
+        Self::group(ConditionType::Not, vec![condition])
+    }
+
+    pub fn is_group(&self) -> bool {
+        matches!(self.condition_type, ConditionType::All | ConditionType::Any | ConditionType::Not)
+    }
+
+    /// The conditions in `conditions` that aren't groups, including those
+    /// nested in groups.
+    pub fn leaves(conditions: &[Condition]) -> Vec<&Condition> {
+        conditions.iter()
+            .flat_map(|condition| match condition.is_group() {
+                true => Self::leaves(&condition.conditions),
+                false => vec![condition],
+            })
+            .collect()
+    }
+
+    /// Errors for groups among `conditions` of `owner` that have no
+    /// conditions to combine.
+    pub fn group_errors(owner: &str, conditions: &[Condition]) -> Vec<String> {
+        conditions.iter()
+            .filter(|condition| condition.is_group())
+            .flat_map(|group| match group.conditions.is_empty() {
+                true => vec![format!("{}: {:?} condition group has no conditions", owner, group.condition_type)],
+                false => Self::group_errors(owner, &group.conditions),
+            })
+            .collect()
+    }
+
     // Convenience constructors
     pub fn flag_equals<S: Into<String>>(key: S, value: bool) -> Self {
         Self::new(
@@ -102,11 +172,73 @@ impl Condition {
         )
     }
 
+    pub fn active_character<S: Into<String>>(character_id: S) -> Self {
+        Self::new(
+            ConditionType::ActiveCharacter,
+            character_id.into(),
+            ComparisonOperator::Equals,
+            serde_json::Value::Bool(true),
+        )
+    }
+
+    /// Player-facing text for what the condition asks of the player, like
+    /// "Requires Strength 15" or "Requires: Rusty Key". `item_name` names the
+    /// item of an inventory condition. Conditions that would give away
+    /// story secrets (flags, visited scenes) have no description.
+    pub fn describe_requirement(&self, item_name: Option<&str>) -> Option<String> {
+        self.requirement(item_name).map(|(prefix, what)| format!("{} {}", prefix, what))
+    }
+
+    /// `describe_requirement` for an alternative listed after the first, so
+    /// "Requires" isn't repeated: "Strength 15" rather than "Requires Strength 15".
+    pub fn describe_alternative(&self, item_name: Option<&str>) -> Option<String> {
+        self.requirement(item_name).map(|(prefix, what)| match prefix {
+            REQUIRES | REQUIRES_ITEM => what,
+            _ => format!("{} {}", prefix, what),
+        })
+    }
+
+    fn requirement(&self, item_name: Option<&str>) -> Option<(&'static str, String)> {
+        let number = self.value.as_i64();
+        match (&self.condition_type, &self.operator, number) {
+            (ConditionType::Stat, operator, Some(value)) => {
+                let stat = title_case(&self.key);
+                match operator {
+                    ComparisonOperator::GreaterEqual | ComparisonOperator::Equals => Some((REQUIRES, format!("{} {}", stat, value))),
+                    ComparisonOperator::GreaterThan => Some((REQUIRES, format!("{} {}", stat, value + 1))),
+                    ComparisonOperator::LessThan => Some((REQUIRES, format!("{} below {}", stat, value))),
+                    ComparisonOperator::LessEqual => Some((REQUIRES, format!("{} {} or less", stat, value))),
+                    _ => None,
+                }
+            }
+            (ConditionType::Level, ComparisonOperator::GreaterEqual, Some(value)) => Some((REQUIRES, format!("Level {}", value))),
+            (ConditionType::Level, ComparisonOperator::GreaterThan, Some(value)) => Some((REQUIRES, format!("Level {}", value + 1))),
+            (ConditionType::Inventory, operator, Some(quantity)) => {
+                let item = item_name.map(str::to_string).unwrap_or_else(|| title_case(&self.key));
+                match operator {
+                    ComparisonOperator::GreaterEqual | ComparisonOperator::Has if quantity <= 1 => Some((REQUIRES_ITEM, item)),
+                    ComparisonOperator::GreaterEqual | ComparisonOperator::Has => Some((REQUIRES_ITEM, format!("{} × {}", quantity, item))),
+                    ComparisonOperator::GreaterThan => Some((REQUIRES_ITEM, format!("{} × {}", quantity + 1, item))),
+                    ComparisonOperator::LessThan | ComparisonOperator::NotHas => Some(("Can't carry", item)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     pub fn custom<S: Into<String>>(key: S, operator: ComparisonOperator, value: serde_json::Value) -> Self {
         Self::new(ConditionType::Custom, key.into(), operator, value)
     }
+
+    pub fn macro_conditions<S: Into<String>>(name: S) -> Self {
+        Self::new(ConditionType::Macro, name.into(), ComparisonOperator::Equals, serde_json::Value::Null)
+    }
 }
 
+const REQUIRES: &str = "Requires";
+const REQUIRES_ITEM: &str = "Requires:";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,6 +251,42 @@ mod tests {
         assert!(matches!(condition.operator, ComparisonOperator::Equals));
     }
 
+    #[test]
+    fn test_describe_requirement() {
+        assert_eq!(
+            Condition::stat_greater_equal("strength", 15).describe_requirement(None).as_deref(),
+            Some("Requires Strength 15")
+        );
+        assert_eq!(
+            Condition::stat_greater_than("max_health", 99).describe_requirement(None).as_deref(),
+            Some("Requires Max Health 100")
+        );
+        assert_eq!(
+            Condition::has_item("rusty_key", 1).describe_requirement(Some("Rusty Key")).as_deref(),
+            Some("Requires: Rusty Key")
+        );
+        assert_eq!(Condition::level_at_least(3).describe_requirement(None).as_deref(), Some("Requires Level 3"));
+        assert_eq!(Condition::level_at_least(3).describe_alternative(None).as_deref(), Some("Level 3"));
+        assert_eq!(Condition::flag_equals("met_king", true).describe_requirement(None), None);
+    }
+
+    #[test]
+    fn test_condition_groups() {
+        let condition: Condition = serde_json::from_value(serde_json::json!({
+            "condition_type": "Any",
+            "conditions": [
+                {"condition_type": "Inventory", "key": "sword", "operator": "GreaterEqual", "value": 1},
+                {"condition_type": "Not", "conditions": [
+                    {"condition_type": "Stat", "key": "strength", "operator": "LessThan", "value": 15}
+                ]}
+            ]
+        })).unwrap();
+        assert!(condition.is_group());
+        let keys: Vec<&str> = Condition::leaves(std::slice::from_ref(&condition)).iter().map(|leaf| leaf.key.as_str()).collect();
+        assert_eq!(keys, ["sword", "strength"]);
+        assert_eq!(Condition::group_errors("Choice 'fight'", &[Condition::any(vec![Condition::all(Vec::new())])]), ["Choice 'fight': All condition group has no conditions"]);
+    }
+
     #[test]
     fn test_stat_condition() {
         let condition = Condition::stat_greater_than("strength", 15);
@@ -0,0 +1,318 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use crate::story::{Choice, Scene, Story};
+use crate::story::metadata::EDITOR_NAMESPACE;
+
+/// Semantic difference between two versions of a story, keyed by scene and
+/// choice IDs rather than JSON layout.
+#[derive(Debug, Clone, Default)]
+pub struct StoryDiff {
+    pub story_changes: Vec<FieldChange>,
+    pub added_scenes: Vec<String>,
+    pub removed_scenes: Vec<String>,
+    pub changed_scenes: Vec<SceneDiff>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneDiff {
+    pub scene_id: String,
+    pub changes: Vec<FieldChange>,
+    pub added_choices: Vec<Choice>,
+    pub removed_choices: Vec<String>,
+    pub changed_choices: Vec<ChoiceDiff>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChoiceDiff {
+    pub choice_id: String,
+    pub changes: Vec<FieldChange>,
+}
+
+impl StoryDiff {
+    pub fn between(old: &Story, new: &Story) -> Self {
+        let mut story_changes = Vec::new();
+        compare(&mut story_changes, "id", &old.id, &new.id);
+        compare(&mut story_changes, "title", &old.title, &new.title);
+        compare(&mut story_changes, "description", &old.description, &new.description);
+        compare(&mut story_changes, "author", &old.author, &new.author);
+        compare(&mut story_changes, "version", &old.version, &new.version);
+        compare(&mut story_changes, "license", &old.license, &new.license);
+        compare(&mut story_changes, "intro", &old.intro, &new.intro);
+        compare(&mut story_changes, "credits", &old.credits, &new.credits);
+        compare(&mut story_changes, "starting_scene_id", &old.starting_scene_id, &new.starting_scene_id);
+        compare(&mut story_changes, "resume_fallback_scene", &old.resume_fallback_scene, &new.resume_fallback_scene);
+        compare(&mut story_changes, "currency", &old.currency, &new.currency);
+        compare(&mut story_changes, "leveling", &old.leveling, &new.leveling);
+        compare(&mut story_changes, "derived_stats", &old.derived_stats, &new.derived_stats);
+        compare(&mut story_changes, "characters", &old.characters, &new.characters);
+        compare(&mut story_changes, "items", &old.items, &new.items);
+        compare(&mut story_changes, "initial_player_stats", &old.initial_player_stats, &new.initial_player_stats);
+        compare(&mut story_changes, "metadata", &story_metadata(&old.metadata), &story_metadata(&new.metadata));
+
+        let old_scenes = all_scenes(old);
+        let new_scenes = all_scenes(new);
+
+        let removed_scenes = old_scenes.keys()
+            .filter(|id| !new_scenes.contains_key(*id))
+            .cloned()
+            .collect();
+
+        let mut added_scenes = Vec::new();
+        let mut changed_scenes = Vec::new();
+        for (id, new_scene) in &new_scenes {
+            match old_scenes.get(id) {
+                Some(old_scene) => {
+                    let scene_diff = SceneDiff::between(old_scene, new_scene);
+                    if !scene_diff.is_empty() {
+                        changed_scenes.push(scene_diff);
+                    }
+                }
+                None => added_scenes.push(id.clone()),
+            }
+        }
+
+        Self {
+            story_changes,
+            added_scenes,
+            removed_scenes,
+            changed_scenes,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.story_changes.is_empty()
+            && self.added_scenes.is_empty()
+            && self.removed_scenes.is_empty()
+            && self.changed_scenes.is_empty()
+    }
+}
+
+impl SceneDiff {
+    pub fn between(old: &Scene, new: &Scene) -> Self {
+        let mut changes = Vec::new();
+        compare(&mut changes, "title", &old.title, &new.title);
+        compare(&mut changes, "description", &old.description, &new.description);
+        compare(&mut changes, "conditions", &old.conditions, &new.conditions);
+        compare(&mut changes, "effects", &old.effects, &new.effects);
+        compare(&mut changes, "is_ending", &old.is_ending(), &new.is_ending());
+        compare(&mut changes, "ending", &old.ending, &new.ending);
+        compare(&mut changes, "background_music", &old.background_music, &new.background_music);
+        compare(&mut changes, "image", &old.image, &new.image);
+        compare(&mut changes, "auto_advance", &old.auto_advance, &new.auto_advance);
+        compare(&mut changes, "shuffle_choices", &old.shuffle_choices, &new.shuffle_choices);
+        compare(&mut changes, "metadata", &story_metadata(&old.metadata), &story_metadata(&new.metadata));
+
+        let removed_choices = old.choices.iter()
+            .filter(|choice| !new.choices.iter().any(|c| c.id == choice.id))
+            .map(|choice| choice.id.clone())
+            .collect();
+
+        let mut added_choices = Vec::new();
+        let mut changed_choices = Vec::new();
+        for new_choice in &new.choices {
+            match old.choices.iter().find(|c| c.id == new_choice.id) {
+                Some(old_choice) => {
+                    let choice_diff = ChoiceDiff::between(old_choice, new_choice);
+                    if !choice_diff.changes.is_empty() {
+                        changed_choices.push(choice_diff);
+                    }
+                }
+                None => added_choices.push(new_choice.clone()),
+            }
+        }
+
+        Self {
+            scene_id: new.id.clone(),
+            changes,
+            added_choices,
+            removed_choices,
+            changed_choices,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.added_choices.is_empty()
+            && self.removed_choices.is_empty()
+            && self.changed_choices.is_empty()
+    }
+}
+
+impl ChoiceDiff {
+    pub fn between(old: &Choice, new: &Choice) -> Self {
+        let mut changes = Vec::new();
+        compare(&mut changes, "text", &old.text, &new.text);
+        compare(&mut changes, "target_scene_id", &old.target_scene_id, &new.target_scene_id);
+        compare(&mut changes, "conditions", &old.conditions, &new.conditions);
+        compare(&mut changes, "effects", &old.effects, &new.effects);
+        compare(&mut changes, "disabled", &old.disabled, &new.disabled);
+        compare(&mut changes, "disabled_reason", &old.disabled_reason, &new.disabled_reason);
+        compare(&mut changes, "hint", &old.hint, &new.hint);
+        compare(&mut changes, "group", &old.group, &new.group);
+        compare(&mut changes, "order", &old.order, &new.order);
+        compare(&mut changes, "metadata", &story_metadata(&old.metadata), &story_metadata(&new.metadata));
+
+        Self {
+            choice_id: new.id.clone(),
+            changes,
+        }
+    }
+}
+
+impl fmt::Display for StoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+
+        for change in &self.story_changes {
+            writeln!(f, "~ story {}", change)?;
+        }
+        for scene_id in &self.removed_scenes {
+            writeln!(f, "- scene {}", scene_id)?;
+        }
+        for scene_id in &self.added_scenes {
+            writeln!(f, "+ scene {}", scene_id)?;
+        }
+        for scene_diff in &self.changed_scenes {
+            writeln!(f, "~ scene {}", scene_diff.scene_id)?;
+            for change in &scene_diff.changes {
+                writeln!(f, "    ~ {}", change)?;
+            }
+            for choice_id in &scene_diff.removed_choices {
+                writeln!(f, "    - choice {}", choice_id)?;
+            }
+            for choice in &scene_diff.added_choices {
+                writeln!(f, "    + choice {} -> {}: {:?}", choice.id, choice.target_scene_id, choice.text)?;
+            }
+            for choice_diff in &scene_diff.changed_choices {
+                writeln!(f, "    ~ choice {}", choice_diff.choice_id)?;
+                for change in &choice_diff.changes {
+                    writeln!(f, "        ~ {}", change)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+/// Records a change if the two values serialize differently. Values are shown
+/// as compact JSON so conditions and effects stay readable on one line.
+fn compare<T: Serialize>(changes: &mut Vec<FieldChange>, field: &'static str, old: &T, new: &T) {
+    let old = to_compact_json(old);
+    let new = to_compact_json(new);
+    if old != new {
+        changes.push(FieldChange { field, old, new });
+    }
+}
+
+/// Metadata without the `editor.` keys, which only record layout, sorted so
+/// equal maps compare equal.
+fn story_metadata(metadata: &Option<HashMap<String, Value>>) -> BTreeMap<&String, &Value> {
+    metadata.iter()
+        .flatten()
+        .filter(|(key, _)| !key.starts_with(EDITOR_NAMESPACE))
+        .collect()
+}
+
+fn to_compact_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|e| format!("<unserializable: {}>", e))
+}
+
+/// Every scene of the story by ID, including ones stored in chunks.
+fn all_scenes(story: &Story) -> BTreeMap<String, Arc<Scene>> {
+    let mut scenes: BTreeMap<String, Arc<Scene>> = story.scenes.iter()
+        .map(|scene| (scene.id.clone(), Arc::clone(scene)))
+        .collect();
+
+    if let Some(chunked_scenes) = story.chunked_scenes() {
+        for scene_id in chunked_scenes.scene_ids() {
+            if let Some(scene) = story.get_shared_scene(scene_id) {
+                scenes.insert(scene_id.to_string(), scene);
+            }
+        }
+    }
+
+    scenes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Condition, EditorPosition, HasMetadata};
+
+    fn base_story() -> Story {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "You wake up");
+        start.add_choice(Choice::new("go", "Go on", "end"));
+        start.add_choice(Choice::new("wait", "Wait", "start"));
+        story.add_scene(start);
+        story.add_scene(Scene::new("end", "End", "The end"));
+        story.add_scene(Scene::new("old", "Old", "Cut content"));
+        story
+    }
+
+    #[test]
+    fn test_identical_stories() {
+        let diff = StoryDiff::between(&base_story(), &base_story());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences\n");
+    }
+
+    #[test]
+    fn test_story_diff() {
+        let old = base_story();
+
+        let mut new = Story::new("test", "Test Story", "start", PlayerStats::default());
+        new.version = "1.1.0".to_string();
+        let mut start = Scene::new("start", "Start", "You wake up in a cave");
+        start.add_choice(
+            Choice::new("go", "Go on", "end").with_conditions(vec![Condition::has_item("torch", 1)])
+        );
+        start.add_choice(Choice::new("dig", "Dig", "tunnel"));
+        new.add_scene(start);
+        let mut end = Scene::new("end", "End", "The end");
+        // Moving a node in an editor isn't a change to the story
+        end.set_editor_position(Some(EditorPosition { x: 10.0, y: 20.0 }));
+        new.add_scene(end);
+        new.add_scene(Scene::new("tunnel", "Tunnel", "Dark"));
+
+        let diff = StoryDiff::between(&old, &new);
+        assert_eq!(diff.story_changes.len(), 1);
+        assert_eq!(diff.story_changes[0].field, "version");
+        assert_eq!(diff.added_scenes, vec!["tunnel"]);
+        assert_eq!(diff.removed_scenes, vec!["old"]);
+        assert_eq!(diff.changed_scenes.len(), 1);
+
+        let scene_diff = &diff.changed_scenes[0];
+        assert_eq!(scene_diff.scene_id, "start");
+        assert_eq!(scene_diff.changes[0].field, "description");
+        assert_eq!(scene_diff.removed_choices, vec!["wait"]);
+        assert_eq!(scene_diff.added_choices[0].id, "dig");
+        assert_eq!(scene_diff.changed_choices[0].choice_id, "go");
+        assert_eq!(scene_diff.changed_choices[0].changes[0].field, "conditions");
+
+        let output = diff.to_string();
+        assert!(output.contains("+ scene tunnel"));
+        assert!(output.contains("- scene old"));
+        assert!(output.contains("    ~ choice go"));
+    }
+}
@@ -5,6 +5,8 @@ use crate::core::InventoryItem;
 pub struct Effect {
     pub effect_type: EffectType,
     pub key: String,
+    /// Optional for `Macro` effects.
+    #[serde(default)]
     pub value: serde_json::Value,
     pub operation: Option<EffectOperation>,
 }
@@ -16,7 +18,17 @@ pub enum EffectType {
     AddItem,
     RemoveItem,
     ModifyHealth,
+    /// Uses up one of the consumable `key`, applying its properties.
+    UseItem,
+    /// Changes the durability of item `key`; subtracting wears it down,
+    /// adding repairs it.
+    ModifyDurability,
+    /// Makes character `key` the active protagonist; `"player"` switches
+    /// back to the player's own character.
+    SwitchCharacter,
     Custom,
+    /// Stands for the effects of the story's macro `key`; loaders expand it.
+    Macro,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +64,17 @@ impl Effect {
         )
     }
 
+    /// Adds `amount` (negative to spend) to a numeric flag, such as the
+    /// story's currency.
+    pub fn add_to_flag<S: Into<String>>(key: S, amount: i64) -> Self {
+        Self::new(
+            EffectType::SetFlag,
+            key.into(),
+            serde_json::Value::Number(serde_json::Number::from(amount)),
+            Some(EffectOperation::Add),
+        )
+    }
+
     pub fn modify_stat<S: Into<String>>(key: S, value: i32, operation: EffectOperation) -> Self {
         Self::new(
             EffectType::ModifyStat,
@@ -83,6 +106,16 @@ impl Effect {
         Self::modify_stat("experience", value, EffectOperation::Add)
     }
 
+    /// Adds `quantity` of an item from the story's catalog.
+    pub fn add_catalog_item<S: Into<String>>(item_id: S, quantity: i32) -> Self {
+        Self::new(
+            EffectType::AddItem,
+            item_id.into(),
+            serde_json::Value::Number(serde_json::Number::from(quantity)),
+            None,
+        )
+    }
+
     pub fn add_item_effect(item: InventoryItem, quantity: Option<i32>) -> Self {
         let mut item_data = serde_json::to_value(item).unwrap();
         if let Some(qty) = quantity {
@@ -100,28 +133,69 @@ impl Effect {
     }
 
     pub fn remove_item_effect<S: Into<String>>(item_id: S, quantity: i32) -> Self {
+        let item_id = item_id.into();
         let remove_data = serde_json::json!({
-            "id": item_id.into(),
+            "id": item_id,
             "quantity": quantity
         });
 
         Self::new(
             EffectType::RemoveItem,
-            item_id.into(),
+            item_id,
             remove_data,
             None,
         )
     }
 
+    pub fn use_item<S: Into<String>>(item_id: S) -> Self {
+        Self::new(
+            EffectType::UseItem,
+            item_id.into(),
+            serde_json::Value::Null,
+            None,
+        )
+    }
+
+    pub fn switch_character<S: Into<String>>(character_id: S) -> Self {
+        Self::new(
+            EffectType::SwitchCharacter,
+            character_id.into(),
+            serde_json::Value::Null,
+            None,
+        )
+    }
+
+    pub fn wear_item<S: Into<String>>(item_id: S, amount: i32) -> Self {
+        Self::new(
+            EffectType::ModifyDurability,
+            item_id.into(),
+            serde_json::Value::Number(serde_json::Number::from(amount)),
+            Some(EffectOperation::Subtract),
+        )
+    }
+
+    pub fn repair_item<S: Into<String>>(item_id: S, amount: i32) -> Self {
+        Self::new(
+            EffectType::ModifyDurability,
+            item_id.into(),
+            serde_json::Value::Number(serde_json::Number::from(amount)),
+            Some(EffectOperation::Add),
+        )
+    }
+
     pub fn custom<S: Into<String>>(key: S, value: serde_json::Value, operation: Option<EffectOperation>) -> Self {
         Self::new(EffectType::Custom, key.into(), value, operation)
     }
+
+    pub fn macro_effects<S: Into<String>>(name: S) -> Self {
+        Self::new(EffectType::Macro, name.into(), serde_json::Value::Null, None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::{ItemType, PlayerStats};
+    use crate::core::ItemType;
     use std::collections::HashMap;
 
     #[test]
@@ -167,5 +241,9 @@ mod tests {
 
         let remove_effect = Effect::remove_item_effect("sword", 1);
         assert!(matches!(remove_effect.effect_type, EffectType::RemoveItem));
+
+        let use_effect = Effect::use_item("potion");
+        assert!(matches!(use_effect.effect_type, EffectType::UseItem));
+        assert_eq!(use_effect.key, "potion");
     }
 }
\ No newline at end of file
@@ -0,0 +1,439 @@
+//! Best-effort conversion of a story to Twee 3 (for SugarCube 2) or Ink, so
+//! it can move to other tools. Flags, stats, conditions and effects become
+//! variables, inventory becomes `item_<id>` counts, and whatever the other
+//! format has no equivalent for is left out with a warning.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use serde_json::Value;
+use crate::core::SeededRng;
+use crate::story::{Choice, ComparisonOperator, Condition, ConditionType, Effect, EffectOperation, EffectType, HasMetadata, Scene, Story};
+
+/// SugarCube release the Twee output declares in `StoryData`.
+const SUGARCUBE_VERSION: &str = "2.37.3";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Twee,
+    Ink,
+}
+
+/// A story in another format.
+#[derive(Debug, Clone)]
+pub struct StoryExport {
+    pub text: String,
+    /// Features that were left out or only approximated.
+    pub warnings: Vec<String>,
+}
+
+impl StoryExport {
+    pub fn new(story: &Story, format: ExportFormat) -> Self {
+        let mut exporter = Exporter { story, format, variables: BTreeMap::new(), warnings: Vec::new() };
+        exporter.story_warnings();
+        let text = match format {
+            ExportFormat::Twee => exporter.twee(),
+            ExportFormat::Ink => exporter.ink(),
+        };
+        Self { text, warnings: exporter.warnings }
+    }
+}
+
+struct Exporter<'a> {
+    story: &'a Story,
+    format: ExportFormat,
+    /// Variable names and their starting values.
+    variables: BTreeMap<String, Value>,
+    warnings: Vec<String>,
+}
+
+impl Exporter<'_> {
+    fn story_warnings(&mut self) {
+        let story = self.story;
+        if !story.characters.is_empty() {
+            self.warnings.push("Characters are left out; everything happens to the player".to_string());
+        }
+        if !story.derived_stats.is_empty() {
+            self.warnings.push("Derived stats are left out".to_string());
+        }
+        if story.leveling.is_some() {
+            self.warnings.push("The leveling curve is left out; experience won't raise the level".to_string());
+        }
+        if !story.items.is_empty() {
+            self.warnings.push("Items are exported as item_<id> counts, without names or properties".to_string());
+        }
+    }
+
+    fn twee(&mut self) -> String {
+        let story = self.story;
+        let mut passages = String::new();
+        for scene in &story.scenes {
+            let mut header = format!(":: {}", scene.id);
+            let tags: Vec<String> = scene.tags().iter().map(|tag| tag.replace(char::is_whitespace, "-")).collect();
+            if !tags.is_empty() {
+                let _ = write!(header, " [{}]", tags.join(" "));
+            }
+            if let Some(position) = scene.editor_position() {
+                let _ = write!(header, " {{\"position\":\"{},{}\"}}", position.x, position.y);
+            }
+            let body = self.twee_passage(scene);
+            let _ = write!(passages, "{}\n{}\n\n", header, body);
+        }
+
+        let mut bytes = [0u8; 16];
+        let mut rng = SeededRng::for_key(0, &story.id);
+        bytes[..8].copy_from_slice(&rng.next_u64().to_le_bytes());
+        bytes[8..].copy_from_slice(&rng.next_u64().to_le_bytes());
+        let ifid = uuid::Builder::from_random_bytes(bytes).into_uuid().to_string().to_uppercase();
+        let data = serde_json::json!({
+            "ifid": ifid,
+            "format": "SugarCube",
+            "format-version": SUGARCUBE_VERSION,
+            "start": story.starting_scene_id,
+        });
+
+        let mut text = format!(":: StoryTitle\n{}\n\n", story.title);
+        let _ = write!(text, ":: StoryData\n{}\n\n", serde_json::to_string_pretty(&data).unwrap_or_default());
+        if !self.variables.is_empty() {
+            text.push_str(":: StoryInit\n");
+            for (name, value) in &self.variables {
+                let _ = writeln!(text, "<<set ${} to {}>>", name, value);
+            }
+            text.push('\n');
+        }
+        text.push_str(&passages);
+        text.trim_end().to_string() + "\n"
+    }
+
+    fn twee_passage(&mut self, scene: &Scene) -> String {
+        let mut lines = Vec::new();
+        self.scene_warnings(scene);
+        for effect in scene.effects.iter().flatten() {
+            if let Some(statement) = self.effect(&format!("Scene '{}'", scene.id), effect) {
+                lines.push(format!("<<set {}>>", statement));
+            }
+        }
+        lines.push(format!("!!{}", twee_text(&scene.title)));
+        lines.push(twee_text(&scene.description));
+        if !scene.choices.is_empty() {
+            lines.push(String::new());
+        }
+
+        for choice in &scene.choices {
+            let location = format!("Scene '{}', choice '{}'", scene.id, choice.id);
+            let (conditions, effects) = self.choice_parts(&location, choice);
+            let plain = !choice.text.contains([']', '|']) && !choice.text.contains("->") && !choice.text.contains("<-");
+            let link = match (plain, effects.is_empty()) {
+                (true, true) => format!("[[{}->{}]]", choice.text, choice.target_scene_id),
+                (true, false) => format!("[[{}->{}][{}]]", choice.text, choice.target_scene_id, effects.join("; ")),
+                (false, _) => format!(
+                    "<<link {} {}>>{}<</link>>",
+                    Value::from(choice.text.as_str()),
+                    Value::from(choice.target_scene_id.as_str()),
+                    effects.iter().map(|effect| format!("<<set {}>>", effect)).collect::<String>()
+                ),
+            };
+            lines.push(match conditions.is_empty() {
+                true => link,
+                false => format!("<<if {}>>{}<</if>>", conditions.join(" and "), link),
+            });
+        }
+        lines.join("\n")
+    }
+
+    fn ink(&mut self) -> String {
+        let story = self.story;
+        let mut knots = String::new();
+        for scene in &story.scenes {
+            let _ = write!(knots, "=== {} ===\n{}\n\n", identifier(&scene.id), self.ink_knot(scene));
+        }
+
+        let mut text = format!("// {} {}\n", story.title, story.byline());
+        for (name, value) in &self.variables {
+            let _ = writeln!(text, "VAR {} = {}", name, value);
+        }
+        let _ = write!(text, "\n-> {}\n\n{}", identifier(&story.starting_scene_id), knots);
+        text.trim_end().to_string() + "\n"
+    }
+
+    fn ink_knot(&mut self, scene: &Scene) -> String {
+        let mut lines = Vec::new();
+        self.scene_warnings(scene);
+        lines.push(format!("# title: {}", ink_text(&scene.title)));
+        for effect in scene.effects.iter().flatten() {
+            if let Some(statement) = self.effect(&format!("Scene '{}'", scene.id), effect) {
+                lines.push(format!("~ {}", statement));
+            }
+        }
+        lines.extend(scene.description.lines().map(ink_text));
+
+        // Sticky choices, since scenes can be revisited
+        for choice in &scene.choices {
+            let location = format!("Scene '{}', choice '{}'", scene.id, choice.id);
+            let (conditions, effects) = self.choice_parts(&location, choice);
+            let conditions: String = conditions.iter().map(|condition| format!("{{{}}} ", condition)).collect();
+            lines.push(format!("+ {}[{}]", conditions, ink_text(&choice.text)));
+            lines.extend(effects.iter().map(|effect| format!("  ~ {}", effect)));
+            lines.push(format!("  -> {}", identifier(&choice.target_scene_id)));
+        }
+        if scene.choices.is_empty() {
+            if !scene.is_ending() {
+                self.warnings.push(format!("Scene '{}' has no choices and isn't an ending; it ends the story", scene.id));
+            }
+            lines.push("-> END".to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn scene_warnings(&mut self, scene: &Scene) {
+        let mut left_out = Vec::new();
+        if scene.conditions.is_some() {
+            left_out.push("entry conditions");
+        }
+        if scene.auto_advance.is_some() {
+            left_out.push("auto-advance");
+        }
+        if scene.shuffle_choices {
+            left_out.push("choice shuffling");
+        }
+        if scene.image.is_some() || scene.background_music.is_some() {
+            left_out.push("image and music");
+        }
+        if !left_out.is_empty() {
+            self.warnings.push(format!("Scene '{}': {} left out", scene.id, left_out.join(", ")));
+        }
+    }
+
+    /// Expressions for a choice's conditions and statements for its effects.
+    fn choice_parts(&mut self, location: &str, choice: &Choice) -> (Vec<String>, Vec<String>) {
+        if choice.disabled.unwrap_or(false) {
+            self.warnings.push(format!("{}: always disabled, exported as available", location));
+        }
+        let conditions = choice.conditions.iter().flatten()
+            .filter_map(|condition| self.condition(location, condition))
+            .collect();
+        let effects = choice.effects.iter().flatten()
+            .filter_map(|effect| self.effect(location, effect))
+            .collect();
+        (conditions, effects)
+    }
+
+    /// Groups become parenthesized `and`/`or` expressions, which both formats
+    /// share. Conditions left out of a group are warned about like others.
+    fn condition_group(&mut self, location: &str, group: &Condition) -> Option<String> {
+        let parts: Vec<String> = group.conditions.iter()
+            .filter_map(|condition| self.condition(location, condition))
+            .collect();
+        if parts.is_empty() {
+            return None;
+        }
+        Some(match group.condition_type {
+            ConditionType::Any => format!("({})", parts.join(" or ")),
+            ConditionType::Not => format!("not ({})", parts.join(" and ")),
+            _ => format!("({})", parts.join(" and ")),
+        })
+    }
+
+    fn condition(&mut self, location: &str, condition: &Condition) -> Option<String> {
+        let twee = self.format == ExportFormat::Twee;
+        let variable = match condition.condition_type {
+            ConditionType::Flag => self.flag(&condition.key, &condition.value),
+            ConditionType::Stat | ConditionType::Level => {
+                let key = match condition.condition_type {
+                    ConditionType::Level => "level",
+                    _ => condition.key.as_str(),
+                };
+                match self.stat(key) {
+                    Some(variable) => variable,
+                    None => {
+                        self.warnings.push(format!("{}: condition on derived stat '{}' left out", location, key));
+                        return None;
+                    }
+                }
+            }
+            ConditionType::Inventory => self.variable(&format!("item_{}", condition.key), Value::from(0)),
+            ConditionType::SceneVisited => {
+                let visited = matches!(
+                    (&condition.operator, &condition.value),
+                    (ComparisonOperator::Equals, Value::Bool(true)) | (ComparisonOperator::NotEquals, Value::Bool(false))
+                );
+                let expression = match twee {
+                    true => format!("visited({})", Value::from(condition.key.as_str())),
+                    false => identifier(&condition.key),
+                };
+                return Some(if visited { expression } else { format!("not {}", expression) });
+            }
+            ConditionType::All | ConditionType::Any | ConditionType::Not => return self.condition_group(location, condition),
+            ConditionType::ActiveCharacter | ConditionType::Custom | ConditionType::Macro => {
+                self.warnings.push(format!("{}: {:?} condition '{}' left out", location, condition.condition_type, condition.key));
+                return None;
+            }
+        };
+        let variable = if twee { format!("${}", variable) } else { variable };
+
+        let value = &condition.value;
+        Some(match (&condition.operator, twee) {
+            (ComparisonOperator::Equals, true) => format!("{} is {}", variable, value),
+            (ComparisonOperator::NotEquals, true) => format!("{} isnot {}", variable, value),
+            (ComparisonOperator::Equals, false) => format!("{} == {}", variable, value),
+            (ComparisonOperator::NotEquals, false) => format!("{} != {}", variable, value),
+            (ComparisonOperator::GreaterThan, _) => format!("{} > {}", variable, value),
+            (ComparisonOperator::LessThan, _) => format!("{} < {}", variable, value),
+            (ComparisonOperator::GreaterEqual, _) => format!("{} >= {}", variable, value),
+            (ComparisonOperator::LessEqual, _) => format!("{} <= {}", variable, value),
+            (ComparisonOperator::Has, true) => format!("def {}", variable),
+            (ComparisonOperator::NotHas, true) => format!("ndef {}", variable),
+            (ComparisonOperator::Contains, true) => format!("{}.includes({})", variable, value),
+            (ComparisonOperator::NotContains, true) => format!("not {}.includes({})", variable, value),
+            (ComparisonOperator::Contains, false) => format!("{} ? {}", variable, value),
+            (ComparisonOperator::NotContains, false) => format!("{} !? {}", variable, value),
+            (ComparisonOperator::Has | ComparisonOperator::NotHas, false) => {
+                self.warnings.push(format!("{}: Ink variables are always set; condition on '{}' left out", location, condition.key));
+                return None;
+            }
+        })
+    }
+
+    fn effect(&mut self, location: &str, effect: &Effect) -> Option<String> {
+        let (variable, amount) = match effect.effect_type {
+            EffectType::SetFlag => (self.flag(&effect.key, &effect.value), effect.value.clone()),
+            EffectType::ModifyStat | EffectType::ModifyHealth => match self.stat(&effect.key) {
+                Some(variable) => (variable, effect.value.clone()),
+                None => {
+                    self.warnings.push(format!("{}: effect on unknown stat '{}' left out", location, effect.key));
+                    return None;
+                }
+            },
+            EffectType::AddItem | EffectType::RemoveItem => {
+                // Catalog items are added by ID; other effects carry the item
+                let item_id = effect.value.get("id").and_then(Value::as_str).unwrap_or(&effect.key);
+                let quantity = effect.value.as_i64()
+                    .or_else(|| effect.value.get("quantity").and_then(Value::as_i64))
+                    .unwrap_or(1);
+                let variable = self.variable(&format!("item_{}", item_id), Value::from(0));
+                let sign = if matches!(effect.effect_type, EffectType::AddItem) { "+" } else { "-" };
+                return Some(self.assignment(&variable, &format!("{}=", sign), &Value::from(quantity)));
+            }
+            EffectType::UseItem | EffectType::ModifyDurability | EffectType::SwitchCharacter | EffectType::Custom | EffectType::Macro => {
+                self.warnings.push(format!("{}: {:?} effect on '{}' left out", location, effect.effect_type, effect.key));
+                return None;
+            }
+        };
+
+        let operator = match effect.operation {
+            None | Some(EffectOperation::Set) => "=",
+            Some(EffectOperation::Add) => "+=",
+            Some(EffectOperation::Subtract) => "-=",
+            Some(EffectOperation::Multiply) => "*=",
+        };
+        Some(self.assignment(&variable, operator, &amount))
+    }
+
+    /// `variable operator value` in the output's syntax; Ink has no `*=`.
+    fn assignment(&self, variable: &str, operator: &str, value: &Value) -> String {
+        match (self.format, operator) {
+            (ExportFormat::Twee, "=") => format!("${} to {}", variable, value),
+            (ExportFormat::Twee, _) => format!("${} {} {}", variable, operator, value),
+            (ExportFormat::Ink, "*=") => format!("{} = {} * {}", variable, variable, value),
+            (ExportFormat::Ink, _) => format!("{} {} {}", variable, operator, value),
+        }
+    }
+
+    /// A flag's variable, starting at the zero value of the type of `value`.
+    fn flag(&mut self, key: &str, value: &Value) -> String {
+        let initial = match value {
+            Value::Number(_) => Value::from(0),
+            Value::String(_) => Value::from(""),
+            _ => Value::Bool(false),
+        };
+        self.variable(key, initial)
+    }
+
+    /// A player stat's variable, starting at the story's initial value.
+    fn stat(&mut self, key: &str) -> Option<String> {
+        let initial = self.story.initial_player_stats.get(key)?;
+        Some(self.variable(key, Value::from(initial)))
+    }
+
+    fn variable(&mut self, key: &str, initial: Value) -> String {
+        let name = identifier(key);
+        self.variables.entry(name.clone()).or_insert(initial);
+        name
+    }
+}
+
+/// `id` as a variable or knot name: letters, digits and underscores, not
+/// starting with a digit.
+fn identifier(id: &str) -> String {
+    let name: String = id.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() => name,
+        _ => format!("_{}", name),
+    }
+}
+
+/// `text` kept from being read as SugarCube markup.
+fn twee_text(text: &str) -> String {
+    const MARKUP: [&str; 8] = ["[[", "<<", "$", "//", "''", "@@", "{{{", "/*"];
+    match MARKUP.iter().any(|markup| text.contains(markup)) {
+        true => format!("\"\"\"{}\"\"\"", text),
+        false => text.to_string(),
+    }
+}
+
+/// A line of `text` kept from being read as Ink syntax.
+fn ink_text(line: &str) -> String {
+    let mut escaped = String::new();
+    for (index, c) in line.chars().enumerate() {
+        let line_start = index == 0 && matches!(c, '*' | '+' | '-' | '=' | '~');
+        if line_start || matches!(c, '\\' | '{' | '}' | '[' | ']' | '|' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.replace("//", "/\\/").replace("->", "-\\>").replace("<>", "<\\>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::story::{EditorPosition, StoryTemplate};
+
+    #[test]
+    fn test_export_twee() {
+        let mut story = StoryTemplate::new("tale", "A Tale", "Ada").with_item().with_ending().build();
+        Arc::make_mut(&mut story.scenes[1]).set_editor_position(Some(EditorPosition { x: 120.0, y: 40.0 }));
+        let export = StoryExport::new(&story, ExportFormat::Twee);
+
+        assert!(export.text.starts_with(":: StoryTitle\nA Tale\n\n:: StoryData\n"));
+        assert!(export.text.contains("\"start\": \"start\""));
+        assert!(export.text.contains(":: StoryInit\n<<set $item_lantern to 0>>\n"));
+        assert!(export.text.contains(":: explore {\"position\":\"120,40\"}\n"));
+        assert!(export.text.contains("<<if $item_lantern < 1>>[[Pick up the lantern->explore][$item_lantern += 1]]<</if>>"));
+        assert!(export.text.contains("[[Explore the area->explore]]"));
+        assert_eq!(export.warnings.len(), 1);
+        // The IFID is the same every time
+        assert_eq!(export.text, StoryExport::new(&story, ExportFormat::Twee).text);
+    }
+
+    #[test]
+    fn test_export_ink() {
+        let mut story = StoryTemplate::new("tale", "A Tale", "Ada").build();
+        let rest = Arc::make_mut(&mut story.scenes[2]);
+        rest.description = "You rest {briefly}.\n- Then you stand.".to_string();
+        rest.choices[0] = rest.choices[0].clone()
+            .with_conditions(vec![
+                Condition::stat_greater_than("strength", 3),
+                Condition::custom("luck", ComparisonOperator::Equals, Value::from(1)),
+                Condition::any(vec![Condition::scene_visited("start"), Condition::negate(Condition::scene_visited("rest"))]),
+            ])
+            .with_effects(vec![Effect::add_to_flag("gold", 5), Effect::modify_stat("strength", 2, EffectOperation::Multiply)]);
+        let export = StoryExport::new(&story, ExportFormat::Ink);
+
+        assert!(export.text.contains("VAR gold = 0\nVAR strength = 10\n\n-> start\n"));
+        assert!(export.text.contains("You rest \\{briefly\\}.\n\\- Then you stand.\n"));
+        assert!(export.text.contains("+ {strength > 3} {(start or not (rest))} [Continue your journey]\n  ~ gold += 5\n  ~ strength = strength * 2\n  -> start"));
+        assert_eq!(export.warnings, ["Scene 'rest', choice 'continue': Custom condition 'luck' left out"]);
+        assert_eq!(identifier("3-doors"), "_3_doors");
+    }
+}
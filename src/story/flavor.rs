@@ -0,0 +1,171 @@
+//! Flavor text from an external writer, usually an LLM, that rewrites scene
+//! descriptions as they are shown. Stories opt in with
+//! `metadata.ai_narrator`, e.g. `{"enabled": true, "style": "terse, gothic"}`,
+//! and single scenes opt out with `"ai_narrator": false` in their metadata.
+//! The HTTP writer talks to a chat completions endpoint configured under
+//! `[ai_narrator]` and needs the `ai-narrator` feature.
+
+use serde::Deserialize;
+use serde_json::Value;
+use crate::config::AiNarratorConfig;
+use crate::story::{Scene, Story};
+use crate::utils::GameResult;
+
+/// Story and scene metadata key.
+pub const AI_NARRATOR_KEY: &str = "ai_narrator";
+
+/// A story's opt-in, from `metadata.ai_narrator`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FlavorSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Tone to write in, passed on to the writer.
+    #[serde(default)]
+    pub style: Option<String>,
+}
+
+impl FlavorSettings {
+    pub fn of(story: &Story) -> Self {
+        story.metadata.as_ref()
+            .and_then(|metadata| metadata.get(AI_NARRATOR_KEY))
+            .and_then(|settings| serde_json::from_value(settings.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether `scene` of an opted-in story should be rewritten.
+    pub fn applies_to(&self, scene: &Scene) -> bool {
+        let opted_out = scene.metadata.as_ref()
+            .and_then(|metadata| metadata.get(AI_NARRATOR_KEY))
+            .is_some_and(|value| value == &Value::Bool(false));
+        self.enabled && !opted_out && !scene.description.trim().is_empty()
+    }
+}
+
+/// What a writer works from.
+#[derive(Debug, Clone)]
+pub struct FlavorRequest<'a> {
+    pub story_title: &'a str,
+    pub scene: &'a Scene,
+    pub player_name: &'a str,
+    pub style: Option<&'a str>,
+}
+
+impl FlavorRequest<'_> {
+    /// Instructions for a chat model; the scene description is sent as the
+    /// user message.
+    pub fn instructions(&self) -> String {
+        let style = self.style.map(|style| format!(" Write in this style: {}.", style)).unwrap_or_default();
+        format!(
+            "You narrate the interactive story \"{}\" for a player called {}. Rewrite the description of the scene \"{}\" \
+             with more atmosphere.{} Keep every fact, name, item and exit, and don't add events, choices or outcomes. \
+             Reply with the description only.",
+            self.story_title, self.player_name, self.scene.title, style
+        )
+    }
+}
+
+/// Rewrites scene descriptions. Implement it to plug in another service.
+pub trait FlavorWriter: Send + Sync {
+    /// The description to show instead of the authored one, or `None` to keep it.
+    fn rewrite(&self, request: &FlavorRequest) -> GameResult<Option<String>>;
+}
+
+/// Body of a chat completions request for `request`.
+pub fn chat_request(model: &str, request: &FlavorRequest) -> Value {
+    serde_json::json!({
+        "model": model,
+        "messages": [
+            {"role": "system", "content": request.instructions()},
+            {"role": "user", "content": request.scene.description}
+        ]
+    })
+}
+
+/// The text of the first choice of a chat completions reply.
+pub fn chat_reply_text(reply: &Value) -> Option<String> {
+    let text = reply["choices"][0]["message"]["content"].as_str()?.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Writer for an OpenAI-compatible chat completions endpoint, such as a
+/// local Ollama server.
+#[cfg(feature = "ai-narrator")]
+pub struct HttpFlavorWriter {
+    agent: ureq::Agent,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[cfg(feature = "ai-narrator")]
+impl HttpFlavorWriter {
+    pub fn new(endpoint: String, model: String, api_key: Option<String>, timeout: std::time::Duration) -> Self {
+        let agent = ureq::Agent::config_builder().timeout_global(Some(timeout)).build().into();
+        Self { agent, endpoint, model, api_key }
+    }
+}
+
+#[cfg(feature = "ai-narrator")]
+impl FlavorWriter for HttpFlavorWriter {
+    fn rewrite(&self, request: &FlavorRequest) -> GameResult<Option<String>> {
+        use crate::utils::GameError;
+
+        let mut call = self.agent.post(&self.endpoint);
+        if let Some(api_key) = &self.api_key {
+            call = call.header("Authorization", &format!("Bearer {}", api_key));
+        }
+        let reply: Value = call.send_json(chat_request(&self.model, request))
+            .map_err(|e| GameError::plugin(format!("AI narrator request failed: {}", e)))?
+            .body_mut()
+            .read_json()
+            .map_err(|e| GameError::plugin(format!("Invalid AI narrator reply: {}", e)))?;
+        Ok(chat_reply_text(&reply))
+    }
+}
+
+/// The writer `config` describes, or `None` if no endpoint is set.
+pub fn configured_writer(config: &AiNarratorConfig) -> Option<Box<dyn FlavorWriter>> {
+    let endpoint = config.endpoint.as_ref()?;
+
+    #[cfg(feature = "ai-narrator")]
+    {
+        let api_key = config.api_key_env.as_ref().and_then(|name| std::env::var(name).ok());
+        let timeout = std::time::Duration::from_secs(config.timeout_seconds);
+        Some(Box::new(HttpFlavorWriter::new(endpoint.clone(), config.model.clone(), api_key, timeout)))
+    }
+
+    #[cfg(not(feature = "ai-narrator"))]
+    {
+        tracing::warn!("AI narrator endpoint {} is set but the game was built without the ai-narrator feature", endpoint);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use crate::core::PlayerStats;
+    use serde_json::json;
+
+    #[test]
+    fn test_flavor_settings() {
+        let mut story = Story::new("crypt", "The Crypt", "gate", PlayerStats::default());
+        let mut scene = Scene::new("gate", "Gate", "An iron gate.");
+        assert!(!FlavorSettings::of(&story).applies_to(&scene));
+
+        story.metadata = Some(HashMap::from([(AI_NARRATOR_KEY.to_string(), json!({"enabled": true, "style": "gothic"}))]));
+        let settings = FlavorSettings::of(&story);
+        assert!(settings.applies_to(&scene));
+        scene.metadata = Some(HashMap::from([(AI_NARRATOR_KEY.to_string(), json!(false))]));
+        assert!(!settings.applies_to(&scene));
+
+        let request = FlavorRequest { story_title: &story.title, scene: &scene, player_name: "Ada", style: settings.style.as_deref() };
+        let body = chat_request("llama3.2", &request);
+        assert_eq!(body["messages"][1]["content"], "An iron gate.");
+        assert!(body["messages"][0]["content"].as_str().unwrap().contains("Write in this style: gothic."));
+
+        assert_eq!(chat_reply_text(&json!({"choices": [{"message": {"content": " A rusted gate. "}}]})).as_deref(), Some("A rusted gate."));
+        assert_eq!(chat_reply_text(&json!({"error": "overloaded"})), None);
+    }
+}
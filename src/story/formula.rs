@@ -0,0 +1,213 @@
+//! Integer arithmetic formulas for derived stats, e.g. `strength * 5` or
+//! `(armor_bonus + level) / 2`.
+
+use std::iter::Peekable;
+use std::str::Chars;
+use crate::utils::{GameError, GameResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Formula {
+    Number(i64),
+    Variable(String),
+    Negate(Box<Formula>),
+    Binary(Box<Formula>, BinaryOp, Box<Formula>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+/// Deepest nesting of parentheses and negations a formula may have, so
+/// evaluating it can't exhaust the stack.
+pub const MAX_FORMULA_DEPTH: usize = 32;
+/// Longest formula, which bounds chains like `a + a + ...` the same way.
+pub const MAX_FORMULA_LENGTH: usize = 1024;
+
+impl Formula {
+    pub fn parse(source: &str) -> GameResult<Self> {
+        if source.len() > MAX_FORMULA_LENGTH {
+            return Err(GameError::story(format!("Invalid formula: longer than {} characters", MAX_FORMULA_LENGTH)));
+        }
+        let mut parser = Parser { chars: source.chars().peekable(), depth: 0 };
+        let formula = parser.expression().and_then(|formula| {
+            parser.skip_whitespace();
+            match parser.chars.peek() {
+                None => Ok(formula),
+                Some(c) => Err(format!("unexpected '{}'", c)),
+            }
+        });
+        formula.map_err(|message| GameError::story(format!("Invalid formula '{}': {}", source, message)))
+    }
+
+    /// Names the formula refers to, in order of appearance.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables = Vec::new();
+        self.collect_variables(&mut variables);
+        variables
+    }
+
+    fn collect_variables<'a>(&'a self, variables: &mut Vec<&'a str>) {
+        match self {
+            Formula::Number(_) => {}
+            Formula::Variable(name) => variables.push(name),
+            Formula::Negate(inner) => inner.collect_variables(variables),
+            Formula::Binary(left, _, right) => {
+                left.collect_variables(variables);
+                right.collect_variables(variables);
+            }
+        }
+    }
+
+    /// Computes the formula, looking variables up with `resolve`. Division
+    /// rounds toward zero; dividing by zero or overflowing is an error.
+    pub fn evaluate<F>(&self, resolve: &F) -> GameResult<i64>
+    where
+        F: Fn(&str) -> GameResult<i64>,
+    {
+        match self {
+            Formula::Number(value) => Ok(*value),
+            Formula::Variable(name) => resolve(name),
+            Formula::Negate(inner) => inner.evaluate(resolve)?
+                .checked_neg()
+                .ok_or_else(|| GameError::story("Formula overflowed")),
+            Formula::Binary(left, op, right) => {
+                let left = left.evaluate(resolve)?;
+                let right = right.evaluate(resolve)?;
+                let result = match op {
+                    BinaryOp::Add => left.checked_add(right),
+                    BinaryOp::Subtract => left.checked_sub(right),
+                    BinaryOp::Multiply => left.checked_mul(right),
+                    BinaryOp::Divide if right == 0 => {
+                        return Err(GameError::story("Division by zero in formula"));
+                    }
+                    BinaryOp::Divide => left.checked_div(right),
+                };
+                result.ok_or_else(|| GameError::story("Formula overflowed"))
+            }
+        }
+    }
+}
+
+type ParseResult = Result<Formula, String>;
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn next_operator(&mut self, operators: &[(char, BinaryOp)]) -> Option<BinaryOp> {
+        self.skip_whitespace();
+        let &(_, op) = operators.iter().find(|(c, _)| self.chars.peek() == Some(c))?;
+        self.chars.next();
+        Some(op)
+    }
+
+    // expression := term (('+' | '-') term)*
+    fn expression(&mut self) -> ParseResult {
+        let mut formula = self.term()?;
+        while let Some(op) = self.next_operator(&[('+', BinaryOp::Add), ('-', BinaryOp::Subtract)]) {
+            formula = Formula::Binary(Box::new(formula), op, Box::new(self.term()?));
+        }
+        Ok(formula)
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> ParseResult {
+        let mut formula = self.factor()?;
+        while let Some(op) = self.next_operator(&[('*', BinaryOp::Multiply), ('/', BinaryOp::Divide)]) {
+            formula = Formula::Binary(Box::new(formula), op, Box::new(self.factor()?));
+        }
+        Ok(formula)
+    }
+
+    // factor := number | name | '-' factor | '(' expression ')'
+    fn factor(&mut self) -> ParseResult {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some('-') => {
+                self.chars.next();
+                let inner = self.nested(Self::factor)?;
+                Ok(Formula::Negate(Box::new(inner)))
+            }
+            Some('(') => {
+                self.chars.next();
+                let formula = self.nested(Self::expression)?;
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some(')') => Ok(formula),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut digits = String::new();
+                while let Some(digit) = self.chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(digit);
+                }
+                digits.parse()
+                    .map(Formula::Number)
+                    .map_err(|_| format!("number too large: {}", digits))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                Ok(Formula::Variable(name))
+            }
+            Some(c) => Err(format!("unexpected '{}'", c)),
+            None => Err("unexpected end".to_string()),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> ParseResult) -> ParseResult {
+        if self.depth >= MAX_FORMULA_DEPTH {
+            return Err(format!("nested more than {} deep", MAX_FORMULA_DEPTH));
+        }
+        self.depth += 1;
+        let formula = parse(self);
+        self.depth -= 1;
+        formula
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(source: &str) -> GameResult<i64> {
+        Formula::parse(source)?.evaluate(&|name| match name {
+            "strength" => Ok(12),
+            "level" => Ok(3),
+            _ => Err(GameError::story(format!("unknown {}", name))),
+        })
+    }
+
+    #[test]
+    fn test_formula_evaluation() {
+        assert_eq!(evaluate("strength * 5").unwrap(), 60);
+        assert_eq!(evaluate("2 + 3 * 4").unwrap(), 14);
+        assert_eq!(evaluate("(2 + 3) * 4").unwrap(), 20);
+        assert_eq!(evaluate("-level + 10 / 3").unwrap(), 0);
+        assert!(evaluate("strength / (level - 3)").is_err());
+        assert!(evaluate("luck").is_err());
+    }
+
+    #[test]
+    fn test_formula_parse_errors() {
+        assert!(Formula::parse("strength *").is_err());
+        assert!(Formula::parse("(level + 1").is_err());
+        assert!(Formula::parse("level 2").is_err());
+        assert!(Formula::parse(&format!("{}1{}", "(".repeat(MAX_FORMULA_DEPTH), ")".repeat(MAX_FORMULA_DEPTH))).is_ok());
+        assert!(Formula::parse(&"-".repeat(MAX_FORMULA_DEPTH + 1)).unwrap_err().to_string().contains("nested"));
+        assert_eq!(Formula::parse("armor_bonus + level").unwrap().variables(), vec!["armor_bonus", "level"]);
+    }
+}
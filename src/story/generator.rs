@@ -0,0 +1,277 @@
+//! Generated stories for stress-testing the engine and roguelike
+//! experiments: open grids, mazes and random dungeons of any size. The same
+//! seed always gives the same story. `text-game generate` writes one out as
+//! story JSON.
+
+use std::collections::{BTreeSet, VecDeque};
+use crate::core::{PlayerStats, SeededRng};
+use crate::story::{Choice, ComparisonOperator, Condition, ConditionType, Currency, Effect, Scene, Story};
+use crate::utils::{GameError, GameResult};
+
+/// ID of the ending scene of every layout.
+pub const EXIT_SCENE: &str = "exit";
+
+const DIRECTIONS: [(&str, i64, i64); 4] = [("north", 0, -1), ("east", 1, 0), ("south", 0, 1), ("west", -1, 0)];
+const ADJECTIVES: [&str; 8] = ["Damp", "Narrow", "Vaulted", "Flooded", "Collapsed", "Silent", "Smoky", "Frozen"];
+const ROOMS: [&str; 8] = ["Cellar", "Crypt", "Hall", "Armory", "Shrine", "Library", "Barracks", "Cistern"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    /// Every cell opens onto its neighbours; the exit is off the far corner.
+    Grid { width: usize, height: usize },
+    /// A grid with passages carved so there is exactly one way between any
+    /// two cells.
+    Maze { width: usize, height: usize },
+    /// Rooms joined at random, with some loops, coins to pick up and traps.
+    /// The exit is off the room furthest from the entrance.
+    Dungeon { rooms: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct StoryGenerator {
+    layout: Layout,
+    seed: u64,
+    id: String,
+    title: String,
+}
+
+impl StoryGenerator {
+    pub fn new(layout: Layout) -> Self {
+        let (id, title) = match layout {
+            Layout::Grid { .. } => ("generated_grid", "Generated Grid"),
+            Layout::Maze { .. } => ("generated_maze", "Generated Maze"),
+            Layout::Dungeon { .. } => ("generated_dungeon", "Generated Dungeon"),
+        };
+        Self { layout, seed: 1, id: id.to_string(), title: title.to_string() }
+    }
+
+    pub fn grid(width: usize, height: usize) -> Self {
+        Self::new(Layout::Grid { width, height })
+    }
+
+    pub fn maze(width: usize, height: usize) -> Self {
+        Self::new(Layout::Maze { width, height })
+    }
+
+    pub fn dungeon(rooms: usize) -> Self {
+        Self::new(Layout::Dungeon { rooms })
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    pub fn with_title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn generate(&self) -> GameResult<Story> {
+        match self.layout {
+            Layout::Grid { width, height } => {
+                check_size(width * height)?;
+                Ok(self.grid_story(width, height, |_, _| true))
+            }
+            Layout::Maze { width, height } => {
+                check_size(width * height)?;
+                let passages = carve_maze(width, height, &mut SeededRng::new(self.seed));
+                Ok(self.grid_story(width, height, |a, b| passages.contains(&(a.min(b), a.max(b)))))
+            }
+            Layout::Dungeon { rooms } => {
+                check_size(rooms)?;
+                Ok(self.dungeon_story(rooms))
+            }
+        }
+    }
+
+    /// Cells named `cell_<x>_<y>`, starting top left with the exit off the
+    /// bottom right, with a choice for each direction `open` allows between
+    /// cell indices.
+    fn grid_story(&self, width: usize, height: usize, open: impl Fn(usize, usize) -> bool) -> Story {
+        let mut story = Story::new(self.id.clone(), self.title.clone(), cell_id(0, 0), PlayerStats::default());
+        story.description = format!("A {}×{} generated layout.", width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut scene = Scene::new(cell_id(x, y), format!("Cell ({}, {})", x, y), String::new());
+                for (direction, dx, dy) in DIRECTIONS {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    if open(y * width + x, ny * width + nx) {
+                        scene.add_choice(Choice::new(direction.to_string(), format!("Go {}", direction), cell_id(nx, ny)));
+                    }
+                }
+                let exits: Vec<&str> = scene.choices.iter().map(|choice| choice.id.as_str()).collect();
+                scene.description = format!("Passages lead {}.", exits.join(", "));
+                if (x, y) == (width - 1, height - 1) {
+                    scene.description.push_str(" Daylight shows through a door.");
+                    scene.add_choice(Choice::new("leave".to_string(), "Step out through the door".to_string(), EXIT_SCENE.to_string()));
+                }
+                story.add_scene(scene);
+            }
+        }
+        story.add_scene(exit_scene());
+        story
+    }
+
+    fn dungeon_story(&self, rooms: usize) -> Story {
+        let mut rng = SeededRng::new(self.seed);
+        let mut story = Story::new(self.id.clone(), self.title.clone(), room_id(0), PlayerStats::default());
+        story.description = format!("A dungeon of {} rooms.", rooms);
+        story.currency = Some(Currency::default());
+
+        // A random tree keeps every room reachable; extra passages add loops
+        let mut passages = BTreeSet::new();
+        for room in 1..rooms {
+            passages.insert((rng.below(room), room));
+        }
+        for _ in 0..rooms / 5 {
+            let (a, b) = (rng.below(rooms), rng.below(rooms));
+            if a != b {
+                passages.insert((a.min(b), a.max(b)));
+            }
+        }
+        let neighbours = |room: usize| passages.iter()
+            .filter_map(move |&(a, b)| if a == room { Some(b) } else if b == room { Some(a) } else { None });
+
+        let names: Vec<String> = (0..rooms)
+            .map(|room| match room {
+                0 => "Entrance".to_string(),
+                _ => format!("{} {}", ADJECTIVES[rng.below(ADJECTIVES.len())], ROOMS[rng.below(ROOMS.len())]),
+            })
+            .collect();
+        let exit_room = furthest_room(rooms, &neighbours);
+
+        for (room, name) in names.iter().enumerate() {
+            let mut scene = Scene::new(room_id(room), name.clone(), format!("You stand in the {}.", name.to_lowercase()));
+            if room > 0 && rng.below(4) == 0 {
+                let damage = 5 + rng.below(11) as i32;
+                scene.description.push_str(" A dart trap clicks as you enter.");
+                scene.effects = Some(vec![Effect::subtract_health(damage)]);
+            }
+            if room > 0 && rng.below(3) == 0 {
+                let coins = 1 + rng.below(20) as i64;
+                let looted = format!("looted_{}", room_id(room));
+                let condition = Condition::new(ConditionType::Flag, looted.clone(), ComparisonOperator::NotEquals, serde_json::json!(true));
+                scene.add_choice(Choice::new("loot".to_string(), format!("Pick up the coins ({})", coins), room_id(room))
+                    .with_conditions(vec![condition])
+                    .with_effects(vec![Effect::add_to_flag("gold", coins), Effect::set_flag(looted, true)]));
+            }
+            for neighbour in neighbours(room) {
+                scene.add_choice(Choice::new(room_id(neighbour), format!("Go to the {}", names[neighbour].to_lowercase()), room_id(neighbour)));
+            }
+            if room == exit_room {
+                scene.add_choice(Choice::new("climb".to_string(), "Climb the stairs out".to_string(), EXIT_SCENE.to_string()));
+            }
+            story.add_scene(scene);
+        }
+
+        story.add_scene(exit_scene());
+        story
+    }
+}
+
+fn check_size(cells: usize) -> GameResult<()> {
+    if cells < 2 {
+        return Err(GameError::story("A generated story needs at least two scenes"));
+    }
+    Ok(())
+}
+
+fn exit_scene() -> Scene {
+    let mut exit = Scene::new(EXIT_SCENE, "Daylight", "You made it out into the light.");
+    exit.is_ending = Some(true);
+    exit
+}
+
+fn cell_id(x: usize, y: usize) -> String {
+    format!("cell_{}_{}", x, y)
+}
+
+fn room_id(room: usize) -> String {
+    format!("room_{}", room)
+}
+
+/// Passages of a randomized depth-first maze, as pairs of cell indices
+/// with the lower first.
+fn carve_maze(width: usize, height: usize, rng: &mut SeededRng) -> BTreeSet<(usize, usize)> {
+    let mut passages = BTreeSet::new();
+    let mut visited = vec![false; width * height];
+    let mut stack = vec![0];
+    visited[0] = true;
+
+    while let Some(&cell) = stack.last() {
+        let (x, y) = (cell % width, cell / width);
+        let unvisited: Vec<usize> = DIRECTIONS.iter()
+            .map(|(_, dx, dy)| (x as i64 + dx, y as i64 + dy))
+            .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < width as i64 && ny < height as i64)
+            .map(|(nx, ny)| ny as usize * width + nx as usize)
+            .filter(|&next| !visited[next])
+            .collect();
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = unvisited[rng.below(unvisited.len())];
+        visited[next] = true;
+        passages.insert((cell.min(next), cell.max(next)));
+        stack.push(next);
+    }
+    passages
+}
+
+/// The room with the most passages between it and the entrance.
+fn furthest_room<I: Iterator<Item = usize>>(rooms: usize, neighbours: &impl Fn(usize) -> I) -> usize {
+    let mut distance = vec![None; rooms];
+    distance[0] = Some(0);
+    let mut queue = VecDeque::from([0]);
+    let mut furthest = 0;
+    while let Some(room) = queue.pop_front() {
+        furthest = room;
+        for next in neighbours(room) {
+            if distance[next].is_none() {
+                distance[next] = distance[room].map(|d: usize| d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    furthest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::PathAnalysis;
+
+    #[test]
+    fn test_generated_stories_are_playable() {
+        for generator in [StoryGenerator::grid(4, 3), StoryGenerator::maze(8, 8), StoryGenerator::dungeon(40)] {
+            let story = generator.with_seed(7).generate().unwrap();
+            assert_eq!(story.validate(), Ok(()));
+            let analysis = PathAnalysis::of(&story);
+            assert_eq!(analysis.reachable_endings, 1);
+            assert_eq!(analysis.reachable_scenes, story.scenes.len());
+        }
+    }
+
+    #[test]
+    fn test_maze_is_a_tree() {
+        let story = StoryGenerator::maze(6, 5).with_seed(3).generate().unwrap();
+        let choices: usize = story.scenes.iter().map(|scene| scene.choices.len()).sum();
+        // A choice each way through every passage, and the way out
+        assert_eq!(choices, 2 * (6 * 5 - 1) + 1);
+
+        let again = StoryGenerator::maze(6, 5).with_seed(3).generate().unwrap();
+        assert_eq!(serde_json::to_value(&story).unwrap(), serde_json::to_value(&again).unwrap());
+        assert!(StoryGenerator::dungeon(1).generate().is_err());
+    }
+}
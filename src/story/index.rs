@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::fs;
+use crate::story::StoryMetadata;
+use crate::utils::{GameError, GameResult};
+
+/// Story metadata remembered between runs, keyed by story path and
+/// invalidated when the file's modification time changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StoryIndex {
+    entries: HashMap<PathBuf, StoryIndexEntry>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoryIndexEntry {
+    modified: SystemTime,
+    metadata: StoryMetadata,
+}
+
+impl StoryIndex {
+    pub async fn load(path: &Path) -> GameResult<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to read story index: {}", e)))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| GameError::story(format!("Failed to parse story index: {}", e)))
+    }
+
+    pub async fn save(&mut self, path: &Path) -> GameResult<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| GameError::story(format!("Failed to create cache directory: {}", e)))?;
+        }
+
+        let json = serde_json::to_string(self)
+            .map_err(|e| GameError::story(format!("Failed to serialize story index: {}", e)))?;
+
+        fs::write(path, json)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to write story index: {}", e)))?;
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    pub fn get(&self, path: &Path, modified: SystemTime) -> Option<&StoryMetadata> {
+        self.entries
+            .get(path)
+            .filter(|entry| entry.modified == modified)
+            .map(|entry| &entry.metadata)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, modified: SystemTime, metadata: StoryMetadata) {
+        self.entries.insert(path, StoryIndexEntry { modified, metadata });
+        self.dirty = true;
+    }
+
+    /// Drops entries for stories that no longer exist.
+    pub fn retain_paths(&mut self, paths: &HashSet<PathBuf>) {
+        let before = self.entries.len();
+        self.entries.retain(|path, _| paths.contains(path));
+        if self.entries.len() != before {
+            self.dirty = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
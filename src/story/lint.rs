@@ -0,0 +1,276 @@
+//! Spelling and style checks over a story's text, for `text-game lint`.
+//! Stories can silence words with `metadata.lint.ignore`, e.g.
+//! `"lint": {"ignore": ["Eldoria", "glowcap"], "max_sentence_words": 50}`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use crate::story::Story;
+use crate::utils::{GameError, GameResult};
+
+pub const DEFAULT_MAX_SENTENCE_WORDS: usize = 40;
+
+/// Word lists tried when no dictionary is given.
+pub const SYSTEM_DICTIONARIES: [&str; 2] = ["/usr/share/dict/words", "/usr/share/dict/american-english"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintWarning {
+    /// E.g. "scene 'cave'" or "choice 'cave/enter'".
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Known words, lowercase.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Reads one word per line. Hunspell `.dic` files work too: the count on
+    /// the first line and the `/FLAGS` after each word are ignored.
+    pub fn parse(content: &str) -> Self {
+        let words = content.lines()
+            .filter_map(|line| line.split('/').next())
+            .map(|word| word.trim().to_lowercase())
+            .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+            .collect();
+        Self { words }
+    }
+
+    pub fn from_file(path: &Path) -> GameResult<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| GameError::story(format!("Failed to read dictionary {}: {}", path.display(), e)))?;
+        Ok(Self::parse(&content))
+    }
+
+    /// The first of `SYSTEM_DICTIONARIES` that exists.
+    pub fn system() -> Option<Self> {
+        SYSTEM_DICTIONARIES.iter()
+            .map(Path::new)
+            .find(|path| path.exists())
+            .and_then(|path| Self::from_file(path).ok())
+    }
+
+    /// Hyphenated words are known if each part is.
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase().replace('’', "'");
+        self.words.contains(&word)
+            || (word.contains('-') && word.split('-').all(|part| part.is_empty() || self.words.contains(part)))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Linter {
+    /// Without one, only words that look like misspellings of a word used
+    /// elsewhere in the story are reported.
+    dictionary: Option<Dictionary>,
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    pub fn lint(&self, story: &Story) -> Vec<LintWarning> {
+        let settings = story.metadata.as_ref().and_then(|metadata| metadata.get("lint"));
+        let ignored: HashSet<String> = settings
+            .and_then(|lint| lint.get("ignore"))
+            .and_then(|ignore| ignore.as_array())
+            .map(|words| words.iter().filter_map(|word| word.as_str()).map(str::to_lowercase).collect())
+            .unwrap_or_default();
+        let max_sentence_words = settings
+            .and_then(|lint| lint.get("max_sentence_words"))
+            .and_then(|max| max.as_u64())
+            .map_or(DEFAULT_MAX_SENTENCE_WORDS, |max| max as usize);
+
+        let mut warnings = Vec::new();
+        let mut titles: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let scenes = story.all_scenes();
+        let mut texts = Vec::new();
+
+        for scene in &scenes {
+            let location = format!("scene '{}'", scene.id);
+            if scene.description.trim().is_empty() {
+                warnings.push(LintWarning { location: location.clone(), message: "empty description".to_string() });
+            }
+            titles.entry(scene.title.trim()).or_default().push(&scene.id);
+            texts.push((location.clone(), scene.title.as_str()));
+            texts.push((location, scene.description.as_str()));
+
+            for choice in &scene.choices {
+                let location = format!("choice '{}/{}'", scene.id, choice.id);
+                if choice.text.trim().is_empty() {
+                    warnings.push(LintWarning { location: location.clone(), message: "empty text".to_string() });
+                }
+                texts.push((location.clone(), choice.text.as_str()));
+                if let Some(hint) = &choice.hint {
+                    texts.push((location, hint.as_str()));
+                }
+            }
+        }
+
+        for (title, scene_ids) in titles.iter().filter(|(title, ids)| ids.len() > 1 && !title.is_empty()) {
+            warnings.push(LintWarning {
+                location: format!("scene '{}'", scene_ids[0]),
+                message: format!("title '{}' is shared with {}", title, quoted(&scene_ids[1..])),
+            });
+        }
+
+        for (location, text) in &texts {
+            for sentence in sentences(text) {
+                let count = words(sentence).count();
+                if count > max_sentence_words {
+                    warnings.push(LintWarning {
+                        location: location.clone(),
+                        message: format!("{}-word sentence starting \"{}…\"", count, words(sentence).take(6).collect::<Vec<_>>().join(" ")),
+                    });
+                }
+            }
+        }
+
+        warnings.extend(self.spelling(&texts, &ignored));
+        warnings
+    }
+
+    /// Each unknown word once, at its first location.
+    fn spelling(&self, texts: &[(String, &str)], ignored: &HashSet<String>) -> Vec<LintWarning> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (_, text) in texts {
+            for word in words(text) {
+                *counts.entry(word.to_lowercase()).or_default() += 1;
+            }
+        }
+
+        let mut reported = HashSet::new();
+        let mut warnings = Vec::new();
+        for (location, text) in texts {
+            for word in words(text) {
+                let lower = word.to_lowercase();
+                if ignored.contains(&lower) || reported.contains(&lower) {
+                    continue;
+                }
+                let message = match &self.dictionary {
+                    Some(dictionary) if !dictionary.contains(&lower) => format!("unknown word '{}'", word),
+                    Some(_) => continue,
+                    None => match likely_intended(&lower, &counts) {
+                        Some(intended) => format!("'{}' may be a typo of '{}'", word, intended),
+                        None => continue,
+                    },
+                };
+                reported.insert(lower);
+                warnings.push(LintWarning { location: location.clone(), message });
+            }
+        }
+        warnings
+    }
+}
+
+fn quoted(scene_ids: &[&str]) -> String {
+    scene_ids.iter().map(|id| format!("'{}'", id)).collect::<Vec<_>>().join(", ")
+}
+
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.split(['.', '!', '?', '\n']).filter(|sentence| !sentence.trim().is_empty())
+}
+
+/// Alphabetic words, keeping inner apostrophes and hyphens ("don't", "half-elf").
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphabetic() || c == '\'' || c == '’' || c == '-'))
+        .map(|word| word.trim_matches(|c: char| !c.is_alphabetic()))
+        .filter(|word| !word.is_empty())
+}
+
+/// A word used at least three times that `word`, used once, looks like a
+/// typo of. Only the typos that rarely make another real word count:
+/// swapped letters ("gaurd") and doubled or undoubled ones ("ocurred").
+fn likely_intended<'a>(word: &str, counts: &'a HashMap<String, usize>) -> Option<&'a str> {
+    if counts.get(word) != Some(&1) || word.chars().count() < 5 {
+        return None;
+    }
+    counts.iter()
+        .filter(|(other, count)| **count >= 3 && is_typo_of(word, other))
+        .max_by_key(|(other, count)| (**count, std::cmp::Reverse(other.as_str())))
+        .map(|(other, _)| other.as_str())
+}
+
+/// Whether `typo` is `word` with two letters swapped or one doubled.
+pub(crate) fn is_typo_of(typo: &str, word: &str) -> bool {
+    let a: Vec<char> = typo.chars().collect();
+    let b: Vec<char> = word.chars().collect();
+    let (short, long) = if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+    if long.len() - short.len() > 1 || a == b {
+        return false;
+    }
+
+    let prefix = short.iter().zip(long.iter()).take_while(|(x, y)| x == y).count();
+    if short.len() == long.len() {
+        prefix + 1 < short.len()
+            && short[prefix] == long[prefix + 1]
+            && short[prefix + 1] == long[prefix]
+            && short[prefix + 2..] == long[prefix + 2..]
+    } else {
+        // The extra letter repeats the one before it
+        prefix > 0 && long[prefix] == long[prefix - 1] && short[prefix..] == long[prefix + 1..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Choice, Scene};
+
+    fn story() -> Story {
+        let mut story = Story::new("lint", "Lint", "gate", PlayerStats::default());
+        let mut gate = Scene::new("gate", "The Gate", "The guard watches the gate. The guard yawns. Eldoria lies beyond the guard.");
+        gate.add_choice(Choice::new("talk", "Talk to the gaurd", "hall"));
+        gate.add_choice(Choice::new("wait", "", "hall"));
+        story.add_scene(gate);
+        story.add_scene(Scene::new("hall", "The Gate", &"word ".repeat(45)));
+        story.add_scene(Scene::new("yard", "Yard", "  "));
+        story
+    }
+
+    #[test]
+    fn test_lint() {
+        let warnings: Vec<String> = Linter::new().lint(&story()).iter().map(ToString::to_string).collect();
+        assert!(warnings.contains(&"scene 'yard': empty description".to_string()));
+        assert!(warnings.contains(&"choice 'gate/wait': empty text".to_string()));
+        assert!(warnings.contains(&"scene 'gate': title 'The Gate' is shared with 'hall'".to_string()));
+        assert!(warnings.contains(&"scene 'hall': 45-word sentence starting \"word word word word word word…\"".to_string()));
+        assert!(warnings.contains(&"choice 'gate/talk': 'gaurd' may be a typo of 'guard'".to_string()));
+
+        let dictionary = Dictionary::parse("3\nthe/S\nguard\nwatches\nwaits");
+        assert!(dictionary.contains("The"));
+        assert!(dictionary.contains("guard-the"));
+        let mut story = story();
+        story.metadata = Some(HashMap::from([
+            ("lint".to_string(), serde_json::json!({"ignore": ["eldoria"], "max_sentence_words": 50})),
+        ]));
+        let warnings: Vec<String> = Linter::new().with_dictionary(dictionary).lint(&story).iter().map(ToString::to_string).collect();
+        assert!(warnings.contains(&"scene 'gate': unknown word 'Gate'".to_string()));
+        assert!(!warnings.iter().any(|warning| warning.contains("Eldoria") || warning.contains("sentence")));
+    }
+
+    #[test]
+    fn test_is_typo_of() {
+        assert!(is_typo_of("gaurd", "guard"));
+        assert!(is_typo_of("ocurred", "occurred"));
+        assert!(is_typo_of("torrch", "torch"));
+        assert!(!is_typo_of("guards", "guard"));
+        assert!(!is_typo_of("fate", "face"));
+        assert!(!is_typo_of("guard", "guard"));
+    }
+}
@@ -1,46 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::fs;
-use crate::story::Story;
+use crate::story::{Story, ChunkManifest, ChunkedScenes, PathAnalysis, StoryIndex, StoryOverlay, StoryTemplate};
+use crate::story::lint::is_typo_of;
+use crate::story::overlay::OVERLAYS_DIR;
+use crate::story::parse::{parse_json, parse_story};
+use crate::story::chunked::{CHUNK_MANIFEST_FILE, CHUNKS_DIR};
 use crate::utils::{GameError, GameResult};
-use tracing::{info, warn, error};
+use tracing::{info, warn};
 
 pub struct StoryLoader {
     stories_directory: PathBuf,
+    index_path: Option<PathBuf>,
+    validate_chunks: bool,
 }
 
 impl StoryLoader {
     pub fn new<P: AsRef<Path>>(stories_directory: P) -> Self {
         Self {
             stories_directory: stories_directory.as_ref().to_path_buf(),
+            index_path: None,
+            validate_chunks: false,
         }
     }
 
+    /// Remember story metadata in `index_path` so unchanged stories aren't
+    /// re-read by `list_available_stories`.
+    pub fn with_index_cache<P: AsRef<Path>>(mut self, index_path: P) -> Self {
+        self.index_path = Some(index_path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Read and validate every chunk of a chunked story when loading it,
+    /// rather than leaving hand-edited chunks to fail when first played.
+    pub fn with_chunk_validation(mut self, validate_chunks: bool) -> Self {
+        self.validate_chunks = validate_chunks;
+        self
+    }
+
     pub async fn load_story(&self, story_id: &str) -> GameResult<Story> {
-        let story_path = self.stories_directory.join(format!("{}.json", story_id));
-        
-        info!("Loading story from: {:?}", story_path);
+        self.load_story_file(story_id).await.map_err(|e| e.with_story(story_id))
+    }
+
+    async fn load_story_file(&self, story_id: &str) -> GameResult<Story> {
+        let story_path = self.story_path(story_id)?;
         
         if !story_path.exists() {
-            return Err(GameError::story(format!("Story file not found: {}", story_id)));
+            if self.chunked_story_path(story_id).exists() {
+                return self.read_chunked_story(story_id).await;
+            }
+            let similar = self.similar_story_id(story_id);
+            return Err(GameError::story(format!("Story file not found: {}", story_id))
+                .with_suggestion("story", similar.as_deref()));
         }
 
+        let mut story = Self::read_story_file(&story_path).await?;
+        self.apply_overlays(&mut story).await?;
+
+        // Validate the story
+        if let Err(errors) = story.validate() {
+            return Err(GameError::story_errors("Story validation failed", &errors));
+        }
+
+        info!("Successfully loaded story: {} ({})", story.title, story.id);
+        Ok(story)
+    }
+
+    /// Loads a story from an explicit path: either a story JSON file or the
+    /// directory of a chunked story. Single-file stories are only parsed, not
+    /// validated, so that work-in-progress stories can be inspected.
+    pub async fn load_story_from_path<P: AsRef<Path>>(path: P) -> GameResult<Story> {
+        let path = path.as_ref();
+
+        if path.is_dir() {
+            let story_id = path.file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| GameError::story(format!("Invalid story directory: {:?}", path)))?;
+            let parent = path.parent().unwrap_or_else(|| Path::new("."));
+            return StoryLoader::new(parent).read_chunked_story(story_id).await;
+        }
+
+        if !path.exists() {
+            return Err(GameError::story(format!("Story file not found: {:?}", path)));
+        }
+
+        let mut story = Self::read_story_file(path).await?;
+        story.rebuild_scene_index()
+            .map_err(|errors| GameError::story_errors("Story validation failed", &errors))?;
+        Ok(story)
+    }
+
+    async fn read_story_file(story_path: &Path) -> GameResult<Story> {
+        info!("Loading story from: {:?}", story_path);
+
+        let content = fs::read_to_string(story_path)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to read story file: {}", e)))?;
+
+        let mut story = parse_story(&content)
+            .map_err(|errors| GameError::story_errors("Failed to parse story JSON", &errors))?;
+
+        // Duplicate IDs are reported along with everything else by `validate`
+        let _ = story.rebuild_scene_index();
+        story.expand_snippets();
+        story.expand_macros();
+
+        Ok(story)
+    }
+
+    /// Loads a story stored as `<id>/story.json` plus scene chunks listed in
+    /// `<id>/chunks.json`. Only the story file is read up front; chunk files
+    /// are read when one of their scenes is first needed.
+    pub async fn load_chunked_story(&self, story_id: &str) -> GameResult<Story> {
+        check_story_id(story_id)?;
+        self.read_chunked_story(story_id).await
+    }
+
+    /// `load_chunked_story` for a directory named by the user rather than by
+    /// a story ID.
+    async fn read_chunked_story(&self, story_id: &str) -> GameResult<Story> {
+        let story_dir = self.stories_directory.join(story_id);
+        let story_path = self.chunked_story_path(story_id);
+
+        info!("Loading chunked story from: {:?}", story_dir);
+
         let content = fs::read_to_string(&story_path)
             .await
             .map_err(|e| GameError::story(format!("Failed to read story file: {}", e)))?;
 
-        let story: Story = serde_json::from_str(&content)
-            .map_err(|e| GameError::story(format!("Failed to parse story JSON: {}", e)))?;
+        let mut story = parse_story(&content)
+            .map_err(|errors| GameError::story_errors("Failed to parse story JSON", &errors))?;
 
-        // Validate the story
-        if let Err(errors) = story.validate() {
-            let error_msg = errors.join("; ");
-            return Err(GameError::story(format!("Story validation failed: {}", error_msg)));
+        let manifest = self.read_chunk_manifest(&story_dir).await?;
+
+        let _ = story.rebuild_scene_index();
+        story.expand_snippets();
+        story.expand_macros();
+        let chunked_scenes = ChunkedScenes::new(&story_dir, manifest)
+            .with_snippets(story.snippets.clone())
+            .with_macros(story.macros.clone());
+        story.attach_chunked_scenes(chunked_scenes);
+        self.apply_overlays(&mut story).await?;
+
+        // Reading every chunk up front defeats lazy loading, so it's opt-in
+        let mut errors = story.validate().err().unwrap_or_default();
+        if self.validate_chunks {
+            errors.extend(story.validate_chunks().err().unwrap_or_default());
+        }
+        if !errors.is_empty() {
+            return Err(GameError::story_errors("Story validation failed", &errors));
         }
 
-        info!("Successfully loaded story: {} ({})", story.title, story.id);
+        info!("Successfully loaded chunked story: {} ({} scenes)", story.title, story.get_scene_count());
         Ok(story)
     }
 
+    /// Writes `story` in the chunked layout with at most `scenes_per_chunk`
+    /// scenes per chunk file. The starting scene stays in the story file.
+    pub async fn save_chunked_story(&self, story: &Story, scenes_per_chunk: usize) -> GameResult<()> {
+        if let Err(errors) = story.validate() {
+            return Err(GameError::story_errors("Cannot save invalid story", &errors));
+        }
+
+        check_story_id(&story.id)?;
+        let story_dir = self.stories_directory.join(&story.id);
+        let chunks_dir = story_dir.join(CHUNKS_DIR);
+        fs::create_dir_all(&chunks_dir)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to create directory: {}", e)))?;
+
+        let (resident, chunked): (Vec<_>, Vec<_>) = story.scenes
+            .iter()
+            .partition(|scene| scene.id == story.starting_scene_id);
+
+        let mut manifest = ChunkManifest::default();
+        for (index, scenes) in chunked.chunks(scenes_per_chunk.max(1)).enumerate() {
+            let chunk_name = format!("{:05}.json", index);
+            let json = serde_json::to_string(scenes)
+                .map_err(|e| GameError::story(format!("Failed to serialize story chunk: {}", e)))?;
+
+            fs::write(chunks_dir.join(&chunk_name), json)
+                .await
+                .map_err(|e| GameError::story(format!("Failed to write story chunk: {}", e)))?;
+
+            manifest.chunks.insert(chunk_name, scenes.iter().map(|scene| scene.id.clone()).collect());
+        }
+
+        let mut head = story.clone();
+        head.scenes = resident.into_iter().cloned().collect();
+
+        let story_json = serde_json::to_string_pretty(&head)
+            .map_err(|e| GameError::story(format!("Failed to serialize story: {}", e)))?;
+        let manifest_json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| GameError::story(format!("Failed to serialize chunk manifest: {}", e)))?;
+
+        fs::write(self.chunked_story_path(&story.id), story_json)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to write story file: {}", e)))?;
+        fs::write(story_dir.join(CHUNK_MANIFEST_FILE), manifest_json)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to write chunk manifest: {}", e)))?;
+
+        info!("Saved chunked story: {} ({} chunks)", story.id, manifest.chunks.len());
+        Ok(())
+    }
+
     pub async fn list_available_stories(&self) -> GameResult<Vec<StoryMetadata>> {
         info!("Scanning for stories in: {:?}", self.stories_directory);
         
@@ -56,6 +222,8 @@ impl StoryLoader {
             .await
             .map_err(|e| GameError::story(format!("Failed to read stories directory: {}", e)))?;
 
+        let mut index = self.load_index().await;
+        let mut seen_paths = HashSet::new();
         let mut stories = Vec::new();
 
         while let Some(entry) = entries.next_entry().await
@@ -63,9 +231,22 @@ impl StoryLoader {
             
             let path = entry.path();
             
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            if path.extension().and_then(|s| s.to_str()) == Some("json") || path.join("story.json").is_file() {
+                let modified = story_modified_time(&path).await;
+                seen_paths.insert(path.clone());
+
+                if let Some(cached) = modified.and_then(|time| index.get(&path, time)) {
+                    stories.push(cached.clone());
+                    continue;
+                }
+
                 match self.load_story_metadata(&path).await {
-                    Ok(metadata) => stories.push(metadata),
+                    Ok(metadata) => {
+                        if let Some(time) = modified {
+                            index.insert(path, time, metadata.clone());
+                        }
+                        stories.push(metadata);
+                    }
                     Err(e) => {
                         warn!("Failed to load metadata for story at {:?}: {}", path, e);
                         continue;
@@ -74,6 +255,13 @@ impl StoryLoader {
             }
         }
 
+        index.retain_paths(&seen_paths);
+        if let Some(index_path) = &self.index_path {
+            if let Err(e) = index.save(index_path).await {
+                warn!("Failed to update story index: {}", e);
+            }
+        }
+
         // Sort by title
         stories.sort_by(|a, b| a.title.cmp(&b.title));
         
@@ -81,19 +269,33 @@ impl StoryLoader {
         Ok(stories)
     }
 
+    /// The story in the stories directory `story_id` is likely a typo of.
+    fn similar_story_id(&self, story_id: &str) -> Option<String> {
+        std::fs::read_dir(&self.stories_directory).ok()?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = if path.is_dir() { path.file_name() } else { path.file_stem() };
+                name.and_then(|name| name.to_str()).map(str::to_string)
+            })
+            .filter(|id| is_typo_of(story_id, id))
+            .min()
+    }
+
     pub async fn story_exists(&self, story_id: &str) -> bool {
-        let story_path = self.stories_directory.join(format!("{}.json", story_id));
-        story_path.exists()
+        match self.story_path(story_id) {
+            Ok(story_path) => story_path.exists() || self.chunked_story_path(story_id).exists(),
+            Err(_) => false,
+        }
     }
 
     pub async fn save_story(&self, story: &Story) -> GameResult<()> {
         // Validate before saving
         if let Err(errors) = story.validate() {
-            let error_msg = errors.join("; ");
-            return Err(GameError::story(format!("Cannot save invalid story: {}", error_msg)));
+            return Err(GameError::story_errors("Cannot save invalid story", &errors));
         }
 
-        let story_path = self.stories_directory.join(format!("{}.json", story.id));
+        let story_path = self.story_path(&story.id)?;
         
         // Create directory if it doesn't exist
         if let Some(parent) = story_path.parent() {
@@ -114,7 +316,7 @@ impl StoryLoader {
     }
 
     pub async fn delete_story(&self, story_id: &str) -> GameResult<()> {
-        let story_path = self.stories_directory.join(format!("{}.json", story_id));
+        let story_path = self.story_path(story_id)?;
         
         if !story_path.exists() {
             return Err(GameError::story(format!("Story not found: {}", story_id)));
@@ -129,24 +331,161 @@ impl StoryLoader {
     }
 
     pub async fn create_story_template(&self, story_id: &str, title: &str, author: &str) -> GameResult<Story> {
-        if self.story_exists(story_id).await {
-            return Err(GameError::story(format!("Story already exists: {}", story_id)));
+        self.create_story_from_template(&StoryTemplate::new(story_id, title, author)).await
+    }
+
+    /// Writes the story `template` describes, unless one with its ID exists.
+    pub async fn create_story_from_template(&self, template: &StoryTemplate) -> GameResult<Story> {
+        if self.story_exists(template.id()).await {
+            return Err(GameError::story(format!("Story already exists: {}", template.id())));
         }
 
-        let story = self.create_basic_story_template(story_id, title, author);
+        let story = template.build();
         self.save_story(&story).await?;
         
-        info!("Created story template: {}", story_id);
+        info!("Created story template: {}", story.id);
         Ok(story)
     }
 
+    /// Where `save_story` writes the story with `story_id`.
+    pub fn story_path(&self, story_id: &str) -> GameResult<PathBuf> {
+        check_story_id(story_id)?;
+        Ok(self.stories_directory.join(format!("{}.json", story_id)))
+    }
+
+    /// Applies every overlay in `overlays/<story_id>/` to `story`, in
+    /// precedence order.
+    pub async fn apply_overlays(&self, story: &mut Story) -> GameResult<()> {
+        let mut overlays = self.load_overlays(&story.id).await?;
+        StoryOverlay::sort_by_precedence(&mut overlays);
+
+        for overlay in &overlays {
+            info!("Applying overlay '{}' to story {}", overlay.id, story.id);
+            overlay.apply_to(story)?;
+        }
+        // Overlays can use snippets and macros in what they replace
+        story.expand_snippets();
+        story.expand_macros();
+
+        Ok(())
+    }
+
+    async fn load_overlays(&self, story_id: &str) -> GameResult<Vec<StoryOverlay>> {
+        let overlays_dir = self.stories_directory.join(OVERLAYS_DIR).join(story_id);
+        if !overlays_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = fs::read_dir(&overlays_dir)
+            .await
+            .map_err(|e| GameError::story(format!("Failed to read overlays directory: {}", e)))?;
+
+        let mut overlays = Vec::new();
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| GameError::story(format!("Failed to read directory entry: {}", e)))? {
+
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+
+            let content = fs::read_to_string(&path)
+                .await
+                .map_err(|e| GameError::story(format!("Failed to read overlay {:?}: {}", path, e)))?;
+            let overlay: StoryOverlay = parse_json(&content)
+                .map_err(|e| GameError::story(format!("Failed to parse overlay {:?}: {}", path, e)))?;
+            overlays.push(overlay);
+        }
+
+        Ok(overlays)
+    }
+
+    async fn load_index(&self) -> StoryIndex {
+        let Some(index_path) = &self.index_path else {
+            return StoryIndex::default();
+        };
+
+        StoryIndex::load(index_path).await.unwrap_or_else(|e| {
+            warn!("Ignoring unreadable story index: {}", e);
+            StoryIndex::default()
+        })
+    }
+
+    /// Reads the title art file named by the story's `title_art` metadata,
+    /// relative to the story's directory (the stories directory itself for
+    /// single-file stories). Missing or unreadable art is logged and skipped.
+    pub async fn load_title_art(&self, story: &Story) -> Option<String> {
+        let art_path = story.metadata.as_ref()?.get("title_art")?.as_str()?;
+        let relative = Path::new(art_path);
+        if relative.is_absolute() || relative.components().any(|part| matches!(part, std::path::Component::ParentDir)) {
+            warn!("Ignoring title art {:?} of story '{}': it must stay inside the story directory", art_path, story.id);
+            return None;
+        }
+
+        let story_dir = if self.chunked_story_path(&story.id).exists() {
+            self.stories_directory.join(&story.id)
+        } else {
+            self.stories_directory.clone()
+        };
+
+        // A symlink inside the story directory could still point anywhere
+        let resolved = match self.resolve_inside_stories(&story_dir.join(relative)).await {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                warn!("Ignoring title art {:?} of story '{}': it links outside the stories directory", art_path, story.id);
+                return None;
+            }
+            Err(e) => {
+                warn!("Failed to read title art {:?} of story '{}': {}", art_path, story.id, e);
+                return None;
+            }
+        };
+
+        match fs::read_to_string(resolved).await {
+            Ok(art) => Some(art.trim_end().to_string()),
+            Err(e) => {
+                warn!("Failed to read title art {:?} of story '{}': {}", art_path, story.id, e);
+                None
+            }
+        }
+    }
+
+    /// `path` with symlinks resolved, or `None` if it ends up outside the
+    /// stories directory.
+    async fn resolve_inside_stories(&self, path: &Path) -> std::io::Result<Option<PathBuf>> {
+        let stories_directory = fs::canonicalize(&self.stories_directory).await?;
+        let resolved = fs::canonicalize(path).await?;
+        Ok(resolved.starts_with(&stories_directory).then_some(resolved))
+    }
+
+    fn chunked_story_path(&self, story_id: &str) -> PathBuf {
+        self.stories_directory.join(story_id).join("story.json")
+    }
+
+    async fn read_chunk_manifest(&self, story_dir: &Path) -> GameResult<ChunkManifest> {
+        let content = fs::read_to_string(story_dir.join(CHUNK_MANIFEST_FILE))
+            .await
+            .map_err(|e| GameError::story(format!("Failed to read chunk manifest: {}", e)))?;
+
+        parse_json(&content)
+            .map_err(|e| GameError::story(format!("Failed to parse chunk manifest: {}", e)))
+    }
+
     async fn load_story_metadata(&self, path: &Path) -> GameResult<StoryMetadata> {
-        let content = fs::read_to_string(path)
+        // Chunked stories are directories holding story.json and a chunk manifest
+        let (path, chunked_scene_count) = if path.is_dir() {
+            let manifest = self.read_chunk_manifest(path).await?;
+            (path.join("story.json"), manifest.scene_count())
+        } else {
+            (path.to_path_buf(), 0)
+        };
+
+        let content = fs::read_to_string(&path)
             .await
             .map_err(|e| GameError::story(format!("Failed to read story file: {}", e)))?;
 
         // Parse just the metadata we need
-        let value: serde_json::Value = serde_json::from_str(&content)
+        let value: serde_json::Value = parse_json(&content)
             .map_err(|e| GameError::story(format!("Failed to parse story JSON: {}", e)))?;
 
         Ok(StoryMetadata {
@@ -173,72 +512,61 @@ impl StoryLoader {
             scene_count: value.get("scenes")
                 .and_then(|v| v.as_array())
                 .map(|arr| arr.len())
-                .unwrap_or(0),
+                .unwrap_or(0) + chunked_scene_count,
+            estimated_minutes: estimated_minutes(&value, chunked_scene_count > 0),
         })
     }
+}
 
-    fn create_basic_story_template(&self, story_id: &str, title: &str, author: &str) -> Story {
-        use crate::story::{Scene, Choice};
-        use crate::core::PlayerStats;
-
-        let mut story = Story::new(story_id, title, "start", PlayerStats::default());
-        story.author = author.to_string();
-        story.description = "A new adventure awaits...".to_string();
+/// Latest modification time of a story file, or of the story and manifest
+/// files for a chunked story directory.
+/// Whether `story_id` can name a story: letters, digits, `-` and `_`. Story
+/// IDs come from players and API clients, and anything else could reach
+/// files outside the stories directory.
+pub fn is_valid_story_id(story_id: &str) -> bool {
+    !story_id.is_empty() && story_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
 
-        // Create starting scene
-        let mut start_scene = Scene::new(
-            "start",
-            "The Beginning",
-            "Your adventure starts here. What will you do?"
-        );
-        
-        start_scene.add_choice(Choice::new(
-            "explore",
-            "Explore the area",
-            "explore"
-        ));
-        
-        start_scene.add_choice(Choice::new(
-            "rest",
-            "Rest and think",
-            "rest"
-        ));
-
-        // Create exploration scene
-        let mut explore_scene = Scene::new(
-            "explore",
-            "Exploration",
-            "You decide to explore your surroundings."
-        );
-        
-        explore_scene.add_choice(Choice::new(
-            "return",
-            "Return to the beginning",
-            "start"
-        ));
-
-        // Create rest scene
-        let mut rest_scene = Scene::new(
-            "rest",
-            "Contemplation",
-            "You take a moment to rest and gather your thoughts."
-        );
-        
-        rest_scene.add_choice(Choice::new(
-            "continue",
-            "Continue your journey",
-            "start"
-        ));
+fn check_story_id(story_id: &str) -> GameResult<()> {
+    match is_valid_story_id(story_id) {
+        true => Ok(()),
+        false => Err(GameError::story(format!("Invalid story ID {:?}: story IDs may only use letters, digits, - and _", story_id))),
+    }
+}
 
-        story.add_scene(start_scene);
-        story.add_scene(explore_scene);
-        story.add_scene(rest_scene);
+async fn story_modified_time(path: &Path) -> Option<SystemTime> {
+    let files = if path.is_dir() {
+        vec![path.join("story.json"), path.join(CHUNK_MANIFEST_FILE)]
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut latest = None;
+    for file in files {
+        let modified = fs::metadata(&file).await.ok()?.modified().ok()?;
+        latest = latest.max(Some(modified));
+    }
+    latest
+}
 
-        story
+/// The author's estimate, or one from the story's paths. Chunked stories
+/// only have an estimate if the author gives one, as working it out would
+/// mean reading every chunk.
+fn estimated_minutes(value: &serde_json::Value, chunked: bool) -> Option<u32> {
+    let declared = value.pointer("/metadata/estimated_minutes").and_then(|v| v.as_u64());
+    if let Some(minutes) = declared {
+        return Some(minutes as u32);
     }
+    if chunked {
+        return None;
+    }
+
+    let mut story: Story = serde_json::from_value(value.clone()).ok()?;
+    story.rebuild_scene_index().ok()?;
+    PathAnalysis::of(&story).estimated_minutes()
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoryMetadata {
     pub id: String,
     pub title: String,
@@ -246,18 +574,31 @@ pub struct StoryMetadata {
     pub author: String,
     pub version: String,
     pub scene_count: usize,
+    /// Typical playthrough length, from `metadata.estimated_minutes` or
+    /// `PathAnalysis`. Not worked out for chunked stories.
+    #[serde(default)]
+    pub estimated_minutes: Option<u32>,
 }
 
 impl StoryMetadata {
     pub fn display_name(&self) -> String {
         format!("{} by {} (v{})", self.title, self.author, self.version)
     }
+
+    /// E.g. "~45 minutes".
+    pub fn playtime_text(&self) -> Option<String> {
+        self.estimated_minutes.map(|minutes| match minutes {
+            1 => "~1 minute".to_string(),
+            minutes => format!("~{} minutes", minutes),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::tempdir;
+    use crate::story::{macros, Choice, Effect, EffectType, Macro, Scene};
 
     #[tokio::test]
     async fn test_story_loader_creation() {
@@ -279,4 +620,210 @@ mod tests {
         assert_eq!(story.author, "Test Author");
         assert!(!story.scenes.is_empty());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_title_art() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path());
+        let mut story = loader.create_story_template("test", "Test Story", "Test Author").await.unwrap();
+        assert_eq!(loader.load_title_art(&story).await, None);
+
+        std::fs::write(temp_dir.path().join("test-title.txt"), "  /\\  TEST\n").unwrap();
+        story.metadata = Some(std::collections::HashMap::from([
+            ("title_art".to_string(), serde_json::json!("test-title.txt")),
+        ]));
+        assert_eq!(loader.load_title_art(&story).await.as_deref(), Some("  /\\  TEST"));
+
+        story.metadata = Some(std::collections::HashMap::from([
+            ("title_art".to_string(), serde_json::json!("../secret.txt")),
+        ]));
+        assert_eq!(loader.load_title_art(&story).await, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_title_art_symlink_outside_stories() {
+        let temp_dir = tempdir().unwrap();
+        let stories_dir = temp_dir.path().join("stories");
+        let loader = StoryLoader::new(&stories_dir);
+        let mut story = loader.create_story_template("test", "Test Story", "Test Author").await.unwrap();
+
+        std::fs::write(temp_dir.path().join("secret.txt"), "secret").unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("secret.txt"), stories_dir.join("title.txt")).unwrap();
+        story.metadata = Some(std::collections::HashMap::from([
+            ("title_art".to_string(), serde_json::json!("title.txt")),
+        ]));
+        assert_eq!(loader.load_title_art(&story).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_story_index_cache() {
+        let temp_dir = tempdir().unwrap();
+        let stories_dir = temp_dir.path().join("stories");
+        let index_path = temp_dir.path().join("cache").join("stories-index.json");
+        let loader = StoryLoader::new(&stories_dir).with_index_cache(&index_path);
+
+        loader.create_story_template("test", "Test Story", "Test Author").await.unwrap();
+        let stories = loader.list_available_stories().await.unwrap();
+        assert_eq!(stories.len(), 1);
+        // The template's scenes loop without an ending
+        assert_eq!(stories[0].estimated_minutes, None);
+        assert!(index_path.exists());
+
+        let index = StoryIndex::load(&index_path).await.unwrap();
+        assert_eq!(index.len(), 1);
+
+        // Removed stories are dropped from the index
+        loader.delete_story("test").await.unwrap();
+        assert!(loader.list_available_stories().await.unwrap().is_empty());
+        assert!(StoryIndex::load(&index_path).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_estimated_minutes() {
+        let mut story = StoryTemplate::new("test", "Test Story", "Author").build();
+        let mut ending = Scene::new("end", "The End", "It's over.");
+        ending.is_ending = Some(true);
+        std::sync::Arc::make_mut(&mut story.scenes[0]).choices[0].target_scene_id = "end".to_string();
+        story.add_scene(ending);
+
+        let mut value = serde_json::to_value(&story).unwrap();
+        assert_eq!(estimated_minutes(&value, false), Some(1));
+        assert_eq!(estimated_minutes(&value, true), None);
+
+        value["metadata"] = serde_json::json!({"estimated_minutes": 45});
+        assert_eq!(estimated_minutes(&value, true), Some(45));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_story_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path());
+
+        let story = StoryTemplate::new("big", "Big Story", "Author").build();
+        loader.save_chunked_story(&story, 1).await.unwrap();
+
+        assert!(loader.story_exists("big").await);
+
+        let loaded = loader.load_story("big").await.unwrap();
+        assert_eq!(loaded.scenes.len(), 1);
+        assert_eq!(loaded.get_scene_count(), 3);
+        assert!(loaded.get_scene("explore").is_none());
+        assert_eq!(loaded.get_shared_scene("explore").unwrap().title, "Exploration");
+
+        let stories = loader.list_available_stories().await.unwrap();
+        assert_eq!(stories.len(), 1);
+        assert_eq!(stories[0].scene_count, 3);
+        assert_eq!(stories[0].estimated_minutes, None);
+    }
+
+    #[tokio::test]
+    async fn test_overlays_applied_on_load() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path());
+
+        let story = StoryTemplate::new("big", "Big Story", "Author").build();
+        loader.save_chunked_story(&story, 1).await.unwrap();
+
+        let overlays_dir = temp_dir.path().join(OVERLAYS_DIR).join("big");
+        std::fs::create_dir_all(&overlays_dir).unwrap();
+        std::fs::write(overlays_dir.join("retitle.json"), r#"{
+            "id": "retitle",
+            "story_id": "big",
+            "title": "Grosse Geschichte",
+            "patches": [{ "scene_id": "explore", "title": "Erkundung" }]
+        }"#).unwrap();
+
+        let loaded = loader.load_story("big").await.unwrap();
+        assert_eq!(loaded.title, "Grosse Geschichte");
+        assert_eq!(loaded.get_shared_scene("explore").unwrap().title, "Erkundung");
+        assert_eq!(loaded.get_scene_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_snippets_and_macros_expanded_on_load() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path());
+
+        let mut story = StoryTemplate::new("shop", "Shop", "Author").build();
+        story.snippets.insert("greeting".to_string(), "\"Welcome!\"".to_string());
+        story.macros.insert("rested".to_string(), Macro { effects: vec![Effect::add_health(5)], conditions: Vec::new() });
+        for scene in &mut story.scenes {
+            let scene = std::sync::Arc::make_mut(scene);
+            scene.description.push_str(" {{> greeting}}");
+            scene.effects = Some(vec![Effect::macro_effects("rested")]);
+        }
+        loader.save_story(&story).await.unwrap();
+        let loaded = loader.load_story("shop").await.unwrap();
+        let rest = loaded.get_scene("rest").unwrap();
+        assert_eq!(rest.description, "You take a moment to rest and gather your thoughts. \"Welcome!\"");
+        assert!(matches!(rest.effects.as_deref(), Some([Effect { effect_type: EffectType::ModifyHealth, .. }])));
+
+        story.id = "chunked_shop".to_string();
+        loader.save_chunked_story(&story, 1).await.unwrap();
+        let loaded = loader.load_story("chunked_shop").await.unwrap();
+        let rest = loaded.get_shared_scene("rest").unwrap();
+        assert!(rest.description.ends_with("\"Welcome!\""));
+        assert!(!macros::uses_macros(&rest));
+
+        story.snippets.clear();
+        story.macros.clear();
+        let errors = story.validate().unwrap_err();
+        assert!(errors.iter().any(|error| error.contains("Unknown snippet 'greeting'")));
+        assert!(errors.iter().any(|error| error.contains("Unknown macro 'rested'")));
+    }
+
+    #[tokio::test]
+    async fn test_load_reports_every_problem() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path());
+        let mut story = loader.create_story_template("test", "Test Story", "Test Author").await.unwrap();
+        let mut lost = Scene::new("lost", "Lost", "Nowhere.");
+        lost.add_choice(Choice::new("back", "Go back", "missing"));
+        story.add_scene(lost.clone());
+        story.add_scene(lost);
+        std::fs::write(temp_dir.path().join("test.json"), serde_json::to_string(&story).unwrap()).unwrap();
+
+        let error = loader.load_story("test").await.unwrap_err().to_string();
+        assert!(error.contains("Story validation failed (3 problems):"), "{}", error);
+        assert!(error.contains("- Scene 'lost': Choice 'back': Target scene 'missing' not found"), "{}", error);
+        assert!(error.contains("- Duplicate scene ID: 'lost'"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_hand_edited_chunks_validated_on_request() {
+        let temp_dir = tempdir().unwrap();
+        let loader = StoryLoader::new(temp_dir.path());
+        let story = StoryTemplate::new("big", "Big Story", "Author").build();
+        loader.save_chunked_story(&story, 1).await.unwrap();
+
+        let chunk_path = temp_dir.path().join("big").join(CHUNKS_DIR).join("00000.json");
+        let mut scenes: Vec<Scene> = serde_json::from_str(&std::fs::read_to_string(&chunk_path).unwrap()).unwrap();
+        scenes[0].add_choice(Choice::new("lost", "Get lost", "missing"));
+        scenes.push(scenes[0].clone());
+        std::fs::write(&chunk_path, serde_json::to_string(&scenes).unwrap()).unwrap();
+
+        assert!(loader.load_story("big").await.is_ok());
+
+        let error = loader.with_chunk_validation(true).load_story("big").await.unwrap_err().to_string();
+        assert!(error.contains("Choice 'lost': Target scene 'missing' not found"), "{}", error);
+        assert!(error.contains("Chunk '00000.json': Duplicate scene ID"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_story_ids_stay_in_stories_directory() {
+        let temp_dir = tempdir().unwrap();
+        let stories_dir = temp_dir.path().join("stories");
+        std::fs::create_dir_all(&stories_dir).unwrap();
+        std::fs::write(temp_dir.path().join("secrets.json"), "{\"token\": \"hunter2\"}").unwrap();
+        let loader = StoryLoader::new(&stories_dir);
+
+        let error = loader.load_story("../secrets").await.unwrap_err().to_string();
+        assert!(error.contains("Invalid story ID"), "{}", error);
+        assert!(!error.contains("hunter2"));
+        assert!(loader.load_chunked_story("..").await.is_err());
+        assert!(loader.story_path("../secrets").is_err());
+        assert!(!loader.story_exists("../secrets").await);
+        assert!(is_valid_story_id("dark_forest-2"));
+    }
+}
@@ -0,0 +1,184 @@
+//! Named bundles of effects and conditions, declared once under a story's
+//! `macros`, e.g. `"take_damage_small": {"effects": [...]}`. A `Macro`
+//! effect or condition with the bundle's name as its `key` stands for the
+//! bundle, and loaders expand it in place. Macros can use other macros.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::story::{Condition, ConditionType, Effect, EffectType, Scene};
+
+/// How deeply macros can use each other; deeper means a cycle.
+const MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Macro {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<Effect>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+}
+
+/// `effects` with each `Macro` effect replaced by its macro's effects.
+/// Unknown macros, and macros that use themselves, are left in place.
+pub fn expand_effects(effects: &[Effect], macros: &BTreeMap<String, Macro>) -> Vec<Effect> {
+    expand(effects, macros, 0, &|effect| matches!(effect.effect_type, EffectType::Macro).then_some(&effect.key), &|bundle| &bundle.effects)
+}
+
+/// `conditions` with each `Macro` condition replaced by its macro's
+/// conditions, all of which must hold, including inside condition groups.
+pub fn expand_conditions(conditions: &[Condition], macros: &BTreeMap<String, Macro>) -> Vec<Condition> {
+    expand_condition_groups(conditions, macros, 0)
+}
+
+fn expand_condition_groups(conditions: &[Condition], macros: &BTreeMap<String, Macro>, depth: usize) -> Vec<Condition> {
+    expand(conditions, macros, depth, &|condition| matches!(condition.condition_type, ConditionType::Macro).then_some(&condition.key), &|bundle| &bundle.conditions)
+        .into_iter()
+        .map(|mut condition| {
+            // A macro can be used in a group inside itself, so groups count as depth too
+            if condition.is_group() && depth < MAX_DEPTH {
+                condition.conditions = expand_condition_groups(&condition.conditions, macros, depth + 1);
+            }
+            condition
+        })
+        .collect()
+}
+
+/// Expands the macros in the effects and conditions of `scene` and its
+/// choices.
+pub fn expand_scene(scene: &mut Scene, macros: &BTreeMap<String, Macro>) {
+    fn expand_list<T>(list: &mut Option<Vec<T>>, expand: impl Fn(&[T]) -> Vec<T>) {
+        if let Some(items) = list {
+            *items = expand(items);
+        }
+    }
+    expand_list(&mut scene.effects, |effects| expand_effects(effects, macros));
+    expand_list(&mut scene.conditions, |conditions| expand_conditions(conditions, macros));
+    for choice in &mut scene.choices {
+        expand_list(&mut choice.effects, |effects| expand_effects(effects, macros));
+        expand_list(&mut choice.conditions, |conditions| expand_conditions(conditions, macros));
+    }
+}
+
+/// Whether `scene` or its choices use any macro.
+pub fn uses_macros(scene: &Scene) -> bool {
+    let effects = |effects: &Option<Vec<Effect>>| effects.iter().flatten().any(|effect| matches!(effect.effect_type, EffectType::Macro));
+    let conditions = |conditions: &Option<Vec<Condition>>| Condition::leaves(conditions.as_deref().unwrap_or_default()).iter()
+        .any(|condition| matches!(condition.condition_type, ConditionType::Macro));
+    effects(&scene.effects) || conditions(&scene.conditions)
+        || scene.choices.iter().any(|choice| effects(&choice.effects) || conditions(&choice.conditions))
+}
+
+/// Errors for `Macro` effects and conditions of `owner` that name unknown
+/// macros, or macros without effects or conditions to stand for.
+pub fn reference_errors(owner: &str, effects: &[Effect], conditions: &[Condition], macros: &BTreeMap<String, Macro>) -> Vec<String> {
+    let mut errors = Vec::new();
+    let effect_macros = effects.iter().filter(|effect| matches!(effect.effect_type, EffectType::Macro));
+    for effect in effect_macros {
+        match macros.get(&effect.key) {
+            None => errors.push(format!("{}: Unknown macro '{}'", owner, effect.key)),
+            Some(bundle) if bundle.effects.is_empty() => errors.push(format!("{}: Macro '{}' has no effects", owner, effect.key)),
+            Some(_) => {}
+        }
+    }
+    let condition_macros = Condition::leaves(conditions).into_iter().filter(|condition| matches!(condition.condition_type, ConditionType::Macro));
+    for condition in condition_macros {
+        match macros.get(&condition.key) {
+            None => errors.push(format!("{}: Unknown macro '{}'", owner, condition.key)),
+            Some(bundle) if bundle.conditions.is_empty() => errors.push(format!("{}: Macro '{}' has no conditions", owner, condition.key)),
+            Some(_) => {}
+        }
+    }
+    errors
+}
+
+/// Errors for macros that are empty, use unknown macros or use themselves.
+pub fn macro_errors(macros: &BTreeMap<String, Macro>) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (name, bundle) in macros {
+        let owner = format!("Macro '{}'", name);
+        if bundle.effects.is_empty() && bundle.conditions.is_empty() {
+            errors.push(format!("{}: has no effects or conditions", owner));
+        }
+        errors.extend(reference_errors(&owner, &bundle.effects, &bundle.conditions, macros));
+
+        // Expansion only leaves usable macros in place when they nest too deeply
+        let effects_nest = expand_effects(&bundle.effects, macros).iter()
+            .any(|effect| matches!(effect.effect_type, EffectType::Macro) && macros.get(&effect.key).is_some_and(|used| !used.effects.is_empty()));
+        let conditions = expand_conditions(&bundle.conditions, macros);
+        let conditions_nest = Condition::leaves(&conditions).iter()
+            .any(|condition| matches!(condition.condition_type, ConditionType::Macro) && macros.get(&condition.key).is_some_and(|used| !used.conditions.is_empty()));
+        if effects_nest || conditions_nest {
+            errors.push(format!("{}: macros nest too deeply or use themselves", owner));
+        }
+    }
+    errors
+}
+
+fn expand<T: Clone>(
+    items: &[T],
+    macros: &BTreeMap<String, Macro>,
+    depth: usize,
+    macro_name: &impl Fn(&T) -> Option<&String>,
+    contents: &impl Fn(&Macro) -> &Vec<T>,
+) -> Vec<T> {
+    let mut expanded = Vec::with_capacity(items.len());
+    for item in items {
+        let bundle = macro_name(item)
+            .and_then(|name| macros.get(name))
+            .filter(|bundle| depth < MAX_DEPTH && !contents(bundle).is_empty());
+        match bundle {
+            Some(bundle) => expanded.extend(expand(contents(bundle), macros, depth + 1, macro_name, contents)),
+            None => expanded.push(item.clone()),
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::{Choice, ComparisonOperator};
+
+    fn macros() -> BTreeMap<String, Macro> {
+        serde_json::from_value(serde_json::json!({
+            "take_damage_small": {"effects": [
+                {"effect_type": "ModifyHealth", "key": "health", "value": 10, "operation": "Subtract"},
+                {"effect_type": "SetFlag", "key": "wounded", "value": true}
+            ]},
+            "ambush": {
+                "effects": [{"effect_type": "Macro", "key": "take_damage_small"}, {"effect_type": "SetFlag", "key": "ambushed", "value": true}],
+                "conditions": [{"condition_type": "Flag", "key": "ambushed", "operator": "NotEquals", "value": true}]
+            },
+            "loop": {"effects": [{"effect_type": "Macro", "key": "loop"}]}
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_expand_macros() {
+        let macros = macros();
+        let mut scene = Scene::new("road", "Road", "A quiet road.");
+        scene.effects = Some(vec![Effect::macro_effects("ambush"), Effect::add_experience(5)]);
+        scene.add_choice(Choice::new("wait", "Wait", "road").with_conditions(vec![
+            Condition::macro_conditions("ambush"),
+            Condition::new(ConditionType::Level, String::new(), ComparisonOperator::GreaterEqual, serde_json::json!(2)),
+        ]));
+        assert!(uses_macros(&scene));
+
+        expand_scene(&mut scene, &macros);
+        assert!(!uses_macros(&scene));
+        let keys: Vec<&str> = scene.effects.iter().flatten().map(|effect| effect.key.as_str()).collect();
+        assert_eq!(keys, ["health", "wounded", "ambushed", "experience"]);
+        assert_eq!(scene.choices[0].conditions.as_ref().unwrap().len(), 2);
+        let grouped = expand_conditions(&[Condition::negate(Condition::macro_conditions("ambush"))], &macros);
+        assert!(matches!(grouped[0].conditions[..], [Condition { condition_type: ConditionType::Flag, .. }]));
+
+        // Unknown macros stay for validation to report
+        let unknown = expand_effects(&[Effect::macro_effects("missing")], &macros);
+        assert_eq!(reference_errors("Scene 'road'", &unknown, &[], &macros), ["Scene 'road': Unknown macro 'missing'"]);
+        assert_eq!(
+            reference_errors("Choice 'wait'", &[], &[Condition::macro_conditions("take_damage_small")], &macros),
+            ["Choice 'wait': Macro 'take_damage_small' has no conditions"]
+        );
+        assert_eq!(macro_errors(&macros), ["Macro 'loop': macros nest too deeply or use themselves"]);
+    }
+}
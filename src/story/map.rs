@@ -0,0 +1,133 @@
+//! The discovered part of the scene graph around the current scene, for the
+//! map screen.
+
+use std::collections::HashSet;
+use crate::core::GameState;
+use crate::story::Story;
+
+/// A scene on the map and the exits leading out of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapNode {
+    pub scene_id: String,
+    /// `None` for scenes the player hasn't been to yet.
+    pub title: Option<String>,
+    /// The scene already appears higher up the map, so its exits aren't
+    /// repeated.
+    pub repeated: bool,
+    pub exits: Vec<MapNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneMap {
+    pub here: MapNode,
+    /// Titles of visited scenes the player has come here from.
+    pub came_from: Vec<String>,
+}
+
+impl MapNode {
+    pub fn is_discovered(&self) -> bool {
+        self.title.is_some()
+    }
+}
+
+impl SceneMap {
+    /// Maps exits up to `depth` steps from the current scene. Only visited
+    /// scenes are named and explored further.
+    pub fn build(story: &Story, game_state: &GameState, depth: usize) -> Option<Self> {
+        let current = game_state.current_scene_id.as_str();
+        let visited: HashSet<&str> = game_state.visited_scenes.scene_ids()
+            .map(String::as_str)
+            .chain(std::iter::once(current))
+            .collect();
+
+        let mut shown = HashSet::new();
+        let here = map_node(story, current, &visited, &mut shown, depth)?;
+
+        let mut came_from = Vec::new();
+        let history: Vec<&String> = game_state.visited_scenes.history().collect();
+        for pair in history.windows(2) {
+            if pair[1] != current || pair[0] == current {
+                continue;
+            }
+            if let Some(scene) = story.get_shared_scene(pair[0]) {
+                if !came_from.contains(&scene.title) {
+                    came_from.push(scene.title.clone());
+                }
+            }
+        }
+
+        Some(Self { here, came_from })
+    }
+}
+
+fn map_node(
+    story: &Story,
+    scene_id: &str,
+    visited: &HashSet<&str>,
+    shown: &mut HashSet<String>,
+    depth: usize,
+) -> Option<MapNode> {
+    let scene = story.get_shared_scene(scene_id)?;
+    if !visited.contains(scene_id) {
+        return Some(MapNode { scene_id: scene_id.to_string(), title: None, repeated: false, exits: Vec::new() });
+    }
+
+    let repeated = !shown.insert(scene_id.to_string());
+    let mut node = MapNode {
+        scene_id: scene_id.to_string(),
+        title: Some(scene.title.clone()),
+        repeated,
+        exits: Vec::new(),
+    };
+    if repeated || depth == 0 {
+        return Some(node);
+    }
+
+    let mut targets: Vec<&str> = Vec::new();
+    let choice_targets = scene.choices.iter().map(|choice| choice.target_scene_id.as_str());
+    let auto_target = scene.auto_advance.iter().map(|auto| auto.target_scene_id.as_str());
+    for target in choice_targets.chain(auto_target) {
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    node.exits = targets.into_iter()
+        .filter_map(|target| map_node(story, target, visited, shown, depth - 1))
+        .collect();
+    Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Player, PlayerStats};
+    use crate::story::{Choice, Scene};
+
+    #[test]
+    fn test_scene_map() {
+        let mut story = Story::new("map", "Map", "road", PlayerStats::default());
+        let mut road = Scene::new("road", "The Road", "A dusty road.");
+        road.add_choice(Choice::new("to_tavern", "Enter the tavern", "tavern"));
+        let mut tavern = Scene::new("tavern", "The Tavern", "A noisy tavern.");
+        tavern.add_choice(Choice::new("to_cellar", "Go down", "cellar"));
+        tavern.add_choice(Choice::new("to_road", "Leave", "road"));
+        story.add_scene(road);
+        story.add_scene(tavern);
+        story.add_scene(Scene::new("cellar", "The Cellar", "Dark and damp."));
+
+        let mut game_state = GameState::new("map".to_string(), "road".to_string(), Player::new("Tester", None));
+        game_state.visit_scene("road");
+        game_state.visit_scene("tavern");
+
+        let map = SceneMap::build(&story, &game_state, 2).unwrap();
+        assert_eq!(map.here.title.as_deref(), Some("The Tavern"));
+        assert_eq!(map.came_from, vec!["The Road".to_string()]);
+
+        let [cellar, road] = &map.here.exits[..] else { panic!("expected two exits") };
+        assert!(!cellar.is_discovered());
+        assert_eq!(road.title.as_deref(), Some("The Road"));
+        // The road leads back here, which is already on the map
+        assert!(road.exits[0].repeated);
+    }
+}
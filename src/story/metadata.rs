@@ -0,0 +1,182 @@
+//! Reserved `metadata` keys shared by editors and exporters, with typed
+//! accessors on `Story`, `Scene` and `Choice`. Keys are namespaced so they
+//! can't clash with a story's own: `editor.` for layout that doesn't change
+//! the story, `author.` for notes and tags. Other keys are left untouched.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::story::{Choice, Scene, Story};
+
+/// Layout only; `StoryDiff` ignores keys in this namespace.
+pub const EDITOR_NAMESPACE: &str = "editor.";
+/// Where an editor draws the node, `{"x": 120.0, "y": 40.0}`.
+pub const EDITOR_POSITION: &str = "editor.position";
+/// Notes for whoever works on the story next; never shown to players.
+pub const AUTHOR_NOTES: &str = "author.notes";
+/// Free-form labels, e.g. `["act-2", "needs-review"]`.
+pub const AUTHOR_TAGS: &str = "author.tags";
+
+type Metadata = Option<HashMap<String, Value>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EditorPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+pub trait HasMetadata {
+    fn metadata_map(&self) -> &Metadata;
+    fn metadata_map_mut(&mut self) -> &mut Metadata;
+
+    fn metadata_value(&self, key: &str) -> Option<&Value> {
+        self.metadata_map().as_ref()?.get(key)
+    }
+
+    /// Sets or, with `None`, removes `key`. The map itself is removed once
+    /// empty, so setting and clearing a key round-trips to the original.
+    fn set_metadata_value(&mut self, key: &str, value: Option<Value>) {
+        let metadata = self.metadata_map_mut();
+        match value {
+            Some(value) => {
+                metadata.get_or_insert_with(HashMap::new).insert(key.to_string(), value);
+            }
+            None => {
+                if let Some(map) = metadata {
+                    map.remove(key);
+                    if map.is_empty() {
+                        *metadata = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn editor_position(&self) -> Option<EditorPosition> {
+        serde_json::from_value(self.metadata_value(EDITOR_POSITION)?.clone()).ok()
+    }
+
+    fn set_editor_position(&mut self, position: Option<EditorPosition>) {
+        let value = position.map(|position| serde_json::json!({"x": position.x, "y": position.y}));
+        self.set_metadata_value(EDITOR_POSITION, value);
+    }
+
+    fn author_notes(&self) -> Option<&str> {
+        self.metadata_value(AUTHOR_NOTES)?.as_str()
+    }
+
+    fn set_author_notes(&mut self, notes: Option<String>) {
+        self.set_metadata_value(AUTHOR_NOTES, notes.map(Value::String));
+    }
+
+    fn tags(&self) -> Vec<&str> {
+        self.metadata_value(AUTHOR_TAGS)
+            .and_then(Value::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    fn has_tag(&self, tag: &str) -> bool {
+        self.tags().contains(&tag)
+    }
+
+    /// An empty list removes the key.
+    fn set_tags(&mut self, tags: Vec<String>) {
+        let value = (!tags.is_empty()).then(|| Value::from(tags));
+        self.set_metadata_value(AUTHOR_TAGS, value);
+    }
+}
+
+impl HasMetadata for Story {
+    fn metadata_map(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn metadata_map_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+}
+
+impl HasMetadata for Scene {
+    fn metadata_map(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn metadata_map_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+}
+
+impl HasMetadata for Choice {
+    fn metadata_map(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn metadata_map_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+}
+
+/// Reserved keys holding the wrong type, e.g. tags that aren't a list of
+/// strings. `owner` prefixes each error, e.g. "Scene 'cave'".
+pub fn validate_metadata(owner: &str, metadata: &Metadata) -> Vec<String> {
+    let Some(metadata) = metadata else {
+        return Vec::new();
+    };
+    let mut errors = Vec::new();
+
+    if let Some(position) = metadata.get(EDITOR_POSITION) {
+        if serde_json::from_value::<EditorPosition>(position.clone()).is_err() {
+            errors.push(format!("{}: metadata '{}' must be {{\"x\": number, \"y\": number}}", owner, EDITOR_POSITION));
+        }
+    }
+    if metadata.get(AUTHOR_NOTES).is_some_and(|notes| !notes.is_string()) {
+        errors.push(format!("{}: metadata '{}' must be text", owner, AUTHOR_NOTES));
+    }
+    let valid_tags = |tags: &Value| tags.as_array().is_some_and(|tags| tags.iter().all(Value::is_string));
+    if metadata.get(AUTHOR_TAGS).is_some_and(|tags| !valid_tags(tags)) {
+        errors.push(format!("{}: metadata '{}' must be a list of text", owner, AUTHOR_TAGS));
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_accessors() {
+        let mut scene = Scene::new("cave", "Cave", "A dark cave.");
+        scene.metadata = Some(HashMap::from([("mood".to_string(), json!("eerie"))]));
+
+        scene.set_editor_position(Some(EditorPosition { x: 120.0, y: 40.5 }));
+        scene.set_author_notes(Some("Foreshadow the dragon".to_string()));
+        scene.set_tags(vec!["act-2".to_string(), "needs-review".to_string()]);
+
+        let round_tripped: Scene = serde_json::from_str(&serde_json::to_string(&scene).unwrap()).unwrap();
+        assert_eq!(round_tripped.editor_position(), Some(EditorPosition { x: 120.0, y: 40.5 }));
+        assert_eq!(round_tripped.author_notes(), Some("Foreshadow the dragon"));
+        assert!(round_tripped.has_tag("needs-review"));
+        assert_eq!(round_tripped.metadata_value("mood"), Some(&json!("eerie")));
+
+        let mut choice = Choice::new("enter", "Enter", "cave");
+        choice.set_tags(vec!["risky".to_string()]);
+        choice.set_tags(Vec::new());
+        assert!(choice.metadata.is_none());
+    }
+
+    #[test]
+    fn test_validate_metadata() {
+        let metadata = Some(HashMap::from([
+            (EDITOR_POSITION.to_string(), json!({"x": 1})),
+            (AUTHOR_NOTES.to_string(), json!("fine")),
+            (AUTHOR_TAGS.to_string(), json!(["ok", 3])),
+        ]));
+        let errors = validate_metadata("Scene 'cave'", &metadata);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Scene 'cave': metadata 'editor.position'"));
+        assert!(validate_metadata("Story", &None).is_empty());
+    }
+}
@@ -1,9 +1,50 @@
+#[allow(clippy::module_inception)]
 pub mod story;
 pub mod loader;
 pub mod conditions;
 pub mod effects;
+pub mod view;
+pub mod chunked;
+pub mod assets;
+pub mod index;
+pub mod diff;
+pub mod overlay;
+pub mod parse;
+pub mod formula;
+pub mod map;
+pub mod analysis;
+pub mod lint;
+pub mod metadata;
+pub mod flavor;
+pub mod generator;
+pub mod template;
+pub mod choicescript;
+pub mod export;
+pub mod snippets;
+pub mod macros;
+pub mod variables;
+pub mod testing;
 
-pub use story::{Story, Scene, Choice};
-pub use loader::StoryLoader;
+pub use story::{Story, Scene, Choice, AutoAdvance, Currency, Character, CatalogItem, Ending};
+pub use loader::{StoryLoader, StoryMetadata};
 pub use conditions::{Condition, ConditionType, ComparisonOperator};
-pub use effects::{Effect, EffectType, EffectOperation};
\ No newline at end of file
+pub use effects::{Effect, EffectType, EffectOperation};
+pub use view::{SceneView, ChoiceView};
+pub use chunked::{ChunkManifest, ChunkedScenes};
+pub use assets::AssetCache;
+pub use index::StoryIndex;
+pub use diff::StoryDiff;
+pub use overlay::{StoryOverlay, ScenePatch};
+pub use parse::JsonError;
+pub use formula::Formula;
+pub use map::{MapNode, SceneMap};
+pub use analysis::{EndingPath, PathAnalysis};
+pub use lint::{Dictionary, LintWarning, Linter};
+pub use metadata::{EditorPosition, HasMetadata};
+pub use flavor::{FlavorRequest, FlavorSettings, FlavorWriter};
+pub use generator::{Layout, StoryGenerator};
+pub use template::StoryTemplate;
+pub use choicescript::ChoiceScriptImport;
+pub use export::{ExportFormat, StoryExport};
+pub use macros::Macro;
+pub use variables::{Variable, VariableType};
\ No newline at end of file
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::story::{Choice, Scene, Story};
+use crate::utils::{GameError, GameResult};
+
+/// Directory under the stories directory holding `<story_id>/*.json` overlays.
+pub const OVERLAYS_DIR: &str = "overlays";
+
+/// A patch applied on top of a base story at load time, used for mods and
+/// translations.
+///
+/// Overlays are applied in ascending `priority`, then by `id`, so a later
+/// overlay wins wherever two touch the same text. Within one overlay, whole
+/// `scenes` are added or replaced first and `patches` run afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoryOverlay {
+    pub id: String,
+    pub story_id: String,
+    #[serde(default)]
+    pub priority: i32,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Scenes to add, replacing any existing scene with the same ID.
+    #[serde(default)]
+    pub scenes: Vec<Scene>,
+    #[serde(default)]
+    pub patches: Vec<ScenePatch>,
+}
+
+/// Edits to a single existing scene.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScenePatch {
+    pub scene_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// New text for existing choices, by choice ID.
+    #[serde(default)]
+    pub choice_text: HashMap<String, String>,
+    #[serde(default)]
+    pub remove_choices: Vec<String>,
+    /// Choices to insert; one with an existing ID replaces that choice in place.
+    #[serde(default)]
+    pub add_choices: Vec<Choice>,
+}
+
+impl StoryOverlay {
+    pub fn apply_to(&self, story: &mut Story) -> GameResult<()> {
+        if self.story_id != story.id {
+            return Err(GameError::story(format!(
+                "Overlay '{}' targets story '{}', not '{}'", self.id, self.story_id, story.id
+            )));
+        }
+
+        if let Some(title) = &self.title {
+            story.title = title.clone();
+        }
+        if let Some(description) = &self.description {
+            story.description = description.clone();
+        }
+
+        for scene in &self.scenes {
+            story.put_scene(scene.clone());
+        }
+
+        for patch in &self.patches {
+            let scene = story.get_shared_scene(&patch.scene_id).ok_or_else(|| GameError::story(format!(
+                "Overlay '{}' patches unknown scene '{}'", self.id, patch.scene_id
+            )))?;
+
+            let mut scene = Scene::clone(&scene);
+            patch.apply_to(&mut scene)
+                .map_err(|e| GameError::story(format!("Overlay '{}': {}", self.id, e)))?;
+            story.put_scene(scene);
+        }
+
+        Ok(())
+    }
+
+    /// Sorts overlays into the order they should be applied.
+    pub fn sort_by_precedence(overlays: &mut [StoryOverlay]) {
+        overlays.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+    }
+}
+
+impl ScenePatch {
+    fn apply_to(&self, scene: &mut Scene) -> Result<(), String> {
+        if let Some(title) = &self.title {
+            scene.title = title.clone();
+        }
+        if let Some(description) = &self.description {
+            scene.description = description.clone();
+        }
+
+        for (choice_id, text) in &self.choice_text {
+            let choice = scene.choices.iter_mut()
+                .find(|c| &c.id == choice_id)
+                .ok_or_else(|| format!("scene '{}' has no choice '{}'", scene.id, choice_id))?;
+            choice.text = text.clone();
+        }
+
+        scene.choices.retain(|c| !self.remove_choices.contains(&c.id));
+
+        for choice in &self.add_choices {
+            match scene.choices.iter_mut().find(|c| c.id == choice.id) {
+                Some(existing) => *existing = choice.clone(),
+                None => scene.choices.push(choice.clone()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+
+    fn base_story() -> Story {
+        let mut story = Story::new("forest", "The Forest", "start", PlayerStats::default());
+        let mut start = Scene::new("start", "Start", "You stand at the edge of a forest.");
+        start.add_choice(Choice::new("enter", "Enter the forest", "start"));
+        start.add_choice(Choice::new("leave", "Go home", "start"));
+        story.add_scene(start);
+        story
+    }
+
+    #[test]
+    fn test_overlay_precedence() {
+        let mut story = base_story();
+
+        let mut overlays = vec![
+            StoryOverlay {
+                id: "b-fixes".to_string(),
+                story_id: "forest".to_string(),
+                priority: 0,
+                title: None,
+                description: None,
+                scenes: vec![Scene::new("clearing", "Clearing", "A quiet clearing.")],
+                patches: vec![ScenePatch {
+                    scene_id: "start".to_string(),
+                    remove_choices: vec!["leave".to_string()],
+                    add_choices: vec![Choice::new("clearing", "Walk to the clearing", "clearing")],
+                    ..Default::default()
+                }],
+            },
+            StoryOverlay {
+                id: "a-translation".to_string(),
+                story_id: "forest".to_string(),
+                priority: 10,
+                title: Some("Der Wald".to_string()),
+                description: None,
+                scenes: Vec::new(),
+                patches: vec![ScenePatch {
+                    scene_id: "start".to_string(),
+                    description: Some("Du stehst am Waldrand.".to_string()),
+                    choice_text: HashMap::from([("enter".to_string(), "Den Wald betreten".to_string())]),
+                    ..Default::default()
+                }],
+            },
+        ];
+
+        StoryOverlay::sort_by_precedence(&mut overlays);
+        for overlay in &overlays {
+            overlay.apply_to(&mut story).unwrap();
+        }
+
+        assert_eq!(story.title, "Der Wald");
+        assert!(story.validate().is_ok());
+
+        let start = story.get_scene("start").unwrap();
+        assert_eq!(start.description, "Du stehst am Waldrand.");
+        let choice_ids: Vec<&str> = start.choices.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(choice_ids, vec!["enter", "clearing"]);
+        assert_eq!(start.choices[0].text, "Den Wald betreten");
+        assert!(story.get_scene("clearing").is_some());
+    }
+
+    #[test]
+    fn test_overlay_unknown_scene() {
+        let mut story = base_story();
+        let overlay = StoryOverlay {
+            id: "broken".to_string(),
+            story_id: "forest".to_string(),
+            priority: 0,
+            title: None,
+            description: None,
+            scenes: Vec::new(),
+            patches: vec![ScenePatch {
+                scene_id: "missing".to_string(),
+                ..Default::default()
+            }],
+        };
+
+        assert!(overlay.apply_to(&mut story).is_err());
+    }
+}
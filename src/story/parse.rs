@@ -0,0 +1,179 @@
+//! JSON parsing for story files that reports where a value went wrong:
+//! the field path, the line and column, and the offending line itself.
+
+use std::fmt;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use crate::story::{Scene, Story};
+
+/// A story file that failed to parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    /// Where in the document, e.g. `scenes[12].choices[3].target_scene_id`;
+    /// empty for syntax errors outside any value.
+    pub path: String,
+    /// The scene the error is in, when it could be told.
+    pub scene_id: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    /// The source line with a caret under the column.
+    pub snippet: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scene_id) = &self.scene_id {
+            write!(f, "Scene '{}': ", scene_id)?;
+        }
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)?;
+        if !self.snippet.is_empty() {
+            write!(f, "\n{}", self.snippet)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_json<T: DeserializeOwned>(content: &str) -> Result<T, JsonError> {
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+    let value = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|e| json_error(content, e.path().to_string(), e.into_inner()))?;
+    deserializer.end().map_err(|e| json_error(content, String::new(), e))?;
+    Ok(value)
+}
+
+/// Parses a story file. When it doesn't parse, each scene is parsed on its
+/// own too, so that every broken scene is reported rather than the first.
+pub fn parse_story(content: &str) -> Result<Story, Vec<JsonError>> {
+    let first = match parse_json(content) {
+        Ok(story) => return Ok(story),
+        Err(error) => error,
+    };
+
+    #[derive(Deserialize)]
+    struct RawStory<'a> {
+        #[serde(borrow, default)]
+        scenes: Vec<&'a RawValue>,
+    }
+    // Scenes can only be told apart in well-formed JSON
+    let Ok(raw) = serde_json::from_str::<RawStory>(content) else {
+        return Err(vec![first]);
+    };
+
+    let mut errors = Vec::new();
+    if !first.path.starts_with("scenes[") {
+        errors.push(first.clone());
+    }
+    for (index, scene) in raw.scenes.iter().enumerate() {
+        if let Err(error) = parse_json::<Scene>(scene.get()) {
+            errors.push(in_document(content, scene.get(), &format!("scenes[{}]", index), error));
+        }
+    }
+    // Whatever failed the full parse must be among the scenes' errors
+    if errors.is_empty() {
+        errors.push(first);
+    }
+    Err(errors)
+}
+
+/// Moves an error from parsing `part`, a slice of `content`, to its place
+/// in `content`.
+fn in_document(content: &str, part: &str, prefix: &str, error: JsonError) -> JsonError {
+    let offset = part.as_ptr() as usize - content.as_ptr() as usize;
+    let before = &content[..offset];
+    let start_line = before.matches('\n').count() + 1;
+    let start_column = offset - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+    let line = start_line + error.line - 1;
+    let column = if error.line == 1 { start_column + error.column - 1 } else { error.column };
+    let scene_id = serde_json::from_str::<serde_json::Value>(part).ok()
+        .and_then(|scene| scene.get("id")?.as_str().map(str::to_string));
+
+    JsonError {
+        path: if error.path.is_empty() { prefix.to_string() } else { format!("{}.{}", prefix, error.path) },
+        scene_id,
+        line,
+        column,
+        message: error.message,
+        snippet: snippet(content, line, column),
+    }
+}
+
+fn json_error(content: &str, path: String, error: serde_json::Error) -> JsonError {
+    let (line, column) = (error.line(), error.column());
+    // serde_json appends the position, which is reported separately
+    let message = error.to_string();
+    let message = message
+        .strip_suffix(&format!(" at line {} column {}", line, column))
+        .unwrap_or(&message)
+        .to_string();
+    JsonError {
+        path: if path == "." { String::new() } else { path },
+        scene_id: None,
+        line,
+        column,
+        message,
+        snippet: snippet(content, line, column),
+    }
+}
+
+fn snippet(content: &str, line: usize, column: usize) -> String {
+    let Some(source) = line.checked_sub(1).and_then(|index| content.lines().nth(index)) else {
+        return String::new();
+    };
+    let gutter = line.to_string();
+    // Columns count bytes; the caret goes under the character they land in
+    let caret = source
+        .char_indices()
+        .take_while(|(offset, _)| *offset < column.saturating_sub(1))
+        .count();
+    format!("{} | {}\n{} | {}^", gutter, source, " ".repeat(gutter.len()), " ".repeat(caret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORY: &str = r#"{
+  "id": "cave",
+  "title": "The Cave",
+  "description": "",
+  "author": "",
+  "version": "1.0",
+  "starting_scene_id": "mouth",
+  "scenes": [
+    {
+      "id": "mouth",
+      "title": "Cave Mouth",
+      "description": "Darkness ahead.",
+      "choices": [{"id": "enter", "text": "Go in", "target_scene_id": 7}]
+    },
+    {"id": "hall", "title": "Great Hall", "description": "Echoes everywhere.", "choices": []}
+  ]
+}"#;
+
+    #[test]
+    fn test_parse_errors_have_positions() {
+        let error = parse_json::<Story>(STORY).unwrap_err();
+        assert_eq!(error.path, "scenes[0].choices[0].target_scene_id");
+        assert_eq!(error.line, 13);
+        assert!(error.message.starts_with("invalid type: integer `7`, expected a string"), "{}", error.message);
+        assert!(error.snippet.starts_with("13 |       \"choices\""));
+        assert!(error.to_string().starts_with("line 13, column "));
+
+        let errors = parse_story(&STORY.replace("\"Great Hall\"", "false")).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].snippet.as_str()), (error.line, error.snippet.as_str()));
+        assert!(errors[0].to_string().starts_with("Scene 'mouth': line 13, column "));
+        assert_eq!(errors[1].path, "scenes[1].title");
+        assert_eq!((errors[1].line, errors[1].column), (15, 33));
+
+        let error = parse_json::<Story>("{\n  \"id\": \"cave\",\n}").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.snippet, "3 | }\n  | ^");
+    }
+}
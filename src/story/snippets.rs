@@ -0,0 +1,100 @@
+//! Reusable text, such as shop greetings or rules, declared once under a
+//! story's `snippets` and included in scene descriptions with
+//! `{{> name}}`. Loaders expand the includes, so the engine only ever sees
+//! the full text. Snippets can include other snippets.
+
+use std::collections::BTreeMap;
+
+const OPEN: &str = "{{>";
+const CLOSE: &str = "}}";
+/// How deeply snippets can include each other; deeper means a cycle.
+const MAX_DEPTH: usize = 8;
+
+/// Whether `text` has any includes left to expand.
+pub fn has_includes(text: &str) -> bool {
+    includes(text).next().is_some()
+}
+
+/// Names of the snippets `text` includes, in order.
+pub fn included_snippets(text: &str) -> Vec<&str> {
+    includes(text).map(|(_, name)| name).collect()
+}
+
+/// `text` with each include replaced by its snippet, itself expanded.
+/// Includes of unknown snippets, and of snippets that include themselves,
+/// are left as they are.
+pub fn expand_snippets(text: &str, snippets: &BTreeMap<String, String>) -> String {
+    expand(text, snippets, 0)
+}
+
+/// Errors for snippets that include unknown snippets or themselves.
+pub fn snippet_errors(snippets: &BTreeMap<String, String>) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (name, text) in snippets {
+        for included in included_snippets(text) {
+            if !snippets.contains_key(included) {
+                errors.push(format!("Snippet '{}' includes unknown snippet '{}'", name, included));
+            }
+        }
+        let expanded = expand_snippets(text, snippets);
+        if included_snippets(&expanded).iter().any(|included| snippets.contains_key(*included)) {
+            errors.push(format!("Snippet '{}' includes itself", name));
+        }
+    }
+    errors
+}
+
+fn expand(text: &str, snippets: &BTreeMap<String, String>, depth: usize) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = 0;
+    for (range, name) in includes(text) {
+        let Some(snippet) = snippets.get(name).filter(|_| depth < MAX_DEPTH) else {
+            continue;
+        };
+        expanded.push_str(&text[rest..range.start]);
+        expanded.push_str(&expand(snippet, snippets, depth + 1));
+        rest = range.end;
+    }
+    expanded.push_str(&text[rest..]);
+    expanded
+}
+
+/// Each `{{> name}}` in `text`, with its byte range.
+fn includes(text: &str) -> impl Iterator<Item = (std::ops::Range<usize>, &str)> {
+    let mut position = 0;
+    std::iter::from_fn(move || loop {
+        let start = position + text[position..].find(OPEN)?;
+        let end = start + text[start..].find(CLOSE)? + CLOSE.len();
+        position = start + OPEN.len();
+        let name = text[start + OPEN.len()..end - CLOSE.len()].trim();
+        if !name.is_empty() && !name.contains(char::is_whitespace) {
+            position = end;
+            return Some((start..end, name));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_snippets() {
+        let snippets = BTreeMap::from([
+            ("greeting".to_string(), "\"Welcome, traveller!\" {{> rules}}".to_string()),
+            ("rules".to_string(), "No refunds.".to_string()),
+            ("loop".to_string(), "Again: {{>loop}}".to_string()),
+        ]);
+
+        let text = "The shopkeeper looks up. {{> greeting }} {{> missing}} {{ not an include }}";
+        assert_eq!(included_snippets(text), ["greeting", "missing"]);
+        assert_eq!(
+            expand_snippets(text, &snippets),
+            "The shopkeeper looks up. \"Welcome, traveller!\" No refunds. {{> missing}} {{ not an include }}"
+        );
+        assert!(expand_snippets("{{> loop}}", &snippets).ends_with("Again: {{>loop}}"));
+        assert!(!has_includes("No includes {{> }} here"));
+
+        assert_eq!(snippet_errors(&snippets), ["Snippet 'loop' includes itself"]);
+    }
+}
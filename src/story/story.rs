@@ -1,7 +1,14 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use crate::core::PlayerStats;
-use crate::story::{Condition, Effect};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use crate::core::{GameState, InventoryItem, ItemType, LevelCurve, Player, PlayerStats, MAIN_CHARACTER};
+use crate::story::{ChunkedScenes, Condition, ConditionType, Effect, EffectType, Formula};
+use crate::story::formula::MAX_FORMULA_DEPTH;
+use crate::story::lint::is_typo_of;
+use crate::story::metadata::validate_metadata;
+use crate::story::{macros, snippets, variables, Macro, Variable, VariableType};
+use crate::utils::{GameError, GameResult};
+use tracing::warn;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Story {
@@ -10,10 +17,77 @@ pub struct Story {
     pub description: String,
     pub author: String,
     pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Shown before the first scene of a new game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intro: Option<String>,
+    /// Shown after an ending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits: Option<String>,
     pub starting_scene_id: String,
-    pub scenes: Vec<Scene>,
+    /// Where a saved game resumes if its scene was removed from the story;
+    /// defaults to the starting scene.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_fallback_scene: Option<String>,
+    /// Money kept in a numeric flag, shown as a wallet in the stats header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    /// Experience curve and level-up rewards; see `LevelCurve`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leveling: Option<LevelCurve>,
+    /// Stats computed from formulas over stats, other derived stats and
+    /// numeric flags, e.g. `"carry_capacity": "strength * 5"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub derived_stats: BTreeMap<String, String>,
+    /// Protagonists besides the player's own, by ID, for stories that switch
+    /// viewpoints with `SwitchCharacter` effects.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub characters: BTreeMap<String, Character>,
+    /// Items `AddItem` effects can refer to by ID instead of inlining them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub items: BTreeMap<String, CatalogItem>,
+    /// Text scene descriptions include with `{{> name}}`; see `snippets`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub snippets: BTreeMap<String, String>,
+    /// Effect and condition bundles that `Macro` effects and conditions
+    /// stand for; see `macros`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub macros: BTreeMap<String, Macro>,
+    /// Declared flags; when there are any, every flag used must be declared.
+    /// See `variables`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, Variable>,
+    pub scenes: Vec<Arc<Scene>>,
     pub initial_player_stats: PlayerStats,
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    #[serde(skip)]
+    scene_index: HashMap<String, usize>,
+    #[serde(skip)]
+    chunked_scenes: Option<Arc<ChunkedScenes>>,
+    /// `derived_stats` parsed by `parse_formulas`.
+    #[serde(skip)]
+    formulas: HashMap<String, Formula>,
+}
+
+/// A protagonist declared by the story, with their own stats and inventory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    /// Starting stats; defaults to the story's `initial_player_stats`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<PlayerStats>,
+}
+
+/// An entry of the story's item catalog; the catalog key is the item ID.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogItem {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub item_type: ItemType,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +102,69 @@ pub struct Scene {
     pub background_music: Option<String>,
     pub image: Option<String>,
     pub metadata: Option<HashMap<String, serde_json::Value>>,
+    pub auto_advance: Option<AutoAdvance>,
+    /// Present the choices in a random order, so their position doesn't
+    /// hint at the right answer. Reshuffled each time the scene is entered.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shuffle_choices: bool,
+    /// How the ending is named; declaring one makes the scene an ending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ending: Option<Ending>,
+}
+
+/// Names an ending for the ending gallery, statistics and `GameEnded` events.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ending {
+    /// Defaults to the scene ID. Scenes sharing an ID count as one ending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Defaults to the scene title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// E.g. "good", "bad" or "secret".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
+/// A story's money: which flag holds the balance and how it is shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Currency {
+    #[serde(default = "default_currency_flag")]
+    pub flag: String,
+    #[serde(default = "default_currency_symbol")]
+    pub symbol: String,
+    #[serde(default = "default_currency_name")]
+    pub name: String,
+}
+
+fn default_currency_flag() -> String {
+    "gold".to_string()
+}
+
+fn default_currency_symbol() -> String {
+    "🪙".to_string()
+}
+
+fn default_currency_name() -> String {
+    "Gold".to_string()
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self {
+            flag: default_currency_flag(),
+            symbol: default_currency_symbol(),
+            name: default_currency_name(),
+        }
+    }
+}
+
+/// Moves the game on to `target_scene_id` if the player hasn't chosen within
+/// `after_seconds`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoAdvance {
+    pub after_seconds: u64,
+    pub target_scene_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +176,16 @@ pub struct Choice {
     pub effects: Option<Vec<Effect>>,
     pub disabled: Option<bool>,
     pub disabled_reason: Option<String>,
+    /// Heading the choice is listed under, e.g. "Talk" or "Fight".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Position within its group; lower comes first, ties keep story order.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<i32>,
+    /// Dimmed line under the choice that telegraphs its stakes, e.g. "This
+    /// will anger the guild".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
@@ -55,19 +202,180 @@ impl Story {
             description: String::new(),
             author: String::new(),
             version: "1.0.0".to_string(),
+            license: None,
+            intro: None,
+            credits: None,
             starting_scene_id: starting_scene_id.into(),
+            resume_fallback_scene: None,
+            currency: None,
+            leveling: None,
+            derived_stats: BTreeMap::new(),
+            characters: BTreeMap::new(),
+            items: BTreeMap::new(),
+            snippets: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            variables: BTreeMap::new(),
             scenes: Vec::new(),
             initial_player_stats: initial_stats,
             metadata: None,
+            scene_index: HashMap::new(),
+            chunked_scenes: None,
+            formulas: HashMap::new(),
         }
     }
 
+    /// Author, version and license for the intro and credits screens,
+    /// e.g. "by Jane Doe · v1.2.0 · CC BY 4.0".
+    pub fn byline(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.author.is_empty() {
+            parts.push(format!("by {}", self.author));
+        }
+        if !self.version.is_empty() {
+            parts.push(format!("v{}", self.version));
+        }
+        if let Some(license) = &self.license {
+            parts.push(license.clone());
+        }
+        parts.join(" · ")
+    }
+
     pub fn add_scene(&mut self, scene: Scene) {
-        self.scenes.push(scene);
+        self.scene_index.entry(scene.id.clone()).or_insert(self.scenes.len());
+        self.scenes.push(Arc::new(scene));
+    }
+
+    /// Adds `scene`, replacing the resident scene with the same ID if there is
+    /// one. A scene that also exists in a chunk is shadowed by the resident copy.
+    pub fn put_scene(&mut self, scene: Scene) {
+        let existing = self.scene_index.get(&scene.id)
+            .copied()
+            .filter(|&index| self.scenes.get(index).is_some_and(|s| s.id == scene.id));
+
+        match existing {
+            Some(index) => self.scenes[index] = Arc::new(scene),
+            None => {
+                self.scene_index.insert(scene.id.clone(), self.scenes.len());
+                self.scenes.push(Arc::new(scene));
+            }
+        }
+    }
+
+    /// Rebuilds the id -> position index used by `get_scene`. Must be called
+    /// after deserializing or editing `scenes` directly; fails on duplicate IDs.
+    pub fn rebuild_scene_index(&mut self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        self.scene_index.clear();
+
+        for (index, scene) in self.scenes.iter().enumerate() {
+            if self.scene_index.contains_key(&scene.id) {
+                errors.push(format!("Duplicate scene ID: '{}'", scene.id));
+            } else {
+                self.scene_index.insert(scene.id.clone(), index);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Replaces the `{{> name}}` includes in scene descriptions with the
+    /// story's snippets. Loaders call this for resident scenes; chunked ones
+    /// are expanded as their chunk is read.
+    pub fn expand_snippets(&mut self) {
+        for scene in &mut self.scenes {
+            if snippets::has_includes(&scene.description) {
+                let expanded = snippets::expand_snippets(&scene.description, &self.snippets);
+                Arc::make_mut(scene).description = expanded;
+            }
+        }
     }
 
+    /// Replaces `Macro` effects and conditions with the story's macros, like
+    /// `expand_snippets`.
+    pub fn expand_macros(&mut self) {
+        for scene in &mut self.scenes {
+            if macros::uses_macros(scene) {
+                macros::expand_scene(Arc::make_mut(scene), &self.macros);
+            }
+        }
+    }
+
+    /// Scenes stored outside `scenes` and loaded on demand by `get_shared_scene`.
+    pub fn attach_chunked_scenes(&mut self, chunked_scenes: ChunkedScenes) {
+        self.chunked_scenes = Some(Arc::new(chunked_scenes));
+    }
+
+    pub fn chunked_scenes(&self) -> Option<&ChunkedScenes> {
+        self.chunked_scenes.as_deref()
+    }
+
+    /// Looks up a scene held in memory. Scenes of chunked stories that haven't
+    /// been loaded are not visible here; use `get_shared_scene` for those.
     pub fn get_scene(&self, scene_id: &str) -> Option<&Scene> {
-        self.scenes.iter().find(|s| s.id == scene_id)
+        self.get_resident_scene(scene_id).map(Arc::as_ref)
+    }
+
+    /// Returns a shared handle to the scene, loading it from its chunk file if
+    /// the story is chunked.
+    pub fn get_shared_scene(&self, scene_id: &str) -> Option<Arc<Scene>> {
+        if let Some(scene) = self.get_resident_scene(scene_id) {
+            return Some(Arc::clone(scene));
+        }
+
+        let chunked_scenes = self.chunked_scenes.as_ref()?;
+        match chunked_scenes.get(scene_id) {
+            Ok(scene) => scene,
+            Err(e) => {
+                warn!("Failed to load scene '{}': {}", scene_id, e);
+                None
+            }
+        }
+    }
+
+    fn get_resident_scene(&self, scene_id: &str) -> Option<&Arc<Scene>> {
+        let indexed = self.scene_index
+            .get(scene_id)
+            .and_then(|&index| self.scenes.get(index))
+            .filter(|scene| scene.id == scene_id);
+
+        // `scenes` may have been edited directly since the index was built, so
+        // a miss is only trusted after a scan. Misses are rare for resident
+        // stories, and chunked ones keep few scenes resident.
+        indexed.or_else(|| self.scenes.iter().find(|s| s.id == scene_id))
+    }
+
+    pub fn has_scene(&self, scene_id: &str) -> bool {
+        self.get_resident_scene(scene_id).is_some()
+            || self.chunked_scenes.as_ref().is_some_and(|chunks| chunks.contains(scene_id))
+    }
+
+    /// The scene `scene_id` is likely a typo of, for "did you mean" hints.
+    pub fn similar_scene_id(&self, scene_id: &str) -> Option<&str> {
+        let chunked = self.chunked_scenes.iter().flat_map(|chunks| chunks.scene_ids());
+        self.scenes.iter()
+            .map(|scene| scene.id.as_str())
+            .chain(chunked)
+            .filter(|id| is_typo_of(scene_id, id))
+            .min()
+    }
+
+    /// "<what> '<scene_id>' not found", suggesting the scene likely meant.
+    fn missing_scene(&self, what: &str, scene_id: &str) -> String {
+        match self.similar_scene_id(scene_id) {
+            Some(similar) => format!("{} '{}' not found (did you mean '{}'?)", what, scene_id, similar),
+            None => format!("{} '{}' not found", what, scene_id),
+        }
+    }
+
+    /// Scene to resume at when a save's current scene no longer exists.
+    pub fn resume_scene_id(&self) -> &str {
+        self.resume_fallback_scene.as_deref()
+            .filter(|scene_id| self.has_scene(scene_id))
+            .unwrap_or(&self.starting_scene_id)
     }
 
     pub fn get_starting_scene(&self) -> Option<&Scene> {
@@ -78,13 +386,44 @@ impl Story {
         let mut errors = Vec::new();
 
         // Check if starting scene exists
-        if self.get_starting_scene().is_none() {
-            errors.push(format!("Starting scene '{}' not found", self.starting_scene_id));
+        if !self.has_scene(&self.starting_scene_id) {
+            errors.push(self.missing_scene("Starting scene", &self.starting_scene_id));
+        }
+
+        if let Some(fallback) = &self.resume_fallback_scene {
+            if !self.has_scene(fallback) {
+                errors.push(self.missing_scene("Resume fallback scene", fallback));
+            }
+        }
+
+        if let Some(Err(error)) = self.leveling.as_ref().map(LevelCurve::validate) {
+            errors.push(error);
+        }
+
+        errors.extend(self.validate_derived_stats());
+        errors.extend(snippets::snippet_errors(&self.snippets));
+        errors.extend(macros::macro_errors(&self.macros));
+        errors.extend(self.validate_variables());
+        errors.extend(validate_metadata("Story", &self.metadata));
+
+        if self.characters.contains_key(MAIN_CHARACTER) {
+            errors.push(format!("Character ID '{}' is reserved for the player's own character", MAIN_CHARACTER));
+        }
+
+        for (stat, bounds) in &self.initial_player_stats.bounds {
+            if !PlayerStats::NAMES.contains(&stat.as_str()) {
+                errors.push(format!("Bounds declared for unknown stat '{}'", stat));
+            }
+            if let (Some(min), Some(max)) = (bounds.min, bounds.max) {
+                if min > max {
+                    errors.push(format!("Stat '{}': min {} is greater than max {}", stat, min, max));
+                }
+            }
         }
 
         // Validate each scene
         for scene in &self.scenes {
-            if let Err(mut scene_errors) = scene.validate(&self.scenes) {
+            if let Err(mut scene_errors) = scene.validate(self) {
                 errors.append(&mut scene_errors);
             }
         }
@@ -104,15 +443,217 @@ impl Story {
         }
     }
 
+    /// Validates the scenes in every chunk file of a chunked story, which
+    /// `validate` doesn't read. Scenes shadowed by resident ones are skipped.
+    pub fn validate_chunks(&self) -> Result<(), Vec<String>> {
+        let Some(chunks) = self.chunked_scenes() else {
+            return Ok(());
+        };
+
+        let (scenes, mut errors) = chunks.load_all();
+        for scene in scenes.iter().filter(|scene| self.get_resident_scene(&scene.id).is_none()) {
+            if let Err(mut scene_errors) = scene.validate(self) {
+                errors.append(&mut scene_errors);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// `quantity` of the catalog item `item_id`.
+    pub fn catalog_item(&self, item_id: &str, quantity: i32) -> Option<InventoryItem> {
+        self.items.get(item_id).map(|item| InventoryItem {
+            id: item_id.to_string(),
+            name: item.name.clone(),
+            description: item.description.clone(),
+            item_type: item.item_type.clone(),
+            quantity,
+            properties: item.properties.clone(),
+        })
+    }
+
+    /// `AddItem` effects that refer to an item by ID must name a catalog item
+    /// and a positive quantity (or none, for 1), and `SwitchCharacter`
+    /// effects a declared character.
+    fn validate_effects(&self, owner: &str, effects: Option<&Vec<Effect>>) -> Vec<String> {
+        let effects = effects.map(Vec::as_slice).unwrap_or_default();
+        let catalog_effects = || effects.iter()
+            .filter(|effect| matches!(effect.effect_type, EffectType::AddItem) && !effect.value.is_object());
+        let items = catalog_effects()
+            .filter(|effect| !self.items.contains_key(&effect.key))
+            .map(|effect| format!("{}: Item '{}' not found in the item catalog", owner, effect.key));
+        let quantities = catalog_effects()
+            .filter(|effect| {
+                !effect.value.is_null()
+                    && !effect.value.as_i64().is_some_and(|quantity| (1..=i64::from(i32::MAX)).contains(&quantity))
+            })
+            .map(|effect| format!("{}: Item '{}' quantity {} must be a whole number from 1 to {}", owner, effect.key, effect.value, i32::MAX));
+        let characters = effects.iter()
+            .filter(|effect| matches!(effect.effect_type, EffectType::SwitchCharacter))
+            .filter_map(|effect| self.unknown_character(owner, &effect.key));
+        items.chain(quantities).chain(characters).collect()
+    }
+
+    /// `ActiveCharacter` conditions must name a declared character.
+    fn validate_conditions(&self, owner: &str, conditions: Option<&Vec<Condition>>) -> Vec<String> {
+        Condition::leaves(conditions.map(Vec::as_slice).unwrap_or_default())
+            .into_iter()
+            .filter(|condition| matches!(condition.condition_type, ConditionType::ActiveCharacter))
+            .filter_map(|condition| self.unknown_character(owner, &condition.key))
+            .collect()
+    }
+
+    fn unknown_character(&self, owner: &str, character_id: &str) -> Option<String> {
+        (character_id != MAIN_CHARACTER && !self.characters.contains_key(character_id))
+            .then(|| format!("{}: Character '{}' not declared (use '{}' for the player's own)", owner, character_id, MAIN_CHARACTER))
+    }
+
+    /// A fresh player for the declared character `character_id`.
+    pub fn new_character(&self, character_id: &str) -> GameResult<Player> {
+        let character = self.characters.get(character_id)
+            .ok_or_else(|| GameError::story(format!("Unknown character: {}", character_id)))?;
+        let stats = character.stats.clone().unwrap_or_else(|| self.initial_player_stats.clone());
+
+        let mut player = Player::new(character.name.clone(), Some(stats));
+        player.level_curve = self.leveling.clone();
+        Ok(player)
+    }
+
+    /// Parses the derived stats once, so evaluating them doesn't. Invalid
+    /// formulas are left out; `validate` reports them.
+    pub fn parse_formulas(&mut self) {
+        self.formulas = self.derived_stats.iter()
+            .filter_map(|(name, source)| Some((name.clone(), Formula::parse(source).ok()?)))
+            .collect();
+    }
+
+    /// Value of a derived stat, or `None` if the story declares no stat of
+    /// that name. Names in the formula resolve to player stats, then to
+    /// derived stats, then to numeric flags (0 when unset).
+    pub fn derived_stat(&self, name: &str, game_state: &GameState) -> GameResult<Option<i64>> {
+        self.derived_stat_at(name, game_state, 0)
+    }
+
+    /// `derived_stat` for a stat `depth` derived stats deep, which stops
+    /// at `MAX_FORMULA_DEPTH` rather than recursing forever on a cycle.
+    fn derived_stat_at(&self, name: &str, game_state: &GameState, depth: usize) -> GameResult<Option<i64>> {
+        let Some(source) = self.derived_stats.get(name) else {
+            return Ok(None);
+        };
+        if depth >= MAX_FORMULA_DEPTH {
+            return Err(GameError::story(format!("Derived stat '{}' depends on stats nested too deeply", name)));
+        }
+
+        // Stats added since `parse_formulas` ran are parsed as they go
+        let parsed;
+        let formula = match self.formulas.get(name) {
+            Some(formula) => formula,
+            None => {
+                parsed = Formula::parse(source)?;
+                &parsed
+            }
+        };
+
+        let value = formula.evaluate(&|variable| {
+            if let Some(value) = game_state.player.stats.get(variable) {
+                return Ok(value as i64);
+            }
+            Ok(self.derived_stat_at(variable, game_state, depth + 1)?
+                .unwrap_or_else(|| game_state.get_flag_as_i64(variable)))
+        })?;
+        Ok(Some(value))
+    }
+
+    /// Declared variables need defaults of their type, and the currency
+    /// must be a declared number.
+    fn validate_variables(&self) -> Vec<String> {
+        let mut errors = variables::declaration_errors(&self.variables);
+        if let (false, Some(currency)) = (self.variables.is_empty(), &self.currency) {
+            match self.variables.get(&currency.flag) {
+                None => errors.push(variables::undeclared("Currency", &currency.flag, &self.variables)),
+                Some(variable) if variable.variable_type != VariableType::Number => {
+                    errors.push(format!("Currency: Variable '{}' must be a number", currency.flag));
+                }
+                Some(_) => {}
+            }
+        }
+        errors
+    }
+
+    /// Derived stats must parse, must not shadow player stats and must not
+    /// depend on themselves.
+    fn validate_derived_stats(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut dependencies = HashMap::new();
+
+        for (name, source) in &self.derived_stats {
+            if self.initial_player_stats.get(name).is_some() {
+                errors.push(format!("Derived stat '{}' shadows a player stat", name));
+            }
+            match Formula::parse(source) {
+                Ok(formula) => {
+                    let derived: Vec<String> = formula.variables().into_iter()
+                        .filter(|variable| self.derived_stats.contains_key(*variable))
+                        .map(str::to_string)
+                        .collect();
+                    dependencies.insert(name.as_str(), derived);
+                }
+                Err(e) => errors.push(format!("Derived stat '{}': {}", name, e)),
+            }
+        }
+
+        for name in dependencies.keys() {
+            // Walk the stats this one depends on, looking for the way back
+            let mut stack: Vec<&str> = dependencies[name].iter().map(String::as_str).collect();
+            let mut seen = std::collections::HashSet::new();
+            while let Some(next) = stack.pop() {
+                if next == *name {
+                    errors.push(format!("Derived stat '{}' depends on itself", name));
+                    break;
+                }
+                if seen.insert(next) {
+                    if let Some(more) = dependencies.get(next) {
+                        stack.extend(more.iter().map(String::as_str));
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Every scene, loading the chunk files of chunked stories.
+    pub fn all_scenes(&self) -> Vec<Arc<Scene>> {
+        let mut scenes = self.scenes.clone();
+        if let Some(chunks) = self.chunked_scenes() {
+            let chunked = chunks.scene_ids()
+                .filter(|scene_id| self.get_resident_scene(scene_id).is_none())
+                .filter_map(|scene_id| self.get_shared_scene(scene_id));
+            scenes.extend(chunked);
+        }
+        scenes
+    }
+
     pub fn get_endings(&self) -> Vec<&Scene> {
         self.scenes
             .iter()
-            .filter(|scene| scene.is_ending.unwrap_or(false))
+            .map(Arc::as_ref)
+            .filter(|scene| scene.is_ending())
             .collect()
     }
 
     pub fn get_scene_count(&self) -> usize {
-        self.scenes.len()
+        let Some(chunks) = self.chunked_scenes.as_ref() else {
+            return self.scenes.len();
+        };
+
+        // Resident scenes may shadow chunked ones (e.g. patched by an overlay)
+        let shadowed = self.scenes.iter().filter(|scene| chunks.contains(&scene.id)).count();
+        self.scenes.len() + chunks.scene_count() - shadowed
     }
 }
 
@@ -129,9 +670,20 @@ impl Scene {
             background_music: None,
             image: None,
             metadata: None,
+            auto_advance: None,
+            shuffle_choices: false,
+            ending: None,
         }
     }
 
+    pub fn with_auto_advance<S: Into<String>>(mut self, after_seconds: u64, target_scene_id: S) -> Self {
+        self.auto_advance = Some(AutoAdvance {
+            after_seconds,
+            target_scene_id: target_scene_id.into(),
+        });
+        self
+    }
+
     pub fn add_choice(&mut self, choice: Choice) {
         self.choices.push(choice);
     }
@@ -141,16 +693,36 @@ impl Scene {
     }
 
     pub fn is_ending(&self) -> bool {
-        self.is_ending.unwrap_or(false)
+        self.is_ending.unwrap_or(self.ending.is_some())
+    }
+
+    pub fn ending_id(&self) -> Option<&str> {
+        let id = self.ending.as_ref().and_then(|ending| ending.id.as_deref());
+        self.is_ending().then(|| id.unwrap_or(&self.id))
     }
 
-    pub fn validate(&self, all_scenes: &[Scene]) -> Result<(), Vec<String>> {
+    pub fn ending_title(&self) -> Option<&str> {
+        let title = self.ending.as_ref().and_then(|ending| ending.title.as_deref());
+        self.is_ending().then(|| title.unwrap_or(&self.title))
+    }
+
+    pub fn ending_category(&self) -> Option<&str> {
+        self.ending.as_ref()?.category.as_deref().filter(|_| self.is_ending())
+    }
+
+    pub fn validate(&self, story: &Story) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
-        // Validate each choice
+        // Validate each choice, naming the scene it's in
         for choice in &self.choices {
-            if let Err(mut choice_errors) = choice.validate(all_scenes) {
-                errors.append(&mut choice_errors);
+            if let Err(choice_errors) = choice.validate(story) {
+                errors.extend(choice_errors.into_iter().map(|error| format!("Scene '{}': {}", self.id, error)));
+            }
+        }
+
+        for name in snippets::included_snippets(&self.description) {
+            if !story.snippets.contains_key(name) {
+                errors.push(format!("Scene '{}': Unknown snippet '{}'", self.id, name));
             }
         }
 
@@ -162,6 +734,40 @@ impl Scene {
             }
         }
 
+        if let Some(auto_advance) = &self.auto_advance {
+            if auto_advance.after_seconds == 0 {
+                errors.push(format!("Scene '{}': auto_advance.after_seconds must be greater than 0", self.id));
+            }
+            if !story.has_scene(&auto_advance.target_scene_id) {
+                errors.push(format!(
+                    "Scene '{}': {}",
+                    self.id,
+                    story.missing_scene("Auto-advance target scene", &auto_advance.target_scene_id)
+                ));
+            }
+        }
+
+        errors.extend(story.validate_effects(&format!("Scene '{}'", self.id), self.effects.as_ref()));
+        errors.extend(story.validate_conditions(&format!("Scene '{}'", self.id), self.conditions.as_ref()));
+        errors.extend(macros::reference_errors(
+            &format!("Scene '{}'", self.id),
+            self.effects.as_deref().unwrap_or_default(),
+            self.conditions.as_deref().unwrap_or_default(),
+            &story.macros,
+        ));
+        errors.extend(variables::reference_errors(
+            &format!("Scene '{}'", self.id),
+            self.effects.as_deref().unwrap_or_default(),
+            self.conditions.as_deref().unwrap_or_default(),
+            &story.variables,
+        ));
+        errors.extend(Condition::group_errors(&format!("Scene '{}'", self.id), self.conditions.as_deref().unwrap_or_default()));
+        errors.extend(validate_metadata(&format!("Scene '{}'", self.id), &self.metadata));
+
+        if self.ending.is_some() && !self.is_ending() {
+            errors.push(format!("Scene '{}': declares an ending but has is_ending set to false", self.id));
+        }
+
         // Ending scenes should have no choices (or only meta choices)
         if self.is_ending() && !self.choices.is_empty() {
             let non_meta_choices = self.choices.iter()
@@ -190,10 +796,28 @@ impl Choice {
             effects: None,
             disabled: None,
             disabled_reason: None,
+            group: None,
+            order: None,
+            hint: None,
             metadata: None,
         }
     }
 
+    pub fn with_group<S: Into<String>>(mut self, group: S) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    pub fn with_hint<S: Into<String>>(mut self, hint: S) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
     pub fn with_conditions(mut self, conditions: Vec<Condition>) -> Self {
         self.conditions = Some(conditions);
         self
@@ -210,21 +834,38 @@ impl Choice {
         self
     }
 
-    pub fn validate(&self, all_scenes: &[Scene]) -> Result<(), Vec<String>> {
+    pub fn validate(&self, story: &Story) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
         // Check if target scene exists (unless it's a special target)
         let special_targets = ["END", "RESTART", "MAIN_MENU"];
-        if !special_targets.contains(&self.target_scene_id.as_str()) {
-            if !all_scenes.iter().any(|s| s.id == self.target_scene_id) {
-                errors.push(format!(
-                    "Choice '{}': Target scene '{}' not found", 
-                    self.id, 
-                    self.target_scene_id
-                ));
-            }
+        if !special_targets.contains(&self.target_scene_id.as_str())
+            && !story.has_scene(&self.target_scene_id)
+        {
+            errors.push(format!(
+                "Choice '{}': {}",
+                self.id,
+                story.missing_scene("Target scene", &self.target_scene_id)
+            ));
         }
 
+        errors.extend(story.validate_effects(&format!("Choice '{}'", self.id), self.effects.as_ref()));
+        errors.extend(story.validate_conditions(&format!("Choice '{}'", self.id), self.conditions.as_ref()));
+        errors.extend(macros::reference_errors(
+            &format!("Choice '{}'", self.id),
+            self.effects.as_deref().unwrap_or_default(),
+            self.conditions.as_deref().unwrap_or_default(),
+            &story.macros,
+        ));
+        errors.extend(variables::reference_errors(
+            &format!("Choice '{}'", self.id),
+            self.effects.as_deref().unwrap_or_default(),
+            self.conditions.as_deref().unwrap_or_default(),
+            &story.variables,
+        ));
+        errors.extend(Condition::group_errors(&format!("Choice '{}'", self.id), self.conditions.as_deref().unwrap_or_default()));
+        errors.extend(validate_metadata(&format!("Choice '{}'", self.id), &self.metadata));
+
         if errors.is_empty() {
             Ok(())
         } else {
@@ -247,6 +888,16 @@ mod tests {
         assert_eq!(story.starting_scene_id, "start");
     }
 
+    #[test]
+    fn test_byline() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        assert_eq!(story.byline(), "v1.0.0");
+
+        story.author = "Jane Doe".to_string();
+        story.license = Some("CC BY 4.0".to_string());
+        assert_eq!(story.byline(), "by Jane Doe · v1.0.0 · CC BY 4.0");
+    }
+
     #[test]
     fn test_scene_creation() {
         let scene = Scene::new("test", "Test Scene", "A test scene");
@@ -266,6 +917,17 @@ mod tests {
         assert_eq!(choice.target_scene_id, "target");
     }
 
+    #[test]
+    fn test_choice_hint_serde() {
+        // Hints are optional in story files
+        let json = r#"{"id": "join", "text": "Join the guild", "target_scene_id": "hall",
+            "conditions": null, "effects": null, "disabled": null, "disabled_reason": null,
+            "metadata": null, "hint": "The thieves will remember this"}"#;
+        let choice: Choice = serde_json::from_str(json).unwrap();
+        assert_eq!(choice.hint.as_deref(), Some("The thieves will remember this"));
+        assert!(!serde_json::to_string(&Choice::new("a", "A", "b")).unwrap().contains("hint"));
+    }
+
     #[test]
     fn test_story_validation() {
         let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
@@ -279,4 +941,74 @@ mod tests {
         // Should pass
         assert!(story.validate().is_ok());
     }
+
+    #[test]
+    fn test_stat_bounds_validation() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.add_scene(Scene::new("start", "Start", "Starting scene"));
+
+        // An unknown stat and a minimum above the maximum
+        story.initial_player_stats.bounds.insert("sanity".to_string(), Default::default());
+        story.initial_player_stats.bounds.insert(
+            "charisma".to_string(),
+            crate::core::StatBounds { min: Some(5), max: Some(2) },
+        );
+        assert_eq!(story.validate().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_derived_stat_validation() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.add_scene(Scene::new("start", "Start", "Starting scene"));
+
+        story.derived_stats.insert("a".to_string(), "b + 1".to_string());
+        story.derived_stats.insert("b".to_string(), "a * 2".to_string());
+        story.derived_stats.insert("strength".to_string(), "(".to_string());
+        // Two cycle errors, a shadowed stat and a parse error
+        assert_eq!(story.validate().unwrap_err().len(), 4);
+        let game_state = GameState::new("test".to_string(), "start".to_string(), Player::new("Tester", None));
+        story.parse_formulas();
+        assert!(story.derived_stat("a", &game_state).unwrap_err().to_string().contains("nested too deeply"));
+    }
+
+    #[test]
+    fn test_character_validation() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.characters.insert("ada".to_string(), Character { name: "Ada".to_string(), stats: None });
+        let mut start = Scene::new("start", "Start", "Starting scene");
+        start.effects = Some(vec![Effect::switch_character("ada"), Effect::switch_character("bob")]);
+        start.add_choice(
+            Choice::new("back", "Back", "start")
+                .with_conditions(vec![Condition::active_character(MAIN_CHARACTER), Condition::active_character("eve")])
+        );
+        story.add_scene(start);
+
+        let errors = story.validate().unwrap_err();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().any(|error| error.contains("Character 'bob' not declared")), "{:?}", errors);
+        assert!(errors.iter().any(|error| error.contains("Character 'eve' not declared")), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_scene_index() {
+        let mut story = Story::new("test", "Test Story", "start", PlayerStats::default());
+        story.add_scene(Scene::new("start", "Start", "Starting scene"));
+        story.add_scene(Scene::new("next", "Next", "Next scene"));
+
+        let json = serde_json::to_string(&story).unwrap();
+        let mut loaded: Story = serde_json::from_str(&json).unwrap();
+        assert!(loaded.rebuild_scene_index().is_ok());
+        assert_eq!(loaded.get_scene("next").unwrap().title, "Next");
+        assert!(loaded.get_scene("missing").is_none());
+
+        // Direct edits are still found before the index is rebuilt
+        loaded.scenes.push(Arc::new(Scene::new("late", "Late", "Added directly")));
+        assert!(loaded.has_scene("late"));
+        loaded.scenes[1] = Arc::new(Scene::new("swapped", "Swapped", "Replaced directly"));
+        assert!(loaded.has_scene("swapped"));
+        assert!(!loaded.has_scene("next"));
+
+        loaded.scenes.push(Arc::new(Scene::new("start", "Again", "Duplicate")));
+        assert!(loaded.rebuild_scene_index().is_err());
+    }
 }
\ No newline at end of file
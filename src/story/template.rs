@@ -0,0 +1,174 @@
+//! Starter stories for `text-game new` and `StoryLoader::create_story_template`.
+//! Story JSON can't hold comments, so the explanations go in
+//! `author.notes`, which players never see.
+
+use std::collections::HashMap;
+use crate::core::{ItemType, PlayerStats};
+use crate::story::{CatalogItem, Choice, ComparisonOperator, Condition, ConditionType, Effect, Ending, HasMetadata, Scene, Story};
+
+/// ID of the example item.
+pub const TEMPLATE_ITEM: &str = "lantern";
+/// ID of the ending scene.
+pub const TEMPLATE_ENDING: &str = "ending";
+
+#[derive(Debug, Clone)]
+pub struct StoryTemplate {
+    id: String,
+    title: String,
+    author: String,
+    initial_stats: PlayerStats,
+    chapters: Vec<String>,
+    with_item: bool,
+    with_ending: bool,
+}
+
+impl StoryTemplate {
+    /// The opening scene with two short branches, and nothing else.
+    pub fn new<S: Into<String>>(id: S, title: S, author: S) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            author: author.into(),
+            initial_stats: PlayerStats::default(),
+            chapters: Vec::new(),
+            with_item: false,
+            with_ending: false,
+        }
+    }
+
+    pub fn with_initial_stats(mut self, stats: PlayerStats) -> Self {
+        self.initial_stats = stats;
+        self
+    }
+
+    /// A scene per chapter title, each leading to the next.
+    pub fn with_chapters(mut self, titles: Vec<String>) -> Self {
+        self.chapters = titles;
+        self
+    }
+
+    /// An item in the catalog and a choice that picks it up.
+    pub fn with_item(mut self) -> Self {
+        self.with_item = true;
+        self
+    }
+
+    /// A named ending after the last chapter.
+    pub fn with_ending(mut self) -> Self {
+        self.with_ending = true;
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn build(&self) -> Story {
+        let mut story = Story::new(self.id.as_str(), self.title.as_str(), "start", self.initial_stats.clone());
+        story.author = self.author.clone();
+        story.description = "A new adventure awaits...".to_string();
+        story.set_author_notes(Some(
+            "Scenes link up through each choice's target_scene_id. Fields under \"metadata\" starting with \
+             \"author.\" are notes like this one and are never shown to players. Check your work with \
+             `text-game lint` and `text-game explore`.".to_string()
+        ));
+
+        let mut start = Scene::new("start", "The Beginning", "Your adventure starts here. What will you do?");
+        start.set_author_notes(Some("The story's starting_scene_id points here. Replace the text and choices with your opening.".to_string()));
+        start.add_choice(Choice::new("explore", "Explore the area", "explore"));
+        start.add_choice(Choice::new("rest", "Rest and think", "rest"));
+
+        let mut explore = Scene::new("explore", "Exploration", "You decide to explore your surroundings.");
+        explore.add_choice(Choice::new("return", "Return to the beginning", "start"));
+
+        let mut rest = Scene::new("rest", "Contemplation", "You take a moment to rest and gather your thoughts.");
+        rest.add_choice(Choice::new("continue", "Continue your journey", "start"));
+
+        if self.with_item {
+            story.items.insert(TEMPLATE_ITEM.to_string(), CatalogItem {
+                name: "Lantern".to_string(),
+                description: "An old brass lantern.".to_string(),
+                item_type: ItemType::KeyItem,
+                properties: HashMap::new(),
+            });
+            let not_carried = Condition::new(ConditionType::Inventory, TEMPLATE_ITEM.to_string(), ComparisonOperator::LessThan, serde_json::json!(1));
+            let mut pick_up = Choice::new("take_lantern", "Pick up the lantern", "explore")
+                .with_conditions(vec![not_carried])
+                .with_effects(vec![Effect::add_catalog_item(TEMPLATE_ITEM, 1)]);
+            pick_up.set_author_notes(Some(
+                "Items are declared once under the story's \"items\" and added by ID. The condition hides \
+                 this choice once the lantern is carried.".to_string()
+            ));
+            explore.add_choice(pick_up);
+        }
+
+        let mut scenes = vec![start, explore, rest];
+        for (index, title) in self.chapters.iter().enumerate() {
+            let mut chapter = Scene::new(chapter_id(index), title.clone(), format!("{} begins.", title));
+            chapter.set_author_notes(Some(format!("Chapter {}. Add scenes between chapters by pointing choices at them.", index + 1)));
+            scenes.push(chapter);
+        }
+        if self.with_ending {
+            let mut ending = Scene::new(TEMPLATE_ENDING, "The End", "Your adventure is over.");
+            ending.ending = Some(Ending {
+                id: Some("good".to_string()),
+                title: Some("A Happy Ending".to_string()),
+                category: Some("good".to_string()),
+            });
+            ending.set_author_notes(Some(
+                "Declaring an ending makes this an ending scene. Scenes sharing an ending ID count as one \
+                 ending in the player's gallery.".to_string()
+            ));
+            scenes.push(ending);
+        }
+
+        // The opening leads into the first chapter, each chapter into the
+        // next, and the last one into the ending or back to the start
+        let onward: Vec<(String, String)> = self.chapters.iter().enumerate()
+            .map(|(index, title)| (chapter_id(index), format!("Begin {}", title)))
+            .chain(self.with_ending.then(|| (TEMPLATE_ENDING.to_string(), "Finish the adventure".to_string())))
+            .collect();
+        let mut path = vec![0];
+        path.extend(3..3 + self.chapters.len());
+        for (from, (target, text)) in path.iter().zip(&onward) {
+            scenes[*from].add_choice(Choice::new("onward".to_string(), text.clone(), target.clone()));
+        }
+        if !self.chapters.is_empty() && !self.with_ending {
+            let last = scenes.len() - 1;
+            scenes[last].add_choice(Choice::new("return", "Return to the beginning", "start"));
+        }
+
+        for scene in scenes {
+            story.add_scene(scene);
+        }
+        story
+    }
+}
+
+fn chapter_id(index: usize) -> String {
+    format!("chapter_{}", index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::PathAnalysis;
+
+    #[test]
+    fn test_story_template() {
+        let basic = StoryTemplate::new("tale", "A Tale", "Ada").build();
+        assert_eq!(basic.scenes.len(), 3);
+        assert_eq!(basic.validate(), Ok(()));
+
+        let story = StoryTemplate::new("tale", "A Tale", "Ada")
+            .with_chapters(vec!["The Road".to_string(), "The Keep".to_string()])
+            .with_item()
+            .with_ending()
+            .build();
+        assert_eq!(story.validate(), Ok(()));
+        assert!(story.items.contains_key(TEMPLATE_ITEM));
+        let shortest = PathAnalysis::of(&story).shortest.unwrap();
+        assert_eq!(shortest.scenes, ["start", "chapter_1", "chapter_2", "ending"]);
+        assert!(story.author_notes().is_some());
+    }
+}
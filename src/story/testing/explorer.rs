@@ -0,0 +1,521 @@
+//! Plays every path through a story (up to a depth) and checks invariants
+//! the author declares, printing the choices that break them. Stories list
+//! invariants under `metadata.invariants`, e.g.
+//! `["health >= 0", "flag has_key before scene vault"]`. Scenes the player
+//! can reach with every choice disabled are reported as soft locks.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use chrono::Utc;
+use serde_json::Value;
+use crate::core::{GameEngine, GameState, SeededRng, StateSnapshot};
+use crate::story::Story;
+use crate::utils::{GameError, GameResult};
+
+/// Path step for a scene's timer running out.
+const AUTO_ADVANCE_STEP: &str = "(auto-advance)";
+
+/// Something that must hold in every state the explorer reaches.
+#[derive(Clone)]
+pub enum Invariant {
+    /// `<stat, derived stat or flag> <op> <number>`, e.g. `health >= 0`.
+    Compare { variable: String, op: String, value: i64 },
+    /// The flag is set whenever the player is in the scene.
+    FlagBeforeScene { flag: String, scene_id: String },
+    Custom { name: String, check: Arc<dyn Fn(&GameState) -> bool + Send + Sync> },
+}
+
+const OPERATORS: [&str; 6] = [">=", "<=", "==", "!=", ">", "<"];
+
+impl Invariant {
+    pub fn parse(source: &str) -> GameResult<Self> {
+        let words: Vec<&str> = source.split_whitespace().collect();
+        if let ["flag", flag, "before", "scene", scene_id] = words[..] {
+            return Ok(Self::FlagBeforeScene { flag: flag.to_string(), scene_id: scene_id.to_string() });
+        }
+
+        let invalid = || GameError::story(format!("Invalid invariant '{}'", source));
+        let op = OPERATORS.iter().find(|op| source.contains(*op)).ok_or_else(invalid)?;
+        let (variable, value) = source.split_once(op).ok_or_else(invalid)?;
+        let variable = variable.trim();
+        if variable.is_empty() || variable.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        Ok(Self::Compare {
+            variable: variable.to_string(),
+            op: op.to_string(),
+            value: value.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+
+    pub fn custom<S, F>(name: S, check: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&GameState) -> bool + Send + Sync + 'static,
+    {
+        Self::Custom { name: name.into(), check: Arc::new(check) }
+    }
+
+    /// The invariants declared in the story's metadata.
+    pub fn from_story(story: &Story) -> GameResult<Vec<Self>> {
+        let Some(declared) = story.metadata.as_ref().and_then(|metadata| metadata.get("invariants")) else {
+            return Ok(Vec::new());
+        };
+        declared.as_array()
+            .ok_or_else(|| GameError::story("metadata.invariants must be a list"))?
+            .iter()
+            .map(|source| source.as_str()
+                .ok_or_else(|| GameError::story(format!("Invalid invariant {}", source)))
+                .and_then(Self::parse))
+            .collect()
+    }
+
+    pub fn holds(&self, story: &Story, game_state: &GameState) -> bool {
+        match self {
+            Self::Compare { variable, op, value } => {
+                let actual = match game_state.player.stats.get(variable) {
+                    Some(stat) => stat as i64,
+                    None => story.derived_stat(variable, game_state).ok().flatten()
+                        .unwrap_or_else(|| game_state.get_flag_as_i64(variable)),
+                };
+                match op.as_str() {
+                    ">=" => actual >= *value,
+                    "<=" => actual <= *value,
+                    ">" => actual > *value,
+                    "<" => actual < *value,
+                    "==" => actual == *value,
+                    _ => actual != *value,
+                }
+            }
+            Self::FlagBeforeScene { flag, scene_id } => {
+                &game_state.current_scene_id != scene_id || game_state.get_flag_as_bool(flag)
+            }
+            Self::Custom { check, .. } => check(game_state),
+        }
+    }
+}
+
+impl fmt::Display for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare { variable, op, value } => write!(f, "{} {} {}", variable, op, value),
+            Self::FlagBeforeScene { flag, scene_id } => write!(f, "flag {} before scene {}", flag, scene_id),
+            Self::Custom { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+impl fmt::Debug for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invariant({})", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Strategy {
+    /// Breadth-first over every distinct state, so counterexamples are as
+    /// short as possible.
+    Exhaustive,
+    /// Random playthroughs, for stories too big to explore exhaustively.
+    RandomWalks { walks: usize, seed: u64 },
+}
+
+/// A path that breaks an invariant, or makes the engine fail.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterexample {
+    /// The invariant, or the engine error.
+    pub broken: String,
+    pub scene_id: String,
+    /// Choice IDs from the start of the story.
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "✗ {}\n  in scene '{}' after: {}", self.broken, self.scene_id, path_text(&self.path))
+    }
+}
+
+/// A reachable state in which the current scene isn't an ending but every
+/// choice is disabled, so the player is stuck.
+#[derive(Debug, Clone)]
+pub struct SoftLock {
+    pub scene_id: String,
+    pub path: Vec<String>,
+    /// Each choice of the scene and why it's disabled.
+    pub blocked: Vec<(String, String)>,
+    pub state: GameState,
+}
+
+impl fmt::Display for SoftLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "⛔ soft lock in scene '{}' after: {}", self.scene_id, path_text(&self.path))?;
+        for (choice_id, reason) in &self.blocked {
+            writeln!(f, "  {} is disabled: {}", choice_id, reason)?;
+        }
+        let mut flags: Vec<String> = self.state.flags.iter()
+            .map(|(flag, value)| format!("{}={}", flag, value))
+            .collect();
+        flags.sort();
+        let items: Vec<String> = self.state.player.inventory.iter()
+            .map(|item| format!("{} x{}", item.id, item.quantity))
+            .collect();
+        writeln!(f, "  flags: {}", if flags.is_empty() { "none".to_string() } else { flags.join(", ") })?;
+        write!(f, "  items: {}", if items.is_empty() { "none".to_string() } else { items.join(", ") })
+    }
+}
+
+fn path_text(path: &[String]) -> String {
+    if path.is_empty() { "(start)".to_string() } else { path.join(" → ") }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExplorationReport {
+    pub states: usize,
+    pub endings: usize,
+    /// Exploration stopped at the state limit before running out of paths.
+    pub truncated: bool,
+    /// The first counterexample found for each invariant or error.
+    pub counterexamples: Vec<Counterexample>,
+    /// The first soft lock found in each scene.
+    pub soft_locks: Vec<SoftLock>,
+}
+
+impl ExplorationReport {
+    pub fn passed(&self) -> bool {
+        self.counterexamples.is_empty() && self.soft_locks.is_empty()
+    }
+
+    fn record(&mut self, broken: String, scene_id: &str, path: &[String]) {
+        if self.counterexamples.iter().all(|c| c.broken != broken) {
+            self.counterexamples.push(Counterexample { broken, scene_id: scene_id.to_string(), path: path.to_vec() });
+        }
+    }
+}
+
+impl fmt::Display for ExplorationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Explored {} states, {} reaching an ending", self.states, self.endings)?;
+        if self.truncated {
+            write!(f, " (stopped at the state limit)")?;
+        }
+        writeln!(f)?;
+        for counterexample in &self.counterexamples {
+            writeln!(f, "{}", counterexample)?;
+        }
+        for soft_lock in &self.soft_locks {
+            writeln!(f, "{}", soft_lock)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Explorer {
+    invariants: Vec<Invariant>,
+    strategy: Strategy,
+    max_depth: usize,
+    max_states: usize,
+}
+
+impl Explorer {
+    pub fn new(invariants: Vec<Invariant>) -> Self {
+        Self {
+            invariants,
+            strategy: Strategy::Exhaustive,
+            max_depth: 50,
+            max_states: 10_000,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_max_states(mut self, max_states: usize) -> Self {
+        self.max_states = max_states;
+        self
+    }
+
+    pub async fn explore(&self, story: Story) -> GameResult<ExplorationReport> {
+        let mut engine = GameEngine::new();
+        engine.load_story(story.clone()).await?;
+        engine.start_new_game("Explorer".to_string()).await?;
+        let start = engine.get_game_state().cloned()
+            .ok_or_else(|| GameError::story("No active game"))?;
+
+        let mut report = ExplorationReport::default();
+        match &self.strategy {
+            Strategy::Exhaustive => self.explore_exhaustively(&mut engine, &story, start, &mut report).await?,
+            Strategy::RandomWalks { walks, seed } => {
+                let mut rng = SeededRng::new(*seed);
+                for _ in 0..*walks {
+                    self.random_walk(&mut engine, &story, start.clone(), &mut rng, &mut report).await?;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn explore_exhaustively(
+        &self,
+        engine: &mut GameEngine,
+        story: &Story,
+        start: GameState,
+        report: &mut ExplorationReport,
+    ) -> GameResult<()> {
+        let mut seen = HashSet::from([state_key(&start)]);
+        let mut queue = VecDeque::from([(start, Vec::new())]);
+
+        while let Some((game_state, path)) = queue.pop_front() {
+            if report.states >= self.max_states {
+                report.truncated = true;
+                break;
+            }
+            let Some(steps) = self.visit(engine, story, &game_state, &path, report).await? else {
+                continue;
+            };
+            if path.len() >= self.max_depth {
+                continue;
+            }
+            for step in steps {
+                let Some(next) = self.take_step(engine, &game_state, &step, &path, report).await? else {
+                    continue;
+                };
+                if seen.insert(state_key(&next)) {
+                    let mut next_path = path.clone();
+                    next_path.push(step);
+                    queue.push_back((next, next_path));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn random_walk(
+        &self,
+        engine: &mut GameEngine,
+        story: &Story,
+        mut game_state: GameState,
+        rng: &mut SeededRng,
+        report: &mut ExplorationReport,
+    ) -> GameResult<()> {
+        let mut path = Vec::new();
+        while report.states < self.max_states {
+            let Some(steps) = self.visit(engine, story, &game_state, &path, report).await? else {
+                return Ok(());
+            };
+            if steps.is_empty() || path.len() >= self.max_depth {
+                return Ok(());
+            }
+            let step = steps[rng.below(steps.len())].clone();
+            let Some(next) = self.take_step(engine, &game_state, &step, &path, report).await? else {
+                return Ok(());
+            };
+            path.push(step);
+            game_state = next;
+        }
+        report.truncated = true;
+        Ok(())
+    }
+
+    /// Checks the invariants in `game_state` and returns the steps that can
+    /// be taken from it, or `None` if it's an ending.
+    async fn visit(
+        &self,
+        engine: &mut GameEngine,
+        story: &Story,
+        game_state: &GameState,
+        path: &[String],
+        report: &mut ExplorationReport,
+    ) -> GameResult<Option<Vec<String>>> {
+        report.states += 1;
+        for invariant in &self.invariants {
+            if !invariant.holds(story, game_state) {
+                report.record(invariant.to_string(), &game_state.current_scene_id, path);
+            }
+        }
+
+        set_state(engine, game_state).await?;
+        let scene = engine.get_current_scene().await?;
+        if scene.is_ending() {
+            report.endings += 1;
+            return Ok(None);
+        }
+
+        let mut steps: Vec<String> = scene.available_choices().map(|choice| choice.id.clone()).collect();
+        if scene.scene.auto_advance.is_some() {
+            steps.push(AUTO_ADVANCE_STEP.to_string());
+        }
+
+        let scene_id = scene.id();
+        if steps.is_empty() && report.soft_locks.iter().all(|lock| lock.scene_id != scene_id) {
+            report.soft_locks.push(SoftLock {
+                scene_id: scene_id.to_string(),
+                path: path.to_vec(),
+                blocked: scene.choices()
+                    .map(|view| (view.choice.id.clone(), view.disabled_reason().unwrap_or_default().to_string()))
+                    .collect(),
+                state: game_state.clone(),
+            });
+        }
+        Ok(Some(steps))
+    }
+
+    /// The state after taking `step` from `game_state`. Engine errors are
+    /// recorded as counterexamples; vetoed choices are skipped.
+    async fn take_step(
+        &self,
+        engine: &mut GameEngine,
+        game_state: &GameState,
+        step: &str,
+        path: &[String],
+        report: &mut ExplorationReport,
+    ) -> GameResult<Option<GameState>> {
+        set_state(engine, game_state).await?;
+        let result = if step == AUTO_ADVANCE_STEP {
+            engine.auto_advance().await
+        } else {
+            engine.make_choice(step).await
+        };
+
+        match result {
+            Ok(()) => Ok(engine.get_game_state().cloned()),
+            Err(error) if matches!(error.inner(), GameError::ChoiceVetoed { .. }) => Ok(None),
+            Err(error) => {
+                let mut path = path.to_vec();
+                path.push(step.to_string());
+                report.record(error.to_string(), &game_state.current_scene_id, &path);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Puts `game_state` into the engine and clears the event log, so long
+/// explorations don't pile up events.
+async fn set_state(engine: &mut GameEngine, game_state: &GameState) -> GameResult<()> {
+    engine.restore(StateSnapshot {
+        game_state: Some(game_state.clone()),
+        last_event_id: None,
+        taken_at: Utc::now(),
+    }).await
+}
+
+/// What makes two states play differently. The order scenes were visited in
+/// is left out, but which scenes were is kept for `SceneVisited` conditions.
+fn state_key(game_state: &GameState) -> String {
+    let visited: std::collections::BTreeSet<&String> = game_state.visited_scenes.scene_ids().collect();
+    let mut player = serde_json::to_value(&game_state.player).unwrap_or(Value::Null);
+    if let Some(player) = player.as_object_mut() {
+        player.remove("id");
+    }
+    serde_json::json!({
+        "scene": game_state.current_scene_id,
+        "visited": visited,
+        "flags": game_state.flags,
+        "player": player,
+        "character": game_state.active_character,
+        "characters": game_state.characters,
+    }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Choice, Condition, Effect, EffectType, Scene};
+
+    fn vault_story() -> Story {
+        let mut story = Story::new("vault", "Vault", "hall", PlayerStats::default());
+        let mut hall = Scene::new("hall", "Hall", "Two doors.");
+        let mut take_key = Choice::new("take_key", "Take the key", "hall");
+        take_key.effects = Some(vec![Effect {
+            effect_type: EffectType::SetFlag,
+            key: "has_key".to_string(),
+            value: serde_json::json!(true),
+            operation: None,
+        }]);
+        hall.add_choice(take_key);
+        hall.add_choice(Choice::new("to_corridor", "Walk on", "corridor"));
+        let mut corridor = Scene::new("corridor", "Corridor", "A long corridor.");
+        corridor.add_choice(Choice::new("to_vault", "Open the vault", "vault"));
+        let mut vault = Scene::new("vault", "Vault", "Gold everywhere.");
+        vault.is_ending = Some(true);
+        story.add_scene(hall);
+        story.add_scene(corridor);
+        story.add_scene(vault);
+        story
+    }
+
+    #[test]
+    fn test_parse_invariant() {
+        assert!(matches!(
+            Invariant::parse("health >= 0").unwrap(),
+            Invariant::Compare { ref variable, ref op, value: 0 } if variable == "health" && op == ">="
+        ));
+        assert!(matches!(
+            Invariant::parse("flag has_key before scene vault").unwrap(),
+            Invariant::FlagBeforeScene { .. }
+        ));
+        assert!(Invariant::parse("health is fine").is_err());
+        assert!(Invariant::parse("max health > 1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_explorer_finds_shortest_counterexample() {
+        let invariants = vec![
+            Invariant::parse("health >= 0").unwrap(),
+            Invariant::parse("flag has_key before scene vault").unwrap(),
+        ];
+        let report = Explorer::new(invariants).explore(vault_story()).await.unwrap();
+
+        assert_eq!(report.endings, 2);
+        assert!(!report.truncated);
+        assert_eq!(report.counterexamples, vec![Counterexample {
+            broken: "flag has_key before scene vault".to_string(),
+            scene_id: "vault".to_string(),
+            path: vec!["to_corridor".to_string(), "to_vault".to_string()],
+        }]);
+    }
+
+    #[tokio::test]
+    async fn test_soft_lock() {
+        let mut story = Story::new("gate", "Gate", "yard", PlayerStats::default());
+        let mut yard = Scene::new("yard", "Yard", "A locked gate.");
+        yard.add_choice(Choice::new("to_gate", "Walk to the gate", "gate"));
+        let mut gate = Scene::new("gate", "Gate", "It is locked.");
+        gate.add_choice(Choice::new("open", "Unlock the gate", "keep")
+            .with_conditions(vec![Condition::has_item("key", 1)]));
+        let mut keep = Scene::new("keep", "Keep", "Home at last.");
+        keep.is_ending = Some(true);
+        story.add_scene(yard);
+        story.add_scene(gate);
+        story.add_scene(keep);
+
+        let report = Explorer::new(Vec::new()).explore(story).await.unwrap();
+        assert!(!report.passed());
+        let [lock] = &report.soft_locks[..] else { panic!("expected one soft lock") };
+        assert_eq!(lock.scene_id, "gate");
+        assert_eq!(lock.path, vec!["to_gate".to_string()]);
+        assert_eq!(lock.blocked, vec![("open".to_string(), "Requires: Key".to_string())]);
+
+        assert!(Explorer::new(Vec::new()).explore(vault_story()).await.unwrap().passed());
+    }
+
+    #[tokio::test]
+    async fn test_random_walks() {
+        let explorer = Explorer::new(vec![Invariant::custom("never in the vault", |state| state.current_scene_id != "vault")])
+            .with_strategy(Strategy::RandomWalks { walks: 20, seed: 7 })
+            .with_max_depth(10);
+        let report = explorer.explore(vault_story()).await.unwrap();
+        assert!(!report.passed());
+    }
+}
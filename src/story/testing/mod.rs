@@ -0,0 +1,5 @@
+//! Tools for testing stories rather than playing them.
+
+pub mod explorer;
+
+pub use explorer::{Counterexample, ExplorationReport, Explorer, Invariant, SoftLock, Strategy};
@@ -0,0 +1,159 @@
+//! Declared flags. A story that lists its flags under `variables`, e.g.
+//! `"gold": {"type": "number", "default": 10}`, is checked strictly: every
+//! flag a condition or effect uses must be declared, with values of its
+//! type. New games start with each declared flag at its default.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::story::lint::is_typo_of;
+use crate::story::{ComparisonOperator, Condition, ConditionType, Effect, EffectType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    Bool,
+    Number,
+    Text,
+}
+
+impl VariableType {
+    pub fn accepts(&self, value: &Value) -> bool {
+        match self {
+            VariableType::Bool => value.is_boolean(),
+            VariableType::Number => value.is_i64(),
+            VariableType::Text => value.is_string(),
+        }
+    }
+
+    fn zero(&self) -> Value {
+        match self {
+            VariableType::Bool => Value::Bool(false),
+            VariableType::Number => Value::from(0),
+            VariableType::Text => Value::from(""),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            VariableType::Bool => "bool",
+            VariableType::Number => "number",
+            VariableType::Text => "text",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variable {
+    #[serde(rename = "type")]
+    pub variable_type: VariableType,
+    /// Starting value; false, 0 or "" if left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+impl Variable {
+    pub fn new(variable_type: VariableType) -> Self {
+        Self { variable_type, default: None }
+    }
+
+    pub fn with_default(mut self, value: Value) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    pub fn initial_value(&self) -> Value {
+        self.default.clone().unwrap_or_else(|| self.variable_type.zero())
+    }
+}
+
+/// Errors for defaults that don't match their variable's type.
+pub fn declaration_errors(variables: &BTreeMap<String, Variable>) -> Vec<String> {
+    variables.iter()
+        .filter(|(_, variable)| variable.default.as_ref().is_some_and(|value| !variable.variable_type.accepts(value)))
+        .map(|(name, variable)| format!("Variable '{}': default must be a {}", name, variable.variable_type.name()))
+        .collect()
+}
+
+/// Errors for flags of `owner`'s conditions and effects that aren't
+/// declared in `variables`, or are used with values of another type. Stories
+/// that declare no variables aren't checked.
+pub fn reference_errors(owner: &str, effects: &[Effect], conditions: &[Condition], variables: &BTreeMap<String, Variable>) -> Vec<String> {
+    if variables.is_empty() {
+        return Vec::new();
+    }
+    let mut errors = Vec::new();
+    let mut check = |key: &str, expected: Option<VariableType>| {
+        match variables.get(key) {
+            None => errors.push(undeclared(owner, key, variables)),
+            Some(variable) => match expected {
+                Some(expected) if expected != variable.variable_type => errors.push(format!(
+                    "{}: Variable '{}' is a {}, not a {}", owner, key, variable.variable_type.name(), expected.name()
+                )),
+                _ => {}
+            },
+        }
+    };
+
+    for effect in effects.iter().filter(|effect| matches!(effect.effect_type, EffectType::SetFlag)) {
+        let expected = match effect.operation {
+            Some(_) if effect.value.is_i64() => Some(VariableType::Number),
+            _ => value_type(&effect.value),
+        };
+        check(&effect.key, expected);
+    }
+    for condition in Condition::leaves(conditions).into_iter().filter(|condition| matches!(condition.condition_type, ConditionType::Flag)) {
+        let expected = match condition.operator {
+            ComparisonOperator::Has | ComparisonOperator::NotHas => None,
+            ComparisonOperator::Contains | ComparisonOperator::NotContains => Some(VariableType::Text),
+            ComparisonOperator::GreaterThan | ComparisonOperator::LessThan
+            | ComparisonOperator::GreaterEqual | ComparisonOperator::LessEqual => Some(VariableType::Number),
+            ComparisonOperator::Equals | ComparisonOperator::NotEquals => value_type(&condition.value),
+        };
+        check(&condition.key, expected);
+    }
+    errors
+}
+
+/// Error for a flag that isn't declared, suggesting the declared name it
+/// was likely meant to be.
+pub fn undeclared(owner: &str, key: &str, variables: &BTreeMap<String, Variable>) -> String {
+    match variables.keys().find(|name| is_typo_of(key, name)) {
+        Some(name) => format!("{}: Undeclared variable '{}' (did you mean '{}'?)", owner, key, name),
+        None => format!("{}: Undeclared variable '{}'", owner, key),
+    }
+}
+
+fn value_type(value: &Value) -> Option<VariableType> {
+    [VariableType::Bool, VariableType::Number, VariableType::Text].into_iter()
+        .find(|variable_type| variable_type.accepts(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variable_references() {
+        let variables: BTreeMap<String, Variable> = serde_json::from_value(serde_json::json!({
+            "gold": {"type": "number", "default": 10},
+            "has_key": {"type": "bool"},
+            "password": {"type": "text", "default": 3}
+        })).unwrap();
+        assert_eq!(variables["has_key"].initial_value(), Value::Bool(false));
+        assert_eq!(declaration_errors(&variables), ["Variable 'password': default must be a text"]);
+
+        let effects = [Effect::add_to_flag("gold", 5), Effect::set_flag("has_kye", true), Effect::set_flag("gold", true)];
+        let conditions = [
+            Condition::flag_equals("has_key", true),
+            Condition::new(ConditionType::Flag, "password".to_string(), ComparisonOperator::GreaterThan, Value::from(1)),
+            Condition::new(ConditionType::Flag, "password".to_string(), ComparisonOperator::Has, Value::Null),
+        ];
+        assert_eq!(reference_errors("Choice 'open'", &effects, &conditions, &variables), [
+            "Choice 'open': Undeclared variable 'has_kye' (did you mean 'has_key'?)",
+            "Choice 'open': Variable 'gold' is a number, not a bool",
+            "Choice 'open': Variable 'password' is a text, not a number",
+        ]);
+        assert!(reference_errors("Choice 'open'", &effects, &conditions, &BTreeMap::new()).is_empty());
+    }
+}
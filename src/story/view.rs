@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::core::SeededRng;
+use crate::story::{Scene, Choice};
+use crate::story::lint::is_typo_of;
+use crate::utils::GameError;
+
+const DEFAULT_DISABLED_REASON: &str = "Requirements not met";
+
+/// A scene as seen by the player: the shared story scene plus whether each
+/// choice is currently available. Cheap to build since nothing is cloned.
+#[derive(Debug, Clone)]
+pub struct SceneView {
+    pub scene: Arc<Scene>,
+    available: Vec<bool>,
+    /// Reasons generated from each choice's failing condition, if any.
+    reasons: Vec<Option<String>>,
+    /// Indices of the scene's choices in display order.
+    order: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ChoiceView<'a> {
+    pub choice: &'a Choice,
+    pub disabled: bool,
+    generated_reason: Option<&'a str>,
+}
+
+impl SceneView {
+    pub fn new(scene: Arc<Scene>, available: Vec<bool>) -> Self {
+        debug_assert_eq!(scene.choices.len(), available.len());
+        let reasons = vec![None; available.len()];
+        let order = display_order(&scene.choices);
+        Self { scene, available, reasons, order }
+    }
+
+    /// Sets the reasons shown for disabled choices that have no
+    /// `disabled_reason` of their own.
+    pub fn with_disabled_reasons(mut self, reasons: Vec<Option<String>>) -> Self {
+        debug_assert_eq!(self.available.len(), reasons.len());
+        self.reasons = reasons;
+        self
+    }
+
+    /// Shuffles the display order, keeping grouped choices together under
+    /// their heading.
+    pub fn with_shuffled_choices(mut self, rng: &mut SeededRng) -> Self {
+        let choices = &self.scene.choices;
+        for run in self.order.chunk_by_mut(|&a, &b| choices[a].group == choices[b].group) {
+            rng.shuffle(run);
+        }
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.scene.id
+    }
+
+    pub fn is_ending(&self) -> bool {
+        self.scene.is_ending()
+    }
+
+    /// The scene's choices in display order: grouped, then by `order`.
+    pub fn choices(&self) -> impl Iterator<Item = ChoiceView<'_>> {
+        self.order.iter().map(|&index| ChoiceView {
+            choice: &self.scene.choices[index],
+            disabled: !self.available[index],
+            generated_reason: self.reasons[index].as_deref(),
+        })
+    }
+
+    pub fn available_choices(&self) -> impl Iterator<Item = &Choice> {
+        self.choices()
+            .filter(|view| !view.disabled)
+            .map(|view| view.choice)
+    }
+
+    pub fn get_choice(&self, choice_id: &str) -> Option<ChoiceView<'_>> {
+        self.choices().find(|view| view.choice.id == choice_id)
+    }
+
+    /// Error for a choice this scene doesn't have, suggesting the one
+    /// likely meant.
+    pub fn missing_choice(&self, choice_id: &str) -> GameError {
+        let similar = self.choices()
+            .map(|view| view.choice.id.as_str())
+            .filter(|id| is_typo_of(choice_id, id))
+            .min();
+        GameError::choice_not_found(choice_id)
+            .with_scene(self.id())
+            .with_suggestion("choice", similar)
+    }
+}
+
+/// Groups are listed in the order they first appear in the story; within a
+/// group choices sort by `order`, then by story order.
+fn display_order(choices: &[Choice]) -> Vec<usize> {
+    let mut group_rank = HashMap::new();
+    for choice in choices {
+        let rank = group_rank.len();
+        group_rank.entry(choice.group.as_deref()).or_insert(rank);
+    }
+
+    let mut order: Vec<usize> = (0..choices.len()).collect();
+    order.sort_by_key(|&index| {
+        let choice = &choices[index];
+        (group_rank[&choice.group.as_deref()], choice.order.unwrap_or(0))
+    });
+    order
+}
+
+impl<'a> ChoiceView<'a> {
+    /// Why the choice is disabled: the author's `disabled_reason`, else one
+    /// generated from the failing condition, else a generic message.
+    pub fn disabled_reason(&self) -> Option<&'a str> {
+        if self.disabled {
+            let reason = self.choice.disabled_reason.as_deref()
+                .or(self.generated_reason)
+                .unwrap_or(DEFAULT_DISABLED_REASON);
+            Some(reason)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scene_view_availability() {
+        let mut scene = Scene::new("start", "Start", "Starting scene");
+        scene.add_choice(Choice::new("open", "Open", "next"));
+        scene.add_choice(Choice::new("locked", "Locked", "vault").disabled_with_reason("Needs a key"));
+        scene.add_choice(Choice::new("hidden", "Hidden", "next"));
+
+        let view = SceneView::new(Arc::new(scene), vec![true, false, false]);
+
+        let available: Vec<&str> = view.available_choices().map(|c| c.id.as_str()).collect();
+        assert_eq!(available, vec!["open"]);
+        assert_eq!(view.get_choice("locked").unwrap().disabled_reason(), Some("Needs a key"));
+        assert_eq!(view.get_choice("hidden").unwrap().disabled_reason(), Some("Requirements not met"));
+        assert_eq!(view.get_choice("open").unwrap().disabled_reason(), None);
+
+        let view = view.with_disabled_reasons(vec![None, Some("Requires Strength 15".to_string()), Some("Requires: Torch".to_string())]);
+        assert_eq!(view.get_choice("locked").unwrap().disabled_reason(), Some("Needs a key"));
+        assert_eq!(view.get_choice("hidden").unwrap().disabled_reason(), Some("Requires: Torch"));
+    }
+
+    #[test]
+    fn test_choice_grouping() {
+        let mut scene = Scene::new("tavern", "Tavern", "A busy tavern");
+        scene.add_choice(Choice::new("ask", "Ask about the ring", "next").with_group("Talk").with_order(2));
+        scene.add_choice(Choice::new("punch", "Punch the bard", "next").with_group("Fight"));
+        scene.add_choice(Choice::new("greet", "Greet the barkeep", "next").with_group("Talk").with_order(1));
+        scene.add_choice(Choice::new("leave", "Leave", "next"));
+        scene.add_choice(Choice::new("gossip", "Listen to gossip", "next").with_group("Talk").with_order(2));
+
+        let view = SceneView::new(Arc::new(scene), vec![true; 5]);
+        let ids: Vec<&str> = view.choices().map(|view| view.choice.id.as_str()).collect();
+        assert_eq!(ids, vec!["greet", "ask", "gossip", "punch", "leave"]);
+
+        let shuffled = view.clone().with_shuffled_choices(&mut SeededRng::new(7));
+        let groups: Vec<Option<&str>> = shuffled.choices().map(|view| view.choice.group.as_deref()).collect();
+        assert_eq!(groups, vec![Some("Talk"), Some("Talk"), Some("Talk"), Some("Fight"), None]);
+        assert!(shuffled.get_choice("gossip").is_some());
+    }
+}
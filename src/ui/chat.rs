@@ -0,0 +1,134 @@
+//! Just enough IRC for chat frontends: log in, join a channel, read what
+//! people say and answer. Plain TCP only, which Twitch and most networks
+//! still accept on port 6667.
+
+/// A chat line someone sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub sender: String,
+    /// Channel, or our own nick for private messages.
+    pub target: String,
+    pub text: String,
+}
+
+/// The server lines chat frontends act on; everything else is ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IrcLine {
+    Ping(String),
+    Message(ChatMessage),
+}
+
+impl IrcLine {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        // Twitch puts IRCv3 tags in front
+        let line = match line.strip_prefix('@') {
+            Some(tagged) => tagged.split_once(' ')?.1,
+            None => line,
+        };
+        let (prefix, rest) = match line.strip_prefix(':') {
+            Some(prefixed) => {
+                let (prefix, rest) = prefixed.split_once(' ')?;
+                (Some(prefix), rest)
+            }
+            None => (None, line),
+        };
+        let (command, params) = rest.split_once(' ').unwrap_or((rest, ""));
+        match command {
+            "PING" => Some(IrcLine::Ping(params.trim_start_matches(':').to_string())),
+            "PRIVMSG" => {
+                let (target, text) = params.split_once(" :")?;
+                let sender = prefix?.split('!').next()?;
+                Some(IrcLine::Message(ChatMessage {
+                    sender: sender.to_string(),
+                    target: target.to_string(),
+                    text: text.to_string(),
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(feature = "twitch", feature = "irc"))]
+pub use client::IrcClient;
+
+#[cfg(any(feature = "twitch", feature = "irc"))]
+mod client {
+    use std::io;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+    use tokio::net::TcpStream;
+    use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+    use tokio::time::{timeout_at, Instant};
+    use super::{ChatMessage, IrcLine};
+
+    pub struct IrcClient {
+        lines: Lines<BufReader<OwnedReadHalf>>,
+        writer: OwnedWriteHalf,
+    }
+
+    impl IrcClient {
+        /// Connects to `server` (`host:port`) and logs in as `nick`.
+        pub async fn connect(server: &str, nick: &str, password: Option<&str>) -> io::Result<Self> {
+            let (reader, writer) = TcpStream::connect(server).await?.into_split();
+            let mut client = Self { lines: BufReader::new(reader).lines(), writer };
+            if let Some(password) = password {
+                client.send(&format!("PASS {}", password)).await?;
+            }
+            client.send(&format!("NICK {}", nick)).await?;
+            client.send(&format!("USER {} 0 * :{}", nick, nick)).await?;
+            Ok(client)
+        }
+
+        pub async fn join(&mut self, channel: &str) -> io::Result<()> {
+            self.send(&format!("JOIN {}", channel)).await
+        }
+
+        /// Says `text` to `target`, one message per line.
+        pub async fn say(&mut self, target: &str, text: &str) -> io::Result<()> {
+            for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                self.send(&format!("PRIVMSG {} :{}", target, line)).await?;
+            }
+            Ok(())
+        }
+
+        /// The next chat message, answering pings while waiting; `None` if
+        /// nothing came before `deadline`.
+        pub async fn next_message(&mut self, deadline: Instant) -> io::Result<Option<ChatMessage>> {
+            loop {
+                let Ok(line) = timeout_at(deadline, self.lines.next_line()).await else {
+                    return Ok(None);
+                };
+                let line = line?.ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "chat server closed the connection"))?;
+                match IrcLine::parse(&line) {
+                    Some(IrcLine::Ping(token)) => self.send(&format!("PONG :{}", token)).await?,
+                    Some(IrcLine::Message(message)) => return Ok(Some(message)),
+                    None => {}
+                }
+            }
+        }
+
+        async fn send(&mut self, line: &str) -> io::Result<()> {
+            self.writer.write_all(format!("{}\r\n", line).as_bytes()).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_irc_lines() {
+        assert_eq!(IrcLine::parse("PING :tmi.twitch.tv\r\n"), Some(IrcLine::Ping("tmi.twitch.tv".to_string())));
+        assert_eq!(
+            IrcLine::parse("@badges=;color=#FF0000 :ana!ana@ana.tmi.twitch.tv PRIVMSG #stream :!2 go left"),
+            Some(IrcLine::Message(ChatMessage {
+                sender: "ana".to_string(),
+                target: "#stream".to_string(),
+                text: "!2 go left".to_string(),
+            }))
+        );
+        assert_eq!(IrcLine::parse(":tmi.twitch.tv 001 justinfan1 :Welcome, GLHF!"), None);
+    }
+}
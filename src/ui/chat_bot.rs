@@ -0,0 +1,374 @@
+//! Chat bot frontends: each room (IRC channel, Matrix room, ...) the bot is
+//! in plays its own game. Scenes are posted to the room and anyone there
+//! picks choices with `!1`, `!2`, ...; `!help` lists the other commands.
+//! After every choice the room's game is saved through `SaveManager`, so a
+//! restarted bot picks up where each room left off. The transports live in
+//! `irc_bot` and `matrix_bot`.
+//!
+//! `config.bots` keeps a public bot stable: users sending commands too fast
+//! are ignored, only so many rooms may play at once, and quiet rooms' games
+//! are saved and put away until they're used again.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::config::{BotConfig, Config, GameConfig};
+use crate::core::GameEngine;
+use crate::story::{SceneView, StoryLoader};
+use crate::story::loader::is_valid_story_id;
+use crate::ui::{wrap_paragraphs, TextLayout};
+use crate::utils::{GameError, GameResult, Metrics, RateLimiter, SaveManager};
+
+/// Longest text sent in one message, leaving room for the IRC framing
+/// within the protocol's 512 bytes.
+const MESSAGE_WIDTH: usize = 400;
+
+/// Name of the protagonist in bot games.
+const BOT_PLAYER: &str = "Party";
+
+const HELP: &str = "!start [story] begins a game, !1 !2 ... pick a choice, !look repeats the scene, \
+                    !stories lists the stories, !save [name] keeps a copy, !reset ends the game";
+
+/// What the bot posts back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotReply {
+    Scene {
+        title: String,
+        /// The description, wrapped to message size.
+        description: Vec<String>,
+        /// The available choices as `!N text`; empty for endings.
+        choices: Vec<String>,
+        ended: bool,
+    },
+    Text(String),
+}
+
+impl BotReply {
+    pub fn scene(scene: &SceneView) -> Self {
+        let layout = TextLayout { paragraph_spacing: 0, paragraph_indent: 0, double_spacing: false };
+        BotReply::Scene {
+            title: scene.scene.title.clone(),
+            description: wrap_paragraphs(&scene.scene.description, MESSAGE_WIDTH, &layout),
+            choices: match scene.is_ending() {
+                true => Vec::new(),
+                false => scene.available_choices().enumerate()
+                    .map(|(index, choice)| format!("!{} {}", index + 1, choice.text))
+                    .collect(),
+            },
+            ended: scene.is_ending(),
+        }
+    }
+
+    /// The reply as plain text messages: the title, the description, then
+    /// the choices on one line, or "The End" for endings.
+    pub fn lines(&self) -> Vec<String> {
+        match self {
+            BotReply::Scene { title, description, choices, ended } => {
+                let mut lines = vec![format!("== {} ==", title)];
+                lines.extend(description.iter().cloned());
+                if *ended {
+                    lines.push("The End. !start to play again".to_string());
+                } else if !choices.is_empty() {
+                    lines.push(choices.join(" | "));
+                }
+                lines
+            }
+            BotReply::Text(text) => vec![text.clone()],
+        }
+    }
+}
+
+pub struct ChatBot {
+    story_loader: StoryLoader,
+    save_manager: SaveManager,
+    /// Network the rooms are on, keeping sessions of different bots apart.
+    network: String,
+    default_story: Option<String>,
+    /// Users allowed to `!save` and `!reset`; everyone if empty.
+    admins: Vec<String>,
+    /// Games being played, by room.
+    sessions: HashMap<String, GameEngine>,
+    /// Settings of the engines the games are played with.
+    game: GameConfig,
+    /// When each room last sent a command.
+    last_active: HashMap<String, Instant>,
+    limits: BotConfig,
+    rate_limiter: RateLimiter,
+    metrics: Arc<Metrics>,
+}
+
+impl ChatBot {
+    /// A bot on `network` (e.g. "irc") playing the stories in `config`'s
+    /// stories directory.
+    pub fn new(config: &Config, network: &str) -> GameResult<Self> {
+        let mut save_manager = SaveManager::new(config.get_saves_dir());
+        if let Some(cipher) = config.save_cipher()? {
+            save_manager = save_manager.with_cipher(cipher);
+        }
+        Ok(Self {
+            story_loader: StoryLoader::new(config.get_stories_dir()),
+            save_manager,
+            network: network.to_string(),
+            default_story: None,
+            admins: Vec::new(),
+            sessions: HashMap::new(),
+            game: config.game.clone(),
+            last_active: HashMap::new(),
+            limits: config.bots.clone(),
+            rate_limiter: RateLimiter::new(config.bots.commands_per_minute),
+            metrics: Arc::new(Metrics::default()),
+        })
+    }
+
+    /// The story `!start` plays when none is named.
+    pub fn with_default_story(mut self, story_id: Option<String>) -> Self {
+        self.default_story = story_id;
+        self
+    }
+
+    /// Restricts `!save` and `!reset` to `admins`.
+    pub fn with_admins(mut self, admins: Vec<String>) -> Self {
+        self.admins = admins;
+        self
+    }
+
+    /// What the bot has been doing, for `serve_metrics`.
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Handles one message `sender` sent to `room`. Returns the replies to
+    /// post; messages that aren't commands, and commands over the sender's
+    /// rate limit, get none.
+    pub async fn handle(&mut self, room: &str, sender: &str, text: &str) -> GameResult<Vec<BotReply>> {
+        let started = Instant::now();
+        self.evict_idle(started).await;
+        if !text.trim_start().starts_with('!') {
+            return Ok(Vec::new());
+        }
+        if !self.rate_limiter.check(sender, started) {
+            tracing::debug!("Ignoring {} in {}: over the rate limit", sender, room);
+            return Ok(Vec::new());
+        }
+        self.last_active.insert(room.to_string(), started);
+
+        let replies = self.run_command(room, sender, text).await;
+        if let Ok(replies) = &replies {
+            if replies.iter().any(|reply| matches!(reply, BotReply::Scene { .. })) {
+                self.metrics.record_scene_latency(started.elapsed());
+            }
+        }
+        self.metrics.set_sessions(self.sessions.len());
+        replies
+    }
+
+    async fn run_command(&mut self, room: &str, sender: &str, text: &str) -> GameResult<Vec<BotReply>> {
+        let Some(command) = text.trim().strip_prefix('!') else {
+            return Ok(Vec::new());
+        };
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default().to_lowercase();
+        let argument = words.next();
+
+        if matches!(name.as_str(), "save" | "reset") && !self.admins.is_empty() && !self.admins.iter().any(|admin| admin == sender) {
+            return Err(GameError::player(format!("Only admins can !{}", name)));
+        }
+
+        match name.as_str() {
+            "help" => Ok(vec![BotReply::Text(HELP.to_string())]),
+            "stories" => {
+                let stories = self.story_loader.list_available_stories().await?;
+                let ids: Vec<&str> = stories.iter().map(|story| story.id.as_str()).collect();
+                Ok(vec![BotReply::Text(format!("Stories: {}", ids.join(", ")))])
+            }
+            "start" => {
+                let story_id = argument.map(str::to_string).or_else(|| self.default_story.clone())
+                    .ok_or_else(|| GameError::player("Which story? Try !stories".to_string()))?;
+                if !is_valid_story_id(&story_id) {
+                    return Err(GameError::player(format!("No story '{}'. Try !stories", story_id)));
+                }
+                self.check_quota(room)?;
+                // Story errors quote the story file; the channel only hears it failed
+                let story = self.story_loader.load_story(&story_id).await.map_err(|e| match e.inner() {
+                    GameError::Story { .. } => {
+                        tracing::warn!("Story '{}' started in {} can't be loaded: {}", story_id, room, e.report());
+                        GameError::story(format!("Story '{}' can't be loaded", story_id))
+                    }
+                    _ => e,
+                })?;
+                let mut engine = self.game.engine();
+                engine.load_story(story).await?;
+                engine.start_new_game(BOT_PLAYER.to_string()).await?;
+                self.sessions.insert(room.to_string(), engine);
+                self.save(room).await?;
+                self.scene_reply(room).await
+            }
+            "look" => self.scene_reply(room).await,
+            "save" => {
+                let name = argument.map(str::to_string).unwrap_or_else(|| format!("{} {}", self.network, room));
+                let game_state = self.session(room).await?.save_game(name.clone()).await?;
+                let user_saves = self.save_manager.for_user(&format!("{}:{}", self.network, sender))?;
+                user_saves.save_game(name.clone(), game_state, None).await?;
+                Ok(vec![BotReply::Text(format!("Saved as '{}' for {}", name, sender))])
+            }
+            "reset" => {
+                self.sessions.remove(room);
+                self.save_manager.delete_session(&self.session_key(room)).await?;
+                Ok(vec![BotReply::Text("Game over. !start to play again".to_string())])
+            }
+            number => {
+                let Ok(number) = number.parse::<usize>() else {
+                    return Ok(Vec::new());
+                };
+                let engine = self.session(room).await?;
+                let scene = engine.get_current_scene().await?;
+                let choice_id = scene.available_choices().nth(number.wrapping_sub(1))
+                    .map(|choice| choice.id.clone())
+                    .ok_or_else(|| GameError::player(format!("There's no choice {}", number)))?;
+                engine.make_choice(&choice_id).await?;
+                self.metrics.record_choice();
+                self.save(room).await?;
+                self.scene_reply(room).await
+            }
+        }
+    }
+
+    /// The room's game, resumed from its save if the bot just started.
+    async fn session(&mut self, room: &str) -> GameResult<&mut GameEngine> {
+        if !self.sessions.contains_key(room) {
+            self.check_quota(room)?;
+            let save = self.save_manager.load_session(&self.session_key(room)).await?
+                .ok_or_else(|| GameError::player("No game here yet. !start to play".to_string()))?;
+            let story = self.story_loader.load_story(&save.game_state.story_id).await?;
+            let mut engine = self.game.engine();
+            engine.load_story(story).await?;
+            engine.load_game(save.game_state).await?;
+            self.sessions.insert(room.to_string(), engine);
+        }
+        Ok(self.sessions.get_mut(room).expect("session was just resumed"))
+    }
+
+    /// Saves and puts away the games of rooms quiet since `idle_minutes`
+    /// before `now`. Returns how many were put away.
+    pub async fn evict_idle(&mut self, now: Instant) -> usize {
+        if self.limits.idle_minutes == 0 {
+            return 0;
+        }
+        let idle = Duration::from_secs(self.limits.idle_minutes * 60);
+        let quiet: Vec<String> = self.sessions.keys()
+            .filter(|room| self.last_active.get(*room).is_none_or(|&active| now.saturating_duration_since(active) >= idle))
+            .cloned()
+            .collect();
+        for room in &quiet {
+            if let Err(e) = self.save(room).await {
+                tracing::warn!("Failed to save the game in {} before putting it away: {}", room, e);
+            }
+            self.sessions.remove(room);
+            self.last_active.remove(room);
+        }
+        self.metrics.set_sessions(self.sessions.len());
+        quiet.len()
+    }
+
+    /// Refuses a new game in `room` if `max_sessions` are already going.
+    fn check_quota(&self, room: &str) -> GameResult<()> {
+        let max = self.limits.max_sessions;
+        if max > 0 && !self.sessions.contains_key(room) && self.sessions.len() >= max {
+            return Err(GameError::player(format!("{} games are already going; try again later", max)));
+        }
+        Ok(())
+    }
+
+    async fn save(&mut self, room: &str) -> GameResult<()> {
+        let key = self.session_key(room);
+        let game_state = self.session(room).await?.save_game(key.clone()).await?;
+        self.save_manager.save_session(&key, game_state).await
+            .inspect_err(|_| self.metrics.record_save_error())?;
+        Ok(())
+    }
+
+    /// The current scene. A finished game is cleared, so the next `!start`
+    /// begins afresh.
+    async fn scene_reply(&mut self, room: &str) -> GameResult<Vec<BotReply>> {
+        let scene = self.session(room).await?.get_current_scene().await?;
+        if scene.is_ending() {
+            self.sessions.remove(room);
+            self.save_manager.delete_session(&self.session_key(room)).await?;
+        }
+        Ok(vec![BotReply::scene(&scene)])
+    }
+
+    /// Name of a room's save.
+    fn session_key(&self, room: &str) -> String {
+        format!("{}:{}", self.network, room.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::PlayerStats;
+    use crate::story::{Choice, Scene, Story};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_chat_bot_sessions() {
+        let temp_dir = tempdir().unwrap();
+        let mut story = Story::new("cave", "The Cave", "mouth", PlayerStats::default());
+        let mut mouth = Scene::new("mouth", "Cave Mouth", "Darkness ahead.");
+        mouth.add_choice(Choice::new("enter", "Go in", "hall"));
+        mouth.add_choice(Choice::new("leave", "Walk away", "home"));
+        story.add_scene(mouth);
+        let mut hall = Scene::new("hall", "Great Hall", "Echoes everywhere.");
+        hall.add_choice(Choice::new("back", "Back out", "mouth"));
+        story.add_scene(hall);
+        let mut home = Scene::new("home", "Home", "You go home.");
+        home.is_ending = Some(true);
+        story.add_scene(home);
+        let stories_dir = temp_dir.path().join("stories");
+        std::fs::create_dir_all(&stories_dir).unwrap();
+        std::fs::write(stories_dir.join("cave.json"), serde_json::to_string(&story).unwrap()).unwrap();
+
+        let mut config = Config::default();
+        config.paths.stories_dir = stories_dir;
+        config.paths.saves_dir = temp_dir.path().join("saves");
+        let lines = |replies: Vec<BotReply>| replies.iter().flat_map(BotReply::lines).collect::<Vec<_>>();
+
+        let mut bot = ChatBot::new(&config, "irc").unwrap().with_default_story(Some("cave".to_string()));
+        assert!(bot.handle("#a", "ana", "hello there").await.unwrap().is_empty());
+        assert!(bot.handle("#a", "ana", "!1").await.is_err());
+        assert!(bot.handle("#a", "ana", "!start ../stories/cave").await.is_err());
+        let replies = bot.handle("#a", "ana", "!start").await.unwrap();
+        assert_eq!(lines(replies), ["== Cave Mouth ==", "Darkness ahead.", "!1 Go in | !2 Walk away"]);
+        assert_eq!(lines(bot.handle("#a", "ben", "!1").await.unwrap())[0], "== Great Hall ==");
+        assert!(bot.handle("#a", "ben", "!5").await.is_err());
+        assert!(bot.metrics().render().contains("\ntext_game_choices_total 1\n"));
+
+        // A restarted bot resumes each room from its save
+        let mut bot = ChatBot::new(&config, "irc").unwrap().with_admins(vec!["ana".to_string()]);
+        assert_eq!(lines(bot.handle("#a", "ben", "!look").await.unwrap())[0], "== Great Hall ==");
+        assert!(bot.handle("#b", "ben", "!look").await.is_err());
+        assert!(bot.handle("#a", "ben", "!save").await.is_err());
+        assert_eq!(lines(bot.handle("#a", "ana", "!save hall").await.unwrap()), ["Saved as 'hall' for ana"]);
+        let ana_saves = SaveManager::new(&config.paths.saves_dir).for_user("irc:ana").unwrap();
+        assert_eq!(ana_saves.list_save_games().await.unwrap()[0].name, "hall");
+        bot.handle("#a", "ben", "!1").await.unwrap();
+        let replies = bot.handle("#a", "ben", "!2").await.unwrap();
+        assert_eq!(lines(replies).last().unwrap(), "The End. !start to play again");
+        assert!(bot.handle("#a", "ben", "!look").await.is_err());
+
+        // Limits: one game at a time, two commands a minute per user
+        config.bots = BotConfig { commands_per_minute: 2, max_sessions: 1, idle_minutes: 30 };
+        let mut bot = ChatBot::new(&config, "irc").unwrap().with_default_story(Some("cave".to_string()));
+        bot.handle("#a", "ana", "!start").await.unwrap();
+        assert!(bot.handle("#b", "ben", "!start").await.is_err());
+        assert_eq!(bot.handle("#a", "ana", "!look").await.unwrap().len(), 1);
+        assert!(bot.handle("#a", "ana", "!look").await.unwrap().is_empty());
+
+        // A quiet room's game is put away, freeing its place, and resumes later
+        assert_eq!(bot.evict_idle(Instant::now() + Duration::from_secs(31 * 60)).await, 1);
+        bot.handle("#b", "ben", "!start").await.unwrap();
+        bot.evict_idle(Instant::now() + Duration::from_secs(31 * 60)).await;
+        assert_eq!(lines(bot.handle("#a", "cy", "!look").await.unwrap())[0], "== Cave Mouth ==");
+    }
+}
@@ -1,33 +1,413 @@
 use console::{Term, Key};
+use dialoguer::{BasicHistory, Confirm, Input, Select};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{cursor, queue, terminal};
 use std::io::{self, Write};
-use crate::ui::ThemeManager;
-use crate::core::{GameState, PlayerStats};
-use crate::story::{Scene, Choice};
+use std::time::{Duration, Instant};
+use crate::config::MIN_TEXT_WIDTH;
+use crate::ui::{HelpTopic, Renderer, ThemeManager};
+use crate::core::{DebugVariable, GameState};
+use crate::story::{Currency, MapNode, Scene, SceneMap, SceneView};
+
+/// Follows choices taken in an earlier completed playthrough.
+pub const TAKEN_MARKER: &str = "✓";
+
+/// Result of `Display::prompt_timed_choice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimedInput {
+    /// 1-based number of the choice as listed by `show_choices`.
+    Choice(usize),
+    /// The player pressed Esc to open the pause menu.
+    Pause,
+    QuickSave,
+    QuickLoad,
+    TimedOut,
+}
+
+/// Result of `Display::select_menu`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuInput {
+    /// Index of the chosen item.
+    Selected(usize),
+    /// Esc or `q`.
+    Pause,
+    /// F5.
+    QuickSave,
+    /// F9.
+    QuickLoad,
+}
+
+/// How paragraphs of scene text are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextLayout {
+    /// Blank lines between paragraphs.
+    pub paragraph_spacing: usize,
+    /// Spaces before each paragraph's first line.
+    pub paragraph_indent: usize,
+    /// A blank line between every line, on top of the paragraph spacing.
+    pub double_spacing: bool,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            paragraph_spacing: 1,
+            paragraph_indent: 0,
+            double_spacing: false,
+        }
+    }
+}
 
 pub struct Display {
     term: Term,
     theme_manager: ThemeManager,
     text_width: usize,
+    fit_to_terminal: bool,
+    layout: TextLayout,
+    show_choice_hints: bool,
+    /// Earlier answers per kind of prompt, recalled with the arrow keys.
+    input_history: RefCell<HashMap<String, BasicHistory>>,
 }
 
+/// Answers remembered per kind of prompt.
+const INPUT_HISTORY_LEN: usize = 20;
+
 impl Display {
     pub fn new(theme_manager: ThemeManager, text_width: usize) -> io::Result<Self> {
         Ok(Self {
             term: Term::stdout(),
             theme_manager,
             text_width,
+            fit_to_terminal: false,
+            layout: TextLayout::default(),
+            show_choice_hints: true,
+            input_history: RefCell::new(HashMap::new()),
         })
     }
 
-    pub fn clear_screen(&self) -> io::Result<()> {
+    pub fn with_text_layout(mut self, layout: TextLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Narrow the text to the terminal whenever it is smaller than the
+    /// configured width. The size is read on every draw, so a resized
+    /// terminal is picked up by the next screen.
+    pub fn with_fit_to_terminal(mut self, fit_to_terminal: bool) -> Self {
+        self.fit_to_terminal = fit_to_terminal;
+        self
+    }
+
+    /// List choice hints under the choices. Either way the hint of the
+    /// highlighted choice can be shown with `h`.
+    pub fn with_choice_hints(mut self, show_choice_hints: bool) -> Self {
+        self.show_choice_hints = show_choice_hints;
+        self
+    }
+
+    /// Styled amount of money, e.g. "🪙 1,250".
+    pub fn format_currency(&self, amount: i64, currency: &Currency) -> String {
+        let text = format!("{} {}", currency.symbol, group_thousands(amount));
+        self.theme_manager.apply_style(&text, "currency")
+    }
+
+    pub fn prompt_input(&self, prompt: &str) -> io::Result<String> {
+        self.prompt_text("input", prompt, None)
+    }
+
+    pub fn prompt_yes_no(&self, prompt: &str, default: bool) -> io::Result<bool> {
+        let default_text = if default { " [Y/n]" } else { " [y/N]" };
+        let full_prompt = format!("{}{} ", prompt, default_text);
+        
+        loop {
+            let input = self.prompt_input(&full_prompt)?;
+            
+            match input.to_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                "" => return Ok(default),
+                _ => {
+                    self.show_error("Please enter 'y' for yes or 'n' for no.")?;
+                    continue;
+                }
+            }
+        }
+    }
+
+    pub fn prompt_number(&self, prompt: &str, min: usize, max: usize) -> io::Result<usize> {
+        loop {
+            let input = self.prompt_input(prompt)?;
+            
+            match input.parse::<usize>() {
+                Ok(num) if num >= min && num <= max => return Ok(num),
+                Ok(_) => {
+                    self.show_error(&format!("Please enter a number between {} and {}.", min, max))?;
+                }
+                Err(_) => {
+                    self.show_error("Please enter a valid number.")?;
+                }
+            }
+        }
+    }
+
+    fn read_timed_choice(&self, max: usize, deadline: Instant) -> io::Result<TimedInput> {
+        let mut input = String::new();
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(TimedInput::TimedOut);
+            }
+
+            let prompt = format!(
+                "⏳ {}s left - choose 1-{} (Esc to pause): ",
+                remaining.as_secs() + 1,
+                max
+            );
+            let styled_prompt = self.theme_manager.apply_style(&prompt, "warning");
+            print!("\r\x1b[2K{}{}", styled_prompt, input);
+            io::stdout().flush()?;
+
+            if !event::poll(remaining.min(Duration::from_millis(250)))? {
+                continue;
+            }
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Esc => return Ok(TimedInput::Pause),
+                KeyCode::F(5) => return Ok(TimedInput::QuickSave),
+                KeyCode::F(9) => return Ok(TimedInput::QuickLoad),
+                KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => match input.parse::<usize>() {
+                    Ok(num) if num >= 1 && num <= max => return Ok(TimedInput::Choice(num)),
+                    _ => input.clear(),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Arrow-key menu in the style of dialoguer's `Select` that also reports
+    /// the in-game hotkeys: Esc to pause, F5 to quick save, F9 to quick load.
+    pub fn select_menu(&self, prompt: &str, items: &[String]) -> io::Result<MenuInput> {
+        self.select_grouped_menu(prompt, items, &vec![None; items.len()], &[])
+    }
+
+    fn read_menu_selection(
+        &self,
+        prompt: &str,
+        items: &[String],
+        groups: &[Option<String>],
+        hints: &[Option<String>],
+    ) -> io::Result<MenuInput> {
+        let mut stdout = io::stdout();
+        let mut selected = 0;
+        let headers: Vec<Option<String>> = (0..items.len())
+            .map(|index| {
+                let previous = index.checked_sub(1).and_then(|i| groups.get(i)).and_then(Option::as_deref);
+                group_header(index, previous, groups.get(index).and_then(Option::as_deref))
+            })
+            .collect();
+        let has_hints = hints.iter().any(Option::is_some);
+        let mut show_hint = false;
+        let mut height = 0;
+
+        loop {
+            if height > 0 {
+                queue!(stdout, cursor::MoveUp(height), terminal::Clear(terminal::ClearType::FromCursorDown))?;
+            }
+            let hint = hints.get(selected).and_then(Option::as_deref).filter(|_| show_hint);
+            height = self.draw_menu(prompt, items, &headers, selected, hint, has_hints)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => selected = (selected + items.len() - 1) % items.len(),
+                KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => selected = (selected + 1) % items.len(),
+                KeyCode::Char(c) if c.is_ascii_digit() => {
+                    let number = c.to_digit(10).unwrap_or(0) as usize;
+                    if number >= 1 && number <= items.len() {
+                        selected = number - 1;
+                    }
+                }
+                KeyCode::Char('h') if has_hints => show_hint = !show_hint,
+                KeyCode::Enter => return Ok(MenuInput::Selected(selected)),
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(MenuInput::Pause),
+                KeyCode::F(5) => return Ok(MenuInput::QuickSave),
+                KeyCode::F(9) => return Ok(MenuInput::QuickLoad),
+                _ => {}
+            }
+        }
+    }
+
+    /// Raw mode needs explicit carriage returns. Returns the number of lines
+    /// drawn.
+    fn draw_menu(
+        &self,
+        prompt: &str,
+        items: &[String],
+        headers: &[Option<String>],
+        selected: usize,
+        hint: Option<&str>,
+        has_hints: bool,
+    ) -> io::Result<u16> {
+        let mut stdout = io::stdout();
+        write!(stdout, "{}\r\n", self.theme_manager.apply_style(&format!("? {}", prompt), "title"))?;
+        // Prompt and key hint line
+        let mut lines = 2;
+
+        for (index, item) in items.iter().enumerate() {
+            if let Some(header) = &headers[index] {
+                write!(stdout, "  {}\r\n", self.theme_manager.apply_style(header, "separator"))?;
+                lines += 1;
+            }
+            if index == selected {
+                write!(stdout, "{}\r\n", self.theme_manager.apply_style(&format!("> {}", item), "choice"))?;
+                if let Some(hint) = hint {
+                    write!(stdout, "    {}\r\n", self.theme_manager.apply_style(hint, "separator"))?;
+                    lines += 1;
+                }
+            } else {
+                write!(stdout, "  {}\r\n", item)?;
+            }
+            lines += 1;
+        }
+
+        let keys = if has_hints {
+            "↑/↓ move · Enter select · h hint · Esc pause · F5 quick save · F9 quick load"
+        } else {
+            "↑/↓ move · Enter select · Esc pause · F5 quick save · F9 quick load"
+        };
+        write!(stdout, "{}\r\n", self.theme_manager.apply_style(keys, "separator"))?;
+        stdout.flush()?;
+        Ok(lines)
+    }
+
+    fn scroll_lines(&self, title: &str, lines: &[(String, &str)], page: usize) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        let last_top = lines.len().saturating_sub(page);
+        let mut top = last_top;
+        let width = self.width();
+
+        loop {
+            queue!(stdout, terminal::Clear(terminal::ClearType::All), cursor::MoveTo(0, 0))?;
+            write!(stdout, "{}\r\n", self.theme_manager.apply_style(title, "scene_title"))?;
+            let bottom = (top + page).min(lines.len());
+            for (line, style) in &lines[top..bottom] {
+                // Wrapped lines would push the title off screen
+                let line: String = line.chars().take(width).collect();
+                write!(stdout, "{}\r\n", self.theme_manager.apply_style(&line, style))?;
+            }
+            let keys = format!(
+                "{}-{} of {} · ↑/↓ scroll · PgUp/PgDn page · Esc close",
+                (top + 1).min(bottom), bottom, lines.len()
+            );
+            write!(stdout, "{}\r\n", self.theme_manager.apply_style(&keys, "separator"))?;
+            stdout.flush()?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => top = top.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => top = (top + 1).min(last_top),
+                KeyCode::PageUp => top = top.saturating_sub(page),
+                KeyCode::PageDown | KeyCode::Char(' ') => top = (top + page).min(last_top),
+                KeyCode::Home => top = 0,
+                KeyCode::End => top = last_top,
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn wait_for_key(&self) -> io::Result<Key> {
+        self.term.read_key()
+    }
+
+    fn show_wrapped_text(&self, text: &str, style: &str) -> io::Result<()> {
+        for line in wrap_paragraphs(text, self.width(), &self.layout) {
+            if line.is_empty() {
+                writeln!(io::stdout())?;
+            } else {
+                writeln!(io::stdout(), "{}", self.theme_manager.apply_style(&line, style))?;
+            }
+        }
+        
+        Ok(())
+    }
+
+    fn create_health_bar(&self, current: i32, max: i32) -> String {
+        let percentage = current as f32 / max as f32;
+        let bar_length = 10;
+        let filled_length = (percentage * bar_length as f32) as usize;
+        let empty_length = bar_length - filled_length;
+        
+        format!("{}{}", "█".repeat(filled_length), "░".repeat(empty_length))
+    }
+
+    fn get_health_style(&self, current: i32, max: i32) -> String {
+        let percentage = current as f32 / max as f32;
+        
+        if percentage > 0.6 {
+            "health_high".to_string()
+        } else if percentage > 0.3 {
+            "health_medium".to_string()
+        } else {
+            "health_low".to_string()
+        }
+    }
+
+    fn get_item_icon(&self, item_type: &crate::core::ItemType) -> &str {
+        match item_type {
+            crate::core::ItemType::Weapon => "⚔️",
+            crate::core::ItemType::Armor => "🛡️",
+            crate::core::ItemType::Consumable => "🧪",
+            crate::core::ItemType::KeyItem => "🔑",
+            crate::core::ItemType::Treasure => "💎",
+        }
+    }
+}
+
+impl Renderer for Display {
+    /// Width text is wrapped to right now.
+    fn width(&self) -> usize {
+        let terminal = self.fit_to_terminal
+            .then(|| self.term.size_checked())
+            .flatten()
+            .map(|(_rows, columns)| columns as usize);
+        fit_width(self.text_width, terminal)
+    }
+
+    fn clear_screen(&self) -> io::Result<()> {
         self.term.clear_screen()
     }
 
-    pub fn show_title(&self, title: &str) -> io::Result<()> {
+    fn show_title(&self, title: &str) -> io::Result<()> {
         let styled_title = self.theme_manager.apply_style(title, "title");
         
         // Create a border
-        let border = "═".repeat(self.text_width);
+        let border = "═".repeat(self.width());
         let styled_border = self.theme_manager.apply_style(&border, "separator");
         
         writeln!(io::stdout(), "{}", styled_title)?;
@@ -37,7 +417,7 @@ impl Display {
         Ok(())
     }
 
-    pub fn show_scene(&self, scene: &Scene) -> io::Result<()> {
+    fn show_scene(&self, scene: &Scene) -> io::Result<()> {
         // Scene title
         let styled_title = self.theme_manager.apply_style(&scene.title, "scene_title");
         writeln!(io::stdout(), "📍 {}", styled_title)?;
@@ -53,13 +433,17 @@ impl Display {
         Ok(())
     }
 
-    pub fn show_player_stats(&self, game_state: &GameState) -> io::Result<()> {
+    /// Shows the stats header; with a currency the player's wallet is
+    /// appended.
+    fn show_player_stats(&self, game_state: &GameState, currency: Option<&Currency>) -> io::Result<()> {
         let stats = &game_state.player.stats;
         
         // Health bar
         let health_bar = self.create_health_bar(stats.health, stats.max_health);
         let health_style = self.get_health_style(stats.health, stats.max_health);
-        let styled_health = self.theme_manager.apply_style(&health_bar, &health_style);
+        // The symbol tells the health level apart without relying on color
+        let health_text = format!("{} {}", health_symbol(&health_style), health_bar);
+        let styled_health = self.theme_manager.apply_style(&health_text, &health_style);
         
         let stats_text = format!(
             "📊 Player Stats: {} Health: {} {}/{} | Level: {} | XP: {} | STR: {} | INT: {} | CHA: {}",
@@ -75,26 +459,57 @@ impl Display {
         );
         
         let styled_stats = self.theme_manager.apply_style(&stats_text, "stats");
-        writeln!(io::stdout(), "{}", styled_stats)?;
+        match currency {
+            Some(currency) => {
+                let balance = game_state.get_flag_as_i64(&currency.flag);
+                writeln!(io::stdout(), "{} | {}", styled_stats, self.format_currency(balance, currency))?;
+            }
+            None => writeln!(io::stdout(), "{}", styled_stats)?,
+        }
         writeln!(io::stdout())?;
         
         Ok(())
     }
 
-    pub fn show_choices(&self, choices: &[Choice]) -> io::Result<()> {
+    /// The recent happenings strip, oldest first; nothing if there are none.
+    fn show_recent_events(&self, happenings: &[String]) -> io::Result<()> {
+        if happenings.is_empty() {
+            return Ok(());
+        }
+        let text = format!("📰 Recently: {}", happenings.join(" · "));
+        writeln!(io::stdout(), "{}", self.theme_manager.apply_style(&text, "info"))
+    }
+
+    /// Choices in `previously_taken` get a dimmed ✓.
+    fn show_choices(&self, scene: &SceneView, previously_taken: Option<&BTreeSet<String>>) -> io::Result<()> {
         writeln!(io::stdout(), "Choose your action:")?;
         
-        for (index, choice) in choices.iter().enumerate() {
-            let choice_text = format!("{}. {}", index + 1, choice.text);
+        let mut previous_group = None;
+        for (index, view) in scene.choices().enumerate() {
+            let group = view.choice.group.as_deref();
+            if let Some(header) = group_header(index, previous_group, group) {
+                writeln!(io::stdout(), "   {}", self.theme_manager.apply_style(&header, "separator"))?;
+            }
+            previous_group = group;
+
+            let choice_text = format!("{}. {}", index + 1, view.choice.text);
+            let marker = if previously_taken.is_some_and(|taken| taken.contains(&view.choice.id)) {
+                format!(" {}", self.theme_manager.apply_style(TAKEN_MARKER, "separator"))
+            } else {
+                String::new()
+            };
             
-            if choice.disabled.unwrap_or(false) {
-                let reason = choice.disabled_reason.as_deref().unwrap_or("Requirements not met");
+            if let Some(reason) = view.disabled_reason() {
                 let disabled_text = format!("{} ({})", choice_text, reason);
                 let styled = self.theme_manager.apply_style(&disabled_text, "choice_disabled");
-                writeln!(io::stdout(), "   {}", styled)?;
+                writeln!(io::stdout(), "   {}{}", styled, marker)?;
             } else {
                 let styled = self.theme_manager.apply_style(&choice_text, "choice");
-                writeln!(io::stdout(), "   {}", styled)?;
+                writeln!(io::stdout(), "   {}{}", styled, marker)?;
+            }
+
+            if let Some(hint) = view.choice.hint.as_deref().filter(|_| self.show_choice_hints) {
+                writeln!(io::stdout(), "      {}", self.theme_manager.apply_style(hint, "separator"))?;
             }
         }
         
@@ -102,7 +517,53 @@ impl Display {
         Ok(())
     }
 
-    pub fn show_inventory(&self, game_state: &GameState) -> io::Result<()> {
+    /// A story's intro or credits page.
+    fn show_story_card(&self, heading: &str, byline: &str, text: &str) -> io::Result<()> {
+        self.show_title(heading)?;
+        if !byline.is_empty() {
+            self.show_message(byline, "info")?;
+            println!();
+        }
+        self.show_wrapped_text(text, "scene_description")?;
+        println!();
+        Ok(())
+    }
+
+    fn show_debug_variables(&self, variables: &[DebugVariable]) -> io::Result<()> {
+        self.show_message("🔎 Variables", "scene_title")?;
+        self.show_separator()?;
+        let name_width = variables.iter().map(|variable| variable.name.len()).max().unwrap_or(0);
+        for variable in variables {
+            let changed = variable.changed_in.as_deref()
+                .map(|scene| format!("  (changed in {})", scene))
+                .unwrap_or_default();
+            let line = format!("{:<7} {:<width$} = {}{}", variable.kind, variable.name, variable.value, changed, width = name_width);
+            self.show_message(&line, if variable.changed_in.is_some() { "stats" } else { "info" })?;
+        }
+        Ok(())
+    }
+
+    fn show_help(&self, topics: &[HelpTopic]) -> io::Result<()> {
+        self.show_message("❓ Help", "scene_title")?;
+        self.show_separator()?;
+        for topic in topics {
+            self.show_message(&topic.title, "stats")?;
+            self.show_wrapped_text(&topic.text, "info")?;
+            println!();
+        }
+        Ok(())
+    }
+
+    fn show_scene_map(&self, map: &SceneMap) -> io::Result<()> {
+        self.show_message("🗺️ Map", "scene_title")?;
+        self.show_separator()?;
+        for (line, style) in map_lines(map) {
+            self.show_message(&line, style)?;
+        }
+        Ok(())
+    }
+
+    fn show_inventory(&self, game_state: &GameState) -> io::Result<()> {
         let styled_title = self.theme_manager.apply_style("🎒 Inventory", "scene_title");
         writeln!(io::stdout(), "{}", styled_title)?;
         
@@ -121,13 +582,26 @@ impl Display {
                     String::new()
                 };
                 
-                let item_text = format!("   {} {}{}", 
+                let durability_text = match (item.durability(), item.max_durability()) {
+                    (Some(durability), Some(max)) => format!(" [{}/{}]", durability, max),
+                    _ => String::new(),
+                };
+                
+                let item_text = format!("   {} {}{}{}", 
                     self.get_item_icon(&item.item_type), 
                     item.name, 
-                    quantity_text
+                    quantity_text,
+                    durability_text
                 );
                 let styled_item = self.theme_manager.apply_style(&item_text, "choice");
-                writeln!(io::stdout(), "{}", styled_item)?;
+                match game_state.item_cooldown_remaining(item) {
+                    0 => writeln!(io::stdout(), "{}", styled_item)?,
+                    remaining => {
+                        let cooldown = format!(" ⏳ {} scene{}", remaining, if remaining == 1 { "" } else { "s" });
+                        let styled_cooldown = self.theme_manager.apply_style(&cooldown, "choice_disabled");
+                        writeln!(io::stdout(), "{}{}", styled_item, styled_cooldown)?;
+                    }
+                }
                 
                 let description = format!("      {}", item.description);
                 let styled_desc = self.theme_manager.apply_style(&description, "info");
@@ -139,81 +613,160 @@ impl Display {
         Ok(())
     }
 
-    pub fn show_message(&self, message: &str, style: &str) -> io::Result<()> {
+    /// `text` in one of the theme's styles.
+    fn style(&self, text: &str, style: &str) -> String {
+        self.theme_manager.apply_style(text, style)
+    }
+
+    fn show_message(&self, message: &str, style: &str) -> io::Result<()> {
         let styled_message = self.theme_manager.apply_style(message, style);
         writeln!(io::stdout(), "{}", styled_message)?;
         Ok(())
     }
 
-    pub fn show_error(&self, error: &str) -> io::Result<()> {
+    fn show_error(&self, error: &str) -> io::Result<()> {
         self.show_message(&format!("❌ {}", error), "error")
     }
 
-    pub fn show_success(&self, message: &str) -> io::Result<()> {
+    fn show_success(&self, message: &str) -> io::Result<()> {
         self.show_message(&format!("✅ {}", message), "success")
     }
 
-    pub fn show_warning(&self, message: &str) -> io::Result<()> {
+    fn show_warning(&self, message: &str) -> io::Result<()> {
         self.show_message(&format!("⚠️ {}", message), "warning")
     }
 
-    pub fn show_separator(&self) -> io::Result<()> {
-        let separator = "━".repeat(self.text_width);
+    fn show_info(&self, message: &str) -> io::Result<()> {
+        self.show_message(&format!("ℹ️ {}", message), "info")
+    }
+
+    fn show_separator(&self) -> io::Result<()> {
+        let separator = "━".repeat(self.width());
         let styled = self.theme_manager.apply_style(&separator, "separator");
         writeln!(io::stdout(), "{}", styled)?;
         Ok(())
     }
 
-    pub fn prompt_input(&self, prompt: &str) -> io::Result<String> {
-        let styled_prompt = self.theme_manager.apply_style(prompt, "info");
-        print!("{}", styled_prompt);
-        io::stdout().flush()?;
-        
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        Ok(input.trim().to_string())
+    /// Reads a line with editing (arrow keys, unicode-aware backspace) and a
+    /// history of earlier answers to prompts of the same `kind`. Falls back
+    /// to a plain read when stdin is not a terminal.
+    fn prompt_text(&self, kind: &str, prompt: &str, default: Option<String>) -> io::Result<String> {
+        if !self.term.is_term() {
+            let styled_prompt = self.theme_manager.apply_style(prompt, "info");
+            print!("{}", styled_prompt);
+            io::stdout().flush()?;
+
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let input = input.trim().to_string();
+            return Ok(match default {
+                Some(default) if input.is_empty() => default,
+                _ => input,
+            });
+        }
+
+        let mut histories = self.input_history.borrow_mut();
+        let history = histories
+            .entry(kind.to_string())
+            .or_insert_with(|| BasicHistory::new().max_entries(INPUT_HISTORY_LEN).no_duplicates(true));
+
+        let mut input = Input::<String>::new()
+            .with_prompt(prompt.trim_end())
+            .allow_empty(true)
+            .history_with(history);
+        if let Some(default) = default {
+            input = input.default(default);
+        }
+
+        input.interact_text()
+            .map(|text| text.trim().to_string())
+            .map_err(|dialoguer::Error::IO(error)| error)
     }
 
-    pub fn prompt_yes_no(&self, prompt: &str, default: bool) -> io::Result<bool> {
-        let default_text = if default { " [Y/n]" } else { " [y/N]" };
-        let full_prompt = format!("{}{} ", prompt, default_text);
-        
-        loop {
-            let input = self.prompt_input(&full_prompt)?;
-            
-            match input.to_lowercase().as_str() {
-                "y" | "yes" => return Ok(true),
-                "n" | "no" => return Ok(false),
-                "" => return Ok(default),
-                _ => {
-                    self.show_error("Please enter 'y' for yes or 'n' for no.")?;
-                    continue;
-                }
-            }
+    /// Reads a choice number while counting down `timeout` on the prompt line.
+    fn prompt_timed_choice(&self, max: usize, timeout: Duration) -> io::Result<TimedInput> {
+        let deadline = Instant::now() + timeout;
+
+        terminal::enable_raw_mode()?;
+        let result = self.read_timed_choice(max, deadline);
+        terminal::disable_raw_mode()?;
+        writeln!(io::stdout())?;
+
+        result
+    }
+
+    /// Like `select_menu`, with a heading line wherever the group of the
+    /// items changes. `groups` has one entry per item; `hints` has one per
+    /// item that may have a hint, which `h` shows under the highlighted item.
+    fn select_grouped_menu(
+        &self,
+        prompt: &str,
+        items: &[String],
+        groups: &[Option<String>],
+        hints: &[Option<String>],
+    ) -> io::Result<MenuInput> {
+        if items.is_empty() {
+            return Ok(MenuInput::Pause);
         }
+
+        terminal::enable_raw_mode()?;
+        let result = self.read_menu_selection(prompt, items, groups, hints);
+        terminal::disable_raw_mode()?;
+
+        result
     }
 
-    pub fn prompt_number(&self, prompt: &str, min: usize, max: usize) -> io::Result<usize> {
-        loop {
-            let input = self.prompt_input(prompt)?;
-            
-            match input.parse::<usize>() {
-                Ok(num) if num >= min && num <= max => return Ok(num),
-                Ok(_) => {
-                    self.show_error(&format!("Please enter a number between {} and {}.", min, max))?;
-                }
-                Err(_) => {
-                    self.show_error("Please enter a valid number.")?;
-                }
+    /// Scrolls through styled `lines` a screen at a time, starting at the
+    /// end: ↑/↓ by line, PgUp/PgDn by page, Home/End to either end, and
+    /// Esc, q or Enter to close. Prints every line when stdin isn't a terminal.
+    fn show_scrollable(&self, title: &str, lines: &[(String, &str)]) -> io::Result<()> {
+        if !self.term.is_term() {
+            self.show_message(title, "scene_title")?;
+            for (line, style) in lines {
+                self.show_message(line, style)?;
             }
+            return Ok(());
         }
+
+        // Title and key hint lines
+        let rows = self.term.size_checked().map_or(24, |(rows, _columns)| rows as usize);
+        let page = rows.saturating_sub(3).max(1);
+
+        terminal::enable_raw_mode()?;
+        let result = self.scroll_lines(title, lines, page);
+        terminal::disable_raw_mode()?;
+
+        result
     }
 
-    pub fn wait_for_key(&self) -> io::Result<Key> {
-        self.term.read_key()
+    fn select(&self, prompt: &str, items: &[String], default: usize) -> io::Result<usize> {
+        Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(default)
+            .interact()
+            .map_err(|dialoguer::Error::IO(error)| error)
     }
 
-    pub fn wait_for_enter(&self) -> io::Result<()> {
+    /// Esc or `q` backs out.
+    fn select_opt(&self, prompt: &str, items: &[String], default: usize) -> io::Result<Option<usize>> {
+        Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(default)
+            .interact_opt()
+            .map_err(|dialoguer::Error::IO(error)| error)
+    }
+
+    fn confirm(&self, prompt: &str, default: bool) -> io::Result<bool> {
+        Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()
+            .map_err(|dialoguer::Error::IO(error)| error)
+    }
+
+    fn wait_for_enter(&self) -> io::Result<()> {
         let styled_prompt = self.theme_manager.apply_style("Press Enter to continue...", "info");
         print!("{}", styled_prompt);
         io::stdout().flush()?;
@@ -223,78 +776,197 @@ impl Display {
         Ok(())
     }
 
-    fn show_wrapped_text(&self, text: &str, style: &str) -> io::Result<()> {
-        let words: Vec<&str> = text.split_whitespace().collect();
-        let mut current_line = String::new();
-        
-        for word in words {
-            if current_line.len() + word.len() + 1 > self.text_width {
-                if !current_line.is_empty() {
-                    let styled_line = self.theme_manager.apply_style(&current_line, style);
-                    writeln!(io::stdout(), "{}", styled_line)?;
-                    current_line.clear();
-                }
+    fn key_pressed_within(&self, timeout: Duration) -> io::Result<bool> {
+        let deadline = Instant::now() + timeout;
+
+        terminal::enable_raw_mode()?;
+        let result = read_key_press(deadline);
+        terminal::disable_raw_mode()?;
+
+        result
+    }
+
+    /// One bar per value, from ▁ at 0 to █ at `max`, styled by the last value.
+    fn health_sparkline(&self, values: &[i32], max: i32) -> String {
+        let sparkline = sparkline(values, max);
+        match values.last() {
+            Some(&last) => self.theme_manager.apply_style(&sparkline, &self.get_health_style(last, max)),
+            None => sparkline,
+        }
+    }
+
+    fn set_theme(&mut self, theme_name: &str) -> bool {
+        self.theme_manager.set_theme(theme_name)
+    }
+
+    fn set_text_width(&mut self, text_width: usize) {
+        self.text_width = text_width;
+    }
+
+    fn set_choice_hints(&mut self, show_choice_hints: bool) {
+        self.show_choice_hints = show_choice_hints;
+    }
+
+    fn set_fit_to_terminal(&mut self, fit_to_terminal: bool) {
+        self.fit_to_terminal = fit_to_terminal;
+    }
+
+    fn set_text_layout(&mut self, layout: TextLayout) {
+        self.layout = layout;
+    }
+
+    fn set_allow_italics(&mut self, allow_italics: bool) {
+        self.theme_manager.set_allow_italics(allow_italics);
+    }
+
+    fn get_available_themes(&self) -> Vec<String> {
+        self.theme_manager.list_themes()
+    }
+}
+
+fn read_key_press(deadline: Instant) -> io::Result<bool> {
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !event::poll(remaining)? {
+            return Ok(false);
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(true);
             }
-            
-            if !current_line.is_empty() {
+        }
+    }
+}
+
+/// Word-wraps `text` to `width` columns. Paragraphs are separated by blank
+/// lines in the source; empty strings in the result are blank lines.
+pub(crate) fn wrap_paragraphs(text: &str, width: usize, layout: &TextLayout) -> Vec<String> {
+    let indent = " ".repeat(layout.paragraph_indent);
+    let paragraphs = text.split("\n\n").filter(|paragraph| !paragraph.trim().is_empty());
+    let mut lines = Vec::new();
+
+    for (index, paragraph) in paragraphs.enumerate() {
+        if index > 0 {
+            lines.extend(std::iter::repeat_n(String::new(), layout.paragraph_spacing));
+        }
+
+        let mut current_line = indent.clone();
+        let mut has_words = false;
+        for word in paragraph.split_whitespace() {
+            if has_words && current_line.len() + word.len() + 1 > width {
+                push_line(&mut lines, std::mem::take(&mut current_line), layout);
+                has_words = false;
+            }
+            if has_words {
                 current_line.push(' ');
             }
             current_line.push_str(word);
+            has_words = true;
         }
-        
-        if !current_line.is_empty() {
-            let styled_line = self.theme_manager.apply_style(&current_line, style);
-            writeln!(io::stdout(), "{}", styled_line)?;
+        if has_words {
+            push_line(&mut lines, current_line, layout);
         }
-        
-        Ok(())
     }
 
-    fn create_health_bar(&self, current: i32, max: i32) -> String {
-        let percentage = current as f32 / max as f32;
-        let bar_length = 10;
-        let filled_length = (percentage * bar_length as f32) as usize;
-        let empty_length = bar_length - filled_length;
-        
-        format!("{}{}", "█".repeat(filled_length), "░".repeat(empty_length))
+    lines
+}
+
+fn push_line(lines: &mut Vec<String>, line: String, layout: &TextLayout) {
+    if layout.double_spacing && lines.last().is_some_and(|last| !last.is_empty()) {
+        lines.push(String::new());
     }
+    lines.push(line);
+}
 
-    fn get_health_style(&self, current: i32, max: i32) -> String {
-        let percentage = current as f32 / max as f32;
-        
-        if percentage > 0.6 {
-            "health_high".to_string()
-        } else if percentage > 0.3 {
-            "health_medium".to_string()
-        } else {
-            "health_low".to_string()
-        }
+/// The map as a tree of exits, with the style for each line.
+fn map_lines(map: &SceneMap) -> Vec<(String, &'static str)> {
+    let here = map.here.title.as_deref().unwrap_or(&map.here.scene_id);
+    let mut lines = vec![(format!("📍 {} (you are here)", here), "scene_title")];
+    push_exits(&map.here, "", &mut lines);
+    if !map.came_from.is_empty() {
+        lines.push((format!("↩ Came from: {}", map.came_from.join(", ")), "info"));
     }
+    lines
+}
 
-    fn get_item_icon(&self, item_type: &crate::core::ItemType) -> &str {
-        match item_type {
-            crate::core::ItemType::Weapon => "⚔️",
-            crate::core::ItemType::Armor => "🛡️",
-            crate::core::ItemType::Consumable => "🧪",
-            crate::core::ItemType::KeyItem => "🔑",
-            crate::core::ItemType::Treasure => "💎",
-        }
+fn push_exits(node: &MapNode, prefix: &str, lines: &mut Vec<(String, &'static str)>) {
+    for (index, exit) in node.exits.iter().enumerate() {
+        let last = index + 1 == node.exits.len();
+        let branch = if last { "└── " } else { "├── " };
+        let (label, style) = match &exit.title {
+            None => ("???".to_string(), "choice_disabled"),
+            Some(title) if exit.repeated => (format!("↺ {}", title), "info"),
+            Some(title) => (title.clone(), "choice"),
+        };
+        lines.push((format!("{}{}{}", prefix, branch, label), style));
+
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        push_exits(exit, &child_prefix, lines);
     }
+}
 
-    pub fn set_theme(&mut self, theme_name: &str) -> bool {
-        self.theme_manager.set_theme(theme_name)
+/// The configured width, narrowed to the terminal's if it is known and
+/// smaller, but never below `MIN_TEXT_WIDTH`.
+fn fit_width(configured: usize, terminal: Option<usize>) -> usize {
+    match terminal {
+        Some(columns) if columns < configured => columns.max(MIN_TEXT_WIDTH),
+        _ => configured,
     }
+}
 
-    pub fn get_available_themes(&self) -> Vec<String> {
-        self.theme_manager.list_themes()
+fn health_symbol(health_style: &str) -> &'static str {
+    match health_style {
+        "health_high" => "●",
+        "health_medium" => "◐",
+        _ => "⚠",
+    }
+}
+
+/// Heading to print before item `index` when its group differs from the
+/// previous item's; a plain rule when leaving a group for ungrouped items.
+fn group_header(index: usize, previous: Option<&str>, group: Option<&str>) -> Option<String> {
+    if index > 0 && previous == group {
+        return None;
+    }
+    match group {
+        Some(group) => Some(format!("── {} ──", group)),
+        None if previous.is_some() => Some("────".to_string()),
+        None => None,
+    }
+}
+
+fn sparkline(values: &[i32], max: i32) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    values.iter()
+        .map(|&value| {
+            let fraction = value.clamp(0, max.max(1)) as f32 / max.max(1) as f32;
+            BARS[(fraction * (BARS.len() - 1) as f32).round() as usize]
+        })
+        .collect()
+}
+
+/// Formats a number with comma thousands separators.
+fn group_thousands(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    if amount < 0 {
+        format!("-{}", grouped)
+    } else {
+        grouped
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::{Player, InventoryItem, ItemType};
-    use std::collections::HashMap;
+    use crate::core::ItemType;
 
     #[test]
     fn test_display_creation() {
@@ -309,7 +981,7 @@ mod tests {
         let display = Display::new(theme_manager, 80).unwrap();
         
         let health_bar = display.create_health_bar(50, 100);
-        assert_eq!(health_bar.len(), 10);
+        assert_eq!(health_bar.chars().count(), 10);
         
         let health_bar_full = display.create_health_bar(100, 100);
         assert_eq!(health_bar_full, "██████████");
@@ -318,6 +990,12 @@ mod tests {
         assert_eq!(health_bar_empty, "░░░░░░░░░░");
     }
 
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[100, 50, 0, 120, -5], 100), "█▅▁█▁");
+        assert_eq!(sparkline(&[], 100), "");
+    }
+
     #[test]
     fn test_health_style() {
         let theme_manager = ThemeManager::new();
@@ -326,6 +1004,75 @@ mod tests {
         assert_eq!(display.get_health_style(80, 100), "health_high");
         assert_eq!(display.get_health_style(50, 100), "health_medium");
         assert_eq!(display.get_health_style(20, 100), "health_low");
+        assert_eq!(health_symbol("health_low"), "⚠");
+    }
+
+    #[test]
+    fn test_fit_width() {
+        assert_eq!(fit_width(80, None), 80);
+        assert_eq!(fit_width(80, Some(120)), 80);
+        assert_eq!(fit_width(80, Some(60)), 60);
+        assert_eq!(fit_width(80, Some(20)), MIN_TEXT_WIDTH);
+    }
+
+    #[test]
+    fn test_wrap_paragraphs() {
+        let text = "The rain falls on the old road.\n\nA lantern glows.";
+        assert_eq!(
+            wrap_paragraphs(text, 20, &TextLayout::default()),
+            vec!["The rain falls on", "the old road.", "", "A lantern glows."]
+        );
+
+        let layout = TextLayout { paragraph_spacing: 0, paragraph_indent: 2, double_spacing: true };
+        assert_eq!(
+            wrap_paragraphs(text, 20, &layout),
+            vec!["  The rain falls on", "", "the old road.", "", "  A lantern glows."]
+        );
+    }
+
+    #[test]
+    fn test_map_lines() {
+        let node = |title: Option<&str>, repeated, exits| MapNode {
+            scene_id: "id".to_string(),
+            title: title.map(str::to_string),
+            repeated,
+            exits,
+        };
+        let map = SceneMap {
+            here: node(Some("Tavern"), false, vec![
+                node(Some("Road"), false, vec![node(Some("Tavern"), true, vec![])]),
+                node(None, false, vec![]),
+            ]),
+            came_from: vec!["Road".to_string()],
+        };
+
+        let lines: Vec<String> = map_lines(&map).into_iter().map(|(line, _)| line).collect();
+        assert_eq!(lines, vec![
+            "📍 Tavern (you are here)",
+            "├── Road",
+            "│   └── ↺ Tavern",
+            "└── ???",
+            "↩ Came from: Road",
+        ]);
+    }
+
+    #[test]
+    fn test_group_headers() {
+        assert_eq!(group_header(0, None, Some("Talk")).as_deref(), Some("── Talk ──"));
+        assert_eq!(group_header(1, Some("Talk"), Some("Talk")), None);
+        assert_eq!(group_header(2, Some("Talk"), None).as_deref(), Some("────"));
+        assert_eq!(group_header(0, None, None), None);
+    }
+
+    #[test]
+    fn test_currency_formatting() {
+        colored::control::set_override(false);
+        let display = Display::new(ThemeManager::new(), 80).unwrap();
+
+        let currency = Currency::default();
+        assert_eq!(display.format_currency(125, &currency), "🪙 125");
+        assert_eq!(display.format_currency(1_250_000, &currency), "🪙 1,250,000");
+        assert_eq!(display.format_currency(-4500, &currency), "🪙 -4,500");
     }
 
     #[test]
@@ -0,0 +1,96 @@
+//! Panic handling for the terminal game. A panic mid-game would otherwise
+//! leave the terminal in raw mode with a hidden cursor and lose the run, so
+//! the hook puts the terminal back, saves the last game state it was shown
+//! and writes a crash report to the logs directory.
+
+use std::backtrace::Backtrace;
+use std::io::{self, Write};
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use chrono::Local;
+use crossterm::{cursor, execute, terminal};
+use crate::core::GameState;
+use crate::utils::SaveManager;
+use tracing::error;
+
+/// Keeps the state the panic hook saves up to date. Dropping it doesn't
+/// remove the hook.
+pub struct CrashGuard {
+    game_state: Arc<Mutex<Option<GameState>>>,
+}
+
+impl CrashGuard {
+    /// Installs the panic hook, saving through `save_manager` and writing
+    /// crash reports to `reports_dir`.
+    pub fn install(save_manager: SaveManager, reports_dir: PathBuf) -> Self {
+        let game_state = Arc::new(Mutex::new(None));
+        let hook_state = Arc::clone(&game_state);
+        let default_hook = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+
+            // The game loop may have panicked while holding the lock; the
+            // state in it is still the last complete one
+            let state = hook_state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take();
+            let save = state.map(|state| save_manager.emergency_save(state));
+            let save_note = match &save {
+                Some(Ok(save)) => format!("Emergency save: {} ({})", save.name, save.id),
+                Some(Err(e)) => format!("Emergency save failed: {}", e),
+                None => "No game in progress, nothing was saved".to_string(),
+            };
+
+            match write_crash_report(&reports_dir, info, &save_note) {
+                Ok(path) => {
+                    error!("Game crashed, report written to {}", path.display());
+                    eprintln!("\nThe game crashed. {}.", save_note);
+                    eprintln!("Crash report: {}", path.display());
+                }
+                Err(e) => {
+                    error!("Game crashed and the crash report couldn't be written: {}", e);
+                    eprintln!("\nThe game crashed. {}.", save_note);
+                }
+            }
+        }));
+
+        Self { game_state }
+    }
+
+    /// Remembers the state to save if the game crashes; `None` once the
+    /// game is over.
+    pub fn record(&self, game_state: Option<&GameState>) {
+        let mut recorded = self.game_state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *recorded = game_state.cloned();
+    }
+}
+
+/// Leaves raw mode and shows the cursor again. Errors are ignored, there's
+/// nothing better to do with them while panicking.
+fn restore_terminal() {
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(io::stdout(), cursor::Show);
+}
+
+/// Writes what panicked and where to a new file in `dir`.
+fn write_crash_report(dir: &Path, info: &PanicHookInfo, save_note: &str) -> io::Result<PathBuf> {
+    let message = info.payload().downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let location = info.location()
+        .map(|location| location.to_string())
+        .unwrap_or_else(|| "unknown location".to_string());
+
+    let now = Local::now();
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    let mut report = std::fs::File::create(&path)?;
+    writeln!(report, "text-game {} crashed at {}", crate::VERSION, now.to_rfc3339())?;
+    writeln!(report, "Panic: {}", message)?;
+    writeln!(report, "Location: {}", location)?;
+    writeln!(report, "{}", save_note)?;
+    writeln!(report, "\nBacktrace:\n{}", Backtrace::force_capture())?;
+
+    Ok(path)
+}
@@ -0,0 +1,91 @@
+//! In-game help, also shown as a tutorial the first time a game is started.
+//! Stories can replace or add topics under `metadata.help`, e.g.
+//! `"help": {"stats": "Sanity drops when...", "magic": "Spells cost..."}`.
+
+use std::collections::HashMap;
+use serde_json::Value;
+use crate::utils::title_case;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpTopic {
+    pub key: String,
+    pub title: String,
+    pub text: String,
+}
+
+const DEFAULT_TOPICS: [(&str, &str, &str); 4] = [
+    (
+        "controls",
+        "🎮 Controls",
+        "Pick a choice with ↑/↓ and Enter. Esc opens the pause menu, F5 quick saves and F9 loads the latest quick save. In timed scenes, type the choice number before the clock runs out.",
+    ),
+    (
+        "saving",
+        "💾 Saving",
+        "Save to a slot from the pause menu, or quick save with F5. The game also saves automatically every few minutes while auto-save is on.",
+    ),
+    (
+        "stats",
+        "📊 Stats",
+        "Health, strength, intelligence and charisma decide which choices are open to you; locked choices say what they need. Experience raises your level and your stats with it.",
+    ),
+    (
+        "inventory",
+        "🎒 Inventory",
+        "Items you pick up are listed under View Inventory, where consumables can be used. Some choices need a particular item.",
+    ),
+];
+
+/// The built-in topics, with the story's `help` metadata replacing their
+/// text by key; other keys add topics after them.
+pub fn help_topics(story_metadata: Option<&HashMap<String, Value>>) -> Vec<HelpTopic> {
+    let mut topics: Vec<HelpTopic> = DEFAULT_TOPICS.iter()
+        .map(|(key, title, text)| HelpTopic {
+            key: key.to_string(),
+            title: title.to_string(),
+            text: text.to_string(),
+        })
+        .collect();
+
+    let Some(overrides) = story_metadata.and_then(|metadata| metadata.get("help")).and_then(Value::as_object) else {
+        return topics;
+    };
+
+    for (key, value) in overrides {
+        let Some(text) = value.as_str() else {
+            tracing::warn!("Ignoring help topic '{}': expected text", key);
+            continue;
+        };
+        match topics.iter_mut().find(|topic| &topic.key == key) {
+            Some(topic) => topic.text = text.to_string(),
+            None => topics.push(HelpTopic {
+                key: key.clone(),
+                title: format!("📜 {}", title_case(key)),
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_help_topics() {
+        assert_eq!(help_topics(None).len(), 4);
+
+        let metadata = HashMap::from([(
+            "help".to_string(),
+            json!({"stats": "Sanity matters most.", "magic": "Spells cost mana."}),
+        )]);
+        let topics = help_topics(Some(&metadata));
+
+        assert_eq!(topics.len(), 5);
+        assert_eq!(topics[2].text, "Sanity matters most.");
+        assert_eq!(topics[4].title, "📜 Magic");
+    }
+}
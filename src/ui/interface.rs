@@ -1,63 +1,224 @@
-use dialoguer::{Select, Input, Confirm, FuzzySelect};
-use std::time::Duration;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use uuid::Uuid;
-
-use crate::core::GameEngine;
-use crate::story::{StoryLoader, StoryMetadata};
-use crate::utils::{SaveManager, SaveGameMetadata};
-use crate::ui::{Display, ThemeManager};
-use crate::config::Config;
+use chrono::Local;
+
+use crate::core::{health_by_scene, Breakpoint, GameEngine, GameEvent, StateDiff, HotseatMode, ItemType, PlayerStats, SeededRng};
+use crate::story::flavor::configured_writer;
+use crate::story::{Choice, FlavorRequest, FlavorSettings, FlavorWriter, Scene, SceneView, Story, StoryLoader};
+use crate::utils::{title_case, SaveGame, SaveGameMetadata, SaveManager, SavePreview, StoryProfile};
+use crate::ui::help::help_topics;
+use crate::ui::twitch::{configured_voting, ChatVoting};
+use crate::ui::{CrashGuard, Display, MenuInput, PlainRenderer, Renderer, Narrator, TextLayout, ThemeManager, TimedInput, TAKEN_MARKER};
+use crate::config::{CliConfig, Config, ConfigWatcher, RendererKind, MIN_TEXT_WIDTH};
 use crate::utils::{GameError, GameResult};
 use tracing::{info, warn, error};
 
+/// File in the config directory recording that the tutorial was shown.
+const TUTORIAL_MARKER: &str = "tutorial-seen";
+
+/// How many steps from the current scene the map shows.
+const MAP_DEPTH: usize = 2;
+
+/// Name of the player in kiosk mode demos.
+const DEMO_PLAYER: &str = "Demo";
+
+/// Most choices a kiosk demo makes before starting over, so stories that
+/// loop forever still end.
+const DEMO_MAX_CHOICES: usize = 100;
+
+/// How many scenes the health sparkline in the statistics covers.
+const HEALTH_HISTORY_SCENES: usize = 20;
+
+/// What the game loop should do after the pause menu closes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseAction {
+    Resume,
+    QuitToMenu,
+    QuitToDesktop,
+}
+
+impl PauseAction {
+    /// `None` to keep playing; otherwise whether the application keeps
+    /// running after leaving the game.
+    fn leave_game(self) -> Option<bool> {
+        match self {
+            PauseAction::Resume => None,
+            PauseAction::QuitToMenu => Some(true),
+            PauseAction::QuitToDesktop => Some(false),
+        }
+    }
+}
+
 pub struct GameInterface {
     engine: GameEngine,
     story_loader: StoryLoader,
     save_manager: SaveManager,
-    display: Display,
+    display: Box<dyn Renderer>,
     config: Config,
+    config_watcher: Option<ConfigWatcher>,
+    narrator: Option<Narrator>,
+    /// Rewrites scene descriptions for stories that opt in.
+    flavor_writer: Option<Box<dyn FlavorWriter>>,
+    /// Scene and visit the last rewrite was for, and the rewrite (`None` if
+    /// it failed), so redrawing the scene doesn't ask again.
+    flavored_description: Option<((String, usize), Option<String>)>,
+    /// Scene and visit of the timed scene being shown, and when its time
+    /// runs out, so redrawing the scene doesn't restart the clock.
+    timed_deadline: Option<((String, usize), Instant)>,
+    /// Messages held back in streamlined mode, shown together as one line.
+    pending_feedback: Vec<(String, &'static str)>,
+    /// Title art of the story loaded with `load_story`.
+    title_art: Option<String>,
+    /// What the last choice changed, for the effects line and the debug console.
+    last_choice_diff: Option<StateDiff>,
+    /// Completed runs of the story being played.
+    story_profile: Option<StoryProfile>,
+    /// Twitch chat that votes on choices, if configured.
+    chat_voting: Option<ChatVoting>,
+    /// Keeps the state saved on a panic current, once installed.
+    crash_guard: Option<CrashGuard>,
 }
 
 impl GameInterface {
     pub async fn new(config: Config) -> GameResult<Self> {
         info!("Initializing game interface");
         
-        // Ensure directories exist
+        // Bring over content from the old relative directories, then ensure directories exist
+        config.migrate_legacy_directories()?;
         config.ensure_directories()?;
         
-        let theme_manager = ThemeManager::new();
-        let mut display = Display::new(theme_manager, config.ui.text_width)
+        let mut display = configured_renderer(&config)
             .map_err(|e| GameError::configuration(format!("Failed to create display: {}", e)))?;
+        display.set_allow_italics(!config.ui.dyslexia_friendly);
         
         // Set theme if configured
         if !display.set_theme(&config.ui.theme) {
             warn!("Unknown theme '{}', using default", config.ui.theme);
         }
 
+        let mut save_manager = SaveManager::new(config.get_saves_dir());
+        if let Some(cipher) = config.save_cipher()? {
+            save_manager = save_manager.with_cipher(cipher);
+        }
+
+        let mut engine = config.game.engine();
+        for plugin in crate::plugins::load_plugins(config.get_plugins_dir())? {
+            engine.register_plugin(plugin);
+        }
+
+        let narrator = config.ui.read_aloud.then(|| Narrator::new(config.ui.tts_command.clone()));
+        let flavor_writer = configured_writer(&config.ai_narrator);
+        let chat_voting = configured_voting(&config.twitch).await;
+
         Ok(Self {
-            engine: GameEngine::new(),
-            story_loader: StoryLoader::new(config.get_stories_dir()),
-            save_manager: SaveManager::new(config.get_saves_dir()),
+            engine,
+            story_loader: StoryLoader::new(config.get_stories_dir())
+                .with_index_cache(config.get_cache_dir().join("stories-index.json")),
+            save_manager,
             display,
             config,
+            config_watcher: None,
+            narrator,
+            flavor_writer,
+            flavored_description: None,
+            timed_deadline: None,
+            pending_feedback: Vec::new(),
+            title_art: None,
+            last_choice_diff: None,
+            story_profile: None,
+            chat_voting,
+            crash_guard: None,
         })
     }
 
+    /// On a panic, restore the terminal, save the game in progress and
+    /// write a crash report to the logs directory.
+    pub fn install_crash_handler(&mut self) {
+        self.crash_guard = Some(CrashGuard::install(
+            self.save_manager.clone(),
+            self.config.get_logs_dir().to_path_buf(),
+        ));
+    }
+
+    /// Replaces the writer that rewrites scene descriptions for stories that
+    /// opt in, e.g. with one for a different service; `None` turns it off.
+    pub fn set_flavor_writer(&mut self, writer: Option<Box<dyn FlavorWriter>>) {
+        self.flavor_writer = writer;
+        self.flavored_description = None;
+    }
+
+    /// Reload appearance settings from `path` whenever it changes during
+    /// play, keeping the command line's `overrides`.
+    pub fn watch_config<P: AsRef<Path>>(&mut self, path: P, overrides: CliConfig) {
+        self.config_watcher = Some(ConfigWatcher::new(path).with_overrides(overrides));
+    }
+
+    async fn check_config_reload(&mut self) {
+        let Some(watcher) = self.config_watcher.as_mut() else {
+            return;
+        };
+
+        let mut new_config = match watcher.poll() {
+            Ok(Some(config)) => config,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Ignoring config change in {:?}: {}", watcher.path(), e);
+                return;
+            }
+        };
+
+        // Checked before storing, so the config keeps naming the theme in use
+        if new_config.ui.theme != self.config.ui.theme && !self.display.set_theme(&new_config.ui.theme) {
+            warn!("Unknown theme '{}', keeping current theme", new_config.ui.theme);
+            new_config.ui.theme = self.config.ui.theme.clone();
+        }
+
+        let changed = self.config.apply_reloadable(&new_config);
+        if changed.is_empty() {
+            return;
+        }
+
+        self.display.set_text_width(self.config.ui.text_width);
+        self.display.set_fit_to_terminal(self.config.ui.fit_to_terminal);
+        self.display.set_choice_hints(self.config.ui.show_choice_hints);
+        self.display.set_text_layout(text_layout(&self.config));
+        self.display.set_allow_italics(!self.config.ui.dyslexia_friendly);
+        if changed.iter().any(|key| key.starts_with("ui.read_aloud") || key.starts_with("ui.tts_command")) {
+            self.update_narrator();
+        }
+        if changed.iter().any(|key| key == "ai_narrator") {
+            self.flavor_writer = configured_writer(&self.config.ai_narrator);
+            self.flavored_description = None;
+        }
+
+        info!("Config reloaded: {}", changed.join(", "));
+        self.engine.emit_event(GameEvent::config_reloaded(&changed)).await;
+    }
+
     pub async fn run(&mut self) -> GameResult<()> {
         info!("Starting game interface");
-        
-        loop {
+
+        let mut keep_running = match self.offer_recovery().await {
+            Ok(keep_running) => keep_running,
+            Err(e) => {
+                error!("Failed to resume recovered game: {}", e);
+                self.display.show_game_error(&e).ok();
+                true
+            }
+        };
+        while keep_running {
             match self.show_main_menu().await {
-                Ok(should_continue) => {
-                    if !should_continue {
-                        break;
-                    }
-                }
+                Ok(should_continue) => keep_running = should_continue,
                 Err(e) => {
                     error!("Main menu error: {}", e);
-                    self.display.show_error(&format!("An error occurred: {}", e)).ok();
-                    self.display.wait_for_enter().ok();
+                    self.display.show_game_error(&e).ok();
+                    // No more input to read, e.g. piped answers ran out
+                    if self.display.wait_for_enter().is_err() {
+                        break;
+                    }
                 }
             }
         }
@@ -71,25 +232,23 @@ impl GameInterface {
     pub async fn show_main_menu(&mut self) -> GameResult<bool> {
         self.display.clear_screen().ok();
         self.show_game_title().await?;
+        self.show_pending_feedback()?;
 
-        let choices = vec![
+        let choices = [
             "🎮 Start New Game",
             "📁 Load Game", 
             "⚙️ Settings",
             "📊 Statistics",
             "🚪 Exit"
-        ];
+        ].map(String::from);
 
-        let selection = Select::new()
-            .with_prompt("What would you like to do?")
-            .items(&choices)
-            .default(0)
-            .interact()
+        let selection = self.display
+            .select("What would you like to do?", &choices, 0)
             .map_err(|e| GameError::configuration(format!("Menu selection error: {}", e)))?;
 
         match selection {
-            0 => self.start_new_game_menu().await?,
-            1 => self.load_game_menu().await?,
+            0 => return self.start_new_game_menu().await,
+            1 => return self.load_game_menu().await,
             2 => self.settings_menu().await?,
             3 => self.statistics_menu().await?,
             4 => return Ok(false), // Exit
@@ -111,20 +270,21 @@ impl GameInterface {
         self.display.show_message("A professional text-based adventure experience", "info")?;
         self.display.show_message(&format!("Version {}", crate::VERSION), "info")?;
         
-        let separator = "═".repeat(self.config.ui.text_width);
+        let separator = "═".repeat(self.display.width());
         self.display.show_message(&separator, "separator")?;
         println!();
         
         Ok(())
     }
 
-    async fn start_new_game_menu(&mut self) -> GameResult<()> {
+    /// Returns whether the application should keep running afterwards.
+    async fn start_new_game_menu(&mut self) -> GameResult<bool> {
         let stories = self.story_loader.list_available_stories().await?;
         
         if stories.is_empty() {
             self.display.show_warning("No stories found! Please add story files to the stories directory.")?;
             self.display.wait_for_enter()?;
-            return Ok(());
+            return Ok(true);
         }
 
         self.display.show_message("📚 Available Stories:", "scene_title")?;
@@ -132,22 +292,21 @@ impl GameInterface {
 
         let story_choices: Vec<String> = stories
             .iter()
-            .map(|story| format!("{} - {}", story.title, story.description))
+            .map(|story| match story.playtime_text() {
+                Some(playtime) => format!("{} - {} ({})", story.title, story.description, playtime),
+                None => format!("{} - {}", story.title, story.description),
+            })
             .collect();
 
-        let selection = Select::new()
-            .with_prompt("Choose your adventure")
-            .items(&story_choices)
-            .interact()
+        let selection = self.display
+            .select("Choose your adventure", &story_choices, 0)
             .map_err(|e| GameError::story(format!("Story selection error: {}", e)))?;
 
         let selected_story = &stories[selection];
         
         // Get player name
-        let player_name: String = Input::new()
-            .with_prompt("Enter your character's name")
-            .default("Adventurer".to_string())
-            .interact_text()
+        let player_name = self.display
+            .prompt_text("player_name", "Enter your character's name", Some("Adventurer".to_string()))
             .map_err(|e| GameError::configuration(format!("Name input error: {}", e)))?;
 
         // Load story and start game
@@ -155,120 +314,387 @@ impl GameInterface {
         self.engine.load_story(story).await?;
         self.engine.start_new_game(player_name).await?;
 
+        self.show_tutorial_once()?;
+        self.show_story_page(|story| story.intro.as_deref(), "Prologue")?;
         self.display.show_success(&format!("Starting \"{}\"...", selected_story.title))?;
         sleep(Duration::from_millis(self.config.get_animation_delay_ms())).await;
 
         // Start game loop
-        self.game_loop().await?;
-        
-        Ok(())
+        self.game_loop().await
     }
 
-    async fn load_game_menu(&mut self) -> GameResult<()> {
+    async fn load_game_menu(&mut self) -> GameResult<bool> {
         let saves = self.save_manager.list_save_games().await?;
         
         if saves.is_empty() {
             self.display.show_warning("No save games found. Starting a new game instead...")?;
             self.display.wait_for_enter()?;
-            self.start_new_game_menu().await?;
-            return Ok(());
+            return self.start_new_game_menu().await;
         }
 
-        self.display.show_message("💾 Saved Games:", "scene_title")?;
-        println!();
+        let story_titles: HashMap<String, String> = match self.story_loader.list_available_stories().await {
+            Ok(stories) => stories.into_iter().map(|story| (story.id, story.title)).collect(),
+            Err(e) => {
+                warn!("Failed to list stories for the load menu: {}", e);
+                HashMap::new()
+            }
+        };
+        let story_title = |story_id: &String| story_titles.get(story_id).unwrap_or(story_id).clone();
 
-        let save_choices: Vec<String> = saves
-            .iter()
-            .map(|save| {
-                format!("{} - {} ({})", 
-                    save.name, 
-                    save.save_time.format("%Y-%m-%d %H:%M"), 
-                    save.get_playtime_formatted()
-                )
-            })
-            .collect();
+        let groups = SaveGameMetadata::group_by_story(saves);
+        // With saves from a single story there is nothing to pick
+        let single_story = groups.len() == 1;
 
-        let mut all_choices = save_choices;
-        all_choices.push("🔙 Back to Main Menu".to_string());
+        let selected_save = loop {
+            let group_index = if single_story {
+                0
+            } else {
+                let mut story_choices: Vec<String> = groups.iter()
+                    .map(|(story_id, story_saves)| format!(
+                        "📖 {} ({} save{})",
+                        story_title(story_id),
+                        story_saves.len(),
+                        if story_saves.len() == 1 { "" } else { "s" }
+                    ))
+                    .collect();
+                story_choices.push("🔙 Back to Main Menu".to_string());
+
+                let selection = self.display
+                    .select("Choose a story", &story_choices, 0)
+                    .map_err(|e| GameError::save_load(format!("Story selection error: {}", e)))?;
+
+                if selection == story_choices.len() - 1 {
+                    return Ok(true);
+                }
+                selection
+            };
 
-        let selection = Select::new()
-            .with_prompt("Choose a save game")
-            .items(&all_choices)
-            .interact()
-            .map_err(|e| GameError::save_load(format!("Save selection error: {}", e)))?;
+            let (story_id, story_saves) = &groups[group_index];
+            self.display.show_message(&format!("💾 Saved Games: {}", story_title(story_id)), "scene_title")?;
+            println!();
 
-        if selection == all_choices.len() - 1 {
-            // Back to main menu
-            return Ok(());
-        }
+            let mut all_choices: Vec<String> = story_saves.iter().map(save_choice_label).collect();
+            all_choices.push(if single_story { "🔙 Back to Main Menu" } else { "🔙 Back to Stories" }.to_string());
 
-        let selected_save = &saves[selection];
+            let selection = self.display
+                .select("Choose a save game", &all_choices, 0)
+                .map_err(|e| GameError::save_load(format!("Save selection error: {}", e)))?;
+
+            if selection < story_saves.len() {
+                break story_saves[selection].clone();
+            }
+            if single_story {
+                return Ok(true);
+            }
+        };
         
         // Load the save
         let save_game = self.save_manager.load_game(selected_save.id).await?;
         let story = self.story_loader.load_story(&save_game.game_state.story_id).await?;
         
         self.engine.load_story(story).await?;
-        self.engine.load_game(save_game.game_state).await?;
+        if !self.resume_save(save_game).await? {
+            return Ok(true);
+        }
 
         self.display.show_success(&format!("Loaded \"{}\"", selected_save.name))?;
         sleep(Duration::from_millis(self.config.get_animation_delay_ms())).await;
 
         // Start game loop
-        self.game_loop().await?;
-        
-        Ok(())
+        self.game_loop().await
     }
 
-    async fn game_loop(&mut self) -> GameResult<()> {
+    /// Loads a saved game into the engine, which must already have the save's
+    /// story. Saves from another story version that refer to removed scenes
+    /// are only loaded, best effort, if the player agrees. Returns whether
+    /// the save was loaded. The save's event history, if it has one,
+    /// replaces the session's.
+    async fn resume_save(&mut self, save_game: SaveGame) -> GameResult<bool> {
+        let game_state = save_game.game_state;
+        let compatibility = self.engine.check_save_compatibility(&game_state)?;
+
+        if compatibility.is_compatible() {
+            if compatibility.version_changed() {
+                self.display.show_warning(&format!(
+                    "This save was made with version {} of the story; the current version is {}.",
+                    compatibility.saved_story_version.as_deref().unwrap_or("unknown"),
+                    compatibility.story_version
+                ))?;
+            }
+            self.engine.replace_event_history(save_game.events).await;
+            self.engine.load_game(game_state).await?;
+            return Ok(true);
+        }
+
+        self.display.show_warning(&format!(
+            "This save was made with version {} of the story and refers to {} scene(s) the current version {} no longer has: {}",
+            compatibility.saved_story_version.as_deref().unwrap_or("unknown"),
+            compatibility.missing_scenes.len(),
+            compatibility.story_version,
+            compatibility.missing_scenes.join(", ")
+        ))?;
+
+        let load_anyway = self.display
+            .confirm("Load anyway? Progress in removed scenes will be dropped", true)
+            .map_err(|e| GameError::save_load(format!("Load confirmation error: {}", e)))?;
+
+        if !load_anyway {
+            return Ok(false);
+        }
+
+        self.engine.replace_event_history(save_game.events).await;
+        self.engine.load_game_best_effort(game_state).await?;
+        Ok(true)
+    }
+
+    /// Plays until the game ends or the player quits. Returns whether the
+    /// application should keep running (false for "quit to desktop").
+    async fn game_loop(&mut self) -> GameResult<bool> {
+        let result = self.play_scenes().await;
+        if let Err(e) = &result {
+            self.save_recovery(e).await;
+        }
+        // Out of the game, a crash has nothing left to save
+        if let Some(guard) = &self.crash_guard {
+            guard.record(None);
+        }
+        result
+    }
+
+    /// Keeps the game an error stopped, to offer resuming it on the next
+    /// launch.
+    async fn save_recovery(&mut self, error: &GameError) {
+        let Some(game_state) = self.engine.get_game_state().cloned() else {
+            return;
+        };
+        let events = self.events_to_save().await;
+        match self.save_manager.save_recovery(game_state, events).await {
+            Ok(_) => {
+                error!("Game stopped on an error, kept for recovery: {}", error);
+                self.display.show_warning("Your progress was kept; you can resume it the next time you start the game.").ok();
+            }
+            Err(e) => warn!("Failed to keep the game for recovery: {}", e),
+        }
+    }
+
+    /// If the last run stopped on an error, offers to pick the game up
+    /// where it stopped. Returns whether the application should keep running.
+    async fn offer_recovery(&mut self) -> GameResult<bool> {
+        let Some(save_game) = self.save_manager.load_recovery().await? else {
+            return Ok(true);
+        };
+        // Only offered once, whatever the player picks
+        self.save_manager.clear_recovery().await?;
+
+        self.display.show_warning(&format!(
+            "{}'s last game of '{}' stopped on an error.",
+            save_game.game_state.player.name, save_game.game_state.story_id
+        ))?;
+        let resume = self.display
+            .confirm("Resume where it stopped?", true)
+            .map_err(|e| GameError::save_load(format!("Recovery confirmation error: {}", e)))?;
+        if !resume {
+            return Ok(true);
+        }
+
+        let story = self.story_loader.load_story(&save_game.game_state.story_id).await?;
+        self.engine.load_story(story).await?;
+        if !self.resume_save(save_game).await? {
+            return Ok(true);
+        }
+        self.game_loop().await
+    }
+
+    async fn play_scenes(&mut self) -> GameResult<bool> {
+        self.load_story_profile().await;
         while self.engine.is_game_active() && !self.engine.is_game_ended().await {
+            if let Some(guard) = &self.crash_guard {
+                guard.record(self.engine.get_game_state());
+            }
+            self.check_config_reload().await;
             self.display.clear_screen().ok();
+
+            let hits = self.engine.take_breakpoint_hits().await;
+            if !hits.is_empty() {
+                for hit in &hits {
+                    self.display.show_warning(&format!("Breakpoint: {}", hit))?;
+                }
+                self.debug_console().await?;
+                self.display.clear_screen().ok();
+            }
+
+            if self.engine.get_game_state().is_some_and(|state| state.player.stats.pending_level_up.is_some()) {
+                self.allocate_level_points().await?;
+                self.display.clear_screen().ok();
+            }
             
             // Show current scene
             let scene = self.engine.get_current_scene().await?;
-            self.display.show_scene(&scene)?;
+            self.show_pending_feedback()?;
+            match self.flavored_scene(&scene.scene) {
+                Some(flavored) => self.display.show_scene(&flavored)?,
+                None => self.display.show_scene(&scene.scene)?,
+            }
+            if let Some(narrator) = self.narrator.as_mut() {
+                narrator.speak_scene(&scene);
+            }
             
             // Show player stats if configured
             if self.config.ui.show_stats_in_header {
                 if let Some(game_state) = self.engine.get_game_state() {
-                    self.display.show_player_stats(game_state)?;
+                    let currency = self.engine.currency().filter(|_| self.config.ui.show_wallet);
+                    self.display.show_player_stats(game_state, currency)?;
                 }
             }
+            if self.config.ui.show_recent_events {
+                let happenings = self.recent_happenings().await;
+                self.display.show_recent_events(&happenings)?;
+            }
 
             // Prepare choices (including system choices)
-            let mut available_choices = scene.choices
-                .iter()
-                .filter(|choice| !choice.disabled.unwrap_or(false))
-                .map(|choice| choice.text.clone())
+            let previously_taken = self.story_profile.as_ref()
+                .filter(|profile| self.config.ui.mark_taken_choices && profile.completed_runs > 0)
+                .and_then(|profile| profile.taken_choices.get(scene.id()))
+                .cloned();
+            let mut available_choices = scene.available_choices()
+                .map(|choice| match &previously_taken {
+                    Some(taken) if taken.contains(&choice.id) => format!("{} {}", choice.text, TAKEN_MARKER),
+                    _ => choice.text.clone(),
+                })
+                .collect::<Vec<_>>();
+            let mut choice_groups = scene.available_choices()
+                .map(|choice| choice.group.clone())
+                .collect::<Vec<_>>();
+            let choice_hints = scene.available_choices()
+                .map(|choice| choice.hint.clone())
                 .collect::<Vec<_>>();
 
-            // Add system choices
+            // Add system choices; saving, settings and quitting live in the pause menu
             available_choices.extend_from_slice(&[
-                "💾 Save Game".to_string(),
                 "🎒 View Inventory".to_string(),
+                "🗺️ View Map".to_string(),
                 "📊 View Statistics".to_string(),
-                "⚙️ Settings".to_string(),
-                "🚪 Quit Game".to_string(),
+                "📖 Log".to_string(),
+                "❓ Help".to_string(),
+                "⏸️ Pause Menu (Esc)".to_string(),
             ]);
+            if self.config.game.dev_mode {
+                available_choices.push("🐞 Debug Console".to_string());
+            }
+            // System choices get their own heading once the scene uses groups
+            let system_group = choice_groups.iter().any(Option::is_some).then(|| "Menu".to_string());
+            choice_groups.resize(available_choices.len(), system_group);
+
+            self.display.show_choices(&scene, previously_taken.as_ref())?;
+            if let Some(party) = self.engine.get_game_state().and_then(|state| state.party.as_ref()) {
+                let banner = match party.mode {
+                    HotseatMode::Alternate => format!("🎲 {}'s turn", party.current().name),
+                    HotseatMode::Vote => format!("🗳️ Party vote, {} goes first", party.current().name),
+                };
+                self.display.show_message(&banner, "info")?;
+            }
 
-            self.display.show_choices(&scene.choices)?;
+            // Timed scenes take a typed choice number while the clock runs;
+            // Esc stops the clock and opens the pause menu
+            if let Some(auto_advance) = scene.scene.auto_advance.as_ref().filter(|_| !scene.is_ending()) {
+                let visit = (scene.scene.id.clone(), self.engine.get_game_state().map_or(0, |state| state.visited_scenes.total()));
+                let deadline = match &self.timed_deadline {
+                    Some((timed, deadline)) if *timed == visit => *deadline,
+                    _ => Instant::now() + Duration::from_secs(auto_advance.after_seconds),
+                };
+                self.timed_deadline = Some((visit.clone(), deadline));
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                let input = self.display.prompt_timed_choice(scene.choices().count(), timeout)
+                    .map_err(|e| GameError::configuration(format!("Choice input error: {}", e)))?;
+
+                match input {
+                    TimedInput::Choice(number) => {
+                        let view = scene.choices().nth(number - 1).expect("choice number is in range");
+                        if let Some(reason) = view.disabled_reason() {
+                            self.acknowledge(&format!("You can't do that: {}", reason), "error")?;
+                        } else {
+                            let last_event_id = self.engine.last_event_id().await;
+                            self.engine.make_choice(&view.choice.id).await?;
+                            self.notify_events(last_event_id).await?;
+                        }
+                        continue;
+                    }
+                    TimedInput::TimedOut => {
+                        self.flash("⚠️ Time's up!", "warning").await?;
+                        let last_event_id = self.engine.last_event_id().await;
+                        self.engine.auto_advance().await?;
+                        self.notify_events(last_event_id).await?;
+                        continue;
+                    }
+                    TimedInput::Pause => {
+                        // The clock stands still while paused
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if let Some(keep_running) = self.pause_menu().await?.leave_game() {
+                            return Ok(keep_running);
+                        }
+                        self.timed_deadline = Some((visit, Instant::now() + remaining));
+                        continue;
+                    }
+                    TimedInput::QuickSave => {
+                        self.quick_save().await?;
+                        continue;
+                    }
+                    TimedInput::QuickLoad => {
+                        self.quick_load().await?;
+                        continue;
+                    }
+                }
+            }
 
-            let selection = Select::new()
-                .with_prompt("What do you choose?")
-                .items(&available_choices)
-                .interact()
-                .map_err(|e| GameError::configuration(format!("Choice selection error: {}", e)))?;
+            let input = match self.chat_vote(&scene).await? {
+                Some(index) => MenuInput::Selected(index),
+                None => self.display.select_grouped_menu("What do you choose?", &available_choices, &choice_groups, &choice_hints)
+                    .map_err(|e| GameError::configuration(format!("Choice selection error: {}", e)))?,
+            };
+
+            let selection = match input {
+                MenuInput::Selected(selection) => selection,
+                // Esc (or q) anywhere in the choice list opens the pause menu
+                MenuInput::Pause => {
+                    if let Some(keep_running) = self.pause_menu().await?.leave_game() {
+                        return Ok(keep_running);
+                    }
+                    continue;
+                }
+                MenuInput::QuickSave => {
+                    self.quick_save().await?;
+                    continue;
+                }
+                MenuInput::QuickLoad => {
+                    self.quick_load().await?;
+                    continue;
+                }
+            };
 
             // Handle choice
-            let valid_scene_choices = scene.choices
-                .iter()
-                .filter(|choice| !choice.disabled.unwrap_or(false))
-                .collect::<Vec<_>>();
+            let valid_scene_choices = scene.available_choices().collect::<Vec<_>>();
 
             if selection < valid_scene_choices.len() {
                 // Scene choice
                 let chosen_choice = valid_scene_choices[selection];
-                self.engine.make_choice(&chosen_choice.id).await?;
+                let before = self.engine.get_game_state().cloned();
+                let last_event_id = self.engine.last_event_id().await;
+                match self.party_votes(&valid_scene_choices, &chosen_choice.id)? {
+                    Some(votes) => {
+                        let chosen = self.engine.vote_choice(&votes).await?;
+                        let text = valid_scene_choices.iter().find(|choice| choice.id == chosen).map(|choice| choice.text.clone()).unwrap_or(chosen);
+                        self.pending_feedback.push((format!("The party chose: {}", text), "info"));
+                    }
+                    None => self.engine.make_choice(&chosen_choice.id).await?,
+                }
+                self.notify_events(last_event_id).await?;
+                self.last_choice_diff = before.zip(self.engine.get_game_state()).map(|(before, after)| before.diff(after));
+                if self.config.game.show_choice_effects {
+                    let summary = self.last_choice_diff.as_ref().map(StateDiff::summary).unwrap_or_default();
+                    if !summary.is_empty() {
+                        self.pending_feedback.push((summary.join(", "), "stats"));
+                    }
+                }
                 
                 // Show animation delay
                 if self.config.get_animation_delay_ms() > 0 {
@@ -280,15 +706,17 @@ impl GameInterface {
                 // System choice
                 let system_choice_index = selection - valid_scene_choices.len();
                 match system_choice_index {
-                    0 => self.save_current_game().await?,
-                    1 => self.show_inventory().await?,
+                    0 => self.show_inventory().await?,
+                    1 => self.show_scene_map()?,
                     2 => self.show_game_statistics().await?,
-                    3 => self.quick_settings().await?,
-                    4 => {
-                        if self.confirm_quit().await? {
-                            break;
+                    3 => self.show_event_log().await?,
+                    4 => self.show_help()?,
+                    5 => {
+                        if let Some(keep_running) = self.pause_menu().await?.leave_game() {
+                            return Ok(keep_running);
                         }
                     }
+                    6 => self.debug_console().await?,
                     _ => unreachable!(),
                 }
             }
@@ -298,46 +726,596 @@ impl GameInterface {
         if self.engine.is_game_ended().await {
             let scene = self.engine.get_current_scene().await?;
             self.display.clear_screen().ok();
-            self.display.show_scene(&scene)?;
+            self.show_pending_feedback()?;
+            self.display.show_scene(&scene.scene)?;
             self.display.show_success("🎊 Adventure Complete! 🎊")?;
+            if let Some(title) = scene.scene.ending_title() {
+                let ending = match scene.scene.ending_category() {
+                    Some(category) => format!("Ending: {} ({})", title, category),
+                    None => format!("Ending: {}", title),
+                };
+                self.display.show_message(&ending, "info")?;
+            }
+            self.record_completed_run().await?;
             self.display.wait_for_enter()?;
+            self.show_story_page(|story| story.credits.as_deref(), "Credits")?;
+        }
+
+        Ok(true)
+    }
+
+    /// In a vote game, asks the rest of the party for their votes, `first`
+    /// being the vote of the seat whose turn it is. `None` otherwise.
+    fn party_votes(&self, choices: &[&Choice], first: &str) -> GameResult<Option<Vec<String>>> {
+        let Some(party) = self.engine.get_game_state().and_then(|state| state.party.as_ref()) else {
+            return Ok(None);
+        };
+        if party.mode != HotseatMode::Vote {
+            return Ok(None);
+        }
+        let texts: Vec<String> = choices.iter().map(|choice| choice.text.clone()).collect();
+        let mut votes = Vec::with_capacity(party.seats.len());
+        for (index, seat) in party.seats.iter().enumerate() {
+            if index == party.turn {
+                votes.push(first.to_string());
+                continue;
+            }
+            let vote = self.display.select(&format!("{}, what do you vote for?", seat.name), &texts, 0)
+                .map_err(|e| GameError::configuration(format!("Vote selection error: {}", e)))?;
+            votes.push(choices[vote].id.clone());
+        }
+        Ok(Some(votes))
+    }
+
+    /// Puts the scene's choices to the Twitch chat, if voting is on.
+    /// Returns the index of the available choice chat picked; `None` leaves
+    /// the choice to the player at the keyboard.
+    async fn chat_vote(&mut self, scene: &SceneView) -> GameResult<Option<usize>> {
+        let Some(voting) = self.chat_voting.as_mut() else {
+            return Ok(None);
+        };
+        let choices: Vec<String> = scene.available_choices().map(|choice| choice.text.clone()).collect();
+        if choices.is_empty() {
+            return Ok(None);
+        }
+
+        self.display.show_info(&format!("🗳️ Chat is voting for {} seconds: type !1 to !{}", voting.window().as_secs(), choices.len()))?;
+        match voting.vote(&choices).await {
+            Ok(Some(index)) => {
+                self.pending_feedback.push((format!("Chat chose: {}", choices[index]), "info"));
+                Ok(Some(index))
+            }
+            Ok(None) => {
+                self.display.show_info("No votes from chat, so it's your call")?;
+                Ok(None)
+            }
+            Err(e) => {
+                warn!("Chat voting stopped: {}", e);
+                self.display.show_warning(&format!("Chat voting stopped: {}", e))?;
+                self.chat_voting = None;
+                Ok(None)
+            }
+        }
+    }
+
+    /// The last `ui.recent_events_count` notable events, oldest first.
+    async fn recent_happenings(&self) -> Vec<String> {
+        let mut happenings: Vec<String> = self.engine.get_event_history().await.iter()
+            .rev()
+            .filter_map(|event| event.happening(&self.config.ui.recent_event_flags))
+            .take(self.config.ui.recent_events_count)
+            .collect();
+        happenings.reverse();
+        happenings
+    }
+
+    /// Loads the profile of the story being played unless it's already loaded.
+    async fn load_story_profile(&mut self) {
+        let Some(story_id) = self.engine.get_game_state().map(|state| state.story_id.clone()) else {
+            return;
+        };
+        if self.story_profile.as_ref().is_some_and(|profile| profile.story_id == story_id) {
+            return;
+        }
+        self.story_profile = match StoryProfile::load(self.config.get_saves_dir(), &story_id).await {
+            Ok(profile) => Some(profile),
+            Err(e) => {
+                warn!("Ignoring story profile for '{}': {}", story_id, e);
+                Some(StoryProfile::new(story_id))
+            }
+        };
+    }
+
+    /// Adds the finished game's ending and choices to the story profile and
+    /// shows how many of the story's endings have been found.
+    async fn record_completed_run(&mut self) -> GameResult<()> {
+        self.load_story_profile().await;
+        let (Some(profile), Some(game_state)) = (self.story_profile.as_mut(), self.engine.get_game_state()) else {
+            return Ok(());
+        };
+        if profile.record_run(game_state) {
+            if let Err(e) = profile.save(self.config.get_saves_dir()).await {
+                warn!("Failed to save story profile: {}", e);
+            }
         }
 
+        let endings: BTreeSet<&str> = self.engine.story()
+            .map(|story| story.get_endings().into_iter().filter_map(Scene::ending_id).collect())
+            .unwrap_or_default();
+        if endings.len() > 1 {
+            let found = endings.iter().filter(|ending| profile.endings.contains(**ending)).count();
+            self.display.show_message(&format!("Endings found: {} of {}", found, endings.len()), "info")?;
+        }
         Ok(())
     }
 
-    async fn save_current_game(&mut self) -> GameResult<()> {
-        let save_name: String = Input::new()
-            .with_prompt("Enter a name for your save")
-            .default(format!("Save {}", chrono::Utc::now().format("%Y-%m-%d %H:%M")))
-            .interact_text()
+    async fn pause_menu(&mut self) -> GameResult<PauseAction> {
+        loop {
+            let choices = [
+                "▶️ Resume",
+                "💾 Save Game",
+                "⚡ Quick Save (F5)",
+                "⚡ Quick Load (F9)",
+                "⚙️ Settings",
+                "🚪 Quit Game",
+            ].map(String::from);
+            self.show_pending_feedback()?;
+
+            let selection = self.display
+                .select_opt("⏸️ Paused", &choices, 0)
+                .map_err(|e| GameError::configuration(format!("Pause menu error: {}", e)))?;
+
+            match selection {
+                // Esc again resumes
+                None | Some(0) => return Ok(PauseAction::Resume),
+                Some(1) => {
+                    self.save_current_game().await?;
+                }
+                Some(2) => {
+                    self.quick_save().await?;
+                    return Ok(PauseAction::Resume);
+                }
+                Some(3) => {
+                    self.quick_load().await?;
+                    return Ok(PauseAction::Resume);
+                }
+                Some(4) => self.quick_settings().await?,
+                Some(5) => match self.quit_menu().await? {
+                    // Cancelled: back to the pause menu
+                    PauseAction::Resume => {}
+                    action => return Ok(action),
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    async fn quit_menu(&mut self) -> GameResult<PauseAction> {
+        let choices = [
+            "💾 Save and Quit to Main Menu",
+            "🏠 Quit to Main Menu",
+            "🖥️ Quit to Desktop",
+            "🔙 Cancel",
+        ].map(String::from);
+
+        let selection = self.display
+            .select_opt("Quit game", &choices, 0)
+            .map_err(|e| GameError::configuration(format!("Quit menu error: {}", e)))?;
+
+        let action = match selection {
+            Some(0) => {
+                if self.save_current_game().await? {
+                    PauseAction::QuitToMenu
+                } else {
+                    PauseAction::Resume
+                }
+            }
+            Some(1) if self.confirm_quit().await? => PauseAction::QuitToMenu,
+            Some(2) if self.confirm_quit().await? => PauseAction::QuitToDesktop,
+            _ => PauseAction::Resume,
+        };
+
+        Ok(action)
+    }
+
+    async fn quick_save(&mut self) -> GameResult<()> {
+        let result = match self.engine.save_game("Quick Save".to_string()).await {
+            Ok(game_state) => {
+                let preview = self.current_save_preview().await;
+                let events = self.events_to_save().await;
+                self.save_manager.quick_save(game_state, preview, events).await.map(|_| ())
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => self.flash("⚡ Quick saved", "success").await,
+            Err(e) => self.flash(&format!("❌ Quick save failed: {}", e.report()), "error").await,
+        }
+    }
+
+    /// Replaces the current game with the story's latest quick save.
+    async fn quick_load(&mut self) -> GameResult<()> {
+        let Some(story_id) = self.engine.get_game_state().map(|state| state.story_id.clone()) else {
+            return Ok(());
+        };
+
+        let result = match self.save_manager.quick_load(&story_id).await {
+            Ok(save_game) => self.resume_save(save_game).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(true) => self.flash("⚡ Quick loaded", "success").await,
+            Ok(false) => Ok(()),
+            Err(e) => self.flash(&format!("❌ Quick load failed: {}", e.report()), "error").await,
+        }
+    }
+
+    /// Announces items found, level-ups and achievements logged after
+    /// `last_event_id`, before the next scene is shown.
+    async fn notify_events(&mut self, last_event_id: Option<Uuid>) -> GameResult<()> {
+        if !self.config.ui.show_notifications {
+            return Ok(());
+        }
+        for event in self.engine.events_since(last_event_id).await {
+            if let Some((message, style)) = event.notification() {
+                self.flash(&message, style).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The event history if `game.save_event_log` is on.
+    async fn events_to_save(&self) -> Vec<GameEvent> {
+        if self.config.game.save_event_log {
+            self.engine.get_event_history().await
+        } else {
+            Vec::new()
+        }
+    }
+
+    async fn current_save_preview(&self) -> Option<SavePreview> {
+        let scene = self.engine.get_current_scene().await.ok()?;
+        let game_state = self.engine.get_game_state()?;
+        Some(SavePreview::new(&scene.scene, game_state))
+    }
+
+    /// Lets the player pick a save slot, confirming before overwriting an
+    /// occupied one. Returns whether the game was saved.
+    async fn save_current_game(&mut self) -> GameResult<bool> {
+        let story_id = self.engine.get_game_state()
+            .map(|state| state.story_id.clone())
+            .ok_or_else(|| GameError::save_load("No active game to save".to_string()))?;
+        let slots = self.save_manager.list_slots(&story_id, self.config.saves.slot_count.max(1)).await?;
+
+        let mut choices: Vec<String> = slots.iter()
+            .enumerate()
+            .map(|(index, slot)| match slot {
+                Some(save) => format!(
+                    "Slot {}: {} · {}{}",
+                    index + 1,
+                    save.display_name(),
+                    save.get_playtime_formatted(),
+                    save.preview.as_ref().map(|preview| format!(" | {}", preview)).unwrap_or_default()
+                ),
+                None => format!("Slot {}: — Empty —", index + 1),
+            })
+            .collect();
+        choices.push("🔙 Cancel".to_string());
+
+        let selection = self.display
+            .select_opt("💾 Choose a save slot", &choices, 0)
+            .map_err(|e| GameError::save_load(format!("Save slot selection error: {}", e)))?;
+
+        let Some(index) = selection.filter(|&index| index < slots.len()) else {
+            return Ok(false);
+        };
+        let slot = index + 1;
+
+        if let Some(existing) = &slots[index] {
+            let overwrite = self.display
+                .confirm(&format!("Overwrite slot {} (\"{}\")?", slot, existing.name), false)
+                .map_err(|e| GameError::save_load(format!("Overwrite confirmation error: {}", e)))?;
+
+            if !overwrite {
+                return Ok(false);
+            }
+        }
+
+        let default_name = format!("Save {}", chrono::Utc::now().format("%Y-%m-%d %H:%M"));
+        let save_name = self.display
+            .prompt_text("save_name", "Enter a name for your save", Some(default_name))
             .map_err(|e| GameError::save_load(format!("Save name input error: {}", e)))?;
 
-        match self.engine.save_game(save_name.clone()).await {
+        let saved = match self.engine.save_game(save_name.clone()).await {
             Ok(game_state) => {
-                self.save_manager.save_game(save_name.clone(), game_state, None).await?;
-                self.display.show_success(&format!("Game saved as \"{}\"", save_name))?;
+                let preview = self.current_save_preview().await;
+                let events = self.events_to_save().await;
+                self.save_manager.save_to_slot(slot, save_name.clone(), game_state, preview, events).await?;
+                self.acknowledge(&format!("✅ Game saved to slot {} as \"{}\"", slot, save_name), "success")?;
+                true
             }
             Err(e) => {
-                self.display.show_error(&format!("Failed to save game: {}", e))?;
+                self.acknowledge(&format!("❌ Failed to save game: {}", e.report()), "error")?;
+                false
             }
-        }
+        };
         
+        Ok(saved)
+    }
+
+    async fn show_inventory(&mut self) -> GameResult<()> {
+        loop {
+            self.display.clear_screen().ok();
+            self.show_pending_feedback()?;
+
+            let Some(game_state) = self.engine.get_game_state() else {
+                return Ok(());
+            };
+            self.display.show_inventory(game_state)?;
+
+            let consumables: Vec<(String, String)> = game_state.player.inventory.iter()
+                .filter(|item| matches!(item.item_type, ItemType::Consumable))
+                .map(|item| {
+                    let label = match game_state.item_use_blocked_reason(item) {
+                        Some(reason) => format!("🧪 Use {} ({})", item.name, reason),
+                        None => format!("🧪 Use {}", item.name),
+                    };
+                    (item.id.clone(), label)
+                })
+                .collect();
+
+            if consumables.is_empty() {
+                self.display.wait_for_enter()?;
+                return Ok(());
+            }
+
+            let mut choices: Vec<String> = consumables.iter().map(|(_, label)| label.clone()).collect();
+            choices.push("🔙 Back".to_string());
+
+            let selection = self.display
+                .select_opt("Use an item?", &choices, choices.len() - 1)
+                .map_err(|e| GameError::configuration(format!("Inventory selection error: {}", e)))?;
+
+            let Some((item_id, _)) = selection.and_then(|index| consumables.get(index)) else {
+                return Ok(());
+            };
+
+            match self.engine.use_item(item_id).await {
+                Ok(()) => self.acknowledge("✅ Item used", "success")?,
+                Err(e) => self.acknowledge(&format!("❌ {}", e.report()), "error")?,
+            }
+        }
+    }
+
+    /// Lets the player spend level-up points one at a time. Esc puts the
+    /// choice off until the next scene.
+    async fn allocate_level_points(&mut self) -> GameResult<()> {
+        let Some(game_state) = self.engine.get_game_state() else {
+            return Ok(());
+        };
+        let Some(pending) = game_state.player.stats.pending_level_up else {
+            return Ok(());
+        };
+        let stats = game_state.player.stats.clone();
+
+        let mut allocation: HashMap<String, i32> = HashMap::new();
+        loop {
+            let spent: i32 = allocation.values().sum();
+            let remaining = pending.points - spent;
+
+            self.display.clear_screen().ok();
+            self.display.show_message(&format!("🎉 Level {}!", stats.level), "scene_title")?;
+            self.display.show_message(&format!("Points to spend: {}", remaining), "info")?;
+
+            let mut items: Vec<String> = PlayerStats::ALLOCATABLE.iter()
+                .map(|&stat| {
+                    let current = stats.get(stat).unwrap_or(0);
+                    let added = allocation.get(stat).copied().unwrap_or(0);
+                    if added > 0 {
+                        format!("{} {} (+{})", title_case(stat), current + added, added)
+                    } else {
+                        format!("{} {}", title_case(stat), current)
+                    }
+                })
+                .collect();
+            if remaining == 0 {
+                items.push("✅ Confirm".to_string());
+            }
+            if spent > 0 {
+                items.push("↩️ Reset".to_string());
+            }
+
+            let Some(selection) = self.display
+                .select_opt("Choose a stat to raise", &items, 0)
+                .map_err(|e| GameError::configuration(format!("Level-up selection error: {}", e)))?
+            else {
+                return Ok(());
+            };
+
+            match items[selection].as_str() {
+                "✅ Confirm" => {
+                    self.engine.allocate_level_points(allocation).await?;
+                    return Ok(());
+                }
+                "↩️ Reset" => allocation.clear(),
+                _ if remaining > 0 => {
+                    *allocation.entry(PlayerStats::ALLOCATABLE[selection].to_string()).or_insert(0) += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Title screen for a story launched directly: its own art if it
+    /// bundles some, otherwise the game's banner.
+    async fn show_story_title(&mut self) -> GameResult<()> {
+        self.display.clear_screen().ok();
+        match self.title_art.clone() {
+            Some(art) => {
+                self.display.show_title(&art)?;
+                if let Some(story) = self.engine.story() {
+                    self.display.show_message(&story.byline(), "info")?;
+                }
+            }
+            None => self.show_game_title().await?,
+        }
         self.display.wait_for_enter()?;
         Ok(())
     }
 
-    async fn show_inventory(&mut self) -> GameResult<()> {
+    /// Shows the story's intro or credits, if it has them.
+    fn show_story_page(&mut self, page: fn(&Story) -> Option<&str>, heading: &str) -> GameResult<()> {
+        let Some(story) = self.engine.story() else {
+            return Ok(());
+        };
+        let Some(text) = page(story) else {
+            return Ok(());
+        };
+
         self.display.clear_screen().ok();
-        
-        if let Some(game_state) = self.engine.get_game_state() {
-            self.display.show_inventory(game_state)?;
+        self.display.show_story_card(&format!("{} — {}", story.title, heading), &story.byline(), text)?;
+        self.display.wait_for_enter()?;
+        Ok(())
+    }
+
+    /// Dev mode tools for story authors.
+    async fn debug_console(&mut self) -> GameResult<()> {
+        loop {
+            let choices = [
+                "🔎 Variables",
+                "Δ Last Choice Changes",
+                "➕ Add Breakpoint",
+                "➖ Remove Breakpoint",
+                "🔙 Back",
+            ].map(String::from);
+
+            let selection = self.display
+                .select_opt("🐞 Debug Console", &choices, 0)
+                .map_err(|e| GameError::configuration(format!("Debug console error: {}", e)))?;
+
+            match selection {
+                Some(0) => {
+                    self.display.clear_screen().ok();
+                    self.display.show_debug_variables(&self.engine.debug_variables()?)?;
+                    self.display.wait_for_enter()?;
+                }
+                Some(1) => {
+                    match &self.last_choice_diff {
+                        Some(diff) if !diff.is_empty() => print!("{}", diff),
+                        Some(_) => self.display.show_message("The last choice changed nothing", "info")?,
+                        None => self.display.show_message("No choice made yet", "info")?,
+                    }
+                    self.display.wait_for_enter()?;
+                }
+                Some(2) => self.add_breakpoint().await?,
+                Some(3) => self.remove_breakpoint().await?,
+                _ => return Ok(()),
+            }
         }
-        
+    }
+
+    async fn add_breakpoint(&mut self) -> GameResult<()> {
+        let kinds = ["🚩 When a flag changes", "🚪 When entering a scene"].map(String::from);
+        let Some(kind) = self.display
+            .select_opt("Pause the game", &kinds, 0)
+            .map_err(|e| GameError::configuration(format!("Breakpoint selection error: {}", e)))?
+        else {
+            return Ok(());
+        };
+
+        let (history, prompt) = if kind == 0 { ("breakpoint_flag", "Flag name") } else { ("breakpoint_scene", "Scene ID") };
+        let name = self.display.prompt_text(history, prompt, None)
+            .map_err(|e| GameError::configuration(format!("Breakpoint input error: {}", e)))?;
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let breakpoint = if kind == 0 { Breakpoint::Flag(name) } else { Breakpoint::Scene(name) };
+        if self.engine.add_breakpoint(breakpoint.clone()).await {
+            self.display.show_success(&format!("Will pause when {}", breakpoint))?;
+        } else {
+            self.display.show_warning(&format!("Already pausing when {}", breakpoint))?;
+        }
+        Ok(())
+    }
+
+    async fn remove_breakpoint(&mut self) -> GameResult<()> {
+        let breakpoints = self.engine.breakpoints().await;
+        if breakpoints.is_empty() {
+            self.display.show_warning("No breakpoints set")?;
+            return Ok(());
+        }
+
+        let labels: Vec<String> = breakpoints.iter().map(|breakpoint| breakpoint.to_string()).collect();
+        let selection = self.display
+            .select_opt("Remove which breakpoint?", &labels, 0)
+            .map_err(|e| GameError::configuration(format!("Breakpoint selection error: {}", e)))?;
+
+        if let Some(breakpoint) = selection.and_then(|index| breakpoints.get(index)) {
+            self.engine.remove_breakpoint(breakpoint).await;
+            self.display.show_success(&format!("No longer pausing when {}", breakpoint))?;
+        }
+        Ok(())
+    }
+
+    fn show_help(&mut self) -> GameResult<()> {
+        self.display.clear_screen().ok();
+        self.display.show_help(&help_topics(self.engine.story_metadata()))?;
         self.display.wait_for_enter()?;
         Ok(())
     }
 
+    /// Walks through the help once, before the first game on this machine.
+    fn show_tutorial_once(&mut self) -> GameResult<()> {
+        let marker = self.config.get_config_dir().join(TUTORIAL_MARKER);
+        if marker.exists() {
+            return Ok(());
+        }
+
+        self.display.clear_screen().ok();
+        self.display.show_message("👋 Welcome! A quick tour before you begin (press ❓ Help in the game to see it again).", "info")?;
+        println!();
+        self.display.show_help(&help_topics(self.engine.story_metadata()))?;
+        self.display.wait_for_enter()?;
+
+        if let Err(e) = std::fs::write(&marker, "") {
+            warn!("Failed to remember that the tutorial was shown: {}", e);
+        }
+        Ok(())
+    }
+
+    fn show_scene_map(&mut self) -> GameResult<()> {
+        self.display.clear_screen().ok();
+        match self.engine.scene_map(MAP_DEPTH) {
+            Some(map) => self.display.show_scene_map(&map)?,
+            None => self.display.show_warning("There is no map for this place.")?,
+        }
+        self.display.wait_for_enter()?;
+        Ok(())
+    }
+
+    /// What happened so far, as far back as the event log goes.
+    async fn show_event_log(&mut self) -> GameResult<()> {
+        let lines: Vec<(String, &str)> = self.engine.get_event_history().await
+            .iter()
+            .filter_map(|event| {
+                let (line, style) = event.describe()?;
+                Some((format!("{}  {}", event.timestamp.with_timezone(&Local).format("%H:%M:%S"), line), style))
+            })
+            .collect();
+
+        self.display.clear_screen().ok();
+        if lines.is_empty() {
+            self.display.show_message("Nothing has happened yet.", "info")?;
+            self.display.wait_for_enter()?;
+            return Ok(());
+        }
+        self.display.show_scrollable("📖 Log", &lines)?;
+        Ok(())
+    }
+
     async fn show_game_statistics(&mut self) -> GameResult<()> {
         self.display.clear_screen().ok();
         
@@ -350,11 +1328,35 @@ impl GameInterface {
             
             self.display.show_message(&format!("Playtime: {}", stats.get_playtime_formatted()), "info")?;
             self.display.show_message(&format!("Scenes Visited: {} (unique: {})", stats.total_scenes_visited, stats.unique_scenes_visited), "info")?;
+            let player_stats = &game_state.player.stats;
+            let history = health_by_scene(&self.engine.get_event_history().await, player_stats.health);
+            let recent = &history[history.len().saturating_sub(HEALTH_HISTORY_SCENES)..];
+            if recent.len() > 1 {
+                self.display.show_message(&format!(
+                    "Health (last {} scenes): {} {}/{}",
+                    recent.len(), self.display.health_sparkline(recent, player_stats.max_health), player_stats.health, player_stats.max_health
+                ), "info")?;
+            }
             self.display.show_message(&format!("Player Level: {}", stats.player_level), "info")?;
             self.display.show_message(&format!("Total Experience: {}", stats.total_experience), "info")?;
+            for (name, value) in self.engine.derived_stats()? {
+                self.display.show_message(&format!("{}: {}", title_case(&name), value), "info")?;
+            }
             self.display.show_message(&format!("Inventory Items: {}", stats.inventory_size), "info")?;
             self.display.show_message(&format!("Total Inventory Value: {}", stats.total_inventory_value), "info")?;
             self.display.show_message(&format!("Flags Set: {}", stats.flags_set), "info")?;
+            if let Some(party) = &game_state.party {
+                for seat in &party.seats {
+                    let line = match party.mode {
+                        HotseatMode::Alternate => {
+                            let health = seat.player.as_ref().unwrap_or(&game_state.player).stats.health;
+                            format!("{}: {} choices made, health {}", seat.name, seat.choices_made, health)
+                        }
+                        HotseatMode::Vote => format!("{}: {} votes won", seat.name, seat.votes_won),
+                    };
+                    self.display.show_message(&line, "info")?;
+                }
+            }
             self.display.show_message(&format!("Game Started: {}", stats.game_start_time.format("%Y-%m-%d %H:%M:%S UTC")), "info")?;
             
             if let Some(last_save) = stats.last_save_time {
@@ -369,22 +1371,26 @@ impl GameInterface {
     }
 
     async fn quick_settings(&mut self) -> GameResult<()> {
-        let choices = vec![
+        let choices = [
             "🎨 Change Theme",
             "⚙️ Toggle Stats Display",
+            "🔊 Toggle Read-Aloud",
+            "📏 Text Width",
+            "⏩ Toggle Streamlined Mode",
             "🔙 Back"
-        ];
+        ].map(String::from);
 
-        let selection = Select::new()
-            .with_prompt("Quick Settings")
-            .items(&choices)
-            .interact()
+        let selection = self.display
+            .select("Quick Settings", &choices, 0)
             .map_err(|e| GameError::configuration(format!("Settings selection error: {}", e)))?;
 
         match selection {
             0 => self.change_theme().await?,
             1 => self.toggle_stats_display(),
-            2 => {} // Back
+            2 => self.toggle_read_aloud(),
+            3 => self.change_text_width()?,
+            4 => self.toggle_streamlined()?,
+            5 => {} // Back
             _ => unreachable!(),
         }
 
@@ -394,63 +1400,198 @@ impl GameInterface {
     async fn change_theme(&mut self) -> GameResult<()> {
         let themes = self.display.get_available_themes();
         
-        let selection = Select::new()
-            .with_prompt("Choose theme")
-            .items(&themes)
-            .interact()
+        let selection = self.display
+            .select("Choose theme", &themes, 0)
             .map_err(|e| GameError::configuration(format!("Theme selection error: {}", e)))?;
 
         let selected_theme = &themes[selection];
         
         if self.display.set_theme(selected_theme) {
-            self.display.show_success(&format!("Theme changed to '{}'", selected_theme))?;
+            self.acknowledge(&format!("✅ Theme changed to '{}'", selected_theme), "success")
         } else {
-            self.display.show_error(&format!("Failed to set theme '{}'", selected_theme))?;
+            self.acknowledge(&format!("❌ Failed to set theme '{}'", selected_theme), "error")
         }
-        
+    }
+
+    fn toggle_read_aloud(&mut self) {
+        self.config.ui.read_aloud = !self.config.ui.read_aloud;
+        self.update_narrator();
+        let status = if self.config.ui.read_aloud { "enabled" } else { "disabled" };
+        self.acknowledge(&format!("✅ Read-aloud {}", status), "success").ok();
+    }
+
+    fn change_text_width(&mut self) -> GameResult<()> {
+        let prompt = format!("Text width (at least {}, now showing {}): ", MIN_TEXT_WIDTH, self.display.width());
+        let text_width = loop {
+            let input = self.display
+                .prompt_text("text_width", &prompt, Some(self.config.ui.text_width.to_string()))
+                .map_err(|e| GameError::configuration(format!("Text width input error: {}", e)))?;
+            match input.parse::<usize>() {
+                Ok(width) if width >= MIN_TEXT_WIDTH => break width,
+                _ => self.display.show_error(&format!("Text width must be at least {}", MIN_TEXT_WIDTH))?,
+            }
+        };
+
+        let fit_to_terminal = self.display
+            .confirm("Shrink text to fit narrower terminals?", self.config.ui.fit_to_terminal)
+            .map_err(|e| GameError::configuration(format!("Text width input error: {}", e)))?;
+
+        self.config.ui.text_width = text_width;
+        self.config.ui.fit_to_terminal = fit_to_terminal;
+        self.display.set_text_width(text_width);
+        self.display.set_fit_to_terminal(fit_to_terminal);
+        self.acknowledge(&format!("✅ Text width set to {}", text_width), "success")
+    }
+
+    /// Shows a message the player confirms with Enter, or in streamlined
+    /// mode holds it for the next feedback line.
+    fn acknowledge(&mut self, message: &str, style: &'static str) -> GameResult<()> {
+        if self.config.ui.streamlined {
+            self.pending_feedback.push((message.to_string(), style));
+            return Ok(());
+        }
+        self.display.show_message(message, style)?;
         self.display.wait_for_enter()?;
         Ok(())
     }
 
+    /// Shows a message for a moment, or in streamlined mode holds it for
+    /// the next feedback line.
+    async fn flash(&mut self, message: &str, style: &'static str) -> GameResult<()> {
+        if self.config.ui.streamlined {
+            self.pending_feedback.push((message.to_string(), style));
+            return Ok(());
+        }
+        self.display.show_message(message, style)?;
+        sleep(Duration::from_millis(self.config.get_animation_delay_ms().max(500))).await;
+        Ok(())
+    }
+
+    /// Prints held-back messages on a single line.
+    fn show_pending_feedback(&mut self) -> GameResult<()> {
+        if self.pending_feedback.is_empty() {
+            return Ok(());
+        }
+        let line = self.pending_feedback.drain(..)
+            .map(|(message, style)| self.display.style(&message, style))
+            .collect::<Vec<_>>()
+            .join(" · ");
+        println!("{}", line);
+        Ok(())
+    }
+
+    fn toggle_streamlined(&mut self) -> GameResult<()> {
+        self.config.ui.streamlined = !self.config.ui.streamlined;
+        let status = if self.config.ui.streamlined { "enabled" } else { "disabled" };
+        self.acknowledge(&format!("Streamlined mode {}", status), "success")
+    }
+
+    /// `scene` with its description rewritten by the flavor writer, if the
+    /// story opted in. A failed rewrite falls back to the authored text.
+    fn flavored_scene(&mut self, scene: &Scene) -> Option<Scene> {
+        let writer = self.flavor_writer.as_ref()?;
+        let story = self.engine.story()?;
+        let game_state = self.engine.get_game_state()?;
+        let settings = FlavorSettings::of(story);
+        if !settings.applies_to(scene) {
+            return None;
+        }
+
+        let visit = (scene.id.clone(), game_state.visited_scenes.total());
+        let description = match &self.flavored_description {
+            Some((cached, description)) if *cached == visit => description.clone(),
+            _ => {
+                let request = FlavorRequest {
+                    story_title: &story.title,
+                    scene,
+                    player_name: &game_state.player.name,
+                    style: settings.style.as_deref(),
+                };
+                let description = writer.rewrite(&request).unwrap_or_else(|e| {
+                    warn!("AI narrator failed, showing the authored text: {}", e);
+                    None
+                });
+                self.flavored_description = Some((visit, description.clone()));
+                description
+            }
+        };
+
+        let mut flavored = scene.clone();
+        flavored.description = description?;
+        Some(flavored)
+    }
+
+    /// Starts or stops the narrator to match the config.
+    fn update_narrator(&mut self) {
+        self.narrator = self.config.ui.read_aloud.then(|| Narrator::new(self.config.ui.tts_command.clone()));
+    }
+
     fn toggle_stats_display(&mut self) {
         self.config.ui.show_stats_in_header = !self.config.ui.show_stats_in_header;
         let status = if self.config.ui.show_stats_in_header { "enabled" } else { "disabled" };
-        self.display.show_success(&format!("Stats display {}", status)).ok();
-        self.display.wait_for_enter().ok();
+        self.acknowledge(&format!("✅ Stats display {}", status), "success").ok();
     }
 
+    /// Asks before leaving the game. With unsaved progress the player is
+    /// offered to save first; returns false if they cancelled.
     async fn confirm_quit(&mut self) -> GameResult<bool> {
-        let confirmed = Confirm::new()
-            .with_prompt("Are you sure you want to quit? (Progress will be lost unless saved)")
-            .default(false)
-            .interact()
+        if !self.engine.has_unsaved_progress() {
+            if self.config.ui.streamlined {
+                return Ok(true);
+            }
+            let confirmed = self.display
+                .confirm("Are you sure you want to quit?", false)
+                .map_err(|e| GameError::configuration(format!("Quit confirmation error: {}", e)))?;
+
+            return Ok(confirmed);
+        }
+
+        let choices = [
+            "💾 Save and quit",
+            "🗑️ Quit without saving",
+            "🔙 Cancel",
+        ].map(String::from);
+
+        let selection = self.display
+            .select_opt("Save before quitting?", &choices, 0)
             .map_err(|e| GameError::configuration(format!("Quit confirmation error: {}", e)))?;
 
-        Ok(confirmed)
+        match selection {
+            Some(0) => self.save_current_game().await,
+            Some(1) => Ok(true),
+            _ => Ok(false),
+        }
     }
 
     async fn settings_menu(&mut self) -> GameResult<()> {
         loop {
-            let choices = vec![
+            self.show_pending_feedback()?;
+            let choices = [
                 "🎨 Theme Settings",
+                "🔊 Toggle Read-Aloud",
+                "📏 Text Width",
+                "⏩ Toggle Streamlined Mode",
                 "💾 Save Management",
                 "📊 View All Statistics", 
                 "🧹 Cleanup Old Saves",
+                "📜 Export Session Log",
                 "🔙 Back to Main Menu"
-            ];
+            ].map(String::from);
 
-            let selection = Select::new()
-                .with_prompt("Settings")
-                .items(&choices)
-                .interact()
+            let selection = self.display
+                .select("Settings", &choices, 0)
                 .map_err(|e| GameError::configuration(format!("Settings selection error: {}", e)))?;
 
             match selection {
                 0 => self.theme_settings().await?,
-                1 => self.save_management().await?,
-                2 => self.all_statistics().await?,
-                3 => self.cleanup_saves().await?,
-                4 => break,
+                1 => self.toggle_read_aloud(),
+                2 => self.change_text_width()?,
+                3 => self.toggle_streamlined()?,
+                4 => self.save_management().await?,
+                5 => self.all_statistics().await?,
+                6 => self.cleanup_saves().await?,
+                7 => self.export_session_log().await?,
+                8 => break,
                 _ => unreachable!(),
             }
         }
@@ -493,17 +1634,15 @@ impl GameInterface {
             )?;
         }
 
-        let choices = vec![
+        let choices = [
             "🗑️ Delete a Save",
             "📤 Export Save",
             "📥 Import Save",
             "🔙 Back"
-        ];
+        ].map(String::from);
 
-        let selection = Select::new()
-            .with_prompt("Save Management Options")
-            .items(&choices)
-            .interact()
+        let selection = self.display
+            .select("Save Management Options", &choices, 0)
             .map_err(|e| GameError::configuration(format!("Save management selection error: {}", e)))?;
 
         match selection {
@@ -531,18 +1670,14 @@ impl GameInterface {
             .map(|save| save.display_name())
             .collect();
 
-        let selection = Select::new()
-            .with_prompt("Choose save to delete")
-            .items(&save_choices)
-            .interact()
+        let selection = self.display
+            .select("Choose save to delete", &save_choices, 0)
             .map_err(|e| GameError::save_load(format!("Delete save selection error: {}", e)))?;
 
         let selected_save = &saves[selection];
         
-        let confirmed = Confirm::new()
-            .with_prompt(&format!("Are you sure you want to delete '{}'?", selected_save.name))
-            .default(false)
-            .interact()
+        let confirmed = self.display
+            .confirm(&format!("Are you sure you want to delete '{}'?", selected_save.name), false)
             .map_err(|e| GameError::configuration(format!("Delete confirmation error: {}", e)))?;
 
         if confirmed {
@@ -579,6 +1714,10 @@ impl GameInterface {
         self.display.show_message(&separator, "separator")?;
         
         self.display.show_message(&format!("Available Stories: {}", stories.len()), "info")?;
+        for story in &stories {
+            let playtime = story.playtime_text().unwrap_or_else(|| "unknown length".to_string());
+            self.display.show_message(&format!("  📖 {} ({} scenes, {})", story.title, story.scene_count, playtime), "info")?;
+        }
         self.display.show_message(&format!("Total Save Games: {}", save_count), "info")?;
         self.display.show_message(&format!("Game Version: {}", crate::VERSION), "info")?;
         
@@ -588,13 +1727,41 @@ impl GameInterface {
         Ok(())
     }
 
+    /// Writes this session's event history to a file, for bug reports.
+    async fn export_session_log(&mut self) -> GameResult<()> {
+        if self.engine.get_event_history().await.is_empty() {
+            self.display.show_info("Nothing has happened this session yet.")?;
+            self.display.wait_for_enter()?;
+            return Ok(());
+        }
+
+        let formats = ["📝 Readable text", "🧾 JSON"].map(String::from);
+        let selection = self.display
+            .select("Log format", &formats, 0)
+            .map_err(|e| GameError::configuration(format!("Log format selection error: {}", e)))?;
+        let readable = selection == 0;
+
+        let extension = if readable { "txt" } else { "json" };
+        let default_path = format!("session-log-{}.{}", Local::now().format("%Y%m%d-%H%M%S"), extension);
+        let path = self.display
+            .prompt_text("log_path", "Write the log to", Some(default_path))
+            .map_err(|e| GameError::configuration(format!("Log path input error: {}", e)))?;
+        if path.is_empty() {
+            return Ok(());
+        }
+
+        let log = self.engine.export_event_history(readable).await?;
+        match tokio::fs::write(&path, log).await {
+            Ok(()) => self.acknowledge(&format!("✅ Session log written to {}", path), "success"),
+            Err(e) => self.acknowledge(&format!("❌ Failed to write {}: {}", path, e), "error"),
+        }
+    }
+
     async fn cleanup_saves(&mut self) -> GameResult<()> {
         let keep_count = self.config.saves.max_saves_per_story;
         
-        let confirmed = Confirm::new()
-            .with_prompt(&format!("This will keep only the {} most recent saves per story. Continue?", keep_count))
-            .default(false)
-            .interact()
+        let confirmed = self.display
+            .confirm(&format!("This will keep only the {} most recent saves per story. Continue?", keep_count), false)
             .map_err(|e| GameError::configuration(format!("Cleanup confirmation error: {}", e)))?;
 
         if confirmed {
@@ -615,25 +1782,181 @@ impl GameInterface {
     // Public API for CLI usage
     pub async fn load_story(&mut self, story_id: &str) -> GameResult<()> {
         let story = self.story_loader.load_story(story_id).await?;
+        self.title_art = self.story_loader.load_title_art(&story).await;
         self.engine.load_story(story).await?;
         Ok(())
     }
 
-    pub async fn start_new_game(&mut self) -> GameResult<()> {
+    /// Plays a new game of the loaded story. Returns false if the player
+    /// chose to quit to the desktop rather than to the main menu.
+    pub async fn start_new_game(&mut self) -> GameResult<bool> {
         let player_name = "Player".to_string(); // Default for CLI usage
         self.engine.start_new_game(player_name).await?;
-        self.game_loop().await?;
+        self.show_story_title().await?;
+        self.show_tutorial_once()?;
+        self.show_story_page(|story| story.intro.as_deref(), "Prologue")?;
+        self.game_loop().await
+    }
+
+    /// Plays a new hotseat game of the loaded story for `names`, see
+    /// `Party`. Returns false if they chose to quit to the desktop.
+    pub async fn start_hotseat_game(&mut self, mode: HotseatMode, names: &[String]) -> GameResult<bool> {
+        self.engine.start_hotseat_game(mode, names).await?;
+        self.show_story_title().await?;
+        self.show_tutorial_once()?;
+        self.show_story_page(|story| story.intro.as_deref(), "Prologue")?;
+        self.game_loop().await
+    }
+
+    /// Attract mode for exhibitions: the title screen waits for a key, and
+    /// when nobody presses one a demo plays random choices until a key
+    /// hands control back to the title screen.
+    pub async fn run_kiosk(&mut self, kiosk: KioskSettings) -> GameResult<()> {
+        info!("Starting kiosk mode");
+        let mut rng = SeededRng::new(chrono::Utc::now().timestamp_millis() as u64);
+        let mut demos = 0;
+
+        loop {
+            self.display.clear_screen().ok();
+            self.show_game_title().await?;
+            self.display.show_message("Press any key to play", "info")?;
+            if self.display.key_pressed_within(kiosk.idle)? {
+                match self.show_main_menu().await {
+                    Ok(true) => continue,
+                    Ok(false) => break,
+                    Err(e) => {
+                        error!("Main menu error: {}", e);
+                        self.display.show_game_error(&e).ok();
+                        self.display.wait_for_enter().ok();
+                        continue;
+                    }
+                }
+            }
+
+            if let Err(e) = self.play_demo(&kiosk, &mut rng).await {
+                warn!("Demo stopped: {}", e);
+            }
+            demos += 1;
+            if kiosk.max_demos.is_some_and(|max| demos >= max) {
+                break;
+            }
+        }
         Ok(())
     }
+
+    /// Plays one demo game. Returns true if a key interrupted it.
+    async fn play_demo(&mut self, kiosk: &KioskSettings, rng: &mut SeededRng) -> GameResult<bool> {
+        let story_id = match &kiosk.story_id {
+            Some(story_id) => story_id.clone(),
+            None => {
+                let stories = self.story_loader.list_available_stories().await?;
+                if stories.is_empty() {
+                    return Err(GameError::story("No stories to play".to_string()));
+                }
+                stories[rng.below(stories.len())].id.clone()
+            }
+        };
+        self.load_story(&story_id).await?;
+        self.engine.start_new_game(DEMO_PLAYER.to_string()).await?;
+
+        for _ in 0..DEMO_MAX_CHOICES {
+            let scene = self.engine.get_current_scene().await?;
+            self.display.clear_screen().ok();
+            self.display.show_message("DEMO - press any key to play", "warning")?;
+            self.display.show_scene(&scene.scene)?;
+            if let Some(game_state) = self.engine.get_game_state() {
+                self.display.show_player_stats(game_state, self.engine.currency())?;
+            }
+            self.display.show_choices(&scene, None)?;
+
+            if self.display.key_pressed_within(kiosk.delay)? {
+                return Ok(true);
+            }
+            let choices: Vec<String> = scene.available_choices().map(|choice| choice.id.clone()).collect();
+            if scene.is_ending() {
+                break;
+            }
+            if choices.is_empty() {
+                if scene.scene.auto_advance.is_none() {
+                    break;
+                }
+                self.engine.auto_advance().await?;
+                continue;
+            }
+            self.engine.make_choice(&choices[rng.below(choices.len())]).await?;
+        }
+        Ok(false)
+    }
+}
+
+/// How `GameInterface::run_kiosk` plays.
+#[derive(Debug, Clone)]
+pub struct KioskSettings {
+    /// Story the demo plays; a random one each time if `None`.
+    pub story_id: Option<String>,
+    /// How long the title screen waits for a key before a demo starts.
+    pub idle: Duration,
+    /// How long each demo scene is shown.
+    pub delay: Duration,
+    /// Stop after this many demos, e.g. for smoke tests.
+    pub max_demos: Option<usize>,
+}
+
+/// One line of the load menu, with the save's slot or quick save marker, its
+/// preview if it has one and a flag if the game was finished.
+fn save_choice_label(save: &SaveGameMetadata) -> String {
+    let label = match save.slot {
+        Some(slot) => format!("[Slot {}] ", slot),
+        None if save.quick_save => "⚡ ".to_string(),
+        None => String::new(),
+    };
+    let mut choice = format!("{}{} - {} ({}, {})", 
+        label,
+        save.name, 
+        save.save_time.format("%Y-%m-%d %H:%M"), 
+        save.get_playtime_formatted(),
+        save.get_size_formatted()
+    );
+    if let Some(preview) = &save.preview {
+        choice.push_str(&format!(" | {}", preview));
+    }
+    if save.completed {
+        choice.push_str(" 🏁");
+    }
+    choice
 }
 
-// Extension trait for display to add missing methods
-trait DisplayExt {
-    fn show_info(&self, message: &str) -> std::io::Result<()>;
+/// "strength" -> "Strength".
+/// Dyslexia-friendly mode spaces everything out further on top of the
+/// configured spacing.
+fn text_layout(config: &Config) -> TextLayout {
+    let dyslexia_friendly = config.ui.dyslexia_friendly;
+    TextLayout {
+        paragraph_spacing: if dyslexia_friendly {
+            config.ui.paragraph_spacing.max(2)
+        } else {
+            config.ui.paragraph_spacing
+        },
+        paragraph_indent: config.ui.paragraph_indent,
+        double_spacing: dyslexia_friendly,
+    }
 }
 
-impl DisplayExt for Display {
-    fn show_info(&self, message: &str) -> std::io::Result<()> {
-        self.show_message(&format!("ℹ️ {}", message), "info")
+/// The frontend picked with `ui.renderer`, set up from the UI settings.
+fn configured_renderer(config: &Config) -> std::io::Result<Box<dyn Renderer>> {
+    match config.ui.renderer {
+        RendererKind::Console => {
+            let display = Display::new(ThemeManager::new(), config.ui.text_width)?
+                .with_fit_to_terminal(config.ui.fit_to_terminal)
+                .with_choice_hints(config.ui.show_choice_hints)
+                .with_text_layout(text_layout(config));
+            Ok(Box::new(display))
+        }
+        RendererKind::Plain => Ok(Box::new(
+            PlainRenderer::new(config.ui.text_width)
+                .with_choice_hints(config.ui.show_choice_hints)
+                .with_text_layout(text_layout(config)),
+        )),
     }
-}
\ No newline at end of file
+}
+
@@ -0,0 +1,54 @@
+//! Runs a `ChatBot` in IRC channels. Needs the `irc` feature.
+
+use crate::ui::ChatBot;
+use crate::utils::{GameError, GameResult};
+
+/// Where `run_irc_bot` connects.
+#[derive(Debug, Clone)]
+pub struct IrcBotSettings {
+    /// `host:port`
+    pub server: String,
+    pub nick: String,
+    pub password: Option<String>,
+    pub channels: Vec<String>,
+}
+
+/// Joins the channels in `settings` and plays until the connection drops.
+#[cfg(feature = "irc")]
+pub async fn run_irc_bot(bot: &mut ChatBot, settings: &IrcBotSettings) -> GameResult<()> {
+    use crate::ui::chat::IrcClient;
+
+    let connection_error = |e: std::io::Error| GameError::configuration(format!("IRC connection failed: {}", e));
+    let mut client = IrcClient::connect(&settings.server, &settings.nick, settings.password.as_deref()).await
+        .map_err(connection_error)?;
+    for channel in &settings.channels {
+        client.join(channel).await.map_err(connection_error)?;
+    }
+    tracing::info!("IRC bot {} joined {}", settings.nick, settings.channels.join(", "));
+
+    loop {
+        // Far enough out that only the connection ends the wait
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(3600);
+        let Some(message) = client.next_message(deadline).await.map_err(connection_error)? else {
+            continue;
+        };
+        if !settings.channels.iter().any(|channel| channel.eq_ignore_ascii_case(&message.target)) {
+            continue;
+        }
+        let lines: Vec<String> = match bot.handle(&message.target, &message.sender, &message.text).await {
+            Ok(replies) => replies.iter().flat_map(|reply| reply.lines()).collect(),
+            Err(e) => e.report().lines().map(|line| format!("{}: {}", message.sender, line)).collect(),
+        };
+        for line in lines {
+            client.say(&message.target, &line).await.map_err(connection_error)?;
+        }
+    }
+}
+
+#[cfg(not(feature = "irc"))]
+pub async fn run_irc_bot(_bot: &mut ChatBot, settings: &IrcBotSettings) -> GameResult<()> {
+    Err(GameError::configuration(format!(
+        "Can't join {} on {}: the game was built without the irc feature",
+        settings.channels.join(", "), settings.server
+    )))
+}
@@ -0,0 +1,264 @@
+//! Runs a `ChatBot` in Matrix rooms through the client-server API. The bot
+//! joins the rooms it's invited to and plays a game in each; scene
+//! descriptions are sent as spoilers, so they stay folded until clicked in
+//! clients that support them. Needs the `matrix` feature.
+
+use serde_json::{json, Value};
+use crate::ui::{BotReply, ChatBot};
+use crate::utils::{GameError, GameResult};
+
+/// Where `run_matrix_bot` connects.
+#[derive(Debug, Clone)]
+pub struct MatrixBotSettings {
+    /// e.g. `https://matrix.example.org`
+    pub homeserver: String,
+    pub access_token: String,
+}
+
+/// A message someone sent to a room.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomMessage {
+    pub room_id: String,
+    pub sender: String,
+    pub body: String,
+}
+
+/// What one `/sync` response brought.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncBatch {
+    /// Where the next sync continues from.
+    pub next_batch: String,
+    /// Rooms the bot was invited to.
+    pub invites: Vec<String>,
+    /// Text messages from others, oldest first.
+    pub messages: Vec<RoomMessage>,
+}
+
+impl SyncBatch {
+    /// Reads a `/sync` response, leaving out `own_user`'s messages.
+    pub fn parse(sync: &Value, own_user: &str) -> Option<Self> {
+        let next_batch = sync["next_batch"].as_str()?.to_string();
+        let invites = sync["rooms"]["invite"].as_object()
+            .map(|rooms| rooms.keys().cloned().collect())
+            .unwrap_or_default();
+
+        let mut messages = Vec::new();
+        for (room_id, room) in sync["rooms"]["join"].as_object().into_iter().flatten() {
+            for event in room["timeline"]["events"].as_array().into_iter().flatten() {
+                let is_text = event["type"] == "m.room.message" && event["content"]["msgtype"] == "m.text";
+                let (Some(sender), Some(body)) = (event["sender"].as_str(), event["content"]["body"].as_str()) else {
+                    continue;
+                };
+                if is_text && sender != own_user {
+                    messages.push(RoomMessage { room_id: room_id.clone(), sender: sender.to_string(), body: body.to_string() });
+                }
+            }
+        }
+        Some(Self { next_batch, invites, messages })
+    }
+}
+
+/// Content of the `m.room.message` event for `reply`. The HTML version
+/// hides scene descriptions behind a spoiler.
+pub fn message_content(reply: &BotReply) -> Value {
+    let formatted = match reply {
+        BotReply::Scene { title, description, choices, ended } => {
+            let mut html = format!("<strong>{}</strong><br><span data-mx-spoiler>{}</span>", escape_html(title), escape_html(&description.join(" ")));
+            if *ended {
+                html.push_str("<br><em>The End. !start to play again</em>");
+            } else if !choices.is_empty() {
+                html.push_str(&format!("<br>{}", escape_html(&choices.join(" | "))));
+            }
+            html
+        }
+        BotReply::Text(text) => escape_html(text),
+    };
+    json!({
+        "msgtype": "m.notice",
+        "body": reply.lines().join("\n"),
+        "format": "org.matrix.custom.html",
+        "formatted_body": formatted,
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Joins rooms as it's invited and plays until the homeserver can't be
+/// reached.
+#[cfg(feature = "matrix")]
+pub async fn run_matrix_bot(bot: &mut ChatBot, settings: &MatrixBotSettings) -> GameResult<()> {
+    let client = client::MatrixClient::new(settings);
+    let own_user = client.whoami().await?;
+    // Skip what was said while the bot was away
+    let mut since = client.sync(None).await?["next_batch"].as_str().map(str::to_string);
+    tracing::info!("Matrix bot {} is listening", own_user);
+
+    loop {
+        let sync = client.sync(since.as_deref()).await?;
+        let batch = SyncBatch::parse(&sync, &own_user)
+            .ok_or_else(|| GameError::configuration("Invalid Matrix sync response".to_string()))?;
+        for room_id in &batch.invites {
+            client.join(room_id).await?;
+        }
+        for message in &batch.messages {
+            let replies = match bot.handle(&message.room_id, &message.sender, &message.body).await {
+                Ok(replies) => replies,
+                Err(e) => vec![BotReply::Text(format!("{}: {}", message.sender, e.report()))],
+            };
+            for reply in &replies {
+                client.send(&message.room_id, message_content(reply)).await?;
+            }
+        }
+        since = Some(batch.next_batch);
+    }
+}
+
+#[cfg(not(feature = "matrix"))]
+pub async fn run_matrix_bot(_bot: &mut ChatBot, settings: &MatrixBotSettings) -> GameResult<()> {
+    Err(GameError::configuration(format!(
+        "Can't connect to {}: the game was built without the matrix feature",
+        settings.homeserver
+    )))
+}
+
+#[cfg(feature = "matrix")]
+mod client {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::Duration;
+    use serde_json::{json, Value};
+    use super::MatrixBotSettings;
+    use crate::utils::{GameError, GameResult};
+
+    /// How long the homeserver may hold a sync open.
+    const SYNC_TIMEOUT_MS: u64 = 30_000;
+
+    enum Method {
+        Get,
+        Post,
+        Put,
+    }
+
+    pub struct MatrixClient {
+        agent: ureq::Agent,
+        homeserver: String,
+        authorization: String,
+        transaction: AtomicU64,
+    }
+
+    impl MatrixClient {
+        pub fn new(settings: &MatrixBotSettings) -> Self {
+            // Long enough for a sync the homeserver holds open
+            let timeout = Duration::from_millis(SYNC_TIMEOUT_MS) * 2;
+            let agent = ureq::Agent::config_builder().timeout_global(Some(timeout)).build().into();
+            Self {
+                agent,
+                homeserver: settings.homeserver.trim_end_matches('/').to_string(),
+                authorization: format!("Bearer {}", settings.access_token),
+                transaction: AtomicU64::new(chrono::Utc::now().timestamp_millis() as u64),
+            }
+        }
+
+        pub async fn whoami(&self) -> GameResult<String> {
+            let reply = self.request(Method::Get, "/account/whoami".to_string(), None).await?;
+            reply["user_id"].as_str().map(str::to_string)
+                .ok_or_else(|| GameError::configuration("Matrix homeserver didn't say who we are".to_string()))
+        }
+
+        /// Syncs from `since`; without it, returns straight away with
+        /// where to start.
+        pub async fn sync(&self, since: Option<&str>) -> GameResult<Value> {
+            let path = match since {
+                Some(since) => format!("/sync?timeout={}&since={}", SYNC_TIMEOUT_MS, encode(since)),
+                None => "/sync?timeout=0".to_string(),
+            };
+            self.request(Method::Get, path, None).await
+        }
+
+        pub async fn join(&self, room_id: &str) -> GameResult<()> {
+            self.request(Method::Post, format!("/join/{}", encode(room_id)), Some(json!({}))).await?;
+            tracing::info!("Joined Matrix room {}", room_id);
+            Ok(())
+        }
+
+        pub async fn send(&self, room_id: &str, content: Value) -> GameResult<()> {
+            let transaction = self.transaction.fetch_add(1, Ordering::Relaxed);
+            let path = format!("/rooms/{}/send/m.room.message/{}", encode(room_id), transaction);
+            self.request(Method::Put, path, Some(content)).await?;
+            Ok(())
+        }
+
+        /// Sends a request to the client-server API off the async runtime,
+        /// since the HTTP client blocks.
+        async fn request(&self, method: Method, path: String, body: Option<Value>) -> GameResult<Value> {
+            let agent = self.agent.clone();
+            let url = format!("{}/_matrix/client/v3{}", self.homeserver, path);
+            let authorization = self.authorization.clone();
+            tokio::task::spawn_blocking(move || {
+                let body = body.unwrap_or_else(|| json!({}));
+                let response = match method {
+                    Method::Get => agent.get(&url).header("Authorization", &authorization).call(),
+                    Method::Post => agent.post(&url).header("Authorization", &authorization).send_json(body),
+                    Method::Put => agent.put(&url).header("Authorization", &authorization).send_json(body),
+                };
+                response
+                    .map_err(|e| GameError::configuration(format!("Matrix request failed: {}", e)))?
+                    .body_mut()
+                    .read_json::<Value>()
+                    .map_err(|e| GameError::configuration(format!("Invalid Matrix reply: {}", e)))
+            })
+            .await
+            .map_err(|e| GameError::configuration(format!("Matrix request failed: {}", e)))?
+        }
+    }
+
+    /// Percent-encodes a path or query component, such as a room ID.
+    fn encode(component: &str) -> String {
+        component.bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+                _ => format!("%{:02X}", byte),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_sync_and_messages() {
+        let sync = json!({
+            "next_batch": "s72595_4483",
+            "rooms": {
+                "invite": {"!new:example.org": {}},
+                "join": {"!cave:example.org": {"timeline": {"events": [
+                    {"type": "m.room.message", "sender": "@ana:example.org", "content": {"msgtype": "m.text", "body": "!1"}},
+                    {"type": "m.room.message", "sender": "@bot:example.org", "content": {"msgtype": "m.notice", "body": "== Hall =="}},
+                    {"type": "m.room.member", "sender": "@ben:example.org", "content": {"membership": "join"}}
+                ]}}}
+            }
+        });
+        let batch = SyncBatch::parse(&sync, "@bot:example.org").unwrap();
+        assert_eq!(batch.next_batch, "s72595_4483");
+        assert_eq!(batch.invites, ["!new:example.org"]);
+        assert_eq!(batch.messages, [RoomMessage {
+            room_id: "!cave:example.org".to_string(),
+            sender: "@ana:example.org".to_string(),
+            body: "!1".to_string(),
+        }]);
+        assert_eq!(SyncBatch::parse(&json!({}), "@bot:example.org"), None);
+
+        let reply = BotReply::Scene {
+            title: "Hall".to_string(),
+            description: vec!["Echoes <everywhere>.".to_string()],
+            choices: vec!["!1 Back out".to_string()],
+            ended: false,
+        };
+        let content = message_content(&reply);
+        assert_eq!(content["body"], "== Hall ==\nEchoes <everywhere>.\n!1 Back out");
+        assert_eq!(content["formatted_body"], "<strong>Hall</strong><br><span data-mx-spoiler>Echoes &lt;everywhere&gt;.</span><br>!1 Back out");
+    }
+}
@@ -1,7 +1,25 @@
 pub mod interface;
 pub mod theme;
 pub mod components;
+pub mod renderer;
+pub mod plain;
+pub mod speech;
+pub mod help;
+pub mod chat;
+pub mod twitch;
+pub mod chat_bot;
+pub mod irc_bot;
+pub mod matrix_bot;
+pub mod crash;
 
-pub use interface::GameInterface;
+pub use interface::{GameInterface, KioskSettings};
 pub use theme::{Theme, ThemeManager};
+pub use speech::Narrator;
+pub use help::HelpTopic;
+pub use renderer::Renderer;
+pub use plain::PlainRenderer;
+pub use crash::CrashGuard;
+pub use chat_bot::{BotReply, ChatBot};
+pub use irc_bot::{run_irc_bot, IrcBotSettings};
+pub use matrix_bot::{run_matrix_bot, MatrixBotSettings};
 pub use components::*;
\ No newline at end of file
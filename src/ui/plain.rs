@@ -0,0 +1,445 @@
+//! Renderer for output that is piped or logged: no colors, emoji or cursor
+//! movement, and menus answered by typing a number.
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+use crate::core::{DebugVariable, GameState};
+use crate::story::{Currency, MapNode, Scene, SceneMap, SceneView};
+use crate::ui::components::wrap_paragraphs;
+use crate::ui::{HelpTopic, MenuInput, Renderer, TextLayout, TimedInput};
+
+pub struct PlainRenderer {
+    text_width: usize,
+    layout: TextLayout,
+    show_choice_hints: bool,
+}
+
+impl PlainRenderer {
+    pub fn new(text_width: usize) -> Self {
+        Self {
+            text_width,
+            layout: TextLayout::default(),
+            show_choice_hints: true,
+        }
+    }
+
+    pub fn with_text_layout(mut self, layout: TextLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_choice_hints(mut self, show_choice_hints: bool) -> Self {
+        self.show_choice_hints = show_choice_hints;
+        self
+    }
+
+    fn line(&self, text: &str) -> io::Result<()> {
+        writeln!(io::stdout(), "{}", strip_emoji(text))
+    }
+
+    fn wrapped(&self, text: &str) -> io::Result<()> {
+        for line in wrap_paragraphs(&strip_emoji(text), self.text_width, &self.layout) {
+            writeln!(io::stdout(), "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Prints `prompt` and reads a trimmed line; `None` at the end of input.
+    fn read_line(&self, prompt: &str) -> io::Result<Option<String>> {
+        print!("{}", strip_emoji(prompt));
+        io::stdout().flush()?;
+        let mut input = String::new();
+        if io::stdin().lock().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(input.trim().to_string()))
+    }
+
+    fn list_items(&self, prompt: &str, items: &[String]) -> io::Result<()> {
+        self.line(prompt)?;
+        for (index, item) in items.iter().enumerate() {
+            self.line(&format!("  {}. {}", index + 1, item))?;
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for PlainRenderer {
+    fn width(&self) -> usize {
+        self.text_width
+    }
+
+    /// Screens are separated by a blank line instead.
+    fn clear_screen(&self) -> io::Result<()> {
+        writeln!(io::stdout())
+    }
+
+    fn show_title(&self, title: &str) -> io::Result<()> {
+        self.line(title)?;
+        self.line(&"=".repeat(self.text_width))?;
+        writeln!(io::stdout())
+    }
+
+    fn show_scene(&self, scene: &Scene) -> io::Result<()> {
+        self.line(&scene.title)?;
+        self.line(&"-".repeat(scene.title.chars().count().max(1)))?;
+        self.wrapped(&scene.description)?;
+        writeln!(io::stdout())
+    }
+
+    fn show_player_stats(&self, game_state: &GameState, currency: Option<&Currency>) -> io::Result<()> {
+        let stats = &game_state.player.stats;
+        let mut text = format!(
+            "{} | Health: {}/{} | Level: {} | XP: {} | STR: {} | INT: {} | CHA: {}",
+            game_state.player.name, stats.health, stats.max_health, stats.level,
+            stats.experience, stats.strength, stats.intelligence, stats.charisma
+        );
+        if let Some(currency) = currency {
+            text.push_str(&format!(" | {}: {}", currency.name, game_state.get_flag_as_i64(&currency.flag)));
+        }
+        self.line(&text)?;
+        writeln!(io::stdout())
+    }
+
+    fn show_recent_events(&self, happenings: &[String]) -> io::Result<()> {
+        if happenings.is_empty() {
+            return Ok(());
+        }
+        self.line(&format!("Recently: {}", happenings.join("; ")))
+    }
+
+    fn show_choices(&self, scene: &SceneView, previously_taken: Option<&BTreeSet<String>>) -> io::Result<()> {
+        self.line("Choose your action:")?;
+        let mut previous_group = None;
+        for (index, view) in scene.choices().enumerate() {
+            let group = view.choice.group.as_deref();
+            if (index == 0 || group != previous_group) && group.is_some() {
+                self.line(&format!("  [{}]", group.unwrap_or_default()))?;
+            }
+            previous_group = group;
+
+            let mut text = format!("  {}. {}", index + 1, view.choice.text);
+            if let Some(reason) = view.disabled_reason() {
+                text.push_str(&format!(" (unavailable: {})", reason));
+            }
+            if previously_taken.is_some_and(|taken| taken.contains(&view.choice.id)) {
+                text.push_str(" (taken before)");
+            }
+            self.line(&text)?;
+            if let Some(hint) = view.choice.hint.as_deref().filter(|_| self.show_choice_hints) {
+                self.line(&format!("     {}", hint))?;
+            }
+        }
+        writeln!(io::stdout())
+    }
+
+    fn show_story_card(&self, heading: &str, byline: &str, text: &str) -> io::Result<()> {
+        self.show_title(heading)?;
+        if !byline.is_empty() {
+            self.line(byline)?;
+            writeln!(io::stdout())?;
+        }
+        self.wrapped(text)?;
+        writeln!(io::stdout())
+    }
+
+    fn show_debug_variables(&self, variables: &[DebugVariable]) -> io::Result<()> {
+        self.line("Variables")?;
+        for variable in variables {
+            let changed = variable.changed_in.as_deref()
+                .map(|scene| format!(" (changed in {})", scene))
+                .unwrap_or_default();
+            self.line(&format!("{} {} = {}{}", variable.kind, variable.name, variable.value, changed))?;
+        }
+        Ok(())
+    }
+
+    fn show_help(&self, topics: &[HelpTopic]) -> io::Result<()> {
+        self.line("Help")?;
+        for topic in topics {
+            writeln!(io::stdout())?;
+            self.line(&topic.title)?;
+            self.wrapped(&topic.text)?;
+        }
+        Ok(())
+    }
+
+    fn show_scene_map(&self, map: &SceneMap) -> io::Result<()> {
+        let here = map.here.title.as_deref().unwrap_or(&map.here.scene_id);
+        self.line(&format!("Map: {} (you are here)", here))?;
+        for line in map_lines(&map.here, 1) {
+            self.line(&line)?;
+        }
+        if !map.came_from.is_empty() {
+            self.line(&format!("Came from: {}", map.came_from.join(", ")))?;
+        }
+        Ok(())
+    }
+
+    fn show_inventory(&self, game_state: &GameState) -> io::Result<()> {
+        self.line("Inventory")?;
+        if game_state.player.inventory.is_empty() {
+            return self.line("  Your inventory is empty.");
+        }
+        for item in &game_state.player.inventory {
+            let mut text = format!("  - {}", item.name);
+            if item.quantity > 1 {
+                text.push_str(&format!(" ({})", item.quantity));
+            }
+            if let (Some(durability), Some(max)) = (item.durability(), item.max_durability()) {
+                text.push_str(&format!(" [{}/{}]", durability, max));
+            }
+            match game_state.item_cooldown_remaining(item) {
+                0 => {}
+                1 => text.push_str(" (ready in 1 scene)"),
+                remaining => text.push_str(&format!(" (ready in {} scenes)", remaining)),
+            }
+            self.line(&text)?;
+            self.line(&format!("    {}", item.description))?;
+        }
+        Ok(())
+    }
+
+    fn show_scrollable(&self, title: &str, lines: &[(String, &str)]) -> io::Result<()> {
+        self.line(title)?;
+        for (line, _style) in lines {
+            self.line(line)?;
+        }
+        Ok(())
+    }
+
+    fn style(&self, text: &str, _style: &str) -> String {
+        strip_emoji(text)
+    }
+
+    fn show_message(&self, message: &str, _style: &str) -> io::Result<()> {
+        self.line(message)
+    }
+
+    fn show_error(&self, error: &str) -> io::Result<()> {
+        self.line(&format!("Error: {}", error))
+    }
+
+    fn show_warning(&self, message: &str) -> io::Result<()> {
+        self.line(&format!("Warning: {}", message))
+    }
+
+    fn show_separator(&self) -> io::Result<()> {
+        self.line(&"-".repeat(self.text_width))
+    }
+
+    /// The values themselves, e.g. "100 80 65".
+    fn health_sparkline(&self, values: &[i32], _max: i32) -> String {
+        values.iter().map(i32::to_string).collect::<Vec<_>>().join(" ")
+    }
+
+    fn prompt_text(&self, _kind: &str, prompt: &str, default: Option<String>) -> io::Result<String> {
+        let input = self.read_line(prompt)?.unwrap_or_default();
+        Ok(match default {
+            Some(default) if input.is_empty() => default,
+            _ => input,
+        })
+    }
+
+    /// Reads a whole line; answers given after `timeout` count as timed out.
+    fn prompt_timed_choice(&self, max: usize, timeout: Duration) -> io::Result<TimedInput> {
+        let deadline = Instant::now() + timeout;
+        let prompt = format!("Choose 1-{} within {}s (q to pause): ", max, timeout.as_secs());
+        loop {
+            let Some(input) = self.read_line(&prompt)? else {
+                return Ok(TimedInput::TimedOut);
+            };
+            if Instant::now() > deadline {
+                return Ok(TimedInput::TimedOut);
+            }
+            match menu_input(&input, max) {
+                Some(MenuInput::Selected(index)) => return Ok(TimedInput::Choice(index + 1)),
+                Some(MenuInput::Pause) => return Ok(TimedInput::Pause),
+                Some(MenuInput::QuickSave) => return Ok(TimedInput::QuickSave),
+                Some(MenuInput::QuickLoad) => return Ok(TimedInput::QuickLoad),
+                None => continue,
+            }
+        }
+    }
+
+    fn select_grouped_menu(
+        &self,
+        prompt: &str,
+        items: &[String],
+        groups: &[Option<String>],
+        hints: &[Option<String>],
+    ) -> io::Result<MenuInput> {
+        if items.is_empty() {
+            return Ok(MenuInput::Pause);
+        }
+
+        self.line(prompt)?;
+        for (index, item) in items.iter().enumerate() {
+            let group = groups.get(index).and_then(Option::as_deref);
+            let previous = index.checked_sub(1).and_then(|i| groups.get(i)).and_then(Option::as_deref);
+            if let Some(group) = group.filter(|_| index == 0 || group != previous) {
+                self.line(&format!("  [{}]", group))?;
+            }
+            self.line(&format!("  {}. {}", index + 1, item))?;
+            if let Some(hint) = hints.get(index).and_then(Option::as_deref).filter(|_| self.show_choice_hints) {
+                self.line(&format!("     {}", hint))?;
+            }
+        }
+
+        let prompt = format!("Enter 1-{} (q pause, s quick save, l quick load): ", items.len());
+        loop {
+            let input = self.read_line(&prompt)?.ok_or_else(end_of_input)?;
+            if let Some(selection) = menu_input(&input, items.len()) {
+                return Ok(selection);
+            }
+        }
+    }
+
+    fn select(&self, prompt: &str, items: &[String], _default: usize) -> io::Result<usize> {
+        self.list_items(prompt, items)?;
+        let prompt = format!("Enter 1-{}: ", items.len());
+        loop {
+            let input = self.read_line(&prompt)?.ok_or_else(end_of_input)?;
+            if let Some(MenuInput::Selected(index)) = menu_input(&input, items.len()) {
+                return Ok(index);
+            }
+        }
+    }
+
+    fn select_opt(&self, prompt: &str, items: &[String], _default: usize) -> io::Result<Option<usize>> {
+        self.list_items(prompt, items)?;
+        let prompt = format!("Enter 1-{} (q to go back): ", items.len());
+        loop {
+            let input = self.read_line(&prompt)?.ok_or_else(end_of_input)?;
+            match menu_input(&input, items.len()) {
+                Some(MenuInput::Selected(index)) => return Ok(Some(index)),
+                Some(MenuInput::Pause) => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+
+    fn confirm(&self, prompt: &str, default: bool) -> io::Result<bool> {
+        let prompt = format!("{} {} ", prompt, if default { "[Y/n]" } else { "[y/N]" });
+        loop {
+            let input = self.read_line(&prompt)?.ok_or_else(end_of_input)?;
+            match input.to_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                "" => return Ok(default),
+                _ => {}
+            }
+        }
+    }
+
+    /// Fails at the end of input, so a script that runs out of answers ends
+    /// the game instead of looping.
+    fn wait_for_enter(&self) -> io::Result<()> {
+        self.read_line("Press Enter to continue...")?.ok_or_else(end_of_input)?;
+        Ok(())
+    }
+
+    /// Keys can't be read without a terminal, so this only waits.
+    fn key_pressed_within(&self, timeout: Duration) -> io::Result<bool> {
+        std::thread::sleep(timeout);
+        Ok(false)
+    }
+
+    fn set_text_width(&mut self, text_width: usize) {
+        self.text_width = text_width;
+    }
+
+    /// There's no terminal to fit.
+    fn set_fit_to_terminal(&mut self, _fit_to_terminal: bool) {}
+
+    fn set_choice_hints(&mut self, show_choice_hints: bool) {
+        self.show_choice_hints = show_choice_hints;
+    }
+
+    fn set_text_layout(&mut self, layout: TextLayout) {
+        self.layout = layout;
+    }
+}
+
+fn end_of_input() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "input ended")
+}
+
+/// A typed menu answer: a number from 1 to `max`, `q`, `s` or `l`.
+fn menu_input(input: &str, max: usize) -> Option<MenuInput> {
+    match input.to_lowercase().as_str() {
+        "q" => Some(MenuInput::Pause),
+        "s" => Some(MenuInput::QuickSave),
+        "l" => Some(MenuInput::QuickLoad),
+        number => number.parse::<usize>().ok()
+            .filter(|number| (1..=max).contains(number))
+            .map(|number| MenuInput::Selected(number - 1)),
+    }
+}
+
+/// Exits of `node` indented by `depth`, ASCII only.
+fn map_lines(node: &MapNode, depth: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for exit in &node.exits {
+        let indent = "  ".repeat(depth);
+        let label = match &exit.title {
+            None => "???".to_string(),
+            Some(title) if exit.repeated => format!("{} (again)", title),
+            Some(title) => title.clone(),
+        };
+        lines.push(format!("{}- {}", indent, label));
+        lines.extend(map_lines(exit, depth + 1));
+    }
+    lines
+}
+
+/// `text` without emoji and pictographs, and without the spaces that
+/// separated them from the text.
+fn strip_emoji(text: &str) -> String {
+    let is_emoji = |c: char| matches!(c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF | 0xFE00..=0xFE0F | 0x200D
+    );
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_emoji(c) {
+            stripped.push(c);
+            continue;
+        }
+        while chars.next_if(|&next| is_emoji(next)).is_some() {}
+        if stripped.is_empty() || stripped.ends_with(' ') {
+            chars.next_if_eq(&' ');
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(strip_emoji("✅ Theme changed to 'dark'"), "Theme changed to 'dark'");
+        assert_eq!(strip_emoji("   ⚔️ Sword and 🛡️ shield"), "   Sword and shield");
+        assert_eq!(strip_emoji("No emoji, just ASCII"), "No emoji, just ASCII");
+
+        assert_eq!(menu_input("2", 3), Some(MenuInput::Selected(1)));
+        assert_eq!(menu_input("Q", 3), Some(MenuInput::Pause));
+        assert_eq!(menu_input("4", 3), None);
+
+        let map = MapNode {
+            scene_id: "hall".to_string(),
+            title: Some("Hall".to_string()),
+            repeated: false,
+            exits: vec![MapNode {
+                scene_id: "cellar".to_string(),
+                title: None,
+                repeated: false,
+                exits: Vec::new(),
+            }],
+        };
+        assert_eq!(map_lines(&map, 1), ["  - ???"]);
+    }
+}
@@ -0,0 +1,129 @@
+//! What `GameInterface` needs from a frontend. `Display` draws to a
+//! terminal with colors, emoji and arrow-key menus; other frontends
+//! implement `Renderer` and are chosen with `ui.renderer`.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::time::Duration;
+use crate::core::{DebugVariable, GameState};
+use crate::story::{Currency, Scene, SceneMap, SceneView};
+use crate::ui::{HelpTopic, MenuInput, TextLayout, TimedInput};
+use crate::utils::GameError;
+
+pub trait Renderer {
+    /// Width text is wrapped to right now.
+    fn width(&self) -> usize;
+
+    fn clear_screen(&self) -> io::Result<()>;
+
+    fn show_title(&self, title: &str) -> io::Result<()>;
+
+    fn show_scene(&self, scene: &Scene) -> io::Result<()>;
+
+    /// Shows the stats header; with a currency the player's wallet is
+    /// appended.
+    fn show_player_stats(&self, game_state: &GameState, currency: Option<&Currency>) -> io::Result<()>;
+
+    /// The recent happenings strip, oldest first; nothing if there are none.
+    fn show_recent_events(&self, happenings: &[String]) -> io::Result<()>;
+
+    /// Lists the choices of `scene`, marking those in `previously_taken`.
+    fn show_choices(&self, scene: &SceneView, previously_taken: Option<&BTreeSet<String>>) -> io::Result<()>;
+
+    /// A story's intro or credits page.
+    fn show_story_card(&self, heading: &str, byline: &str, text: &str) -> io::Result<()>;
+
+    fn show_debug_variables(&self, variables: &[DebugVariable]) -> io::Result<()>;
+
+    fn show_help(&self, topics: &[HelpTopic]) -> io::Result<()>;
+
+    fn show_scene_map(&self, map: &SceneMap) -> io::Result<()>;
+
+    fn show_inventory(&self, game_state: &GameState) -> io::Result<()>;
+
+    /// Shows `lines`, each with its style, under `title`, letting the player
+    /// scroll where the frontend can.
+    fn show_scrollable(&self, title: &str, lines: &[(String, &str)]) -> io::Result<()>;
+
+    /// `text` in one of the theme's styles.
+    fn style(&self, text: &str, style: &str) -> String;
+
+    fn show_message(&self, message: &str, style: &str) -> io::Result<()>;
+
+    fn show_error(&self, error: &str) -> io::Result<()> {
+        self.show_message(error, "error")
+    }
+
+    /// Shows an engine error with its code, context and hint.
+    fn show_game_error(&self, error: &GameError) -> io::Result<()> {
+        self.show_error(&error.report())
+    }
+
+    fn show_success(&self, message: &str) -> io::Result<()> {
+        self.show_message(message, "success")
+    }
+
+    fn show_warning(&self, message: &str) -> io::Result<()> {
+        self.show_message(message, "warning")
+    }
+
+    fn show_info(&self, message: &str) -> io::Result<()> {
+        self.show_message(message, "info")
+    }
+
+    fn show_separator(&self) -> io::Result<()>;
+
+    /// One bar per value, from 0 to `max`.
+    fn health_sparkline(&self, values: &[i32], max: i32) -> String;
+
+    /// Reads a line of text; `kind` groups prompts whose earlier answers
+    /// can be recalled.
+    fn prompt_text(&self, kind: &str, prompt: &str, default: Option<String>) -> io::Result<String>;
+
+    /// Reads a choice number between 1 and `max` within `timeout`.
+    fn prompt_timed_choice(&self, max: usize, timeout: Duration) -> io::Result<TimedInput>;
+
+    /// Lets the player pick one of `items`. `groups` has one entry per item,
+    /// for headings where the group changes; `hints` has one per item that
+    /// may have a hint.
+    fn select_grouped_menu(
+        &self,
+        prompt: &str,
+        items: &[String],
+        groups: &[Option<String>],
+        hints: &[Option<String>],
+    ) -> io::Result<MenuInput>;
+
+    /// Lets the player pick one of `items`, starting at `default`.
+    fn select(&self, prompt: &str, items: &[String], default: usize) -> io::Result<usize>;
+
+    /// Like `select`, but the player can back out; `None` if they did.
+    fn select_opt(&self, prompt: &str, items: &[String], default: usize) -> io::Result<Option<usize>>;
+
+    fn confirm(&self, prompt: &str, default: bool) -> io::Result<bool>;
+
+    fn wait_for_enter(&self) -> io::Result<()>;
+
+    /// Waits up to `timeout` for any key; returns whether one was pressed.
+    fn key_pressed_within(&self, timeout: Duration) -> io::Result<bool>;
+
+    /// Returns false for unknown themes. Renderers without themes accept
+    /// any, and ignore the settings below.
+    fn set_theme(&mut self, _theme_name: &str) -> bool {
+        true
+    }
+
+    fn get_available_themes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn set_allow_italics(&mut self, _allow_italics: bool) {}
+
+    fn set_text_width(&mut self, text_width: usize);
+
+    fn set_fit_to_terminal(&mut self, fit_to_terminal: bool);
+
+    fn set_choice_hints(&mut self, show_choice_hints: bool);
+
+    fn set_text_layout(&mut self, layout: TextLayout);
+}
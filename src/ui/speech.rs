@@ -0,0 +1,116 @@
+//! Read-aloud mode. Text is piped to an external speech command (`espeak`,
+//! `say`, ...) configured as `ui.tts_command`; the game needs to be built
+//! with the `tts` feature.
+
+use crate::story::SceneView;
+
+/// Speaks scenes through the configured command. Starting a new utterance
+/// interrupts the previous one.
+pub struct Narrator {
+    command: Vec<String>,
+    #[cfg(feature = "tts")]
+    speaking: Option<std::process::Child>,
+}
+
+impl Narrator {
+    pub fn new(command: Vec<String>) -> Self {
+        #[cfg(not(feature = "tts"))]
+        tracing::warn!("Read-aloud is enabled but the game was built without the tts feature");
+
+        Self {
+            command,
+            #[cfg(feature = "tts")]
+            speaking: None,
+        }
+    }
+
+    pub fn speak_scene(&mut self, scene: &SceneView) {
+        self.speak(&scene_script(scene));
+    }
+
+    #[cfg(feature = "tts")]
+    pub fn speak(&mut self, text: &str) {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        self.stop();
+        let Some((program, args)) = self.command.split_first() else {
+            return;
+        };
+
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match child {
+            Ok(mut child) => {
+                // Dropping stdin closes it, so the command knows the text is complete
+                if let Some(mut stdin) = child.stdin.take() {
+                    if let Err(e) = stdin.write_all(text.as_bytes()) {
+                        tracing::warn!("Failed to send text to speech command: {}", e);
+                    }
+                }
+                self.speaking = Some(child);
+            }
+            Err(e) => tracing::warn!("Failed to start speech command '{}': {}", program, e),
+        }
+    }
+
+    #[cfg(not(feature = "tts"))]
+    pub fn speak(&mut self, _text: &str) {
+        tracing::debug!("Not speaking with {:?}: built without the tts feature", self.command);
+    }
+
+    /// Cuts off whatever is being read.
+    pub fn stop(&mut self) {
+        #[cfg(feature = "tts")]
+        if let Some(mut child) = self.speaking.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for Narrator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// What is read for a scene: its title, description and the choices the
+/// player can take, numbered like on screen, with their hints.
+pub fn scene_script(scene: &SceneView) -> String {
+    let mut script = format!("{}.\n{}\n", scene.scene.title, scene.scene.description);
+    for (index, view) in scene.choices().enumerate() {
+        if !view.disabled {
+            script.push_str(&format!("Option {}: {}.\n", index + 1, view.choice.text));
+            if let Some(hint) = &view.choice.hint {
+                script.push_str(&format!("{}\n", hint));
+            }
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::story::{Choice, Scene};
+
+    #[test]
+    fn test_scene_script() {
+        let mut scene = Scene::new("gate", "The Gate", "A locked gate blocks the road.");
+        scene.add_choice(Choice::new("climb", "Climb over", "road").with_hint("The guards may see you."));
+        scene.add_choice(Choice::new("open", "Unlock it", "road"));
+        scene.add_choice(Choice::new("back", "Turn back", "start"));
+        let view = crate::story::SceneView::new(Arc::new(scene), vec![true, false, true]);
+
+        assert_eq!(
+            scene_script(&view),
+            "The Gate.\nA locked gate blocks the road.\nOption 1: Climb over.\nThe guards may see you.\nOption 3: Turn back.\n"
+        );
+    }
+}
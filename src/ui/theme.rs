@@ -18,6 +18,7 @@ pub struct ColorConfig {
 pub struct ThemeManager {
     themes: HashMap<String, Theme>,
     current_theme: String,
+    allow_italics: bool,
 }
 
 impl ThemeManager {
@@ -25,6 +26,7 @@ impl ThemeManager {
         let mut manager = Self {
             themes: HashMap::new(),
             current_theme: "default".to_string(),
+            allow_italics: true,
         };
         
         manager.load_default_themes();
@@ -40,6 +42,12 @@ impl ThemeManager {
         }
     }
 
+    /// Italic text is hard to read for some players; when disallowed the
+    /// style is skipped in every theme.
+    pub fn set_allow_italics(&mut self, allow_italics: bool) {
+        self.allow_italics = allow_italics;
+    }
+
     pub fn get_current_theme(&self) -> &Theme {
         self.themes.get(&self.current_theme)
             .unwrap_or_else(|| self.themes.get("default").unwrap())
@@ -58,11 +66,17 @@ impl ThemeManager {
                 }
             }
 
+            if let Some(bg_color) = &color_config.background {
+                if let Some(color) = parse_color(bg_color) {
+                    styled_text = styled_text.on_color(color).to_string();
+                }
+            }
+
             // Apply styles (bold, italic, underline, etc.)
             for style in &color_config.style {
                 styled_text = match style.as_str() {
                     "bold" => styled_text.bold().to_string(),
-                    "italic" => styled_text.italic().to_string(),
+                    "italic" if self.allow_italics => styled_text.italic().to_string(),
                     "underline" => styled_text.underline().to_string(),
                     "dimmed" => styled_text.dimmed().to_string(),
                     "strikethrough" => styled_text.strikethrough().to_string(),
@@ -148,6 +162,11 @@ impl ThemeManager {
             background: None,
             style: vec![],
         });
+        default_colors.insert("currency".to_string(), ColorConfig {
+            foreground: Some("yellow".to_string()),
+            background: None,
+            style: vec!["bold".to_string()],
+        });
         default_colors.insert("separator".to_string(), ColorConfig {
             foreground: Some("bright_black".to_string()),
             background: None,
@@ -207,6 +226,12 @@ impl ThemeManager {
             style: vec!["bold".to_string()],
         });
 
+        dark_colors.insert("currency".to_string(), ColorConfig {
+            foreground: Some("bright_yellow".to_string()),
+            background: None,
+            style: vec!["bold".to_string()],
+        });
+
         self.themes.insert("dark".to_string(), Theme {
             name: "dark".to_string(),
             colors: dark_colors,
@@ -239,6 +264,85 @@ impl ThemeManager {
             name: "light".to_string(),
             colors: light_colors,
         });
+
+        self.load_accessible_themes();
+    }
+
+    /// Themes for low vision and color blindness. Disabled choices and low
+    /// health are also marked by style (strikethrough, underline), not by
+    /// color alone.
+    fn load_accessible_themes(&mut self) {
+        // Bright colors only, emphasis on everything that matters
+        let high_contrast = [
+            ("title", color(Some("bright_white"), None, &["bold", "underline"])),
+            ("scene_title", color(Some("bright_yellow"), None, &["bold"])),
+            ("scene_description", color(Some("bright_white"), None, &[])),
+            ("choice", color(Some("bright_cyan"), None, &["bold"])),
+            ("choice_disabled", color(Some("white"), None, &["strikethrough"])),
+            ("stats", color(Some("bright_white"), None, &["bold"])),
+            ("health_high", color(Some("bright_green"), None, &["bold"])),
+            ("health_medium", color(Some("bright_yellow"), None, &["bold"])),
+            ("health_low", color(Some("bright_red"), None, &["bold", "underline"])),
+            ("error", color(Some("bright_white"), Some("red"), &["bold"])),
+            ("success", color(Some("bright_green"), None, &["bold"])),
+            ("warning", color(Some("black"), Some("bright_yellow"), &["bold"])),
+            ("info", color(Some("bright_white"), None, &[])),
+            ("currency", color(Some("bright_yellow"), None, &["bold"])),
+            ("separator", color(Some("white"), None, &[])),
+        ];
+
+        // Blue/yellow/magenta instead of the red/green pairs
+        let deuteranopia = [
+            ("title", color(Some("bright_cyan"), None, &["bold"])),
+            ("scene_title", color(Some("bright_blue"), None, &["bold"])),
+            ("scene_description", color(Some("white"), None, &[])),
+            ("choice", color(Some("bright_cyan"), None, &[])),
+            ("choice_disabled", color(Some("bright_black"), None, &["strikethrough"])),
+            ("stats", color(Some("yellow"), None, &[])),
+            ("health_high", color(Some("bright_blue"), None, &["bold"])),
+            ("health_medium", color(Some("bright_yellow"), None, &["bold"])),
+            ("health_low", color(Some("bright_magenta"), None, &["bold", "underline"])),
+            ("error", color(Some("bright_magenta"), None, &["bold"])),
+            ("success", color(Some("bright_blue"), None, &["bold"])),
+            ("warning", color(Some("bright_yellow"), None, &["bold"])),
+            ("info", color(Some("cyan"), None, &[])),
+            ("currency", color(Some("bright_yellow"), None, &["bold"])),
+            ("separator", color(Some("bright_black"), None, &[])),
+        ];
+
+        // No colors at all, for terminals or players that can't use them
+        let monochrome = [
+            ("title", color(None, None, &["bold", "underline"])),
+            ("scene_title", color(None, None, &["bold"])),
+            ("choice_disabled", color(None, None, &["dimmed", "strikethrough"])),
+            ("stats", color(None, None, &["bold"])),
+            ("health_high", color(None, None, &["bold"])),
+            ("health_low", color(None, None, &["bold", "underline"])),
+            ("error", color(None, None, &["bold", "underline"])),
+            ("success", color(None, None, &["bold"])),
+            ("warning", color(None, None, &["bold"])),
+            ("currency", color(None, None, &["bold"])),
+            ("separator", color(None, None, &["dimmed"])),
+        ];
+
+        for (name, colors) in [
+            ("high_contrast", high_contrast.to_vec()),
+            ("deuteranopia", deuteranopia.to_vec()),
+            ("monochrome", monochrome.to_vec()),
+        ] {
+            self.themes.insert(name.to_string(), Theme {
+                name: name.to_string(),
+                colors: colors.into_iter().map(|(style, config)| (style.to_string(), config)).collect(),
+            });
+        }
+    }
+}
+
+fn color(foreground: Option<&str>, background: Option<&str>, style: &[&str]) -> ColorConfig {
+    ColorConfig {
+        foreground: foreground.map(str::to_string),
+        background: background.map(str::to_string),
+        style: style.iter().map(|s| s.to_string()).collect(),
     }
 }
 
@@ -281,6 +385,9 @@ mod tests {
         assert!(manager.themes.contains_key("default"));
         assert!(manager.themes.contains_key("dark"));
         assert!(manager.themes.contains_key("light"));
+        assert!(manager.themes.contains_key("high_contrast"));
+        assert!(manager.themes.contains_key("deuteranopia"));
+        assert!(manager.themes.contains_key("monochrome"));
     }
 
     #[test]
@@ -0,0 +1,173 @@
+//! "Chat plays" for streamers: with `twitch.channel` set, each scene's
+//! choice is put to the channel's chat, where viewers vote with `!1`, `!2`,
+//! ... until the countdown ends. Needs the `twitch` feature. Without a token
+//! the game reads chat anonymously and can't post the vote announcements.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::config::TwitchConfig;
+
+/// Votes cast in one round, one per viewer.
+#[derive(Debug, Clone)]
+pub struct ChatVotes {
+    choice_count: usize,
+    votes: HashMap<String, usize>,
+}
+
+impl ChatVotes {
+    pub fn new(choice_count: usize) -> Self {
+        Self { choice_count, votes: HashMap::new() }
+    }
+
+    /// Counts `text` as `voter`'s vote if it starts with `!` and a choice
+    /// number. A viewer's later vote replaces their earlier one.
+    pub fn record(&mut self, voter: &str, text: &str) -> bool {
+        let number = text.strip_prefix('!')
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|number| number.parse::<usize>().ok())
+            .filter(|number| (1..=self.choice_count).contains(number));
+        match number {
+            Some(number) => {
+                self.votes.insert(voter.to_lowercase(), number - 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Votes for each choice, in choice order.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.choice_count];
+        for &choice in self.votes.values() {
+            counts[choice] += 1;
+        }
+        counts
+    }
+
+    /// Index of the most voted choice, ties going to the lower number;
+    /// `None` if nobody voted.
+    pub fn winner(&self) -> Option<usize> {
+        let counts = self.counts();
+        let most = counts.iter().copied().max().filter(|&most| most > 0)?;
+        counts.iter().position(|&count| count == most)
+    }
+}
+
+/// Runs chat votes in one Twitch channel.
+pub struct ChatVoting {
+    channel: String,
+    window: Duration,
+    /// Whether we logged in with a token and may post.
+    can_post: bool,
+    #[cfg(feature = "twitch")]
+    client: crate::ui::chat::IrcClient,
+}
+
+impl ChatVoting {
+    #[cfg(feature = "twitch")]
+    pub async fn connect(config: &TwitchConfig, channel: &str) -> std::io::Result<Self> {
+        use crate::ui::chat::IrcClient;
+
+        let token = config.token_env.as_ref().and_then(|name| std::env::var(name).ok());
+        let nick = match &token {
+            Some(_) => config.nick.clone(),
+            // Twitch lets any justinfan nick read chat without logging in
+            None => format!("justinfan{}", uuid::Uuid::new_v4().as_u64_pair().0 % 100_000),
+        };
+        let password = token.map(|token| match token.starts_with("oauth:") {
+            true => token,
+            false => format!("oauth:{}", token),
+        });
+        let mut client = IrcClient::connect(&config.server, &nick, password.as_deref()).await?;
+        let channel = format!("#{}", channel.trim_start_matches('#').to_lowercase());
+        client.join(&channel).await?;
+        Ok(Self { channel, window: Duration::from_secs(config.vote_seconds), can_post: password.is_some(), client })
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Puts `choices` to the chat and counts votes until the window closes.
+    /// Returns the winning index, or `None` if nobody voted.
+    #[cfg(feature = "twitch")]
+    pub async fn vote(&mut self, choices: &[String]) -> std::io::Result<Option<usize>> {
+        let deadline = tokio::time::Instant::now() + self.window;
+        let options: Vec<String> = choices.iter().enumerate().map(|(index, text)| format!("!{} {}", index + 1, text)).collect();
+        self.post(&format!("Vote now ({}s): {}", self.window.as_secs(), options.join(" | "))).await?;
+
+        let mut votes = ChatVotes::new(choices.len());
+        while let Some(message) = self.client.next_message(deadline).await? {
+            if message.target.eq_ignore_ascii_case(&self.channel) {
+                votes.record(&message.sender, &message.text);
+            }
+        }
+
+        let winner = votes.winner();
+        if let Some(index) = winner {
+            self.post(&format!("Chat chose !{} {} ({} votes)", index + 1, choices[index], votes.counts()[index])).await?;
+        }
+        Ok(winner)
+    }
+
+    #[cfg(not(feature = "twitch"))]
+    pub async fn vote(&mut self, _choices: &[String]) -> std::io::Result<Option<usize>> {
+        tracing::debug!("Not voting in {} (posting: {}): built without the twitch feature", self.channel, self.can_post);
+        Ok(None)
+    }
+
+    #[cfg(feature = "twitch")]
+    async fn post(&mut self, text: &str) -> std::io::Result<()> {
+        if self.can_post {
+            self.client.say(&self.channel, text).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Voting in the configured channel, or `None` if there is none or it
+/// can't be reached.
+pub async fn configured_voting(config: &TwitchConfig) -> Option<ChatVoting> {
+    let channel = config.channel.as_ref()?;
+
+    #[cfg(feature = "twitch")]
+    {
+        match ChatVoting::connect(config, channel).await {
+            Ok(voting) => Some(voting),
+            Err(e) => {
+                tracing::warn!("Failed to join Twitch chat for #{}: {}", channel, e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "twitch"))]
+    {
+        tracing::warn!("Twitch channel {} is set but the game was built without the twitch feature", channel);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_votes() {
+        let mut votes = ChatVotes::new(3);
+        assert_eq!(votes.winner(), None);
+        assert!(votes.record("ana", "!2"));
+        assert!(votes.record("Ben", "!3 the cave!"));
+        assert!(!votes.record("cy", "!4"));
+        assert!(!votes.record("cy", "2"));
+        assert_eq!(votes.winner(), Some(1));
+
+        // Changing a vote replaces it, whatever the case of the name
+        assert!(votes.record("ben", "!1"));
+        assert!(votes.record("ANA", "!1"));
+        assert_eq!(votes.counts(), [2, 0, 0]);
+        assert!(votes.record("cy", "!3"));
+        assert!(votes.record("dee", "!3"));
+        assert_eq!(votes.winner(), Some(0));
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use thiserror::Error;
 
 pub type GameResult<T> = Result<T, GameError>;
@@ -12,6 +13,9 @@ pub enum GameError {
     
     #[error("Choice not found: {choice_id}")]
     ChoiceNotFound { choice_id: String },
+
+    #[error("Choice was vetoed: {reason}")]
+    ChoiceVetoed { reason: String },
     
     #[error("Save/Load error: {message}")]
     SaveLoad { message: String },
@@ -22,6 +26,9 @@ pub enum GameError {
     #[error("Configuration error: {message}")]
     Configuration { message: String },
     
+    #[error("Plugin error: {message}")]
+    Plugin { message: String },
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     
@@ -36,9 +43,114 @@ pub enum GameError {
     
     #[error("Config error: {0}")]
     Config(#[from] config::ConfigError),
+
+    /// Another error, with where it happened and what might fix it.
+    #[error("{error}{context}")]
+    WithContext { error: Box<GameError>, context: ErrorContext },
+}
+
+/// The story, scene and save an error happened in, and a hint for fixing it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorContext {
+    pub story_id: Option<String>,
+    pub scene_id: Option<String>,
+    pub save_id: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let places: Vec<String> = [("story", &self.story_id), ("scene", &self.scene_id), ("save", &self.save_id)]
+            .into_iter()
+            .filter_map(|(kind, id)| id.as_ref().map(|id| format!("{} '{}'", kind, id)))
+            .collect();
+        if !places.is_empty() {
+            write!(f, "\n  in {}", places.join(", "))?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\n  hint: {}", hint)?;
+        }
+        Ok(())
+    }
 }
 
 impl GameError {
+    /// Stable code for the kind of error, for looking it up in the docs
+    /// and matching on it in scripts.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Story { .. } => "E100",
+            Self::SceneNotFound { .. } => "E101",
+            Self::ChoiceNotFound { .. } => "E102",
+            Self::ChoiceVetoed { .. } => "E103",
+            Self::SaveLoad { .. } => "E200",
+            Self::Player { .. } => "E300",
+            Self::Configuration { .. } => "E400",
+            Self::Toml(_) => "E401",
+            Self::Config(_) => "E402",
+            Self::Plugin { .. } => "E500",
+            Self::Io(_) => "E600",
+            Self::Json(_) => "E601",
+            Self::Uuid(_) => "E602",
+            Self::WithContext { error, .. } => error.code(),
+        }
+    }
+
+    /// The error without its context, for matching on its kind.
+    pub fn inner(&self) -> &GameError {
+        match self {
+            Self::WithContext { error, .. } => error,
+            error => error,
+        }
+    }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error as shown to players and authors: its code, message,
+    /// context and hint.
+    pub fn report(&self) -> String {
+        format!("[{}] {}", self.code(), self)
+    }
+
+    pub fn with_story<S: Into<String>>(self, story_id: S) -> Self {
+        self.with_context(|context| context.story_id = Some(story_id.into()))
+    }
+
+    pub fn with_scene<S: Into<String>>(self, scene_id: S) -> Self {
+        self.with_context(|context| context.scene_id = Some(scene_id.into()))
+    }
+
+    pub fn with_save<S: ToString>(self, save_id: S) -> Self {
+        self.with_context(|context| context.save_id = Some(save_id.to_string()))
+    }
+
+    pub fn with_hint<S: Into<String>>(self, hint: S) -> Self {
+        self.with_context(|context| context.hint = Some(hint.into()))
+    }
+
+    /// Adds a "did you mean" hint naming the `kind` (scene, choice, ...)
+    /// that was likely meant, if there is one.
+    pub fn with_suggestion(self, kind: &str, suggestion: Option<&str>) -> Self {
+        match suggestion {
+            Some(suggestion) => self.with_hint(format!("did you mean {} '{}'?", kind, suggestion)),
+            None => self,
+        }
+    }
+
+    fn with_context(self, update: impl FnOnce(&mut ErrorContext)) -> Self {
+        let (error, mut context) = match self {
+            Self::WithContext { error, context } => (error, context),
+            error => (Box::new(error), ErrorContext::default()),
+        };
+        update(&mut context);
+        Self::WithContext { error, context }
+    }
+
     pub fn story<S: Into<String>>(message: S) -> Self {
         Self::Story {
             message: message.into(),
@@ -57,6 +169,12 @@ impl GameError {
         }
     }
     
+    pub fn choice_vetoed<S: Into<String>>(reason: S) -> Self {
+        Self::ChoiceVetoed {
+            reason: reason.into(),
+        }
+    }
+    
     pub fn save_load<S: Into<String>>(message: S) -> Self {
         Self::SaveLoad {
             message: message.into(),
@@ -74,6 +192,25 @@ impl GameError {
             message: message.into(),
         }
     }
+    
+    pub fn plugin<S: Into<String>>(message: S) -> Self {
+        Self::Plugin {
+            message: message.into(),
+        }
+    }
+
+    /// A story error reporting every problem found at once, one per line.
+    pub fn story_errors<E: std::fmt::Display>(heading: &str, errors: &[E]) -> Self {
+        if let [error] = errors {
+            return Self::story(format!("{}: {}", heading, error));
+        }
+        let mut message = format!("{} ({} problems):", heading, errors.len());
+        for error in errors {
+            message.push_str("\n  - ");
+            message.push_str(&error.to_string().replace('\n', "\n    "));
+        }
+        Self::story(message)
+    }
 }
 
 #[cfg(test)]
@@ -93,4 +230,29 @@ mod tests {
         assert!(matches!(error, GameError::SceneNotFound { .. }));
         assert_eq!(error.to_string(), "Scene not found: test_scene");
     }
+
+    #[test]
+    fn test_story_errors() {
+        let error = GameError::story_errors("Story validation failed", &["Starting scene 'a' not found"]);
+        assert_eq!(error.to_string(), "Story error: Story validation failed: Starting scene 'a' not found");
+
+        let error = GameError::story_errors("Story validation failed", &["one", "two\nmore"]);
+        assert_eq!(error.to_string(), "Story error: Story validation failed (2 problems):\n  - one\n  - two\n    more");
+    }
+
+    #[test]
+    fn test_error_context() {
+        let error = GameError::scene_not_found("forrest")
+            .with_story("woods")
+            .with_scene("clearing")
+            .with_suggestion("scene", Some("forest"));
+        assert_eq!(error.code(), "E101");
+        assert!(matches!(error.inner(), GameError::SceneNotFound { .. }));
+        assert_eq!(error.context().unwrap().story_id.as_deref(), Some("woods"));
+        assert_eq!(
+            error.report(),
+            "[E101] Scene not found: forrest\n  in story 'woods', scene 'clearing'\n  hint: did you mean scene 'forest'?"
+        );
+        assert_eq!(GameError::player("No votes cast").report(), "[E300] Player error: No votes cast");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+//! Operational metrics for hosted games (chat bots, API servers), in the Prometheus
+//! text format. Choices per second is `rate(text_game_choices_total[1m])`.
+
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Counters shared between a frontend and its `/metrics` endpoint.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    sessions: AtomicU64,
+    choices: AtomicU64,
+    scene_micros: AtomicU64,
+    scenes: AtomicU64,
+    save_errors: AtomicU64,
+}
+
+impl Metrics {
+    /// Games being played right now.
+    pub fn set_sessions(&self, sessions: usize) {
+        self.sessions.store(sessions as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_choice(&self) {
+        self.choices.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How long it took to get a scene ready after a command.
+    pub fn record_scene_latency(&self, latency: Duration) {
+        self.scene_micros.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+        self.scenes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_save_error(&self) {
+        self.save_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        format!(
+            "# HELP text_game_sessions Games being played.\n\
+             # TYPE text_game_sessions gauge\n\
+             text_game_sessions {}\n\
+             # HELP text_game_choices_total Choices made.\n\
+             # TYPE text_game_choices_total counter\n\
+             text_game_choices_total {}\n\
+             # HELP text_game_scene_latency_seconds Time to get a scene ready after a command.\n\
+             # TYPE text_game_scene_latency_seconds summary\n\
+             text_game_scene_latency_seconds_sum {}\n\
+             text_game_scene_latency_seconds_count {}\n\
+             # HELP text_game_save_errors_total Saves that failed.\n\
+             # TYPE text_game_save_errors_total counter\n\
+             text_game_save_errors_total {}\n",
+            load(&self.sessions),
+            load(&self.choices),
+            load(&self.scene_micros) as f64 / 1_000_000.0,
+            load(&self.scenes),
+            load(&self.save_errors),
+        )
+    }
+}
+
+/// How long a client gets to send its request line before it's dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serves `metrics` at `http://{addr}/metrics`. Only binding `addr` fails;
+/// errors accepting a connection are logged and serving carries on.
+pub async fn serve_metrics(metrics: Arc<Metrics>, addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    tracing::info!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+    loop {
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                // Usually out of file descriptors: give connections time to close
+                tracing::warn!("Failed to accept metrics connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            // The request line is all we look at
+            let mut request = [0; 1024];
+            let read = match tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut request)).await {
+                Ok(read) => read.unwrap_or(0),
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&request[..read]);
+            let response = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+                ["GET", "/metrics"] => {
+                    let body = metrics.render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(), body
+                    )
+                }
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            };
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                tracing::debug!("Failed to answer metrics request: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = Metrics::default();
+        metrics.set_sessions(2);
+        metrics.record_choice();
+        metrics.record_choice();
+        metrics.record_scene_latency(Duration::from_millis(250));
+        metrics.record_save_error();
+
+        let text = metrics.render();
+        assert!(text.contains("\ntext_game_sessions 2\n"));
+        assert!(text.contains("\ntext_game_choices_total 2\n"));
+        assert!(text.contains("\ntext_game_scene_latency_seconds_sum 0.25\n"));
+        assert!(text.contains("\ntext_game_scene_latency_seconds_count 1\n"));
+        assert!(text.contains("\ntext_game_save_errors_total 1\n"));
+    }
+}
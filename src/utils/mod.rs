@@ -1,5 +1,15 @@
 pub mod errors;
+pub mod metrics;
+pub mod save_crypto;
 pub mod save_manager;
+pub mod profile;
+pub mod rate_limit;
+pub mod text;
 
-pub use errors::{GameError, GameResult};
-pub use save_manager::SaveManager;
\ No newline at end of file
+pub use errors::{ErrorContext, GameError, GameResult};
+pub use metrics::{serve_metrics, Metrics};
+pub use save_crypto::SaveCipher;
+pub use save_manager::{SaveManager, SaveGame, SaveGameMetadata, SavePreview};
+pub use profile::StoryProfile;
+pub use rate_limit::RateLimiter;
+pub use text::title_case;
\ No newline at end of file
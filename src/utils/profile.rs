@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use uuid::Uuid;
+use crate::core::GameState;
+use crate::utils::{GameError, GameResult};
+use crate::utils::save_manager::escape_file_name;
+
+/// Directory under the saves directory holding one profile per story.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// What a player has done across completed playthroughs of one story.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoryProfile {
+    pub story_id: String,
+    pub completed_runs: u32,
+    /// Choice IDs taken in completed runs, by scene ID.
+    #[serde(default)]
+    pub taken_choices: BTreeMap<String, BTreeSet<String>>,
+    /// IDs of the endings reached, for the ending gallery.
+    #[serde(default)]
+    pub endings: BTreeSet<String>,
+    /// Games already counted, so finishing a loaded save again doesn't
+    /// count twice.
+    #[serde(default)]
+    pub completed_games: BTreeSet<Uuid>,
+}
+
+impl StoryProfile {
+    pub fn new<S: Into<String>>(story_id: S) -> Self {
+        Self { story_id: story_id.into(), ..Self::default() }
+    }
+
+    pub fn path(saves_dir: &Path, story_id: &str) -> PathBuf {
+        saves_dir.join(PROFILES_DIR).join(format!("{}.json", escape_file_name(story_id)))
+    }
+
+    /// The story's profile, or an empty one if it has never been finished.
+    pub async fn load(saves_dir: &Path, story_id: &str) -> GameResult<Self> {
+        let path = Self::path(saves_dir, story_id);
+        if !path.exists() {
+            return Ok(Self::new(story_id));
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to read story profile: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| GameError::save_load(format!("Failed to parse story profile: {}", e)))
+    }
+
+    pub async fn save(&self, saves_dir: &Path) -> GameResult<()> {
+        let path = Self::path(saves_dir, &self.story_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| GameError::save_load(format!("Failed to create profiles directory: {}", e)))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| GameError::save_load(format!("Failed to serialize story profile: {}", e)))?;
+        fs::write(&path, json)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to write story profile: {}", e)))
+    }
+
+    /// Adds a finished game's ending and choices. Returns false if the game
+    /// isn't finished or was already recorded.
+    pub fn record_run(&mut self, game_state: &GameState) -> bool {
+        let Some(ending_id) = &game_state.completed_ending else {
+            return false;
+        };
+        if !self.completed_games.insert(game_state.id) {
+            return false;
+        }
+
+        self.completed_runs += 1;
+        self.endings.insert(ending_id.clone());
+        for (scene_id, choice_ids) in &game_state.taken_choices {
+            self.taken_choices.entry(scene_id.clone()).or_default().extend(choice_ids.iter().cloned());
+        }
+        true
+    }
+
+    pub fn has_taken(&self, scene_id: &str, choice_id: &str) -> bool {
+        self.taken_choices.get(scene_id).is_some_and(|choices| choices.contains(choice_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Player;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_story_profile() {
+        let temp_dir = tempdir().unwrap();
+        let mut profile = StoryProfile::load(temp_dir.path(), "forest").await.unwrap();
+        assert_eq!(profile.completed_runs, 0);
+
+        let mut game_state = GameState::new("forest".to_string(), "start".to_string(), Player::new("Tester", None));
+        game_state.record_choice("start", "left");
+        game_state.record_choice("cave", "enter");
+        assert!(!profile.record_run(&game_state));
+        game_state.completed_ending = Some("treasure".to_string());
+        assert!(profile.record_run(&game_state));
+        assert!(!profile.record_run(&game_state));
+        profile.save(temp_dir.path()).await.unwrap();
+
+        let loaded = StoryProfile::load(temp_dir.path(), "forest").await.unwrap();
+        assert_eq!(loaded, profile);
+        assert_eq!(loaded.completed_runs, 1);
+        assert!(loaded.has_taken("start", "left"));
+        assert!(!loaded.has_taken("start", "right"));
+        assert!(loaded.endings.contains("treasure"));
+
+        let profiles_dir = temp_dir.path().join(PROFILES_DIR);
+        assert_eq!(StoryProfile::path(temp_dir.path(), "../../escape"), profiles_dir.join("%2E%2E%2F%2E%2E%2Fescape.json"));
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Keys whose bucket is full are forgotten once this many are tracked.
+const PRUNE_AT: usize = 1024;
+
+/// Allows each key (a user, an address, ...) `per_minute` actions a minute,
+/// with bursts up to the same number: a token bucket per key.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    per_minute: u32,
+    /// Tokens left and when they were counted, by key.
+    buckets: HashMap<String, (f64, Instant)>,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `per_minute` actions per key; 0 allows everything.
+    pub fn new(per_minute: u32) -> Self {
+        Self { per_minute, buckets: HashMap::new() }
+    }
+
+    /// Whether `key` may act at `now`, using up one of its tokens if so.
+    pub fn check(&mut self, key: &str, now: Instant) -> bool {
+        if self.per_minute == 0 {
+            return true;
+        }
+        let capacity = self.per_minute as f64;
+        let refill = |(tokens, since): (f64, Instant)| {
+            capacity.min(tokens + now.saturating_duration_since(since).as_secs_f64() * capacity / 60.0)
+        };
+        if self.buckets.len() >= PRUNE_AT {
+            self.buckets.retain(|_, &mut bucket| refill(bucket) < capacity);
+        }
+
+        let tokens = self.buckets.get(key).map_or(capacity, |&bucket| refill(bucket));
+        let allowed = tokens >= 1.0;
+        self.buckets.insert(key.to_string(), (if allowed { tokens - 1.0 } else { tokens }, now));
+        allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rate_limiter() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(2);
+        assert!(limiter.check("ana", start));
+        assert!(limiter.check("ana", start));
+        assert!(!limiter.check("ana", start));
+        assert!(limiter.check("ben", start));
+
+        // One token comes back every 30 seconds
+        assert!(!limiter.check("ana", start + Duration::from_secs(20)));
+        assert!(limiter.check("ana", start + Duration::from_secs(31)));
+        assert!(!limiter.check("ana", start + Duration::from_secs(32)));
+
+        let mut unlimited = RateLimiter::new(0);
+        assert!((0..100).all(|_| unlimited.check("ana", start)));
+    }
+}
@@ -0,0 +1,164 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use crate::utils::{GameError, GameResult};
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+
+/// AES-256-GCM ciphertext of a save payload, base64 encoded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedPayload {
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Clone)]
+enum KeySource {
+    Passphrase(String),
+    MachineKey([u8; KEY_LEN]),
+}
+
+/// Encrypts and decrypts save payloads with a key derived from a passphrase
+/// (Argon2id, salted per save) or a random key stored on this machine.
+#[derive(Clone)]
+pub struct SaveCipher {
+    source: KeySource,
+}
+
+impl fmt::Debug for SaveCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.source {
+            KeySource::Passphrase(_) => "passphrase",
+            KeySource::MachineKey(_) => "machine_key",
+        };
+        f.debug_struct("SaveCipher").field("source", &kind).finish()
+    }
+}
+
+impl SaveCipher {
+    pub fn from_passphrase<S: Into<String>>(passphrase: S) -> Self {
+        Self {
+            source: KeySource::Passphrase(passphrase.into()),
+        }
+    }
+
+    /// Uses the key stored at `path`, generating one on first use.
+    pub fn from_key_file<P: AsRef<Path>>(path: P) -> GameResult<Self> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            let mut key = [0u8; KEY_LEN];
+            OsRng.fill_bytes(&mut key);
+            write_key_file(path, &BASE64.encode(key))?;
+        }
+
+        let encoded = std::fs::read_to_string(path)
+            .map_err(|e| GameError::save_load(format!("Failed to read save key: {}", e)))?;
+        let bytes = BASE64.decode(encoded.trim())
+            .map_err(|e| GameError::save_load(format!("Invalid save key: {}", e)))?;
+        let key: [u8; KEY_LEN] = bytes.try_into()
+            .map_err(|_| GameError::save_load("Invalid save key length".to_string()))?;
+
+        Ok(Self {
+            source: KeySource::MachineKey(key),
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> GameResult<EncryptedPayload> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let cipher = Aes256Gcm::new(&self.derive_key(&salt)?);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext)
+            .map_err(|_| GameError::save_load("Failed to encrypt save".to_string()))?;
+
+        Ok(EncryptedPayload {
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, payload: &EncryptedPayload) -> GameResult<Vec<u8>> {
+        let decode = |field: &str| BASE64.decode(field)
+            .map_err(|e| GameError::save_load(format!("Corrupt encrypted save: {}", e)));
+
+        let salt = decode(&payload.salt)?;
+        let nonce = decode(&payload.nonce)?;
+        let ciphertext = decode(&payload.ciphertext)?;
+
+        if nonce.len() != 12 {
+            return Err(GameError::save_load("Corrupt encrypted save: bad nonce".to_string()));
+        }
+
+        let cipher = Aes256Gcm::new(&self.derive_key(&salt)?);
+        cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| GameError::save_load("Failed to decrypt save: wrong key or corrupted file".to_string()))
+    }
+
+    fn derive_key(&self, salt: &[u8]) -> GameResult<Key<Aes256Gcm>> {
+        match &self.source {
+            KeySource::Passphrase(passphrase) => {
+                let mut key = [0u8; KEY_LEN];
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|e| GameError::save_load(format!("Failed to derive save key: {}", e)))?;
+                Ok(key.into())
+            }
+            KeySource::MachineKey(key) => Ok((*key).into()),
+        }
+    }
+}
+
+fn write_key_file(path: &Path, contents: &str) -> GameResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| GameError::save_load(format!("Failed to create key directory: {}", e)))?;
+    }
+
+    std::fs::write(path, contents)
+        .map_err(|e| GameError::save_load(format!("Failed to write save key: {}", e)))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| GameError::save_load(format!("Failed to restrict save key permissions: {}", e)))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let cipher = SaveCipher::from_passphrase("hunter2");
+        let payload = cipher.encrypt(b"secret flags").unwrap();
+
+        assert_eq!(cipher.decrypt(&payload).unwrap(), b"secret flags");
+        assert!(SaveCipher::from_passphrase("wrong").decrypt(&payload).is_err());
+    }
+
+    #[test]
+    fn test_machine_key_is_persisted() {
+        let temp_dir = tempdir().unwrap();
+        let key_path = temp_dir.path().join("save.key");
+
+        let payload = SaveCipher::from_key_file(&key_path).unwrap().encrypt(b"data").unwrap();
+        let reloaded = SaveCipher::from_key_file(&key_path).unwrap();
+        assert_eq!(reloaded.decrypt(&payload).unwrap(), b"data");
+    }
+}
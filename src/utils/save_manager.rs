@@ -1,10 +1,14 @@
+use fs2::FileExt;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tokio::fs;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::core::GameState;
-use crate::utils::{GameError, GameResult};
+use crate::core::{GameEvent, GameState};
+use crate::story::Scene;
+use crate::utils::{GameError, GameResult, SaveCipher};
+use crate::utils::save_crypto::EncryptedPayload;
 use tracing::{info, warn, error, debug};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,28 +20,300 @@ pub struct SaveGame {
     pub save_time: DateTime<Utc>,
     pub version: String,
     pub metadata: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<SavePreview>,
+    /// The session's event history, when `game.save_event_log` is on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<GameEvent>,
 }
 
+/// Where the player was when the game was saved, shown in the load menu so
+/// similar saves can be told apart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavePreview {
+    pub scene_title: String,
+    /// First sentence of the scene description.
+    pub snippet: String,
+    pub health: i32,
+    pub max_health: i32,
+    /// The scene's `location` metadata, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+const PREVIEW_SNIPPET_LENGTH: usize = 80;
+
+impl SavePreview {
+    pub fn new(scene: &Scene, game_state: &GameState) -> Self {
+        let location = scene.metadata.as_ref()
+            .and_then(|metadata| metadata.get("location"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        Self {
+            scene_title: scene.title.clone(),
+            snippet: first_sentence(&scene.description, PREVIEW_SNIPPET_LENGTH),
+            health: game_state.player.stats.health,
+            max_health: game_state.player.stats.max_health,
+            location,
+        }
+    }
+}
+
+impl std::fmt::Display for SavePreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.scene_title)?;
+        if let Some(location) = &self.location {
+            write!(f, " @ {}", location)?;
+        }
+        write!(f, " ❤️ {}/{} - {}", self.health, self.max_health, self.snippet)
+    }
+}
+
+/// `name` as a file name: every name maps to its own file, and none can
+/// climb out of the directory it's joined to.
+pub(crate) fn escape_file_name(name: &str) -> String {
+    name.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decrypt_preview(cipher: &SaveCipher, payload: &EncryptedPayload) -> GameResult<SavePreview> {
+    serde_json::from_slice(&cipher.decrypt(payload)?)
+        .map_err(|e| GameError::save_load(format!("Failed to parse decrypted save preview: {}", e)))
+}
+
+/// The text up to the end of its first sentence, cut at `max_chars`.
+fn first_sentence(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    let end = text.char_indices()
+        .find(|&(i, c)| {
+            matches!(c, '.' | '!' | '?')
+                && text[i + c.len_utf8()..].chars().next().is_none_or(char::is_whitespace)
+        })
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(text.len());
+    let sentence = &text[..end];
+
+    if sentence.chars().count() > max_chars {
+        let cut: String = sentence.chars().take(max_chars).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        sentence.to_string()
+    }
+}
+
+/// On-disk form of an encrypted save. The summary needed for listing stays in
+/// plain JSON; the game state, event history and preview are encrypted, as the
+/// preview quotes the story.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSaveFile {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    save_time: DateTime<Utc>,
+    version: String,
+    metadata: Option<serde_json::Value>,
+    /// Written in plain by older versions; read but no longer written.
+    #[serde(default, skip_serializing)]
+    preview: Option<SavePreview>,
+    summary: SaveSummary,
+    encrypted_state: EncryptedPayload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted_events: Option<EncryptedPayload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted_preview: Option<EncryptedPayload>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveSummary {
+    story_id: String,
+    #[serde(default)]
+    story_version: Option<String>,
+    player_name: String,
+    player_level: i32,
+    playtime_seconds: i64,
+    /// Only whether the game was finished: the ending itself is a spoiler
+    /// and stays in the encrypted state.
+    #[serde(default)]
+    completed: bool,
+    /// Written by older versions; read but no longer written.
+    #[serde(default, skip_serializing)]
+    completed_ending: Option<String>,
+}
+
+impl SaveSummary {
+    fn from_game_state(game_state: &GameState) -> Self {
+        Self {
+            story_id: game_state.story_id.clone(),
+            story_version: game_state.story_version.clone(),
+            player_name: game_state.player.name.clone(),
+            player_level: game_state.player.stats.level,
+            playtime_seconds: game_state.playtime_seconds,
+            completed: game_state.completed_ending.is_some(),
+            completed_ending: None,
+        }
+    }
+
+    /// Extracts the summary from an unencrypted save without parsing the full state.
+    fn from_plain_save(value: &serde_json::Value) -> Self {
+        let game_state = value.get("game_state");
+
+        let player_name = game_state
+            .and_then(|gs| gs.get("player"))
+            .and_then(|p| p.get("name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("Unknown")
+            .to_string();
+
+        let player_level = game_state
+            .and_then(|gs| gs.get("player"))
+            .and_then(|p| p.get("stats"))
+            .and_then(|s| s.get("level"))
+            .and_then(|v| v.as_i64())
+            .unwrap_or(1) as i32;
+
+        let story_id = game_state
+            .and_then(|gs| gs.get("story_id"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let story_version = game_state
+            .and_then(|gs| gs.get("story_version"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        let playtime_seconds = game_state
+            .and_then(|gs| gs.get("playtime_seconds"))
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0);
+
+        let completed = game_state
+            .and_then(|gs| gs.get("completed_ending"))
+            .is_some_and(|v| v.is_string());
+
+        Self {
+            story_id,
+            story_version,
+            player_name,
+            player_level,
+            playtime_seconds,
+            completed,
+            completed_ending: None,
+        }
+    }
+}
+
+/// Quick saves of a story rotate through this many slots.
+pub const QUICK_SAVE_SLOTS: usize = 3;
+const QUICK_SAVE_NAME: &str = "Quick Save";
+const EMERGENCY_SAVE_NAME: &str = "Emergency Save";
+
+/// Directory under the saves directory holding the game to offer resuming
+/// after the last run stopped on an error.
+pub const RECOVERY_DIR: &str = "recovery";
+const RECOVERY_FILE_NAME: &str = "recovery.json";
+/// Directory under the saves directory holding bot sessions' games.
+const SESSIONS_DIR: &str = "sessions";
+const QUARANTINE_EXTENSION: &str = "broken";
+
+/// Saves larger than this are logged, as a sign a story is storing too much.
+const LARGE_SAVE_BYTES: usize = 1024 * 1024;
+
+const LOCK_FILE_NAME: &str = ".saves.lock";
+/// Directory under the saves directory holding each user's own saves.
+pub const USERS_DIR: &str = "users";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Clone)]
 pub struct SaveManager {
     saves_directory: PathBuf,
+    cipher: Option<SaveCipher>,
+}
+
+/// Advisory lock on the saves directory, released on drop.
+struct SaveLock {
+    file: std::fs::File,
+}
+
+impl SaveLock {
+    async fn acquire(saves_directory: &Path, exclusive: bool) -> GameResult<Self> {
+        fs::create_dir_all(saves_directory)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to create saves directory: {}", e)))?;
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(saves_directory.join(LOCK_FILE_NAME))
+            .map_err(|e| GameError::save_load(format!("Failed to open saves lock: {}", e)))?;
+
+        let started = Instant::now();
+        loop {
+            let result = if exclusive {
+                FileExt::try_lock_exclusive(&file)
+            } else {
+                FileExt::try_lock_shared(&file)
+            };
+
+            match result {
+                Ok(()) => return Ok(Self { file }),
+                Err(_) if started.elapsed() < LOCK_TIMEOUT => {
+                    tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+                }
+                Err(e) => {
+                    return Err(GameError::save_load(format!(
+                        "Saves directory is locked by another process: {}", e
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SaveLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
 }
 
 impl SaveManager {
     pub fn new<P: AsRef<Path>>(saves_directory: P) -> Self {
         Self {
             saves_directory: saves_directory.as_ref().to_path_buf(),
+            cipher: None,
+        }
+    }
+
+    /// Encrypt the game state of every save written from now on.
+    pub fn with_cipher(mut self, cipher: SaveCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// A manager for `user`'s own saves, kept apart from everyone else's
+    /// in a directory of their own.
+    pub fn for_user(&self, user: &str) -> GameResult<Self> {
+        if user.trim().is_empty() {
+            return Err(GameError::save_load("Saves need a user name".to_string()));
         }
+        Ok(Self {
+            saves_directory: self.saves_directory.join(USERS_DIR).join(escape_file_name(user)),
+            cipher: self.cipher.clone(),
+        })
     }
 
     pub async fn save_game(&self, name: String, game_state: GameState, description: Option<String>) -> GameResult<SaveGame> {
         info!("Saving game: {}", name);
 
-        // Create saves directory if it doesn't exist
-        if !self.saves_directory.exists() {
-            fs::create_dir_all(&self.saves_directory)
-                .await
-                .map_err(|e| GameError::save_load(format!("Failed to create saves directory: {}", e)))?;
-        }
+        // Also creates the saves directory if it doesn't exist
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
 
         let save_game = SaveGame {
             id: Uuid::new_v4(),
@@ -47,23 +323,291 @@ impl SaveManager {
             save_time: Utc::now(),
             version: crate::VERSION.to_string(),
             metadata: None,
+            preview: None,
+            events: Vec::new(),
+        };
+
+        self.write_save_file(&save_game).await?;
+        info!("Game saved successfully: {} ({})", name, save_game.id);
+
+        Ok(save_game)
+    }
+
+    /// Saves to the story's quick save slots, overwriting the oldest one once
+    /// all of them are taken. Quick saves are kept apart from named saves.
+    pub async fn quick_save(&self, game_state: GameState, preview: Option<SavePreview>, events: Vec<GameEvent>) -> GameResult<SaveGame> {
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
+        let quick_saves = self.scan_quick_saves(&game_state.story_id).await?;
+
+        // Newest first, so the last one is the oldest
+        let id = match quick_saves.last() {
+            Some(oldest) if quick_saves.len() >= QUICK_SAVE_SLOTS => oldest.id,
+            _ => Uuid::new_v4(),
+        };
+
+        let save_game = SaveGame {
+            id,
+            name: QUICK_SAVE_NAME.to_string(),
+            description: None,
+            game_state,
+            save_time: Utc::now(),
+            version: crate::VERSION.to_string(),
+            metadata: Some(serde_json::json!({ "quick_save": true })),
+            preview,
+            events,
+        };
+
+        self.write_save_file(&save_game).await?;
+        info!("Quick saved story {} ({})", save_game.game_state.story_id, save_game.id);
+
+        Ok(save_game)
+    }
+
+    /// Loads the most recent quick save of a story.
+    pub async fn quick_load(&self, story_id: &str) -> GameResult<SaveGame> {
+        let latest = self.list_save_games().await?
+            .into_iter()
+            .find(|save| save.quick_save && save.story_id == story_id)
+            .ok_or_else(|| GameError::save_load("No quick save for this story yet".to_string()))?;
+
+        self.load_game(latest.id).await
+    }
+
+    /// Saves to a numbered slot (starting at 1) of the game's story,
+    /// overwriting the save that was there instead of adding a new file.
+    pub async fn save_to_slot(&self, slot: usize, name: String, game_state: GameState, preview: Option<SavePreview>, events: Vec<GameEvent>) -> GameResult<SaveGame> {
+        if slot == 0 {
+            return Err(GameError::save_load("Save slots are numbered from 1".to_string()));
+        }
+
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
+        let id = self.scan_save_games().await?
+            .into_iter()
+            .find(|save| save.slot == Some(slot) && save.story_id == game_state.story_id)
+            .map(|save| save.id)
+            .unwrap_or_else(Uuid::new_v4);
+
+        let save_game = SaveGame {
+            id,
+            name,
+            description: None,
+            game_state,
+            save_time: Utc::now(),
+            version: crate::VERSION.to_string(),
+            metadata: Some(serde_json::json!({ "slot": slot })),
+            preview,
+            events,
+        };
+
+        self.write_save_file(&save_game).await?;
+        info!("Game saved to slot {}: {} ({})", slot, save_game.name, save_game.id);
+
+        Ok(save_game)
+    }
+
+    /// Saves the game right away without waiting for the saves lock, for
+    /// when the game is crashing and can't wait for anything.
+    pub fn emergency_save(&self, game_state: GameState) -> GameResult<SaveGame> {
+        std::fs::create_dir_all(&self.saves_directory)
+            .map_err(|e| GameError::save_load(format!("Failed to create saves directory: {}", e)))?;
+
+        let save_game = SaveGame {
+            id: Uuid::new_v4(),
+            name: EMERGENCY_SAVE_NAME.to_string(),
+            description: Some("Saved when the game crashed".to_string()),
+            game_state,
+            save_time: Utc::now(),
+            version: crate::VERSION.to_string(),
+            metadata: Some(serde_json::json!({ "emergency": true })),
+            preview: None,
+            events: Vec::new(),
+        };
+
+        let json = self.encode_save(&save_game)?;
+        std::fs::write(self.get_save_path(&save_game.id), json)
+            .map_err(|e| GameError::save_load(format!("Failed to write emergency save: {}", e)))?;
+
+        Ok(save_game)
+    }
+
+    /// Keeps the game a run stopped on an error in, replacing the previous
+    /// one. It isn't listed with the other saves; `load_recovery` finds it.
+    pub async fn save_recovery(&self, game_state: GameState, events: Vec<GameEvent>) -> GameResult<SaveGame> {
+        let recovery_directory = self.saves_directory.join(RECOVERY_DIR);
+        fs::create_dir_all(&recovery_directory)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to create recovery directory: {}", e)))?;
+
+        let save_game = SaveGame {
+            id: Uuid::new_v4(),
+            name: "Recovered Game".to_string(),
+            description: None,
+            game_state,
+            save_time: Utc::now(),
+            version: crate::VERSION.to_string(),
+            metadata: Some(serde_json::json!({ "recovery": true })),
+            preview: None,
+            events,
         };
 
+        let json = self.encode_save(&save_game)?;
+        fs::write(recovery_directory.join(RECOVERY_FILE_NAME), json)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to write recovery save: {}", e)))?;
+
+        info!("Saved recovery game for story {}", save_game.game_state.story_id);
+        Ok(save_game)
+    }
+
+    /// The game the last run stopped on an error in, if there is one.
+    pub async fn load_recovery(&self) -> GameResult<Option<SaveGame>> {
+        let path = self.recovery_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let decoded = fs::read_to_string(&path)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to read recovery save: {}", e)))
+            .and_then(|content| self.decode_save(&content));
+        match decoded {
+            Ok(save_game) => Ok(Some(save_game)),
+            Err(e) => {
+                // Moved aside so a broken recovery save isn't offered on every start
+                let quarantine = path.with_extension(QUARANTINE_EXTENSION);
+                fs::rename(&path, &quarantine)
+                    .await
+                    .map_err(|e| GameError::save_load(format!("Failed to move aside broken recovery save: {}", e)))?;
+                warn!("Moved broken recovery save to {:?}: {}", quarantine, e);
+                Err(e.with_hint(format!("the recovery save was moved to {}", quarantine.display())))
+            }
+        }
+    }
+
+    /// Forgets the recovery game, once it was resumed or turned down.
+    pub async fn clear_recovery(&self) -> GameResult<()> {
+        let path = self.recovery_path();
+        if path.exists() {
+            fs::remove_file(&path)
+                .await
+                .map_err(|e| GameError::save_load(format!("Failed to delete recovery save: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    fn recovery_path(&self) -> PathBuf {
+        self.saves_directory.join(RECOVERY_DIR).join(RECOVERY_FILE_NAME)
+    }
+
+    /// Saves the game of a bot session, e.g. a chat channel, replacing the
+    /// session's previous save. Each session has its own file under
+    /// `sessions/`, named after it, so saving doesn't read the other saves.
+    pub async fn save_session(&self, session: &str, game_state: GameState) -> GameResult<SaveGame> {
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
+        let sessions_directory = self.saves_directory.join(SESSIONS_DIR);
+        fs::create_dir_all(&sessions_directory)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to create sessions directory: {}", e)))?;
+
+        let save_game = SaveGame {
+            id: Uuid::new_v4(),
+            name: session.to_string(),
+            description: None,
+            game_state,
+            save_time: Utc::now(),
+            version: crate::VERSION.to_string(),
+            metadata: Some(serde_json::json!({ "session": session })),
+            preview: None,
+            events: Vec::new(),
+        };
+
+        let json = self.encode_save(&save_game)?;
+        fs::write(self.session_path(session), json)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to write session save: {}", e)))?;
+        debug!("Saved session {} ({})", session, save_game.id);
+
+        Ok(save_game)
+    }
+
+    /// The save of a bot session, if it has one.
+    pub async fn load_session(&self, session: &str) -> GameResult<Option<SaveGame>> {
+        let path = self.session_path(session);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let _lock = SaveLock::acquire(&self.saves_directory, false).await?;
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to read session save: {}", e)))?;
+        self.decode_save(&content).map(Some)
+    }
+
+    /// Deletes the save of a bot session, if it has one.
+    pub async fn delete_session(&self, session: &str) -> GameResult<()> {
+        let path = self.session_path(session);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
+        fs::remove_file(&path)
+            .await
+            .map_err(|e| GameError::save_load(format!("Failed to delete session save: {}", e)))
+    }
+
+    fn session_path(&self, session: &str) -> PathBuf {
+        self.saves_directory.join(SESSIONS_DIR).join(format!("{}.json", escape_file_name(session)))
+    }
+
+    /// What is saved in each of the slots `1..=slot_count` of a story.
+    pub async fn list_slots(&self, story_id: &str, slot_count: usize) -> GameResult<Vec<Option<SaveGameMetadata>>> {
+        let mut slots = vec![None; slot_count];
+
+        // Newest first, so a slot claimed by several files shows the latest
+        let story_saves = self.list_save_games().await?.into_iter().filter(|save| save.story_id == story_id);
+        for save in story_saves {
+            if let Some(index) = save.slot.and_then(|slot| slot.checked_sub(1)) {
+                if let Some(entry @ None) = slots.get_mut(index) {
+                    *entry = Some(save);
+                }
+            }
+        }
+
+        Ok(slots)
+    }
+
+    async fn scan_quick_saves(&self, story_id: &str) -> GameResult<Vec<SaveGameMetadata>> {
+        Ok(self.scan_save_games().await?
+            .into_iter()
+            .filter(|save| save.quick_save && save.story_id == story_id)
+            .collect())
+    }
+
+    async fn write_save_file(&self, save_game: &SaveGame) -> GameResult<()> {
         let save_path = self.get_save_path(&save_game.id);
-        let json = serde_json::to_string_pretty(&save_game)
-            .map_err(|e| GameError::save_load(format!("Failed to serialize save game: {}", e)))?;
+        let json = self.encode_save(save_game)?;
+        if json.len() > LARGE_SAVE_BYTES {
+            warn!(
+                "Save '{}' is {} KB; the story may be storing too much in flags",
+                save_game.name, json.len() / 1024
+            );
+        }
 
         fs::write(&save_path, json)
             .await
             .map_err(|e| GameError::save_load(format!("Failed to write save file: {}", e)))?;
 
-        info!("Game saved successfully: {} ({})", name, save_game.id);
         debug!("Save file written to: {:?}", save_path);
-
-        Ok(save_game)
+        Ok(())
     }
 
     pub async fn load_game(&self, save_id: Uuid) -> GameResult<SaveGame> {
+        self.read_save(save_id).await.map_err(|e| e.with_save(save_id))
+    }
+
+    async fn read_save(&self, save_id: Uuid) -> GameResult<SaveGame> {
         let save_path = self.get_save_path(&save_id);
         
         if !save_path.exists() {
@@ -76,8 +620,7 @@ impl SaveManager {
             .await
             .map_err(|e| GameError::save_load(format!("Failed to read save file: {}", e)))?;
 
-        let save_game: SaveGame = serde_json::from_str(&content)
-            .map_err(|e| GameError::save_load(format!("Failed to parse save file: {}", e)))?;
+        let save_game = self.decode_save(&content)?;
 
         // Validate version compatibility (for now, just warn on mismatch)
         if save_game.version != crate::VERSION {
@@ -89,12 +632,17 @@ impl SaveManager {
     }
 
     pub async fn list_save_games(&self) -> GameResult<Vec<SaveGameMetadata>> {
-        debug!("Scanning for save games in: {:?}", self.saves_directory);
-
         if !self.saves_directory.exists() {
             return Ok(Vec::new());
         }
 
+        let _lock = SaveLock::acquire(&self.saves_directory, false).await?;
+        self.scan_save_games().await
+    }
+
+    async fn scan_save_games(&self) -> GameResult<Vec<SaveGameMetadata>> {
+        debug!("Scanning for save games in: {:?}", self.saves_directory);
+
         let mut entries = fs::read_dir(&self.saves_directory)
             .await
             .map_err(|e| GameError::save_load(format!("Failed to read saves directory: {}", e)))?;
@@ -118,13 +666,18 @@ impl SaveManager {
         }
 
         // Sort by save time (newest first)
-        save_games.sort_by(|a, b| b.save_time.cmp(&a.save_time));
+        save_games.sort_by_key(|save| std::cmp::Reverse(save.save_time));
         
         info!("Found {} save games", save_games.len());
         Ok(save_games)
     }
 
     pub async fn delete_save(&self, save_id: Uuid) -> GameResult<()> {
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
+        self.remove_save_file(save_id).await
+    }
+
+    async fn remove_save_file(&self, save_id: Uuid) -> GameResult<()> {
         let save_path = self.get_save_path(&save_id);
         
         if !save_path.exists() {
@@ -164,21 +717,27 @@ impl SaveManager {
         Ok(count)
     }
 
+    /// Deletes all but the `keep_count` newest named saves of each story.
+    /// Slots, quick saves and chat sessions are kept; they overwrite
+    /// themselves rather than pile up.
     pub async fn cleanup_old_saves(&self, keep_count: usize) -> GameResult<usize> {
-        let mut save_games = self.list_save_games().await?;
-        
-        if save_games.len() <= keep_count {
-            return Ok(0);
-        }
-
-        // Sort by save time (oldest first for deletion)
-        save_games.sort_by(|a, b| a.save_time.cmp(&b.save_time));
-        
-        let to_delete = save_games.len() - keep_count;
+        // Hold the lock across listing and deleting so another instance can't
+        // add or remove saves in between
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
+        let named_saves = self.scan_save_games().await?
+            .into_iter()
+            .filter(|save| !save.quick_save && save.slot.is_none())
+            .collect();
+
+        // Newest first, so everything past `keep_count` goes
+        let old_saves: Vec<SaveGameMetadata> = SaveGameMetadata::group_by_story(named_saves)
+            .into_iter()
+            .flat_map(|(_, story_saves)| story_saves.into_iter().skip(keep_count))
+            .collect();
         let mut deleted = 0;
 
-        for save_metadata in save_games.iter().take(to_delete) {
-            match self.delete_save(save_metadata.id).await {
+        for save_metadata in &old_saves {
+            match self.remove_save_file(save_metadata.id).await {
                 Ok(()) => {
                     deleted += 1;
                     info!("Deleted old save: {}", save_metadata.name);
@@ -196,8 +755,7 @@ impl SaveManager {
     pub async fn export_save(&self, save_id: Uuid, export_path: &Path) -> GameResult<()> {
         let save_game = self.load_game(save_id).await?;
         
-        let json = serde_json::to_string_pretty(&save_game)
-            .map_err(|e| GameError::save_load(format!("Failed to serialize save for export: {}", e)))?;
+        let json = self.encode_save(&save_game)?;
 
         fs::write(export_path, json)
             .await
@@ -216,17 +774,18 @@ impl SaveManager {
             .await
             .map_err(|e| GameError::save_load(format!("Failed to read import file: {}", e)))?;
 
-        let mut save_game: SaveGame = serde_json::from_str(&content)
-            .map_err(|e| GameError::save_load(format!("Failed to parse import file: {}", e)))?;
+        let mut save_game = self.decode_save(&content)?;
 
-        // Generate new ID to avoid conflicts
+        // Generate new ID to avoid conflicts; an import is a plain named
+        // save, not another claim on a slot or quick save
         save_game.id = Uuid::new_v4();
         save_game.name = format!("{} (Imported)", save_game.name);
+        save_game.metadata = None;
 
         // Save the imported game
+        let _lock = SaveLock::acquire(&self.saves_directory, true).await?;
         let save_path = self.get_save_path(&save_game.id);
-        let json = serde_json::to_string_pretty(&save_game)
-            .map_err(|e| GameError::save_load(format!("Failed to serialize imported save: {}", e)))?;
+        let json = self.encode_save(&save_game)?;
 
         fs::write(&save_path, json)
             .await
@@ -260,31 +819,12 @@ impl SaveManager {
             .map_err(|e| GameError::save_load(format!("Invalid save time format: {}", e)))?
             .with_timezone(&Utc);
 
-        // Extract player name and level from game state
-        let player_name = value.get("game_state")
-            .and_then(|gs| gs.get("player"))
-            .and_then(|p| p.get("name"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string();
-
-        let player_level = value.get("game_state")
-            .and_then(|gs| gs.get("player"))
-            .and_then(|p| p.get("stats"))
-            .and_then(|s| s.get("level"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(1) as i32;
-
-        let story_id = value.get("game_state")
-            .and_then(|gs| gs.get("story_id"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let playtime = value.get("game_state")
-            .and_then(|gs| gs.get("playtime_seconds"))
-            .and_then(|v| v.as_i64())
-            .unwrap_or(0);
+        let encrypted = value.get("encrypted_state").is_some();
+        let summary = match value.get("summary") {
+            Some(summary) => serde_json::from_value(summary.clone())
+                .map_err(|e| GameError::save_load(format!("Invalid save summary: {}", e)))?,
+            None => SaveSummary::from_plain_save(&value),
+        };
 
         Ok(SaveGameMetadata {
             id,
@@ -300,13 +840,119 @@ impl SaveManager {
                 .and_then(|v| v.as_str())
                 .unwrap_or("unknown")
                 .to_string(),
-            story_id,
-            player_name,
-            player_level,
-            playtime_seconds: playtime,
+            story_id: summary.story_id,
+            story_version: summary.story_version,
+            player_name: summary.player_name,
+            player_level: summary.player_level,
+            playtime_seconds: summary.playtime_seconds,
+            completed: summary.completed || summary.completed_ending.is_some(),
+            encrypted,
+            quick_save: value.pointer("/metadata/quick_save")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            slot: value.pointer("/metadata/slot")
+                .and_then(|v| v.as_u64())
+                .map(|slot| slot as usize),
+            preview: self.listed_preview(&value),
+            size_bytes: content.len() as u64,
         })
     }
 
+    fn encode_save(&self, save_game: &SaveGame) -> GameResult<String> {
+        let Some(cipher) = &self.cipher else {
+            return serde_json::to_string_pretty(save_game)
+                .map_err(|e| GameError::save_load(format!("Failed to serialize save game: {}", e)));
+        };
+
+        let state_json = serde_json::to_vec(&save_game.game_state)
+            .map_err(|e| GameError::save_load(format!("Failed to serialize game state: {}", e)))?;
+        let encrypted_events = if save_game.events.is_empty() {
+            None
+        } else {
+            let events_json = serde_json::to_vec(&save_game.events)
+                .map_err(|e| GameError::save_load(format!("Failed to serialize event history: {}", e)))?;
+            Some(cipher.encrypt(&events_json)?)
+        };
+        let encrypted_preview = match &save_game.preview {
+            Some(preview) => {
+                let preview_json = serde_json::to_vec(preview)
+                    .map_err(|e| GameError::save_load(format!("Failed to serialize save preview: {}", e)))?;
+                Some(cipher.encrypt(&preview_json)?)
+            }
+            None => None,
+        };
+
+        let file = EncryptedSaveFile {
+            id: save_game.id,
+            name: save_game.name.clone(),
+            description: save_game.description.clone(),
+            save_time: save_game.save_time,
+            version: save_game.version.clone(),
+            metadata: save_game.metadata.clone(),
+            preview: None,
+            summary: SaveSummary::from_game_state(&save_game.game_state),
+            encrypted_state: cipher.encrypt(&state_json)?,
+            encrypted_events,
+            encrypted_preview,
+        };
+
+        serde_json::to_string_pretty(&file)
+            .map_err(|e| GameError::save_load(format!("Failed to serialize save game: {}", e)))
+    }
+
+    fn decode_save(&self, content: &str) -> GameResult<SaveGame> {
+        let value: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| GameError::save_load(format!("Failed to parse save file: {}", e)))?;
+
+        if value.get("encrypted_state").is_none() {
+            return serde_json::from_value(value)
+                .map_err(|e| GameError::save_load(format!("Failed to parse save file: {}", e)));
+        }
+
+        let file: EncryptedSaveFile = serde_json::from_value(value)
+            .map_err(|e| GameError::save_load(format!("Failed to parse save file: {}", e)))?;
+
+        let cipher = self.cipher.as_ref().ok_or_else(|| GameError::save_load(
+            format!("Save '{}' is encrypted; configure save encryption to load it", file.name)
+        ))?;
+
+        let state_json = cipher.decrypt(&file.encrypted_state)?;
+        let game_state = serde_json::from_slice(&state_json)
+            .map_err(|e| GameError::save_load(format!("Failed to parse decrypted game state: {}", e)))?;
+        let events = match &file.encrypted_events {
+            Some(payload) => serde_json::from_slice(&cipher.decrypt(payload)?)
+                .map_err(|e| GameError::save_load(format!("Failed to parse decrypted event history: {}", e)))?,
+            None => Vec::new(),
+        };
+        let preview = match &file.encrypted_preview {
+            Some(payload) => Some(decrypt_preview(cipher, payload)?),
+            None => file.preview,
+        };
+
+        Ok(SaveGame {
+            id: file.id,
+            name: file.name,
+            description: file.description,
+            game_state,
+            save_time: file.save_time,
+            version: file.version,
+            metadata: file.metadata,
+            preview,
+            events,
+        })
+    }
+
+    /// The preview of a listed save. Encrypted previews are only shown when
+    /// this manager holds the key.
+    fn listed_preview(&self, value: &serde_json::Value) -> Option<SavePreview> {
+        match (value.get("encrypted_preview"), &self.cipher) {
+            (Some(payload), Some(cipher)) => serde_json::from_value(payload.clone()).ok()
+                .and_then(|payload| decrypt_preview(cipher, &payload).ok()),
+            (Some(_), None) => None,
+            (None, _) => value.get("preview").and_then(|v| serde_json::from_value(v.clone()).ok()),
+        }
+    }
+
     fn get_save_path(&self, save_id: &Uuid) -> PathBuf {
         self.saves_directory.join(format!("{}.json", save_id))
     }
@@ -320,12 +966,36 @@ pub struct SaveGameMetadata {
     pub save_time: DateTime<Utc>,
     pub version: String,
     pub story_id: String,
+    pub story_version: Option<String>,
     pub player_name: String,
     pub player_level: i32,
     pub playtime_seconds: i64,
+    /// Whether the game was saved after reaching an ending.
+    pub completed: bool,
+    pub encrypted: bool,
+    pub quick_save: bool,
+    pub slot: Option<usize>,
+    pub preview: Option<SavePreview>,
+    /// Size of the save file.
+    pub size_bytes: u64,
 }
 
 impl SaveGameMetadata {
+    /// Groups saves by story ID. Groups are ordered by their first save and
+    /// saves keep their order, so a newest-first list stays newest first.
+    pub fn group_by_story(saves: Vec<SaveGameMetadata>) -> Vec<(String, Vec<SaveGameMetadata>)> {
+        let mut groups: Vec<(String, Vec<SaveGameMetadata>)> = Vec::new();
+
+        for save in saves {
+            match groups.iter_mut().find(|(story_id, _)| *story_id == save.story_id) {
+                Some((_, story_saves)) => story_saves.push(save),
+                None => groups.push((save.story_id.clone(), vec![save])),
+            }
+        }
+
+        groups
+    }
+
     pub fn display_name(&self) -> String {
         format!(
             "{} - {} (Level {}) - {}",
@@ -336,6 +1006,14 @@ impl SaveGameMetadata {
         )
     }
 
+    pub fn get_size_formatted(&self) -> String {
+        match self.size_bytes {
+            size if size >= 1024 * 1024 => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+            size if size >= 1024 => format!("{} KB", size / 1024),
+            size => format!("{} B", size),
+        }
+    }
+
     pub fn get_playtime_formatted(&self) -> String {
         let hours = self.playtime_seconds / 3600;
         let minutes = (self.playtime_seconds % 3600) / 60;
@@ -406,11 +1084,15 @@ mod tests {
         // Add some saves
         for i in 0..3 {
             let player = Player::new(format!("Player {}", i), Some(PlayerStats::default()));
-            let game_state = crate::core::GameState::new(
-                "test_story".to_string(),
+            let story_id = if i == 1 { "other_story" } else { "test_story" };
+            let mut game_state = crate::core::GameState::new(
+                story_id.to_string(),
                 "start".to_string(),
                 player,
             );
+            if i == 2 {
+                game_state.completed_ending = Some("victory".to_string());
+            }
 
             save_manager.save_game(
                 format!("Save {}", i),
@@ -426,6 +1108,53 @@ mod tests {
         assert_eq!(saves[0].name, "Save 2");
         assert_eq!(saves[1].name, "Save 1");
         assert_eq!(saves[2].name, "Save 0");
+        assert!(saves[0].completed);
+        assert!(saves[0].size_bytes > 0);
+        assert!(!saves[1].completed);
+
+        let groups = SaveGameMetadata::group_by_story(saves);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "test_story");
+        assert_eq!(groups[0].1.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Save 2", "Save 0"]);
+        assert_eq!(groups[1].0, "other_story");
+    }
+
+    #[tokio::test]
+    async fn test_emergency_save() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path().join("saves"));
+
+        let player = Player::new("Test Player", Some(PlayerStats::default()));
+        let game_state = crate::core::GameState::new("test_story".to_string(), "cave".to_string(), player);
+        let save_game = save_manager.emergency_save(game_state).unwrap();
+
+        let loaded = save_manager.load_game(save_game.id).await.unwrap();
+        assert_eq!(loaded.name, "Emergency Save");
+        assert_eq!(loaded.game_state.current_scene_id, "cave");
+    }
+
+    #[tokio::test]
+    async fn test_recovery_save() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path());
+        assert!(save_manager.load_recovery().await.unwrap().is_none());
+
+        let player = Player::new("Test Player", Some(PlayerStats::default()));
+        let game_state = crate::core::GameState::new("test_story".to_string(), "cave".to_string(), player);
+        save_manager.save_recovery(game_state, Vec::new()).await.unwrap();
+
+        // Kept apart from the saves in the load menu
+        assert!(save_manager.list_save_games().await.unwrap().is_empty());
+        let recovered = save_manager.load_recovery().await.unwrap().unwrap();
+        assert_eq!(recovered.game_state.current_scene_id, "cave");
+
+        save_manager.clear_recovery().await.unwrap();
+        assert!(save_manager.load_recovery().await.unwrap().is_none());
+
+        std::fs::write(save_manager.recovery_path(), "{ not json").unwrap();
+        assert!(save_manager.load_recovery().await.is_err());
+        assert!(save_manager.load_recovery().await.unwrap().is_none());
+        assert!(save_manager.recovery_path().with_extension(QUARANTINE_EXTENSION).exists());
     }
 
     #[tokio::test]
@@ -452,6 +1181,91 @@ mod tests {
         assert!(!save_manager.save_exists(save_game.id).await);
     }
 
+    #[tokio::test]
+    async fn test_quick_save_rotation() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path());
+
+        let game_state = |scene: &str| crate::core::GameState::new(
+            "test_story".to_string(),
+            scene.to_string(),
+            Player::new("Test Player", Some(PlayerStats::default())),
+        );
+
+        assert!(save_manager.quick_load("test_story").await.is_err());
+        save_manager.save_game("Named".to_string(), game_state("named"), None).await.unwrap();
+
+        let mut ids = Vec::new();
+        for i in 0..=QUICK_SAVE_SLOTS {
+            ids.push(save_manager.quick_save(game_state(&format!("scene{}", i)), None, Vec::new()).await.unwrap().id);
+        }
+
+        // The oldest slot was reused and the named save was left alone
+        assert_eq!(ids[QUICK_SAVE_SLOTS], ids[0]);
+        let saves = save_manager.list_save_games().await.unwrap();
+        assert_eq!(saves.iter().filter(|s| s.quick_save).count(), QUICK_SAVE_SLOTS);
+        assert_eq!(saves.len(), QUICK_SAVE_SLOTS + 1);
+
+        let loaded = save_manager.quick_load("test_story").await.unwrap();
+        assert_eq!(loaded.game_state.current_scene_id, format!("scene{}", QUICK_SAVE_SLOTS));
+        assert!(save_manager.quick_load("other_story").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_slots() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path());
+
+        let game_state = |scene: &str| crate::core::GameState::new(
+            "test_story".to_string(),
+            scene.to_string(),
+            Player::new("Test Player", Some(PlayerStats::default())),
+        );
+
+        let mut scene = Scene::new("b", "Cellar", "It is dark. Something moves.");
+        scene.metadata = Some([("location".to_string(), serde_json::json!("Old Mill"))].into());
+        let preview = SavePreview::new(&scene, &game_state("b"));
+
+        let events = vec![GameEvent::game_started("test_story", "Test Player")];
+        let first = save_manager.save_to_slot(3, "First".to_string(), game_state("a"), None, Vec::new()).await.unwrap();
+        let second = save_manager.save_to_slot(3, "Second".to_string(), game_state("b"), Some(preview.clone()), events).await.unwrap();
+        assert_eq!(first.id, second.id);
+        assert!(save_manager.save_to_slot(0, "Zero".to_string(), game_state("c"), None, Vec::new()).await.is_err());
+
+        let mut other = game_state("c");
+        other.story_id = "other_story".to_string();
+        let third = save_manager.save_to_slot(3, "Other".to_string(), other, None, Vec::new()).await.unwrap();
+        assert_ne!(third.id, second.id);
+        assert_eq!(save_manager.list_slots("other_story", 4).await.unwrap()[2].as_ref().unwrap().name, "Other");
+
+        let slots = save_manager.list_slots("test_story", 4).await.unwrap();
+        assert_eq!(slots.len(), 4);
+        assert!(slots[0].is_none());
+        assert_eq!(slots[2].as_ref().unwrap().name, "Second");
+        assert_eq!(slots[2].as_ref().unwrap().preview, Some(preview));
+        assert_eq!(save_manager.get_save_count().await.unwrap(), 2);
+
+        let loaded = save_manager.load_game(second.id).await.unwrap();
+        assert_eq!(loaded.game_state.current_scene_id, "b");
+        assert_eq!(loaded.events.len(), 1);
+    }
+
+    #[test]
+    fn test_save_preview_snippet() {
+        assert_eq!(first_sentence("You wake up. The cave is cold.", 80), "You wake up.");
+        assert_eq!(first_sentence("A sign reads 3.14 and nothing else", 80), "A sign reads 3.14 and nothing else");
+        assert_eq!(first_sentence("Aaaaa bbbbb ccccc", 11), "Aaaaa bbbbb…");
+
+        let scene = Scene::new("start", "Start", "You wake up! It is dark.");
+        let game_state = crate::core::GameState::new(
+            "test_story".to_string(),
+            "start".to_string(),
+            Player::new("Test Player", Some(PlayerStats::default())),
+        );
+        let preview = SavePreview::new(&scene, &game_state);
+        assert_eq!(preview.to_string(), "Start ❤️ 100/100 - You wake up!");
+    }
+
     #[tokio::test]
     async fn test_cleanup_old_saves() {
         let temp_dir = tempdir().unwrap();
@@ -476,11 +1290,120 @@ mod tests {
             tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
         }
 
-        assert_eq!(save_manager.get_save_count().await.unwrap(), 5);
+        let player = || Player::new("Player", Some(PlayerStats::default()));
+        let state = |story_id: &str| crate::core::GameState::new(story_id.to_string(), "start".to_string(), player());
+        save_manager.save_to_slot(1, "Slot".to_string(), state("test_story"), None, Vec::new()).await.unwrap();
+        save_manager.quick_save(state("test_story"), None, Vec::new()).await.unwrap();
+        save_manager.save_game("Other".to_string(), state("other_story"), None).await.unwrap();
+        assert_eq!(save_manager.get_save_count().await.unwrap(), 8);
 
-        // Keep only 3 newest saves
+        // Keep only the 3 newest named saves of each story
         let deleted = save_manager.cleanup_old_saves(3).await.unwrap();
         assert_eq!(deleted, 2);
-        assert_eq!(save_manager.get_save_count().await.unwrap(), 3);
+        assert_eq!(save_manager.get_save_count().await.unwrap(), 6);
+        let saves = save_manager.list_save_games().await.unwrap();
+        assert!(saves.iter().all(|save| save.name != "Save 0" && save.name != "Save 1"));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_save() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path())
+            .with_cipher(SaveCipher::from_passphrase("open sesame"));
+
+        let player = Player::new("Test Player", Some(PlayerStats::default()));
+        let mut game_state = crate::core::GameState::new(
+            "test_story".to_string(),
+            "start".to_string(),
+            player,
+        );
+        game_state.set_flag("betrayed_by_mentor", serde_json::Value::Bool(true)).unwrap();
+        game_state.completed_ending = Some("betrayal".to_string());
+
+        let save_game = save_manager.save_game("Secret".to_string(), game_state, None).await.unwrap();
+
+        let raw = std::fs::read_to_string(save_manager.get_save_path(&save_game.id)).unwrap();
+        assert!(!raw.contains("betrayed_by_mentor"));
+        assert!(!raw.contains("betrayal"));
+
+        // Listing works without decrypting
+        let saves = SaveManager::new(temp_dir.path()).list_save_games().await.unwrap();
+        assert_eq!(saves[0].player_name, "Test Player");
+        assert!(saves[0].encrypted);
+        assert!(saves[0].completed);
+
+        assert!(SaveManager::new(temp_dir.path()).load_game(save_game.id).await.is_err());
+        let loaded = save_manager.load_game(save_game.id).await.unwrap();
+        assert!(loaded.game_state.get_flag_as_bool("betrayed_by_mentor"));
+
+        let events = vec![GameEvent::flag_set("betrayed_by_mentor", &serde_json::Value::Bool(true))];
+        let scene = Scene::new("reveal", "The Reveal", "Your mentor draws a blade.");
+        let preview = SavePreview::new(&scene, &loaded.game_state);
+        let slot_save = save_manager.save_to_slot(1, "Slot".to_string(), loaded.game_state, Some(preview.clone()), events).await.unwrap();
+        let raw = std::fs::read_to_string(save_manager.get_save_path(&slot_save.id)).unwrap();
+        assert!(!raw.contains("betrayed_by_mentor"));
+        assert!(!raw.contains("mentor draws a blade"));
+        let loaded = save_manager.load_game(slot_save.id).await.unwrap();
+        assert_eq!(loaded.events[0].data["flag_name"], "betrayed_by_mentor");
+        assert_eq!(loaded.preview.as_ref(), Some(&preview));
+
+        let slot = save_manager.list_slots("test_story", 1).await.unwrap().remove(0).unwrap();
+        assert_eq!(slot.preview, Some(preview));
+        let slot = SaveManager::new(temp_dir.path()).list_slots("test_story", 1).await.unwrap().remove(0).unwrap();
+        assert!(slot.preview.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_user_saves_are_separate() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path());
+        let ana = save_manager.for_user("@ana:example.org").unwrap();
+        let player = Player::new("Ana", Some(PlayerStats::default()));
+        let game_state = crate::core::GameState::new("test_story".to_string(), "start".to_string(), player);
+        ana.save_game("Mine".to_string(), game_state, None).await.unwrap();
+
+        assert_eq!(ana.list_save_games().await.unwrap().len(), 1);
+        assert_eq!(save_manager.list_save_games().await.unwrap().len(), 0);
+        assert_eq!(save_manager.for_user("_ana_example_org").unwrap().list_save_games().await.unwrap().len(), 0);
+        assert!(temp_dir.path().join(USERS_DIR).join("%40ana%3Aexample%2Eorg").is_dir());
+        assert_eq!(save_manager.for_user("../..").unwrap().saves_directory, temp_dir.path().join(USERS_DIR).join("%2E%2E%2F%2E%2E"));
+        assert!(save_manager.for_user(" ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_saves() {
+        let temp_dir = tempdir().unwrap();
+        let save_manager = SaveManager::new(temp_dir.path());
+        let session = "irc:#../tavern";
+        assert!(save_manager.load_session(session).await.unwrap().is_none());
+
+        for level in [1, 2] {
+            let mut player = Player::new("Bot", Some(PlayerStats::default()));
+            player.stats.level = level;
+            let game_state = crate::core::GameState::new("test_story".to_string(), "start".to_string(), player);
+            save_manager.save_session(session, game_state).await.unwrap();
+        }
+
+        // One file per session, kept inside the sessions directory and out of the load menu
+        let files: Vec<_> = std::fs::read_dir(temp_dir.path().join(SESSIONS_DIR)).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        assert!(save_manager.list_save_games().await.unwrap().is_empty());
+        let loaded = save_manager.load_session(session).await.unwrap().unwrap();
+        assert_eq!(loaded.game_state.player.stats.level, 2);
+
+        save_manager.delete_session(session).await.unwrap();
+        assert!(save_manager.load_session(session).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_saves_lock_is_exclusive() {
+        let temp_dir = tempdir().unwrap();
+
+        let lock = SaveLock::acquire(temp_dir.path(), true).await.unwrap();
+        let lock_file = std::fs::File::open(temp_dir.path().join(LOCK_FILE_NAME)).unwrap();
+        assert!(FileExt::try_lock_shared(&lock_file).is_err());
+
+        drop(lock);
+        assert!(FileExt::try_lock_shared(&lock_file).is_ok());
     }
 }
\ No newline at end of file